@@ -15,15 +15,79 @@ use proc_macro::TokenStream;
 /// END_SCHEMA;
 /// "#);
 /// ```
+///
+/// Append `serde` to derive `serde::Serialize` and `serde::Deserialize` for generated types,
+/// `builder` to generate builders for entities,
+/// and `shared` to share referenced entities by `Rc`,
+/// see [espr::codegen::rust::CodegenOptions] for detail:
+///
+/// ```
+/// espr_derive::inline_express!(
+///     r#"
+///     SCHEMA explicit_draughting;
+///       ENTITY a;
+///         x: REAL;
+///       END_ENTITY;
+///     END_SCHEMA;
+///     "#,
+///     serde,
+///     builder
+/// );
+/// ```
 #[proc_macro]
 pub fn inline_express(input: TokenStream) -> TokenStream {
     // FIXME Use proc-macro-error
     //
     // espr::Result does not match its requirement currently. We have to fix it.
     //
-    let input: syn::LitStr =
-        syn::parse(input).expect("inline_express! argument must be string literal");
-    let st = SyntaxTree::parse(&input.value()).expect("Tokenize failed");
+    let input: Input = syn::parse(input).expect(
+        "inline_express! argument must be string literal optionally followed by `serde`, `builder` or `shared`",
+    );
+    let st = SyntaxTree::parse(&input.source.value()).expect("Tokenize failed");
     let ir = IR::from_syntax_tree(&st).expect("Failed in semantic analysis phase");
-    ir.to_token_stream(CratePrefix::External).into()
+    ir.to_token_stream(CodegenOptions {
+        prefix: CratePrefix::External,
+        serde: input.serde,
+        builder: input.builder,
+        shared: input.shared,
+    })
+    .into()
+}
+
+/// Arguments of `inline_express!`, e.g. `r#"SCHEMA ..."#, serde, builder`
+struct Input {
+    source: syn::LitStr,
+    serde: bool,
+    builder: bool,
+    shared: bool,
+}
+
+impl syn::parse::Parse for Input {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let source = input.parse()?;
+        let mut serde = false;
+        let mut builder = false;
+        let mut shared = false;
+        while input.parse::<Option<syn::Token![,]>>()?.is_some() && !input.is_empty() {
+            let option: syn::Ident = input.parse()?;
+            if option == "serde" {
+                serde = true;
+            } else if option == "builder" {
+                builder = true;
+            } else if option == "shared" {
+                shared = true;
+            } else {
+                return Err(syn::Error::new(
+                    option.span(),
+                    "expected `serde`, `builder` or `shared`",
+                ));
+            }
+        }
+        Ok(Input {
+            source,
+            serde,
+            builder,
+            shared,
+        })
+    }
 }
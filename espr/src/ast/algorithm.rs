@@ -50,6 +50,10 @@ pub enum Statement {
     Skip,
     Escape,
     Null,
+
+    /// Raw text of statements which cannot be parsed,
+    /// kept only to skip the body of functions and procedures.
+    Opaque(String),
 }
 
 #[derive(Debug, Clone, PartialEq)]
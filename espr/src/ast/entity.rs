@@ -20,6 +20,9 @@ pub struct Entity {
     pub inverse_clause: Option<InverseClause>,
     pub unique_clause: Option<UniqueClause>,
     pub where_clause: Option<WhereClause>,
+
+    /// Remarks just before `ENTITY` declaration
+    pub remarks: Vec<Remark>,
 }
 
 crate::derive_ast_component!(Entity, entity_decl);
@@ -35,6 +38,31 @@ impl Entity {
             false
         }
     }
+
+    /// If declared as `ABSTRACT` or `ABSTRACT SUPERTYPE`
+    pub fn is_abstract(&self) -> bool {
+        matches!(
+            self.constraint,
+            Some(Constraint::AbstractEntity) | Some(Constraint::AbstractSuperType(..))
+        )
+    }
+
+    /// Expression in `SUPERTYPE OF (...)` or `ABSTRACT SUPERTYPE OF (...)`
+    pub fn supertype_expression(&self) -> Option<&SuperTypeExpression> {
+        match &self.constraint {
+            Some(Constraint::AbstractSuperType(expr)) => expr.as_ref(),
+            Some(Constraint::SuperTypeRule(expr)) => Some(expr),
+            _ => None,
+        }
+    }
+
+    /// Entities listed in `SUBTYPE OF (...)`
+    pub fn supertypes(&self) -> &[String] {
+        match &self.subtype_of {
+            Some(decl) => &decl.entity_references,
+            None => &[],
+        }
+    }
 }
 
 /// Intermediate output of [entity_body]
@@ -75,6 +103,9 @@ pub struct EntityAttribute {
     pub name: AttributeDecl,
     pub ty: Type,
     pub optional: bool,
+
+    /// Remarks just before the attribute declaration, or in the same line after it
+    pub remarks: Vec<Remark>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
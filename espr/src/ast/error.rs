@@ -1,7 +1,20 @@
+use nom::Offset;
 use std::fmt;
 
-/// Error while tokenizing STEP input
+/// Error while tokenizing EXPRESS input
+///
+/// The location points to the innermost error reported by the parser,
+/// measured in the original input given to [SyntaxTree::parse](super::SyntaxTree::parse)
+/// or [Component::parse](super::Component::parse).
 pub struct TokenizeFailed {
+    /// Byte offset from the beginning of the input
+    pub offset: usize,
+    /// Line number, 1-based
+    pub line: usize,
+    /// Column number counted in characters, 1-based
+    pub column: usize,
+    /// Input left unconsumed after parsing succeeded
+    pub residual: Option<String>,
     rendered_error: String,
 }
 
@@ -9,8 +22,8 @@ impl fmt::Debug for TokenizeFailed {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(
             f,
-            "Error while tokenizing STEP input\n{}",
-            self.rendered_error
+            "Error while tokenizing EXPRESS input at line {}, column {}\n{}",
+            self.line, self.column, self.rendered_error
         )?;
         Ok(())
     }
@@ -26,9 +39,72 @@ impl fmt::Display for TokenizeFailed {
 impl std::error::Error for TokenizeFailed {}
 
 impl TokenizeFailed {
+    /// `err` must be reported while parsing `input` or its substring
     pub fn new(input: &str, err: nom::error::VerboseError<&str>) -> Self {
+        let offset = err
+            .errors
+            .first()
+            .map(|(substring, _kind)| input.offset(substring))
+            .unwrap_or(0);
+        Self::at(input, offset, None, nom::error::convert_error(input, err))
+    }
+
+    /// Parser succeeded but left `residual`, a suffix of `input`, unconsumed
+    pub fn residual(input: &str, residual: &str) -> Self {
+        let offset = input.offset(residual);
+        let rendered_error = format!(
+            "Unparsed input remains:\n{}",
+            residual.lines().next().unwrap_or_default()
+        );
+        Self::at(input, offset, Some(residual.to_string()), rendered_error)
+    }
+
+    fn at(input: &str, offset: usize, residual: Option<String>, rendered_error: String) -> Self {
+        let line_begin = input[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
         TokenizeFailed {
-            rendered_error: nom::error::convert_error(input, err),
+            offset,
+            line: input[..offset].matches('\n').count() + 1,
+            column: input[line_begin..offset].chars().count() + 1,
+            residual,
+            rendered_error,
         }
     }
+
+    /// Snippets of the input around each error, rendered by [nom::error::convert_error]
+    pub fn rendered(&self) -> &str {
+        &self.rendered_error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn location() {
+        let input = "\nSCHEMA s;\n  ENTITY e;\n    x: REAL;\n  END_ENTITY;\n  END_SCHEM;\n";
+        let err = SyntaxTree::parse(input).unwrap_err();
+        assert_eq!(&input[err.offset..err.offset + 9], "END_SCHEM");
+        assert_eq!((err.line, err.column), (6, 3));
+        assert!(err.rendered().contains("END_SCHEM;"));
+    }
+
+    #[test]
+    fn location_component() {
+        // Offset is measured in the original input even though it is trimmed before parsing
+        let input = "\n  ENTITY e;\n    x: ;\n  END_ENTITY;";
+        let err = Entity::from_str(input).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 5));
+    }
+
+    #[test]
+    fn residual() {
+        let input = "SCHEMA s;\nEND_SCHEMA;\nENTITY e;\nEND_ENTITY;\n";
+        let err = SyntaxTree::parse(input).unwrap_err();
+        assert_eq!(err.residual.as_deref(), Some("ENTITY e;\nEND_ENTITY;\n"));
+        assert_eq!((err.offset, err.line, err.column), (22, 3, 1));
+
+        let err = Entity::from_str("ENTITY e; END_ENTITY; x").unwrap_err();
+        assert_eq!(err.residual.as_deref(), Some(" x"));
+    }
 }
@@ -114,6 +114,65 @@ impl Expression {
     impl_binary_op_expression!(and, BinaryOperator::And);
     impl_binary_op_expression!(or, BinaryOperator::Or);
     impl_binary_op_expression!(xor, BinaryOperator::Xor);
+
+    /// Call `f` for this expression and every sub-expression in pre-order,
+    /// including arguments of function calls and qualifiers like `[i]`
+    pub fn visit(&self, f: &mut impl FnMut(&Expression)) {
+        f(self);
+        match self {
+            Expression::Unary { arg, .. } => arg.visit(f),
+            Expression::Binary { arg1, arg2, .. } => {
+                arg1.visit(f);
+                arg2.visit(f);
+            }
+            Expression::Relation { lhs, rhs, .. } => {
+                lhs.visit(f);
+                rhs.visit(f);
+            }
+            Expression::QualifiableFactor { factor, qualifiers } => {
+                if let QualifiableFactor::FunctionCall { args, .. } = factor {
+                    for arg in args {
+                        arg.visit(f);
+                    }
+                }
+                for qualifier in qualifiers {
+                    match qualifier {
+                        Qualifier::Index(index) => index.visit(f),
+                        Qualifier::Range { begin, end } => {
+                            begin.visit(f);
+                            end.visit(f);
+                        }
+                        Qualifier::Attribute(_) | Qualifier::Group(_) => {}
+                    }
+                }
+            }
+            Expression::EntityConstructor { values, .. } => {
+                for value in values {
+                    value.visit(f);
+                }
+            }
+            Expression::Interval {
+                high, low, item, ..
+            } => {
+                low.visit(f);
+                item.visit(f);
+                high.visit(f);
+            }
+            Expression::AggregateInitializer { elements } => {
+                for Element { expr, repetition } in elements {
+                    expr.visit(f);
+                    if let Some(repetition) = repetition {
+                        repetition.visit(f);
+                    }
+                }
+            }
+            Expression::Query { source, expr, .. } => {
+                source.visit(f);
+                expr.visit(f);
+            }
+            Expression::Literal(_) | Expression::EnumerationReference { .. } => {}
+        }
+    }
 }
 
 macro_rules! impl_binary_op_expression {
@@ -310,4 +369,6 @@ pub enum Literal {
     Real(f64),
     String(String),
     Logial(Logical),
+    /// Bits of binary literal, e.g. `%0101`, from the most significant one
+    Binary(Vec<bool>),
 }
@@ -33,10 +33,12 @@ macro_rules! derive_ast_component {
                 input: &str,
             ) -> Result<(Self, Vec<$crate::ast::Remark>), $crate::ast::TokenizeFailed> {
                 use nom::Finish;
-                let input = input.trim();
-                let (_input, parsed) = $parser(input)
+                let (residual, parsed) = $parser(input.trim())
                     .finish()
                     .map_err(|err| $crate::ast::TokenizeFailed::new(input, err))?;
+                if !residual.is_empty() {
+                    return Err($crate::ast::TokenizeFailed::residual(input, residual));
+                }
                 Ok(parsed)
             }
         }
@@ -58,15 +60,23 @@ pub struct SyntaxTree {
 }
 
 impl SyntaxTree {
-    pub fn parse(input: &str) -> Result<Self, nom::error::VerboseError<&str>> {
+    pub fn parse(input: &str) -> Result<Self, TokenizeFailed> {
         let (residual, (schemas, remarks)) = tuple((spaces, many1(schema_decl), spaces))
             .map(|(_start_space, schemas, _end_space)| schemas)
             .parse(input)
-            .finish()?;
-        assert!(residual.is_empty());
+            .finish()
+            .map_err(|err| TokenizeFailed::new(input, err))?;
+        if !residual.is_empty() {
+            return Err(TokenizeFailed::residual(input, residual));
+        }
         Ok(SyntaxTree { schemas, remarks })
     }
 
+    /// Declarations in the schemas with their scopes and kinds, see [crate::ir::NamespaceView]
+    pub fn namespace(&self) -> crate::ir::NamespaceView {
+        crate::ir::NamespaceView::new(self)
+    }
+
     // Example syntax tree for easy testing
     //
     // FIXME Replace by e.g. proptest
@@ -10,6 +10,18 @@ struct Arguments {
     num_lines: usize,
     #[structopt(long = "check", help = "Check input EXPRESS definitions can be parsed")]
     check: bool,
+    #[structopt(
+        long = "serde",
+        help = "Derive serde::Serialize and serde::Deserialize for generated types"
+    )]
+    serde: bool,
+    #[structopt(long = "builder", help = "Generate builders for entities")]
+    builder: bool,
+    #[structopt(
+        long = "shared",
+        help = "Share entities referenced from attributes by std::rc::Rc"
+    )]
+    shared: bool,
     #[structopt(parse(from_os_str))]
     source: PathBuf,
 }
@@ -20,15 +32,12 @@ fn main() {
     let st = match SyntaxTree::parse(&src) {
         Ok(st) => st,
         Err(e) => {
-            for (code, kind) in e.errors {
-                eprintln!(
-                    "Syntax Error occurred while parsing following line [{:?}]:",
-                    kind
-                );
-                for line in code.lines().take(args.num_lines) {
-                    eprintln!("> {}", line);
-                }
-                eprintln!();
+            eprintln!(
+                "Syntax Error occurred at line {}, column {}:",
+                e.line, e.column
+            );
+            for line in e.rendered().lines().take(args.num_lines) {
+                eprintln!("> {}", line);
             }
             panic!("Syntax Error");
         }
@@ -39,8 +48,16 @@ fn main() {
     }
 
     let ir = IR::from_syntax_tree(&st).expect("Failed in semantic analysis phase");
+    for warning in ir.warnings() {
+        eprintln!("warning: {}", warning);
+    }
     println!(
         "#![allow(dead_code)]\n{}",
-        ir.to_token_stream(CratePrefix::Internal)
+        ir.to_token_stream(CodegenOptions {
+            prefix: CratePrefix::Internal,
+            serde: args.serde,
+            builder: args.builder,
+            shared: args.shared,
+        })
     );
 }
@@ -0,0 +1,50 @@
+use crate::{ast, ir::*};
+
+use inflector::Inflector;
+use proc_macro2::{Literal, TokenStream};
+use quote::*;
+
+impl ToTokens for Constant {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        // Initializer expression cannot be evaluated at compile time,
+        // and nothing is generated since translated functions do not refer it.
+        let value = match &self.value {
+            Some(value) => value,
+            None => return,
+        };
+        let doc = format!("`CONSTANT {}` declared in EXPRESS schema", self.name);
+        let name = format_ident!("{}", self.name.to_screaming_snake_case());
+        let (ty, value) = match value {
+            ConstantValue::Real(value) => (
+                quote! { f64 },
+                signed(*value < 0.0, Literal::f64_unsuffixed(value.abs())),
+            ),
+            ConstantValue::Integer(value) => (
+                quote! { i64 },
+                signed(*value < 0, Literal::u64_unsuffixed(value.unsigned_abs())),
+            ),
+            ConstantValue::String(value) => (quote! { &str }, quote! { #value }),
+            ConstantValue::Boolean(value) => (quote! { bool }, quote! { #value }),
+            ConstantValue::Logical(value) => (
+                quote! { Logical },
+                match value {
+                    ast::Logical::True => quote! { Logical::True },
+                    ast::Logical::False => quote! { Logical::False },
+                    ast::Logical::Unknown => quote! { Logical::Unknown },
+                },
+            ),
+        };
+        tokens.append_all(quote! {
+            #[doc = #doc]
+            pub const #name: #ty = #value;
+        });
+    }
+}
+
+fn signed(negative: bool, abs: Literal) -> TokenStream {
+    if negative {
+        quote! { -#abs }
+    } else {
+        quote! { #abs }
+    }
+}
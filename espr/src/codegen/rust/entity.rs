@@ -1,3 +1,4 @@
+use super::CodegenOptions;
 use crate::ir::*;
 
 use check_keyword::CheckKeyword;
@@ -19,26 +20,102 @@ struct Field {
 ///
 /// - a simple type
 /// - an enumeration
-/// - a set or list whose base type use place holder
+/// - a set, list or array whose base type use place holder
 ///
 fn use_place_holder(ty: &TypeRef) -> bool {
     match ty {
         TypeRef::SimpleType(..) => false,
         TypeRef::Named { is_enumerate, .. } => !*is_enumerate,
-        TypeRef::Set { base, .. } | TypeRef::List { base, .. } => use_place_holder(base),
+        TypeRef::Set { base, .. } | TypeRef::List { base, .. } | TypeRef::Array { base, .. } => {
+            use_place_holder(base)
+        }
         _ => true,
     }
 }
 
-impl From<EntityAttribute> for Field {
-    fn from(attr: EntityAttribute) -> Self {
-        let EntityAttribute { name, ty, optional } = attr;
+/// `#[holder(bound(lower, upper))]` attribute for `SET` and `LIST` with constant bounds
+///
+/// Bounds of `ARRAY` are not the number of elements but the range of indices,
+/// and a constant size is checked by `[T; N]` type.
+fn bound_attribute(ty: &TypeRef) -> Option<syn::Attribute> {
+    let bound = match ty {
+        TypeRef::Set { bound, .. } | TypeRef::List { bound, .. } => bound.as_ref()?,
+        _ => return None,
+    };
+    let lower = match bound.lower {
+        BoundValue::Constant(lower) if lower > 0 => lower as usize,
+        _ => 0,
+    };
+    let upper = match bound.upper {
+        BoundValue::Constant(upper) if upper >= 0 => Some(upper as usize),
+        _ => None,
+    };
+    let lower_lit = proc_macro2::Literal::usize_unsuffixed(lower);
+    match upper {
+        Some(upper) => {
+            let upper = proc_macro2::Literal::usize_unsuffixed(upper);
+            Some(parse_quote! { #[holder(bound(#lower_lit, #upper))] })
+        }
+        None if lower > 0 => Some(parse_quote! { #[holder(bound(#lower_lit))] }),
+        None => None,
+    }
+}
+
+/// Whether the type has an obvious default value, see `Entity::has_default`
+fn has_default(ty: &TypeRef) -> bool {
+    use crate::ast::SimpleType::*;
+    match ty {
+        TypeRef::SimpleType(ty) => {
+            matches!(ty.0, Number | Real | Integer | Boolen | String_ { .. })
+        }
+        TypeRef::List { bound, .. } | TypeRef::Set { bound, .. } => match bound {
+            Some(bound) => matches!(bound.lower, BoundValue::Constant(0)),
+            None => true,
+        },
+        _ => false,
+    }
+}
+
+/// `#[doc = "..."]` attributes for each line of remarks
+///
+/// Multi-line remark `(* ... *)` is split into lines with their indents removed.
+fn doc_attributes(remarks: &[String]) -> Vec<syn::Attribute> {
+    remarks
+        .iter()
+        .flat_map(|remark| remark.lines())
+        .map(|line| {
+            let line = match line.trim() {
+                "" => String::new(),
+                line => format!(" {}", line),
+            };
+            parse_quote! { #[doc = #line] }
+        })
+        .collect()
+}
+
+impl Field {
+    fn new(attr: EntityAttribute, options: CodegenOptions) -> Self {
+        let EntityAttribute {
+            name,
+            ty,
+            optional,
+            derived_in_subtype,
+            remarks,
+        } = attr;
 
         let name = format_ident!("{}", name.into_safe());
-        let attributes = if use_place_holder(&ty) {
-            vec![parse_quote! { #[holder(use_place_holder)] }]
+        let mut attributes = doc_attributes(&remarks);
+        if use_place_holder(&ty) {
+            attributes.push(parse_quote! { #[holder(use_place_holder)] });
+        }
+        attributes.extend(bound_attribute(&ty));
+        if derived_in_subtype {
+            attributes.push(parse_quote! { #[holder(derived_in_subtype)] });
+        }
+        let ty = if options.shared {
+            ty.to_shared_tokens()
         } else {
-            Vec::new()
+            ty.to_token_stream()
         };
         let ty = if optional {
             parse_quote! { Option<#ty> }
@@ -87,12 +164,20 @@ impl Entity {
     }
 
     /// Generate declaration of `XxxAny` enum
-    fn generate_any_enum(&self, tokens: &mut TokenStream) {
+    ///
+    /// This has a variant for each instantiable subtype,
+    /// and for this entity itself if it is not abstract.
+    fn generate_any_enum(&self, tokens: &mut TokenStream, options: CodegenOptions) {
         let any = self.any_ident();
 
-        let mut fields = vec![format_ident!("{}", self.name.as_str().into_safe())];
-        let mut variants = vec![format_ident!("{}", self.name.to_pascal_case())];
-        let mut constraints = vec![format_ident!("{}", self.name.to_pascal_case())];
+        let mut fields = Vec::new();
+        let mut variants = Vec::new();
+        let mut constraints = Vec::new();
+        if !self.is_abstract {
+            fields.push(format_ident!("{}", self.name.as_str().into_safe()));
+            variants.push(format_ident!("{}", self.name.to_pascal_case()));
+            constraints.push(format_ident!("{}", self.name.to_pascal_case()));
+        }
 
         for ty in &self.constraints {
             match ty {
@@ -111,8 +196,11 @@ impl Entity {
             }
         }
 
+        let serde = options.serde_attributes();
+
         tokens.append_all(quote! {
-            #[derive(Debug, Clone, PartialEq, Holder)]
+            #[derive(Debug, Clone, PartialEq, Holder, Serialize, ApproxEq, HashBits)]
+            #serde
             #[holder(table = Tables)]
             #[holder(generate_deserialize)]
             pub enum #any {
@@ -124,19 +212,47 @@ impl Entity {
         }); // tokens.append_all
     }
 
+    /// Generate `impl TypeOf for SelfAny` delegating to each variant
+    fn generate_typeof_any(&self, tokens: &mut TokenStream) {
+        let any = self.any_ident();
+        let mut variants = Vec::new();
+        if !self.is_abstract {
+            variants.push(self.name_ident());
+        }
+        for ty in &self.constraints {
+            match ty {
+                TypeRef::Entity { name, .. } => {
+                    variants.push(format_ident!("{}", name.to_pascal_case()))
+                }
+                _ => unreachable!(),
+            }
+        }
+        tokens.append_all(quote! {
+            impl TypeOf for #any {
+                fn r#typeof(&self) -> &'static [&'static str] {
+                    match self {
+                        #( #any::#variants(value) => value.r#typeof(), )*
+                    }
+                }
+            }
+        });
+    }
+
     /// Generate `impl Into<SelfAny> for SubType` for self and all constraints
     fn generate_into_any(&self, tokens: &mut TokenStream) {
         let any = self.any_ident();
         let name = self.name_ident();
 
         // `Self` to `SelfAny`
-        tokens.append_all(quote! {
-            impl Into<#any> for #name {
-                fn into(self) -> #any {
-                    #any::#name(Box::new(self))
+        if !self.is_abstract {
+            tokens.append_all(quote! {
+                impl Into<#any> for #name {
+                    fn into(self) -> #any {
+                        #any::#name(Box::new(self))
+                    }
                 }
-            }
-        });
+            });
+        }
 
         for ty in &self.constraints {
             if let TypeRef::Entity { name, .. } = ty {
@@ -168,11 +284,16 @@ impl Entity {
             })
             .collect::<Vec<_>>();
 
+        let self_arm = if self.is_abstract {
+            None
+        } else {
+            Some(quote! { #any::#name (x) => x.as_ref(), })
+        };
         tokens.append_all(quote! {
             impl AsRef<#name> for #any {
                 fn as_ref(&self) -> &#name {
                     match self {
-                        #any::#name (x) => x.as_ref(),
+                        #self_arm
                         #(#any::#constraints (x) => (**x).as_ref(),)*
                     }
                 }
@@ -187,11 +308,16 @@ impl Entity {
                 _ => unreachable!(),
             };
 
+            let self_arm = if self.is_abstract {
+                None
+            } else {
+                Some(quote! { #any::#name (x) => AsRef::<#name>::as_ref(x).as_ref(), })
+            };
             tokens.append_all(quote! {
                 impl AsRef<#supertype> for #any {
                     fn as_ref(&self) -> &#supertype {
                         match self {
-                            #any::#name (x) => AsRef::<#name>::as_ref(x).as_ref(),
+                            #self_arm
                             #(#any::#constraints (x) => AsRef::<#name>::as_ref(x.as_ref()).as_ref(),)*
                         }
                     }
@@ -200,7 +326,111 @@ impl Entity {
         }
     }
 
-    fn supertype_fields(&self) -> Vec<Field> {
+    /// Generate `TryFrom<SelfAny>` for each constraint to downcast into it
+    fn generate_try_from_any(&self, tokens: &mut TokenStream) {
+        let any = self.any_ident();
+        let name = self.name_ident();
+        // No other variant to fail with if the `Any` enum has only one variant
+        let others = if !self.is_abstract as usize + self.constraints.len() > 1 {
+            quote! { _ => Err(any), }
+        } else {
+            quote! {}
+        };
+
+        if !self.is_abstract {
+            tokens.append_all(quote! {
+                impl ::std::convert::TryFrom<#any> for #name {
+                    type Error = #any;
+                    fn try_from(any: #any) -> ::std::result::Result<Self, Self::Error> {
+                        match any {
+                            #any::#name(x) => Ok(*x),
+                            #others
+                        }
+                    }
+                }
+            });
+        }
+
+        for ty in &self.constraints {
+            if let TypeRef::Entity {
+                name, is_supertype, ..
+            } = ty
+            {
+                let variant = format_ident!("{}", name.to_pascal_case());
+                let target = if *is_supertype {
+                    format_ident!("{}Any", name.to_pascal_case())
+                } else {
+                    variant.clone()
+                };
+                tokens.append_all(quote! {
+                    impl ::std::convert::TryFrom<#any> for #target {
+                        type Error = #any;
+                        fn try_from(any: #any) -> ::std::result::Result<Self, Self::Error> {
+                            match any {
+                                #any::#variant(x) => Ok(*x),
+                                #others
+                            }
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    /// Generate `impl From<Self> for Super` and `impl From<&Self> for Super`
+    /// projecting attributes of each supertype
+    fn generate_from_subtype(&self, tokens: &mut TokenStream) {
+        let name = self.name_ident();
+        for ty in &self.supertypes {
+            let (field, supertype) = match ty {
+                TypeRef::Named { name, .. } | TypeRef::Entity { name, .. } => (
+                    format_ident!("{}", name.as_str().into_safe()),
+                    format_ident!("{}", name.to_pascal_case()),
+                ),
+                _ => unreachable!(),
+            };
+            tokens.append_all(quote! {
+                impl From<#name> for #supertype {
+                    fn from(sub: #name) -> Self {
+                        sub.#field
+                    }
+                }
+                impl From<&#name> for #supertype {
+                    fn from(sub: &#name) -> Self {
+                        sub.#field.clone()
+                    }
+                }
+            });
+        }
+    }
+
+    /// `#[holder(derive(path = value))]` attributes filling attributes derived in this entity
+    /// into the supertype field `supertype`
+    fn derive_attributes(&self, supertype: &str, options: CodegenOptions) -> Vec<syn::Attribute> {
+        let ruststep = options.prefix.as_path();
+        self.redeclared
+            .iter()
+            .filter_map(|attr| {
+                let value = attr.value.as_ref()?;
+                if value.path[0] != supertype {
+                    return None;
+                }
+                let path = value.path[1..]
+                    .iter()
+                    .chain(std::iter::once(&attr.name))
+                    .map(|name| format_ident!("{}", name.as_str().into_safe()));
+                let expr = value.expr.derived_value(&value.ty, &ruststep);
+                let expr = if value.optional {
+                    quote! { Some(#expr) }
+                } else {
+                    expr
+                };
+                Some(parse_quote! { #[holder(derive(#(#path).* = #expr))] })
+            })
+            .collect()
+    }
+
+    fn supertype_fields(&self, options: CodegenOptions) -> Vec<Field> {
         self.supertypes
             .iter()
             .map(|ty| {
@@ -215,6 +445,7 @@ impl Entity {
                 attributes.push(parse_quote! { #[holder(use_place_holder)] });
                 let (name, ty) = match ty {
                     TypeRef::Named { name, .. } | TypeRef::Entity { name, .. } => {
+                        attributes.extend(self.derive_attributes(name, options));
                         let ty = format_ident!("{}", name.to_pascal_case());
                         (
                             format_ident!("{}", name.as_str().into_safe()),
@@ -240,6 +471,9 @@ impl Entity {
             syn::parse_str("PartialEq").unwrap(),
             syn::parse_str("::derive_new::new").unwrap(),
             syn::parse_str("Holder").unwrap(),
+            syn::parse_str("Serialize").unwrap(),
+            syn::parse_str("ApproxEq").unwrap(),
+            syn::parse_str("HashBits").unwrap(),
         ];
         if !self.supertypes.is_empty() {
             derives.push(syn::parse_str("AsRef").unwrap());
@@ -249,12 +483,30 @@ impl Entity {
             derives.push(syn::parse_str("Deref").unwrap());
             derives.push(syn::parse_str("DerefMut").unwrap());
         }
+        if self.has_default() {
+            derives.push(syn::parse_str("Default").unwrap());
+        }
         derives
     }
+
+    /// Whether every attribute has an obvious default value,
+    /// i.e. `None` for `OPTIONAL`, zero, `FALSE`, an empty `STRING`, or an empty `LIST` or `SET`
+    ///
+    /// References to other entities and named types do not have such value,
+    /// and entities with supertypes are skipped since their supertype fields are not checked here.
+    fn has_default(&self) -> bool {
+        self.supertypes.is_empty()
+            && self
+                .attributes
+                .iter()
+                .all(|attr| attr.optional || has_default(&attr.ty))
+    }
 }
 
-impl ToTokens for Entity {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+impl Entity {
+    pub fn to_token_stream(&self, options: CodegenOptions) -> TokenStream {
+        let mut stream = TokenStream::new();
+        let tokens = &mut stream;
         let name = self.name_ident();
         let field_name = self.field_ident();
 
@@ -262,32 +514,111 @@ impl ToTokens for Entity {
         // and "attribute" refers other items
         //
         // https://doc.rust-lang.org/std/keyword.struct.html
+        //
+        // Redeclared attributes, e.g. `SELF\base.x : INTEGER;`, do not have their own fields
+        // since they override the inherited fields in the supertype fields.
         let fields = self
             .attributes
             .iter()
-            .map(|attr| Field::from(attr.clone()))
+            .map(|attr| Field::new(attr.clone(), options))
             .collect::<Vec<Field>>();
-        let supertype_fields = self.supertype_fields();
+        let supertype_fields = self.supertype_fields(options);
 
         let derive = self.derives();
+        let doc = doc_attributes(&self.remarks);
+        let serde = options.serde_attributes();
+        let builder = options.builder_attributes();
 
         tokens.append_all(quote! {
+            #( #doc )*
             #( #[derive(#derive)] )*
+            #serde
             #[holder(table = Tables)]
             #[holder(field = #field_name)]
             #[holder(generate_deserialize)]
+            #builder
             pub struct #name {
                 #(#supertype_fields,)*
                 #(#fields,)*
             }
         });
 
+        // Names of this entity and its supertypes for `TYPEOF`
+        let type_names = &self.type_names;
+        tokens.append_all(quote! {
+            impl TypeOf for #name {
+                fn r#typeof(&self) -> &'static [&'static str] {
+                    &[#(#type_names),*]
+                }
+            }
+        });
+
+        // Project into supertypes
+        self.generate_from_subtype(tokens);
+
         // Generate `Any` enum if this entity is a supertype of other entities
         if !self.constraints.is_empty() {
-            self.generate_any_enum(tokens);
+            self.generate_any_enum(tokens, options);
             // Generate `impl Into<XxxAny> for Yyy` for self and all constraints
             self.generate_into_any(tokens);
             self.generate_asref_from_any(tokens);
+            self.generate_try_from_any(tokens);
+            self.generate_typeof_any(tokens);
+        }
+        stream
+    }
+}
+
+// Additional functions to use in codegen/rust for ir::ComplexEntity.
+impl ComplexEntity {
+    /// Concatenated name of components, e.g. `EmployeeStudent`
+    pub(super) fn name_ident(&self) -> syn::Ident {
+        format_ident!("{}", self.component_names().join("_").to_pascal_case())
+    }
+
+    /// Field identifier in `Tables`, e.g. `employee_student`
+    pub(super) fn field_ident(&self) -> syn::Ident {
+        format_ident!("{}", self.component_names().join("_").into_safe())
+    }
+}
+
+impl ComplexEntity {
+    pub fn to_token_stream(&self, options: CodegenOptions) -> TokenStream {
+        let name = self.name_ident();
+        let field_name = self.field_ident();
+        let (fields, types): (Vec<_>, Vec<_>) = self
+            .component_names()
+            .into_iter()
+            .map(|component| {
+                (
+                    format_ident!("{}", component.into_safe()),
+                    format_ident!("{}", component.to_pascal_case()),
+                )
+            })
+            .unzip();
+        let serde = options.serde_attributes();
+        let type_names = &self.type_names;
+
+        quote! {
+            #[derive(Debug, Clone, PartialEq, ::derive_new::new, Holder, Serialize, ApproxEq, HashBits, AsRef, AsMut)]
+            #serde
+            #[holder(table = Tables)]
+            #[holder(field = #field_name)]
+            #[holder(generate_deserialize)]
+            pub struct #name {
+                #(
+                #[as_ref]
+                #[as_mut]
+                #[holder(use_place_holder)]
+                pub #fields: #types,
+                )*
+            }
+
+            impl TypeOf for #name {
+                fn r#typeof(&self) -> &'static [&'static str] {
+                    &[#(#type_names),*]
+                }
+            }
         }
     }
 }
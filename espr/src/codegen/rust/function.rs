@@ -0,0 +1,265 @@
+use super::CratePrefix;
+use crate::{ast, ir::*};
+
+use check_keyword::CheckKeyword;
+use inflector::Inflector;
+use proc_macro2::{Literal, TokenStream};
+use quote::*;
+
+impl Function {
+    /// Rust function translated from the EXPRESS function
+    ///
+    /// Nothing is generated if the signature or the body is not translated,
+    /// and WHERE rules depending on such functions are reported by [IR::warnings].
+    pub fn to_token_stream(&self, prefix: CratePrefix) -> TokenStream {
+        let (signature, body) = match (&self.signature, &self.body) {
+            (Ok(signature), Some(body)) => (signature, body),
+            _ => return TokenStream::new(),
+        };
+        let doc = format!("`FUNCTION {}` declared in EXPRESS schema", self.name);
+        let name = ident(&self.name);
+        let params = signature.parameters.iter().map(|p| {
+            let name = ident(&p.name);
+            let ty = parameter_type(&p.ty);
+            quote! { #name: #ty }
+        });
+        let return_type = value_type(&signature.return_type);
+        let body = body.value(&signature.return_type, &prefix.as_path());
+        quote! {
+            #[doc = #doc]
+            pub fn #name(#(#params),*) -> #return_type {
+                #body
+            }
+        }
+    }
+}
+
+fn ident(name: &str) -> syn::Ident {
+    format_ident!("{}", name.to_snake_case().into_safe())
+}
+
+fn value_type(ty: &ValueType) -> TokenStream {
+    match ty {
+        ValueType::Integer => quote! { i64 },
+        ValueType::Real | ValueType::Number => quote! { f64 },
+        ValueType::Boolean => quote! { bool },
+        ValueType::Logical => quote! { Logical },
+        ValueType::String => quote! { String },
+        ValueType::Aggregate(base) => {
+            let base = value_type(base);
+            quote! { Vec<#base> }
+        }
+    }
+}
+
+fn parameter_type(ty: &ValueType) -> TokenStream {
+    match ty {
+        ValueType::String => quote! { &str },
+        ValueType::Aggregate(base) => {
+            let base = value_type(base);
+            quote! { &[#base] }
+        }
+        _ => value_type(ty),
+    }
+}
+
+impl Expression {
+    /// Rust expression evaluated as a value of `ty`, e.g. `i64` for `INTEGER`
+    pub(super) fn value(&self, ty: &ValueType, ruststep: &syn::Path) -> TokenStream {
+        match ty {
+            ValueType::Integer => {
+                let value = self.operand(ruststep);
+                quote! { #value as i64 }
+            }
+            ValueType::String => {
+                let value = self.operand(ruststep);
+                quote! { #value.to_string() }
+            }
+            ValueType::Logical => {
+                let value = self.expr(ruststep).0;
+                quote! { Logical::from(#value) }
+            }
+            _ => self.expr(ruststep).0,
+        }
+    }
+
+    /// Value of an attribute derived in a subtype computed from the fields of its holder `self`
+    ///
+    /// Attributes referred in the expression are bound as parameters of functions are.
+    pub(super) fn derived_value(&self, ty: &ValueType, ruststep: &syn::Path) -> TokenStream {
+        let mut parameters = Vec::new();
+        self.collect_parameters(&mut parameters);
+        let bindings = parameters.into_iter().map(|(name, ty)| {
+            let name = ident(name);
+            let ty_tokens = parameter_type(ty);
+            match ty {
+                ValueType::String | ValueType::Aggregate(_) => {
+                    quote! { let #name: #ty_tokens = &self.#name; }
+                }
+                _ => quote! { let #name: #ty_tokens = self.#name; },
+            }
+        });
+        let value = self.value(ty, ruststep);
+        quote! {
+            {
+                #( #bindings )*
+                #value
+            }
+        }
+    }
+
+    /// Parameters referred in this expression without duplicates
+    fn collect_parameters<'a>(&'a self, parameters: &mut Vec<(&'a str, &'a ValueType)>) {
+        match self {
+            Expression::Parameter { name, ty } => {
+                if !parameters.iter().any(|(n, _)| n == name) {
+                    parameters.push((name, ty));
+                }
+            }
+            Expression::Unary { arg, .. } | Expression::BuiltIn { arg, .. } => {
+                arg.collect_parameters(parameters)
+            }
+            Expression::Binary { lhs, rhs, .. } | Expression::Relation { lhs, rhs, .. } => {
+                lhs.collect_parameters(parameters);
+                rhs.collect_parameters(parameters);
+            }
+            Expression::Call { args, .. } => {
+                for arg in args {
+                    arg.collect_parameters(parameters);
+                }
+            }
+            Expression::Number(_) | Expression::Boolean(_) | Expression::String(_) => {}
+        }
+    }
+
+    /// Rust expression, and whether it can be used as an operand without parentheses
+    ///
+    /// Numbers are `f64`, and strings are `&str`.
+    fn expr(&self, ruststep: &syn::Path) -> (TokenStream, bool) {
+        use ast::{BinaryOperator::*, BuiltInFunction::*, RelationOperator::*, UnaryOperator::*};
+        match self {
+            Expression::Number(value) => {
+                let abs = Literal::f64_unsuffixed(value.abs());
+                if value.is_sign_negative() {
+                    (quote! { -#abs }, true)
+                } else {
+                    (quote! { #abs }, true)
+                }
+            }
+            Expression::Boolean(value) => (quote! { #value }, true),
+            Expression::String(value) => (quote! { #value }, true),
+            Expression::Parameter { name, ty } => {
+                let name = ident(name);
+                match ty {
+                    ValueType::Integer => (quote! { #name as f64 }, false),
+                    _ => (quote! { #name }, true),
+                }
+            }
+            // Unary operators bind tighter than binary operators
+            Expression::Unary { op, arg } => match op {
+                Plus => arg.expr(ruststep),
+                Minus => {
+                    let arg = arg.operand(ruststep);
+                    (quote! { -#arg }, true)
+                }
+                Not => {
+                    let arg = arg.operand(ruststep);
+                    (quote! { !#arg }, true)
+                }
+            },
+            Expression::Binary {
+                op: Power,
+                lhs,
+                rhs,
+            } => {
+                let lhs = lhs.expr(ruststep).0;
+                let rhs = rhs.expr(ruststep).0;
+                (quote! { f64::powf(#lhs, #rhs) }, true)
+            }
+            Expression::Binary { op, lhs, rhs } => {
+                let lhs = lhs.operand(ruststep);
+                let rhs = rhs.operand(ruststep);
+                let expr = match op {
+                    Add => quote! { #lhs + #rhs },
+                    Sub => quote! { #lhs - #rhs },
+                    Mul => quote! { #lhs * #rhs },
+                    RealDiv => quote! { #lhs / #rhs },
+                    IntegerDiv => return (quote! { f64::trunc(#lhs / #rhs) }, true),
+                    And => quote! { #lhs && #rhs },
+                    Or => quote! { #lhs || #rhs },
+                    Xor => quote! { #lhs ^ #rhs },
+                    _ => unreachable!("Operator {:?} is not translated", op),
+                };
+                (expr, false)
+            }
+            Expression::Relation { op, lhs, rhs } => {
+                let lhs = lhs.operand(ruststep);
+                let rhs = rhs.operand(ruststep);
+                let expr = match op {
+                    Equal => quote! { #lhs == #rhs },
+                    NotEqual => quote! { #lhs != #rhs },
+                    Lt => quote! { #lhs < #rhs },
+                    Gt => quote! { #lhs > #rhs },
+                    Leq => quote! { #lhs <= #rhs },
+                    Geq => quote! { #lhs >= #rhs },
+                    _ => unreachable!("Operator {:?} is not translated", op),
+                };
+                (expr, false)
+            }
+            Expression::BuiltIn { function, arg } => {
+                let arg = match (function, arg.as_ref()) {
+                    // `EXISTS` refers the parameter itself
+                    (EXISTS, Expression::Parameter { name, .. }) => ident(name).to_token_stream(),
+                    _ => arg.expr(ruststep).0,
+                };
+                match function {
+                    SIZEOF => (
+                        quote! { #ruststep::express_builtins::sizeof(#arg) as f64 },
+                        false,
+                    ),
+                    HIINDEX => (
+                        quote! { #ruststep::express_builtins::hiindex(#arg) as f64 },
+                        false,
+                    ),
+                    LOINDEX => (
+                        quote! { #ruststep::express_builtins::loindex(#arg) as f64 },
+                        false,
+                    ),
+                    ABS => (quote! { #ruststep::express_builtins::abs(#arg) }, true),
+                    EXISTS => (quote! { #ruststep::express_builtins::exists(&#arg) }, true),
+                    _ => unreachable!("Built-in function {:?} is not translated", function),
+                }
+            }
+            Expression::Call {
+                function,
+                parameters,
+                args,
+                return_type,
+            } => {
+                let function = ident(function);
+                let args = args.iter().zip(parameters).map(|(arg, ty)| match ty {
+                    ValueType::Integer => {
+                        let arg = arg.operand(ruststep);
+                        quote! { #arg as i64 }
+                    }
+                    _ => arg.expr(ruststep).0,
+                });
+                let call = quote! { #function(#(#args),*) };
+                match return_type {
+                    ValueType::Integer => (quote! { #call as f64 }, false),
+                    ValueType::String => (quote! { #call.as_str() }, true),
+                    ValueType::Logical => unreachable!("LOGICAL value is not translated"),
+                    _ => (call, true),
+                }
+            }
+        }
+    }
+
+    /// Rust expression enclosed by parentheses if necessary
+    fn operand(&self, ruststep: &syn::Path) -> TokenStream {
+        match self.expr(ruststep) {
+            (expr, true) => expr,
+            (expr, false) => quote! { (#expr) },
+        }
+    }
+}
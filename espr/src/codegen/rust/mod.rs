@@ -1,7 +1,9 @@
 //! Generate Rust code using proc-macro utility crates
 
+mod constant;
 mod entity;
 mod format;
+mod function;
 mod schema;
 mod simple_type;
 mod type_decl;
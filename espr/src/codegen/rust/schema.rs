@@ -20,22 +20,97 @@ impl CratePrefix {
     }
 }
 
+/// Options for generating Rust code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodegenOptions {
+    pub prefix: CratePrefix,
+    /// Derive `serde::Serialize` and `serde::Deserialize` for entities, `Any` enums,
+    /// complex entities, and defined types, e.g. to read and write them as JSON.
+    ///
+    /// These types are not serialized into exchange structure in this case,
+    /// while their holders still are.
+    pub serde: bool,
+    /// Generate `XxxBuilder` for entities via `#[holder(generate_builder)]`.
+    ///
+    /// This is disabled by default since builders for every entity
+    /// increase compile time and binary size for large schemas.
+    pub builder: bool,
+    /// Wrap attributes referencing other entities by `Rc`, e.g. `Rc<CartesianPoint>`,
+    /// so that an entity referenced from many entities is resolved only once
+    /// and shared among them by `IntoOwned::into_owned`.
+    ///
+    /// This is disabled by default to keep owned values plain trees.
+    pub shared: bool,
+}
+
+impl From<CratePrefix> for CodegenOptions {
+    fn from(prefix: CratePrefix) -> Self {
+        CodegenOptions {
+            prefix,
+            serde: false,
+            builder: false,
+            shared: false,
+        }
+    }
+}
+
+impl CodegenOptions {
+    /// Attributes to derive serde traits instead of `Serialize` into exchange structure
+    pub(super) fn serde_attributes(&self) -> TokenStream {
+        if self.serde {
+            quote! {
+                #[derive(::serde::Serialize, ::serde::Deserialize)]
+                #[holder(serialize_holder_only)]
+            }
+        } else {
+            TokenStream::new()
+        }
+    }
+
+    /// Attribute to let `#[derive(Holder)]` generate a builder
+    pub(super) fn builder_attributes(&self) -> TokenStream {
+        if self.builder {
+            quote! { #[holder(generate_builder)] }
+        } else {
+            TokenStream::new()
+        }
+    }
+}
+
 impl IR {
-    pub fn to_token_stream(&self, prefix: CratePrefix) -> TokenStream {
+    pub fn to_token_stream(&self, options: impl Into<CodegenOptions>) -> TokenStream {
+        let options = options.into();
         let schemas: Vec<_> = self
             .schemas
             .iter()
-            .map(|schema| schema.to_token_stream(prefix))
+            .map(|schema| schema.to_token_stream(options))
             .collect();
         quote! { #(#schemas)* }
     }
 }
 
 impl Schema {
-    pub fn to_token_stream(&self, prefix: CratePrefix) -> TokenStream {
+    pub fn to_token_stream(&self, options: impl Into<CodegenOptions>) -> TokenStream {
+        let options = options.into();
+        let prefix = options.prefix;
         let name = format_ident!("{}", self.name);
-        let types = &self.types;
+        let schema_name = &self.name;
+        let types: Vec<_> = self
+            .types
+            .iter()
+            .map(|ty| ty.to_token_stream(options))
+            .collect();
         let entities = &self.entities;
+        let entity_tokens: Vec<_> = entities
+            .iter()
+            .map(|e| e.to_token_stream(options))
+            .collect();
+        let constants = &self.constants;
+        let functions: Vec<_> = self
+            .functions
+            .iter()
+            .map(|f| f.to_token_stream(prefix))
+            .collect();
         let type_decls = self
             .types
             .iter()
@@ -64,18 +139,78 @@ impl Schema {
             .chain(type_decls.map(|e| format_ident!("{}_holders", e.id())))
             .collect();
 
+        let complex_entities = &self.complex_entities;
+        let complex_tokens: Vec<_> = complex_entities
+            .iter()
+            .map(|c| c.to_token_stream(options))
+            .collect();
+        let complex_types: Vec<_> = complex_entities.iter().map(|c| c.name_ident()).collect();
+        let complex_name: Vec<_> = complex_entities.iter().map(|c| c.field_ident()).collect();
+        let complex_holders_name: Vec<_> = complex_entities
+            .iter()
+            .map(|c| format_ident!("{}_holders", c.field_ident()))
+            .collect();
+
         let ruststep_path = prefix.as_path();
 
+        // Complex entity instances written with the names of their components and supertypes
+        let complex_instances: Vec<_> = complex_entities
+            .iter()
+            .zip(&complex_name)
+            .map(|(c, field)| {
+                let names = c
+                    .type_names
+                    .iter()
+                    .map(|name| name.rsplit('.').next().unwrap());
+                quote! {
+                    #ruststep_path::tables::complex_entity_instances(&self.#field, &[#(#names),*])?
+                }
+            })
+            .collect();
+
+        // `UNIQUE` rules referring only explicit attributes
+        let mut unique_checks = Vec::new();
+        for entity in entities {
+            let table = format_ident!("{}", entity.name.as_str().into_safe());
+            let ty = format_ident!("{}", entity.name.to_pascal_case());
+            for rule in entity.uniques.iter().filter(|rule| rule.is_supported()) {
+                let label = match &rule.label {
+                    Some(label) => quote! { Some(#label) },
+                    None => quote! { None },
+                };
+                let attrs = rule
+                    .attributes
+                    .iter()
+                    .map(|attr| format_ident!("{}", attr.as_str().into_safe()));
+                unique_checks.push(quote! {
+                    #ruststep_path::tables::check_unique(&self.#table, #label, |holder: &as_holder!(#ty)| {
+                        Ok(vec![ #( #ruststep_path::ast::ser::to_parameter(&holder.#attrs)?, )* ])
+                    })?
+                });
+            }
+        }
+
+        // Owned types of entities read from a single record
+        let record_types: Vec<_> = entities
+            .iter()
+            .map(|e| format_ident!("{}", e.name.to_pascal_case()))
+            .collect();
+
         quote! {
             pub mod #name {
-                use #ruststep_path::{as_holder, Holder, TableInit, primitive::*, derive_more::*};
+                use #ruststep_path::{as_holder, express_builtins::TypeOf, ApproxEq, HashBits, Holder, Serialize, TableInit, primitive::*, derive_more::*};
                 use std::collections::HashMap;
 
                 #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+                #[table_init(schema = #schema_name)]
                 pub struct Tables {
                     #(
                     #holder_name: HashMap<u64, as_holder!(#entity_types)>,
                     )*
+                    #(
+                    #[table_init(complex)]
+                    #complex_name: HashMap<u64, as_holder!(#complex_types)>,
+                    )*
                 }
 
                 impl Tables {
@@ -84,10 +219,99 @@ impl Schema {
                         &self.#holder_name
                     }
                     )*
+                    #(
+                    pub fn #complex_holders_name(&self) -> &HashMap<u64, as_holder!(#complex_types)> {
+                        &self.#complex_name
+                    }
+                    )*
+
+                    /// Ids of all entities in arbitrary order, including complex entity instances
+                    pub fn all_ids(&self) -> impl Iterator<Item = u64> + '_ {
+                        let ids: Vec<Box<dyn Iterator<Item = u64> + '_>> = vec![
+                            #( Box::new(self.#holder_name.keys().cloned()), )*
+                            #( Box::new(self.#complex_name.keys().cloned()), )*
+                        ];
+                        ids.into_iter().flatten()
+                    }
+
+                    /// Records of all entities in arbitrary order, with references kept as references
+                    ///
+                    /// Complex entity instances are not included yet.
+                    pub fn all_records(&self) -> impl Iterator<Item = #ruststep_path::error::Result<(u64, #ruststep_path::ast::Record)>> + '_ {
+                        let records: Vec<Box<dyn Iterator<Item = _> + '_>> = vec![
+                            #( #ruststep_path::tables::records(&self.#holder_name), )*
+                        ];
+                        records.into_iter().flatten()
+                    }
+
+                    /// Check `UNIQUE` rules, and returns entities sharing the same values
+                    ///
+                    /// Rules are checked within the table of each entity,
+                    /// i.e. instances of its subtypes are not checked yet.
+                    pub fn check_unique(&self) -> #ruststep_path::error::Result<Vec<#ruststep_path::tables::UniqueViolation>> {
+                        let violations: Vec<Vec<#ruststep_path::tables::UniqueViolation>> = vec![
+                            #( #unique_checks, )*
+                        ];
+                        Ok(violations.into_iter().flatten().collect())
+                    }
+
+                    /// Compare entities with another tables by their ids,
+                    /// where `REAL` values differing at most `tol` are regarded as equal
+                    ///
+                    /// Complex entity instances are not compared yet.
+                    pub fn diff(&self, other: &Self, tol: f64) -> #ruststep_path::error::Result<#ruststep_path::tables::TablesDiff> {
+                        #ruststep_path::tables::diff_records(self.all_records(), other.all_records(), tol)
+                    }
+
+                    /// Write all entities into a whole exchange structure with `header`,
+                    /// where references are kept as references.
+                    ///
+                    /// The output is read back by `TableInit::from_exchange` into the same tables.
+                    pub fn to_step_string(&self, header: #ruststep_path::header::HeaderInfo) -> #ruststep_path::error::Result<String> {
+                        let mut entities = Vec::new();
+                        #(
+                        entities.append(&mut #ruststep_path::tables::entity_instances(&self.#holder_name)?);
+                        )*
+                        #(
+                        entities.append(&mut #complex_instances);
+                        )*
+                        #ruststep_path::tables::write_exchange::<Self>(&header, entities)
+                    }
+
+                    /// Store each record with its entity id into the table of its entity name,
+                    /// where records whose entity names are not in this schema are skipped.
+                    ///
+                    /// Use `TableInit::from_records_lossy` to get the skipped records.
+                    pub fn try_from_records(records: impl IntoIterator<Item = (u64, #ruststep_path::ast::Record)>) -> #ruststep_path::error::Result<Self> {
+                        let (tables, _unknowns) = <Self as #ruststep_path::tables::TableInit>::from_records_lossy(records)?;
+                        Ok(tables)
+                    }
+                }
+
+                /// Store each record with its entity id into the table of its entity name,
+                /// where records which cannot be stored are skipped, see `TableInit::from_records_skipping`.
+                ///
+                /// Use `Tables::try_from_records` to get errors instead.
+                impl ::std::iter::FromIterator<(u64, #ruststep_path::ast::Record)> for Tables {
+                    fn from_iter<I: IntoIterator<Item = (u64, #ruststep_path::ast::Record)>>(iter: I) -> Self {
+                        <Self as #ruststep_path::tables::TableInit>::from_records_skipping(iter)
+                    }
+                }
+
+                #(
+                impl ::std::convert::TryFrom<&#ruststep_path::ast::Record> for #record_types {
+                    type Error = #ruststep_path::error::Error;
+                    fn try_from(record: &#ruststep_path::ast::Record) -> #ruststep_path::error::Result<Self> {
+                        #ruststep_path::tables::owned_from_record::<as_holder!(#record_types)>(record)
+                    }
                 }
+                )*
 
+                #(#constants)*
+                #(#functions)*
                 #(#types)*
-                #(#entities)*
+                #(#entity_tokens)*
+                #(#complex_tokens)*
             }
         }
     }
@@ -2,34 +2,38 @@ use inflector::Inflector;
 use proc_macro2::TokenStream;
 use quote::*;
 
+use super::CodegenOptions;
 use crate::ir::*;
 
-impl ToTokens for TypeDecl {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+impl TypeDecl {
+    pub fn to_token_stream(&self, options: CodegenOptions) -> TokenStream {
         match self {
-            TypeDecl::Simple(simple) => simple.to_tokens(tokens),
-            TypeDecl::Rename(rename) => rename.to_tokens(tokens),
-            TypeDecl::Enumeration(e) => e.to_tokens(tokens),
-            TypeDecl::Select(select) => select.to_tokens(tokens),
+            TypeDecl::Simple(simple) => simple.to_token_stream(options),
+            TypeDecl::Rename(rename) => rename.to_token_stream(options),
+            // Enumerations always derive serde traits
+            TypeDecl::Enumeration(e) => e.to_token_stream(),
+            TypeDecl::Select(select) => select.to_token_stream(options),
         }
     }
 }
 
-impl ToTokens for Simple {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+impl Simple {
+    fn to_token_stream(&self, options: CodegenOptions) -> TokenStream {
         let field_name = format_ident!("{}", &self.id.to_snake_case());
         let id = format_ident!("{}", &self.id.to_pascal_case());
         let ty = &self.ty;
         let (derive, _) = simple_meta(&field_name);
-        tokens.append_all(quote! {
+        let serde = options.serde_attributes();
+        quote! {
             #derive
+            #serde
             pub struct #id(pub #ty);
-        });
+        }
     }
 }
 
-impl ToTokens for Rename {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+impl Rename {
+    fn to_token_stream(&self, options: CodegenOptions) -> TokenStream {
         let field_name = format_ident!("{}", &self.id.to_snake_case());
         let id = format_ident!("{}", &self.id.to_pascal_case());
         let ty = &self.ty;
@@ -45,18 +49,20 @@ impl ToTokens for Rename {
             }
             _ => rename_meta(&field_name),
         };
+        let serde = options.serde_attributes();
 
-        tokens.append_all(quote! {
+        quote! {
             #derive
+            #serde
             pub struct #id(#use_place_holder pub #ty);
-        });
+        }
     }
 }
 
 fn simple_meta(field_name: &syn::Ident) -> (TokenStream, TokenStream) {
     (
         quote! {
-            #[derive(Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, ::ruststep_derive::Holder)]
+            #[derive(Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, ::ruststep_derive::Holder, ::ruststep_derive::Serialize, ::ruststep_derive::ApproxEq, ::ruststep_derive::HashBits)]
             #[holder(table = Tables)]
             #[holder(field = #field_name)]
             #[holder(generate_deserialize)]
@@ -68,7 +74,7 @@ fn simple_meta(field_name: &syn::Ident) -> (TokenStream, TokenStream) {
 fn rename_meta(field_name: &syn::Ident) -> (TokenStream, TokenStream) {
     (
         quote! {
-            #[derive(Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, ::ruststep_derive::Holder)]
+            #[derive(Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, ::ruststep_derive::Holder, ::ruststep_derive::Serialize, ::ruststep_derive::ApproxEq, ::ruststep_derive::HashBits)]
             #[holder(table = Tables)]
             #[holder(field = #field_name)]
             #[holder(generate_deserialize)]
@@ -86,7 +92,7 @@ impl ToTokens for Enumeration {
             .map(|i| format_ident!("{}", i.to_pascal_case()))
             .collect();
         tokens.append_all(quote! {
-            #[derive(Debug, Clone, PartialEq, ::serde::Deserialize)]
+            #[derive(Debug, Clone, PartialEq, ::serde::Deserialize, ::serde::Serialize, ::ruststep_derive::ApproxEq, ::ruststep_derive::HashBits)]
             pub enum #id {
                 #( #items ),*
             }
@@ -94,8 +100,8 @@ impl ToTokens for Enumeration {
     }
 }
 
-impl ToTokens for Select {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+impl Select {
+    fn to_token_stream(&self, options: CodegenOptions) -> TokenStream {
         let id = format_ident!("{}", &self.id.to_pascal_case());
         let mut entries = Vec::new();
         let mut entry_types = Vec::new();
@@ -128,8 +134,10 @@ impl ToTokens for Select {
                 _ => unimplemented!(),
             }
         }
-        tokens.append_all(quote! {
-            #[derive(Debug, Clone, PartialEq, Holder)]
+        let serde = options.serde_attributes();
+        quote! {
+            #[derive(Debug, Clone, PartialEq, Holder, Serialize, ApproxEq, HashBits)]
+            #serde
             #[holder(table = Tables)]
             #[holder(generate_deserialize)]
             pub enum #id {
@@ -138,6 +146,6 @@ impl ToTokens for Select {
                 #entries(#entry_types)
                 ),*
             }
-        });
+        }
     }
 }
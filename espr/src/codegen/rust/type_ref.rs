@@ -4,8 +4,26 @@ use quote::*;
 
 use crate::ir::*;
 
+/// Upper limit of length of fixed-size array `[T; N]`,
+/// since `serde` implements `Serialize` and `Deserialize` only for arrays up to 32 elements
+const MAX_FIXED_SIZE: usize = 32;
+
 impl ToTokens for TypeRef {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.type_tokens(false, tokens)
+    }
+}
+
+impl TypeRef {
+    /// Rust type where references to entities are wrapped by `Rc`,
+    /// see [CodegenOptions::shared](super::CodegenOptions::shared)
+    pub(super) fn to_shared_tokens(&self) -> TokenStream {
+        let mut tokens = TokenStream::new();
+        self.type_tokens(true, &mut tokens);
+        tokens
+    }
+
+    fn type_tokens(&self, shared: bool, tokens: &mut TokenStream) {
         use TypeRef::*;
         match self {
             SimpleType(ty) => ty.to_tokens(tokens),
@@ -21,10 +39,39 @@ impl ToTokens for TypeRef {
                 } else {
                     format_ident!("{}", name.to_pascal_case())
                 };
-                tokens.append_all(quote! { #name });
+                if shared {
+                    tokens.append_all(quote! { ::std::rc::Rc<#name> });
+                } else {
+                    tokens.append_all(quote! { #name });
+                }
             }
             Set { base, .. } | List { base, .. } => {
-                tokens.append_all(quote! { Vec<#base> });
+                let mut base_tokens = TokenStream::new();
+                base.type_tokens(shared, &mut base_tokens);
+                tokens.append_all(quote! { Vec<#base_tokens> });
+            }
+            Array {
+                base,
+                bound,
+                optional,
+                ..
+            } => {
+                let mut base_tokens = TokenStream::new();
+                base.type_tokens(shared, &mut base_tokens);
+                let elem = if *optional {
+                    quote! { Option<#base_tokens> }
+                } else {
+                    base_tokens
+                };
+                // `ARRAY [1:3] OF REAL` is mapped to `[f64; 3]`,
+                // and it falls back to `Vec` if the bound is not constant.
+                match bound.as_ref().and_then(Bound::fixed_size) {
+                    Some(size) if size <= MAX_FIXED_SIZE => {
+                        let size = proc_macro2::Literal::usize_unsuffixed(size);
+                        tokens.append_all(quote! { [#elem; #size] })
+                    }
+                    _ => tokens.append_all(quote! { Vec<#elem> }),
+                }
             }
         }
     }
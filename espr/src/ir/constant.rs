@@ -0,0 +1,214 @@
+use super::{namespace::*, scope::*, *};
+use crate::ast;
+
+/// Constant declared in `CONSTANT` block of schema
+///
+/// ```text
+/// CONSTANT
+///   max_len : INTEGER := 80;
+///   dummy_gri : geometric_representation_item := representation_item('') || geometric_representation_item();
+/// END_CONSTANT;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constant {
+    pub name: String,
+    pub ty: TypeRef,
+    /// Value evaluated at compile time,
+    /// or `None` if the initializer is not a literal nor a reference to another such constant
+    pub value: Option<ConstantValue>,
+}
+
+/// Value of [Constant] evaluated at compile time
+#[derive(Debug, Clone)]
+pub enum ConstantValue {
+    Real(f64),
+    Integer(i64),
+    String(String),
+    Boolean(bool),
+    Logical(ast::Logical),
+}
+
+// Real value is compared bitwise to satisfy `Eq`
+impl PartialEq for ConstantValue {
+    fn eq(&self, other: &Self) -> bool {
+        use ConstantValue::*;
+        match (self, other) {
+            (Real(a), Real(b)) => a.to_bits() == b.to_bits(),
+            (Integer(a), Integer(b)) => a == b,
+            (String(a), String(b)) => a == b,
+            (Boolean(a), Boolean(b)) => a == b,
+            (Logical(a), Logical(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ConstantValue {}
+
+/// Untyped value of an initializer expression
+enum Value {
+    Number(f64),
+    String(String),
+    Logical(ast::Logical),
+}
+
+impl Value {
+    /// Evaluate literals, signs, built-in constants, and references to other constants in `constants`
+    fn evaluate(expr: &ast::Expression, constants: &[ast::Constant], depth: usize) -> Option<Self> {
+        // Cyclic references between constants
+        if depth > constants.len() {
+            return None;
+        }
+        match expr {
+            ast::Expression::Literal(literal) => match literal {
+                ast::Literal::Real(value) => Some(Value::Number(*value)),
+                ast::Literal::String(value) => Some(Value::String(value.clone())),
+                ast::Literal::Logial(value) => Some(Value::Logical(value.clone())),
+                ast::Literal::Binary(_) => None,
+            },
+            ast::Expression::Unary { op, arg } => {
+                match (op, Self::evaluate(arg, constants, depth)?) {
+                    (ast::UnaryOperator::Plus, Value::Number(value)) => Some(Value::Number(value)),
+                    (ast::UnaryOperator::Minus, Value::Number(value)) => {
+                        Some(Value::Number(-value))
+                    }
+                    _ => None,
+                }
+            }
+            ast::Expression::QualifiableFactor { factor, qualifiers } if qualifiers.is_empty() => {
+                match factor {
+                    ast::QualifiableFactor::BuiltInConstant(ast::BuiltInConstant::Pi) => {
+                        Some(Value::Number(std::f64::consts::PI))
+                    }
+                    ast::QualifiableFactor::BuiltInConstant(ast::BuiltInConstant::Napier) => {
+                        Some(Value::Number(std::f64::consts::E))
+                    }
+                    // EXPRESS identifiers are case insensitive
+                    ast::QualifiableFactor::Reference(name) => constants
+                        .iter()
+                        .find(|c| c.name.eq_ignore_ascii_case(name))
+                        .and_then(|c| Self::evaluate(&c.expr, constants, depth + 1)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn into_typed(self, ty: &TypeRef) -> Option<ConstantValue> {
+        use ast::SimpleType::*;
+        let ty = match ty {
+            TypeRef::SimpleType(SimpleType(ty)) => ty,
+            _ => return None,
+        };
+        match (ty, self) {
+            (Real | Number, Value::Number(value)) => Some(ConstantValue::Real(value)),
+            (Integer, Value::Number(value)) if value.fract() == 0.0 => {
+                Some(ConstantValue::Integer(value as i64))
+            }
+            (String_ { .. }, Value::String(value)) => Some(ConstantValue::String(value)),
+            (Boolen, Value::Logical(ast::Logical::True)) => Some(ConstantValue::Boolean(true)),
+            (Boolen, Value::Logical(ast::Logical::False)) => Some(ConstantValue::Boolean(false)),
+            (Logical, Value::Logical(value)) => Some(ConstantValue::Logical(value)),
+            _ => None,
+        }
+    }
+}
+
+impl Constant {
+    /// Legalize a constant in `constants` declared in the same schema,
+    /// which are looked up to evaluate references between constants.
+    pub fn legalize(
+        ns: &Namespace,
+        ss: &Constraints,
+        scope: &Scope,
+        constant: &ast::Constant,
+        constants: &[ast::Constant],
+    ) -> Result<Self, SemanticError> {
+        let ty = TypeRef::legalize(ns, ss, scope, &constant.ty)?;
+        let value = Value::evaluate(&constant.expr, constants, 0).and_then(|v| v.into_typed(&ty));
+        Ok(Constant {
+            name: constant.name.clone(),
+            ty,
+            value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constants(input: &str) -> Vec<Constant> {
+        let st = SyntaxTree::parse(&format!(
+            "SCHEMA s; CONSTANT {} END_CONSTANT; ENTITY a; END_ENTITY; END_SCHEMA;",
+            input
+        ))
+        .unwrap();
+        let ir = IR::from_syntax_tree(&st).unwrap();
+        ir.schemas[0].constants.clone()
+    }
+
+    fn values(input: &str) -> Vec<Option<ConstantValue>> {
+        constants(input).into_iter().map(|c| c.value).collect()
+    }
+
+    #[test]
+    fn literal() {
+        assert_eq!(
+            values(
+                r#"
+                len : INTEGER := 80;
+                neg : INTEGER := -3;
+                ratio : REAL := 0.5;
+                sep : STRING := '.';
+                flag : BOOLEAN := TRUE;
+                state : LOGICAL := UNKNOWN;
+                "#
+            ),
+            vec![
+                Some(ConstantValue::Integer(80)),
+                Some(ConstantValue::Integer(-3)),
+                Some(ConstantValue::Real(0.5)),
+                Some(ConstantValue::String(".".to_string())),
+                Some(ConstantValue::Boolean(true)),
+                Some(ConstantValue::Logical(ast::Logical::Unknown)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reference() {
+        assert_eq!(
+            values(
+                r#"
+                syn_name_len : INTEGER := Pref_Name_Len;
+                pref_name_len : INTEGER := 70;
+                circle_ratio : REAL := PI;
+                "#
+            ),
+            vec![
+                Some(ConstantValue::Integer(70)),
+                Some(ConstantValue::Integer(70)),
+                Some(ConstantValue::Real(std::f64::consts::PI)),
+            ]
+        );
+    }
+
+    #[test]
+    fn not_evaluated() {
+        assert_eq!(
+            values(
+                r#"
+                fraction : INTEGER := 0.5;
+                cycle1 : INTEGER := cycle2;
+                cycle2 : INTEGER := cycle1;
+                product : INTEGER := 2 * 3;
+                chars : SET OF STRING := ['a', 'b'];
+                dummy : a := a();
+                "#
+            ),
+            vec![None; 6]
+        );
+    }
+}
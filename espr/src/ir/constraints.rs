@@ -62,7 +62,7 @@ impl ConstraintExpr {
     ) -> Result<Self, SemanticError> {
         use ast::SuperTypeExpression::*;
         Ok(match expr {
-            Reference(name) => Self::Reference(ns.resolve(scope, name)?.0),
+            Reference(name) => Self::Reference(ns.resolve_as(scope, name, ScopeType::Entity)?.0),
             AndOr { factors } => Self::AndOr(
                 factors
                     .iter()
@@ -129,7 +129,8 @@ pub fn gather_constraint_expr(
         let scope = root.schema(&schema.name);
         for entity in &schema.entities {
             match &entity.constraint {
-                Some(ast::Constraint::SuperTypeRule(expr)) => {
+                Some(ast::Constraint::SuperTypeRule(expr))
+                | Some(ast::Constraint::AbstractSuperType(Some(expr))) => {
                     let result = exprs.insert(
                         Path::entity(&scope, &entity.name),
                         ConstraintExpr::from_ast_expr(ns, &scope, expr)?,
@@ -156,7 +157,8 @@ pub fn gather_constraint_expr(
         let scope = root.schema(&schema.name);
         for constraint in &schema.subtype_constraints {
             if let Some(expr) = &constraint.expr {
-                let (path, _index) = ns.resolve(&scope, &constraint.entity)?;
+                let (path, _index) =
+                    ns.resolve_as(&scope, &constraint.entity, ScopeType::Entity)?;
                 let expr = ConstraintExpr::from_ast_expr(ns, &scope, expr)?;
                 match exprs.entry(path) {
                     Entry::Occupied(mut e) => {
@@ -183,7 +185,7 @@ pub fn gather_constraint_expr(
         for entity in &schema.entities {
             if let Some(subtype_decl) = &entity.subtype_of {
                 for sup_name in &subtype_decl.entity_references {
-                    let (sup, _) = ns.resolve(&scope, sup_name)?;
+                    let (sup, _) = ns.resolve_as(&scope, sup_name, ScopeType::Entity)?;
                     let subs = super_to_sub.entry(sup).or_default();
                     let sub = Path::entity(&scope, &entity.name);
                     subs.push(sub);
@@ -384,6 +386,51 @@ mod tests {
     END_SCHEMA;
     "#;
 
+    /// External `ONEOF` restricts a part of default subtypes
+    const PERSON_ONEOF_SEPARATE: &str = r#"
+    SCHEMA test_schema;
+      ENTITY person;
+      END_ENTITY;
+
+      ENTITY employee SUBTYPE OF (person);
+      END_ENTITY;
+
+      ENTITY retiree SUBTYPE OF (person);
+      END_ENTITY;
+
+      ENTITY student SUBTYPE OF (person);
+      END_ENTITY;
+
+      SUBTYPE_CONSTRAINT person_prop FOR person;
+        ONEOF(employee, retiree);
+      END_SUBTYPE_CONSTRAINT;
+    END_SCHEMA;
+    "#;
+
+    /// `ABSTRACT SUPERTYPE OF` merged with external `SUBTYPE_CONSTRAINT`
+    const ABSTRACT_SUPERTYPE_OF_SEPARATE: &str = r#"
+    SCHEMA test_schema;
+      ENTITY person ABSTRACT SUPERTYPE OF (ONEOF(male, female));
+      END_ENTITY;
+
+      ENTITY male SUBTYPE OF (person);
+      END_ENTITY;
+
+      ENTITY female SUBTYPE OF (person);
+      END_ENTITY;
+
+      ENTITY citizen SUBTYPE OF (person);
+      END_ENTITY;
+
+      ENTITY alien SUBTYPE OF (person);
+      END_ENTITY;
+
+      SUBTYPE_CONSTRAINT person_prop FOR person;
+        ONEOF(citizen, alien);
+      END_SUBTYPE_CONSTRAINT;
+    END_SCHEMA;
+    "#;
+
     /// Example for using `SUPERTYPE OF` declaration
     const SUPERTYPE_OF: &str = r#"
     SCHEMA test_schema;
@@ -522,6 +569,53 @@ mod tests {
         );
     }
 
+    /// `ONEOF`, `AND` and `ANDOR` nested in a single `SUBTYPE_CONSTRAINT`
+    const NESTED: &str = r#"
+    SCHEMA test_schema;
+      ENTITY base;
+      END_ENTITY;
+
+      ENTITY a SUBTYPE OF (base);
+      END_ENTITY;
+
+      ENTITY b SUBTYPE OF (base);
+      END_ENTITY;
+
+      ENTITY c SUBTYPE OF (base);
+      END_ENTITY;
+
+      ENTITY d SUBTYPE OF (base);
+      END_ENTITY;
+
+      SUBTYPE_CONSTRAINT nested FOR base;
+        ONEOF(a, b AND c) ANDOR d;
+      END_SUBTYPE_CONSTRAINT;
+    END_SCHEMA;
+    "#;
+
+    /// `ANDOR` nested in `ONEOF` nested in `AND` in deprecated `SUPERTYPE OF` form
+    const SUPERTYPE_OF_NESTED: &str = r#"
+    SCHEMA test_schema;
+      ENTITY base SUPERTYPE OF ((a ANDOR b) AND ONEOF(c, ONEOF(d, e)));
+      END_ENTITY;
+
+      ENTITY a SUBTYPE OF (base);
+      END_ENTITY;
+
+      ENTITY b SUBTYPE OF (base);
+      END_ENTITY;
+
+      ENTITY c SUBTYPE OF (base);
+      END_ENTITY;
+
+      ENTITY d SUBTYPE OF (base);
+      END_ENTITY;
+
+      ENTITY e SUBTYPE OF (base);
+      END_ENTITY;
+    END_SCHEMA;
+    "#;
+
     #[test]
     fn constraint_oneof() {
         let st = ast::SyntaxTree::parse(PET).unwrap();
@@ -621,4 +715,104 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn separate_oneof_constraint() {
+        let st = ast::SyntaxTree::parse(PERSON_ONEOF_SEPARATE).unwrap();
+        let ns = Namespace::new(&st);
+        let c = Constraints::new(&ns, &st).unwrap();
+        let scope = Scope::root().schema("test_schema");
+        // `employee` and `retiree` never appear together,
+        // while `student` is still combined by default `ANDOR`
+        assert_eq!(
+            dbg!(c),
+            Constraints {
+                instantiables: maplit::hashmap! {
+                    Path::entity(&scope, "person") => vec![
+                        vec![Path::entity(&scope, "employee")],
+                        vec![Path::entity(&scope, "retiree")],
+                        vec![Path::entity(&scope, "student")],
+                        vec![Path::entity(&scope, "employee"), Path::entity(&scope, "student")],
+                        vec![Path::entity(&scope, "retiree"), Path::entity(&scope, "student")],
+                    ]
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn abstract_supertype_of_with_separate_constraint() {
+        let st = ast::SyntaxTree::parse(ABSTRACT_SUPERTYPE_OF_SEPARATE).unwrap();
+        let ns = Namespace::new(&st);
+        let c = Constraints::new(&ns, &st).unwrap();
+        let scope = Scope::root().schema("test_schema");
+        assert_eq!(
+            dbg!(c),
+            Constraints {
+                instantiables: maplit::hashmap! {
+                    Path::entity(&scope, "person") => vec![
+                        vec![Path::entity(&scope, "male")],
+                        vec![Path::entity(&scope, "female")],
+                        vec![Path::entity(&scope, "citizen")],
+                        vec![Path::entity(&scope, "alien")],
+                        vec![Path::entity(&scope, "male"), Path::entity(&scope, "citizen")],
+                        vec![Path::entity(&scope, "male"), Path::entity(&scope, "alien")],
+                        vec![Path::entity(&scope, "female"), Path::entity(&scope, "citizen")],
+                        vec![Path::entity(&scope, "female"), Path::entity(&scope, "alien")],
+                    ]
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn nested_constraint() {
+        let st = ast::SyntaxTree::parse(NESTED).unwrap();
+        let ns = Namespace::new(&st);
+        let c = Constraints::new(&ns, &st).unwrap();
+        let scope = Scope::root().schema("test_schema");
+        let p = |name| Path::entity(&scope, name);
+        // `b` and `c` always appear together, and never with `a`
+        assert_eq!(
+            dbg!(c),
+            Constraints {
+                instantiables: maplit::hashmap! {
+                    p("base") => vec![
+                        vec![p("a")],
+                        vec![p("d")],
+                        vec![p("a"), p("d")],
+                        vec![p("b"), p("c")],
+                        vec![p("b"), p("c"), p("d")],
+                    ]
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn supertype_of_nested() {
+        let st = ast::SyntaxTree::parse(SUPERTYPE_OF_NESTED).unwrap();
+        let ns = Namespace::new(&st);
+        let c = Constraints::new(&ns, &st).unwrap();
+        let scope = Scope::root().schema("test_schema");
+        let p = |name| Path::entity(&scope, name);
+        assert_eq!(
+            dbg!(c),
+            Constraints {
+                instantiables: maplit::hashmap! {
+                    p("base") => vec![
+                        vec![p("a"), p("c")],
+                        vec![p("a"), p("d")],
+                        vec![p("a"), p("e")],
+                        vec![p("b"), p("c")],
+                        vec![p("b"), p("d")],
+                        vec![p("b"), p("e")],
+                        vec![p("a"), p("b"), p("c")],
+                        vec![p("a"), p("b"), p("d")],
+                        vec![p("a"), p("b"), p("e")],
+                    ]
+                }
+            }
+        );
+    }
 }
@@ -23,6 +23,288 @@ pub struct Entity {
     /// then this `supertypes` is `[base]`.
     ///
     pub supertypes: Vec<TypeRef>,
+
+    /// If declared as `ABSTRACT` or `ABSTRACT SUPERTYPE`,
+    /// i.e. this entity is instantiated only through its subtypes
+    pub is_abstract: bool,
+
+    /// Remarks on this entity in EXPRESS schema
+    pub remarks: Vec<String>,
+
+    /// Attributes of supertypes redeclared in this entity, e.g. `SELF\base.x : INTEGER;`
+    ///
+    /// They are not included in [Entity::attributes]
+    /// since their values are placed at the positions of the original attributes
+    /// in the exchange structure.
+    pub redeclared: Vec<RedeclaredAttribute>,
+
+    /// Domain rules in `WHERE` clause
+    ///
+    /// Their dependencies are resolved by [Schema::legalize]
+    /// since functions and constants are declared in the schema.
+    pub rules: Vec<WhereRule>,
+
+    /// Uniqueness rules in `UNIQUE` clause
+    pub uniques: Vec<UniqueRule>,
+
+    /// Names of this entity and all its supertypes returned by `TYPEOF`,
+    /// qualified by schema names in upper case, e.g. `SCHEMA.ENTITY`
+    pub type_names: Vec<String>,
+}
+
+/// Attribute of a supertype redeclared in a subtype
+///
+/// ```text
+/// ENTITY sub SUBTYPE OF (base);
+///   SELF\base.x : INTEGER;                     -- specialize the type of `x`
+/// DERIVE
+///   SELF\base.y RENAMED w : REAL := 2.0 * x;   -- compute `y` and refer it as `w`
+/// END_ENTITY;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedeclaredAttribute {
+    /// Supertype declaring the original attribute
+    pub supertype: TypeRef,
+    /// Name of the original attribute
+    pub name: String,
+    /// New name given by `RENAMED`
+    pub rename: Option<String>,
+    /// Redeclared type
+    pub ty: TypeRef,
+    pub optional: bool,
+    /// Redeclared in `DERIVE` clause,
+    /// i.e. the value is omitted as `*` in the exchange structure
+    pub derived: bool,
+    /// Value computed in `DERIVE` clause if translated into Rust, see [Schema::legalize]
+    pub value: Option<DerivedValue>,
+}
+
+/// Value of an attribute derived in a subtype, translated from the expression in `DERIVE` clause
+///
+/// It is translated only if the original attribute is a simple value,
+/// and the expression refers only simple attributes of the subtype itself
+/// in addition to what a [Function] body may refer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivedValue {
+    /// Supertypes from the direct supertype of the subtype to the one declaring the attribute
+    pub path: Vec<String>,
+    /// Type of the original attribute
+    pub ty: ValueType,
+    /// Whether the original attribute is `OPTIONAL`
+    pub optional: bool,
+    pub expr: Expression,
+}
+
+impl RedeclaredAttribute {
+    fn new(
+        ns: &Namespace,
+        ss: &Constraints,
+        scope: &Scope,
+        decl: &ast::AttributeDecl,
+        ty: &ast::Type,
+    ) -> Result<Self, SemanticError> {
+        let (group, name, rename) = match decl {
+            ast::AttributeDecl::Qualified {
+                group,
+                attribute,
+                rename,
+            } => (group, attribute, rename),
+            ast::AttributeDecl::Reference(_) => unreachable!("Not a redeclaration"),
+        };
+        let (path, _index) = ns.resolve_as(scope, group, ScopeType::Entity)?;
+        Ok(RedeclaredAttribute {
+            supertype: TypeRef::from_path(ns, ss, &path)?,
+            name: name.clone(),
+            rename: rename.clone(),
+            ty: TypeRef::legalize(ns, ss, scope, ty)?,
+            optional: false,
+            derived: false,
+            value: None,
+        })
+    }
+
+    /// Redeclaration in `DERIVE` clause
+    pub fn derived(
+        ns: &Namespace,
+        ss: &Constraints,
+        scope: &Scope,
+        attr: &ast::DerivedAttribute,
+    ) -> Result<Self, SemanticError> {
+        Ok(RedeclaredAttribute {
+            derived: true,
+            ..Self::new(ns, ss, scope, &attr.attr, &attr.ty)?
+        })
+    }
+}
+
+impl Legalize for RedeclaredAttribute {
+    type Input = ast::EntityAttribute;
+
+    fn legalize(
+        ns: &Namespace,
+        ss: &Constraints,
+        scope: &Scope,
+        attr: &Self::Input,
+    ) -> Result<Self, SemanticError> {
+        Ok(RedeclaredAttribute {
+            optional: attr.optional,
+            ..Self::new(ns, ss, scope, &attr.name, &attr.ty)?
+        })
+    }
+}
+
+/// Domain rule in `WHERE` clause of entity, e.g. `wr1: SIZEOF(items) > 0;`
+///
+/// The expression is not translated yet,
+/// but functions and constants it refers are recorded
+/// to decide whether a validator can be generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhereRule {
+    pub label: Option<String>,
+    /// Functions declared in the schema and called in this rule, in order of appearance
+    pub functions: Vec<String>,
+    /// Constants declared in the schema and referred in this rule, in order of appearance
+    pub constants: Vec<String>,
+}
+
+impl WhereRule {
+    pub fn new(rule: &ast::DomainRule, schema: &ast::Schema) -> Self {
+        let mut functions = Vec::new();
+        let mut constants = Vec::new();
+        rule.expr.visit(&mut |expr| {
+            if let ast::Expression::QualifiableFactor { factor, .. } = expr {
+                match factor {
+                    ast::QualifiableFactor::FunctionCall {
+                        name: ast::FunctionCallName::Reference(name),
+                        ..
+                    } => push_declared(
+                        &mut functions,
+                        schema.functions.iter().map(|f| &f.name),
+                        name,
+                    ),
+                    ast::QualifiableFactor::Reference(name) => push_declared(
+                        &mut constants,
+                        schema.constants.iter().map(|c| &c.name),
+                        name,
+                    ),
+                    _ => {}
+                }
+            }
+        });
+        WhereRule {
+            label: rule.label.clone(),
+            functions,
+            constants,
+        }
+    }
+}
+
+/// Uniqueness rule in `UNIQUE` clause of entity, e.g. `ur1: name, id;`
+///
+/// The values of [UniqueRule::attributes] must not be shared by two instances of the entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniqueRule {
+    pub label: Option<String>,
+    /// Explicit attributes of the entity
+    pub attributes: Vec<String>,
+    /// Attributes which cannot be checked, e.g. derived or inverse attributes,
+    /// or attributes of supertypes like `SELF\base.x`
+    pub unsupported: Vec<String>,
+}
+
+impl UniqueRule {
+    fn new(rule: &ast::UniqueRule, explicit: &[EntityAttribute]) -> Self {
+        let mut attributes = Vec::new();
+        let mut unsupported = Vec::new();
+        for attr in &rule.attributes {
+            match attr {
+                ast::AttributeDecl::Reference(name) => {
+                    // EXPRESS identifiers are case insensitive
+                    match explicit.iter().find(|a| a.name.eq_ignore_ascii_case(name)) {
+                        Some(a) => attributes.push(a.name.clone()),
+                        None => unsupported.push(name.clone()),
+                    }
+                }
+                ast::AttributeDecl::Qualified {
+                    group, attribute, ..
+                } => unsupported.push(format!("SELF\\{}.{}", group, attribute)),
+            }
+        }
+        UniqueRule {
+            label: rule.name.clone(),
+            attributes,
+            unsupported,
+        }
+    }
+
+    /// The rule can be checked, i.e. it refers only explicit attributes of the entity
+    pub fn is_supported(&self) -> bool {
+        self.unsupported.is_empty()
+    }
+}
+
+/// Push the declaration `name` refers to into `found` if exists and not yet found
+fn push_declared<'a>(
+    found: &mut Vec<String>,
+    mut declared: impl Iterator<Item = &'a String>,
+    name: &str,
+) {
+    // EXPRESS identifiers are case insensitive
+    if let Some(declared) = declared.find(|d| d.eq_ignore_ascii_case(name)) {
+        if !found.contains(declared) {
+            found.push(declared.clone());
+        }
+    }
+}
+
+/// Combination of entities which can be instantiated only as a complex entity instance,
+/// e.g. `employee` and `student` for `SUPERTYPE OF (employee ANDOR student)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComplexEntity {
+    /// Components sorted by name
+    pub components: Vec<TypeRef>,
+    /// Qualified names of components and their supertypes for `TYPEOF`
+    pub type_names: Vec<String>,
+}
+
+impl ComplexEntity {
+    pub fn from_paths(
+        ns: &Namespace,
+        ss: &Constraints,
+        paths: &[Path],
+    ) -> Result<Self, SemanticError> {
+        let mut paths = paths.to_vec();
+        paths.sort_by(|a, b| a.name.cmp(&b.name));
+        let components = paths
+            .iter()
+            .map(|path| TypeRef::from_path(ns, ss, path))
+            .collect::<Result<Vec<TypeRef>, _>>()?;
+        let mut names = Vec::new();
+        for path in &paths {
+            if let (Named::Entity(entity), _) = ns.get(path)? {
+                for name in type_names(ns, path, entity)? {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+        Ok(ComplexEntity {
+            components,
+            type_names: names,
+        })
+    }
+
+    /// Names of components in snake_case
+    pub fn component_names(&self) -> Vec<&str> {
+        self.components
+            .iter()
+            .map(|ty| match ty {
+                TypeRef::Entity { name, .. } => name.as_str(),
+                _ => unreachable!("Component of complex entity must be an entity"),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,6 +312,14 @@ pub struct EntityAttribute {
     pub name: String,
     pub ty: TypeRef,
     pub optional: bool,
+
+    /// Redeclared in `DERIVE` clause of a subtype, e.g. `SELF\base.y : REAL := 2.0 * z;`,
+    /// i.e. the value is omitted as `*` in the instances of the subtype
+    /// and taken from [RedeclaredAttribute::value] of the subtype
+    pub derived_in_subtype: bool,
+
+    /// Remarks on this attribute in EXPRESS schema
+    pub remarks: Vec<String>,
 }
 
 impl Legalize for EntityAttribute {
@@ -44,12 +334,16 @@ impl Legalize for EntityAttribute {
         let ty = TypeRef::legalize(ns, ss, scope, &attr.ty)?;
         let name = match &attr.name {
             ast::AttributeDecl::Reference(name) => name.clone(),
-            _ => unimplemented!(),
+            ast::AttributeDecl::Qualified { .. } => {
+                unreachable!("Redeclaration is legalized as RedeclaredAttribute")
+            }
         };
         Ok(EntityAttribute {
             name,
             ty,
             optional: attr.optional,
+            derived_in_subtype: false,
+            remarks: attr.remarks.iter().map(|r| r.remark.clone()).collect(),
         })
     }
 }
@@ -64,17 +358,41 @@ impl Legalize for Entity {
         entity: &ast::Entity,
     ) -> Result<Self, SemanticError> {
         let name = entity.name.clone();
-        let attributes = entity
-            .attributes
+        let mut attributes = Vec::new();
+        let mut redeclared = Vec::new();
+        for attr in &entity.attributes {
+            match attr.name {
+                ast::AttributeDecl::Reference(_) => {
+                    attributes.push(EntityAttribute::legalize(ns, ss, scope, attr)?)
+                }
+                ast::AttributeDecl::Qualified { .. } => {
+                    redeclared.push(RedeclaredAttribute::legalize(ns, ss, scope, attr)?)
+                }
+            }
+        }
+        if let Some(derive) = &entity.derive_clause {
+            for attr in &derive.attributes {
+                if let ast::AttributeDecl::Qualified { .. } = attr.attr {
+                    redeclared.push(RedeclaredAttribute::derived(ns, ss, scope, attr)?);
+                }
+            }
+        }
+
+        let uniques = entity
+            .unique_clause
             .iter()
-            .map(|attr| EntityAttribute::legalize(ns, ss, scope, attr))
-            .collect::<Result<Vec<_>, _>>()?;
+            .flat_map(|clause| &clause.rules)
+            .map(|rule| UniqueRule::new(rule, &attributes))
+            .collect();
 
         let supertypes = if let Some(supertypes) = &entity.subtype_of {
             supertypes
                 .entity_references
                 .iter()
-                .map(|sup| TypeRef::from_path(ns, ss, &ns.resolve(scope, sup)?.0))
+                .map(|sup| {
+                    let (path, _index) = ns.resolve_as(scope, sup, ScopeType::Entity)?;
+                    TypeRef::from_path(ns, ss, &path)
+                })
                 .collect::<Result<Vec<TypeRef>, _>>()?
         } else {
             Vec::new()
@@ -99,10 +417,46 @@ impl Legalize for Entity {
             attributes,
             constraints,
             supertypes,
+            is_abstract: entity.is_abstract(),
+            remarks: entity.remarks.iter().map(|r| r.remark.clone()).collect(),
+            redeclared,
+            rules: Vec::new(),
+            uniques,
+            type_names: type_names(ns, &path, entity)?,
         })
     }
 }
 
+/// Qualified names of `entity` at `path` and its supertypes, in breadth-first order
+fn type_names(
+    ns: &Namespace,
+    path: &Path,
+    entity: &ast::Entity,
+) -> Result<Vec<String>, SemanticError> {
+    let mut paths = vec![path.clone()];
+    let mut queue = vec![(path.clone(), entity)];
+    let mut i = 0;
+    while i < queue.len() {
+        let (path, entity) = queue[i].clone();
+        i += 1;
+        let supertypes = entity
+            .subtype_of
+            .iter()
+            .flat_map(|decl| &decl.entity_references);
+        for sup in supertypes {
+            let (sup, _index) = ns.resolve_as(&path.scope, sup, ScopeType::Entity)?;
+            if paths.contains(&sup) {
+                continue;
+            }
+            if let (Named::Entity(e), _) = ns.get(&sup)? {
+                queue.push((sup.clone(), e));
+            }
+            paths.push(sup);
+        }
+    }
+    Ok(paths.iter().map(|p| p.to_string().to_uppercase()).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,4 +472,196 @@ mod tests {
         let entity = Entity::legalize(&ns, &ss, &scope, entity).unwrap();
         dbg!(&entity);
     }
+
+    #[test]
+    fn redeclared() {
+        let st = SyntaxTree::parse(
+            r#"
+            SCHEMA s;
+              ENTITY base;
+                x : NUMBER;
+                y : OPTIONAL REAL;
+              END_ENTITY;
+
+              ENTITY sub SUBTYPE OF (base);
+                SELF\base.x RENAMED rx : REAL;
+                z : REAL;
+              DERIVE
+                SELF\base.y : REAL := 2.0 * z;
+              END_ENTITY;
+            END_SCHEMA;
+            "#,
+        )
+        .unwrap();
+        let ir = IR::from_syntax_tree(&st).unwrap();
+        let sub = &ir.schemas[0].entities[1];
+        assert_eq!(sub.name, "sub");
+        assert_eq!(sub.attributes.len(), 1);
+        assert_eq!(sub.attributes[0].name, "z");
+
+        // `y` is omitted as `*` in the instances of `sub`
+        let base_entity = &ir.schemas[0].entities[0];
+        assert!(!base_entity.attributes[0].derived_in_subtype);
+        assert!(base_entity.attributes[1].derived_in_subtype);
+
+        let base = &sub.supertypes[0];
+        let real = TypeRef::SimpleType(SimpleType(ast::SimpleType::Real));
+        assert_eq!(
+            sub.redeclared,
+            vec![
+                RedeclaredAttribute {
+                    supertype: base.clone(),
+                    name: "x".to_string(),
+                    rename: Some("rx".to_string()),
+                    ty: real.clone(),
+                    optional: false,
+                    derived: false,
+                    value: None,
+                },
+                RedeclaredAttribute {
+                    supertype: base.clone(),
+                    name: "y".to_string(),
+                    rename: None,
+                    ty: real,
+                    optional: false,
+                    derived: true,
+                    value: Some(DerivedValue {
+                        path: vec!["base".to_string()],
+                        ty: ValueType::Real,
+                        optional: true,
+                        expr: Expression::Binary {
+                            op: ast::BinaryOperator::Mul,
+                            lhs: Box::new(Expression::Number(2.0)),
+                            rhs: Box::new(Expression::Parameter {
+                                name: "z".to_string(),
+                                ty: ValueType::Real,
+                            }),
+                        },
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn where_rule_dependencies() {
+        let st = SyntaxTree::parse(
+            r#"
+            SCHEMA s;
+              CONSTANT
+                max_size : INTEGER := 10;
+              END_CONSTANT;
+
+              ENTITY e;
+                items : LIST OF INTEGER;
+              WHERE
+                wr1: SIZEOF(items) <= max_size;
+                wr2: is_valid(items[max_size]) AND is_valid(items);
+                wr3: items[1] > 0;
+              END_ENTITY;
+
+              FUNCTION is_valid(x : GENERIC) : BOOLEAN;
+                RETURN (TRUE);
+              END_FUNCTION;
+            END_SCHEMA;
+            "#,
+        )
+        .unwrap();
+        let ir = IR::from_syntax_tree(&st).unwrap();
+        let rules = &ir.schemas[0].entities[0].rules;
+        assert_eq!(
+            rules,
+            &[
+                WhereRule {
+                    label: Some("wr1".to_string()),
+                    functions: vec![],
+                    constants: vec!["max_size".to_string()],
+                },
+                WhereRule {
+                    label: Some("wr2".to_string()),
+                    functions: vec!["is_valid".to_string()],
+                    constants: vec!["max_size".to_string()],
+                },
+                WhereRule {
+                    label: Some("wr3".to_string()),
+                    functions: vec![],
+                    constants: vec![],
+                },
+            ]
+        );
+        assert_eq!(
+            ir.warnings(),
+            vec![SemanticWarning::RuleDependsOnFunction {
+                entity: "e".to_string(),
+                rule: Some("wr2".to_string()),
+                function: "is_valid".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn unique_rules() {
+        let st = SyntaxTree::parse(
+            r#"
+            SCHEMA s;
+              ENTITY base;
+                id : STRING;
+              END_ENTITY;
+
+              ENTITY e SUBTYPE OF (base);
+                name : STRING;
+                Version : INTEGER;
+              DERIVE
+                label : STRING := name;
+              UNIQUE
+                ur1: name, version;
+                ur2: label;
+                SELF\base.id;
+              END_ENTITY;
+            END_SCHEMA;
+            "#,
+        )
+        .unwrap();
+        let ir = IR::from_syntax_tree(&st).unwrap();
+        let e = ir.schemas[0]
+            .entities
+            .iter()
+            .find(|e| e.name == "e")
+            .unwrap();
+        assert_eq!(
+            e.uniques,
+            vec![
+                UniqueRule {
+                    label: Some("ur1".to_string()),
+                    attributes: vec!["name".to_string(), "Version".to_string()],
+                    unsupported: vec![],
+                },
+                UniqueRule {
+                    label: Some("ur2".to_string()),
+                    attributes: vec![],
+                    unsupported: vec!["label".to_string()],
+                },
+                UniqueRule {
+                    label: None,
+                    attributes: vec![],
+                    unsupported: vec!["SELF\\base.id".to_string()],
+                },
+            ]
+        );
+        assert_eq!(
+            ir.warnings(),
+            vec![
+                SemanticWarning::UniqueRuleNotChecked {
+                    entity: "e".to_string(),
+                    rule: Some("ur2".to_string()),
+                    attribute: "label".to_string(),
+                },
+                SemanticWarning::UniqueRuleNotChecked {
+                    entity: "e".to_string(),
+                    rule: None,
+                    attribute: "SELF\\base.id".to_string(),
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,648 @@
+use super::*;
+use crate::ast;
+
+/// Type of parameters and return values of [Function] which can be represented in Rust
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueType {
+    Integer,
+    Real,
+    Number,
+    Boolean,
+    Logical,
+    String,
+    /// `SET`, `BAG`, `LIST`, or `ARRAY` of simple values
+    Aggregate(Box<ValueType>),
+}
+
+impl ValueType {
+    /// Returns the name of the type not representable in Rust as `Err`, e.g. `GENERIC`
+    pub(super) fn new(ty: &ast::Type) -> Result<Self, String> {
+        use ast::SimpleType::*;
+        match ty {
+            ast::Type::Simple(ty) => match ty {
+                Integer => Ok(ValueType::Integer),
+                Real => Ok(ValueType::Real),
+                Number => Ok(ValueType::Number),
+                Boolen => Ok(ValueType::Boolean),
+                Logical => Ok(ValueType::Logical),
+                String_ { .. } => Ok(ValueType::String),
+                Binary { .. } => Err("BINARY".to_string()),
+            },
+            ast::Type::Set { base, .. }
+            | ast::Type::Bag { base, .. }
+            | ast::Type::List { base, .. }
+            | ast::Type::Array { base, .. } => match Self::new(base)? {
+                ValueType::Aggregate(_) => Err("nested aggregate".to_string()),
+                base => Ok(ValueType::Aggregate(Box::new(base))),
+            },
+            ast::Type::Named(name) => Err(format!("`{}`", name)),
+            ast::Type::Enumeration { .. } => Err("ENUMERATION".to_string()),
+            ast::Type::Select { .. } => Err("SELECT".to_string()),
+            ast::Type::Aggregate { .. } => Err("AGGREGATE".to_string()),
+            ast::Type::GenericEntity(_) => Err("GENERIC_ENTITY".to_string()),
+            ast::Type::Generic(_) => Err("GENERIC".to_string()),
+        }
+    }
+
+    fn kind(&self) -> Kind {
+        match self {
+            ValueType::Integer | ValueType::Real | ValueType::Number => Kind::Number,
+            ValueType::Boolean => Kind::Boolean,
+            ValueType::Logical => Kind::Logical,
+            ValueType::String => Kind::String,
+            ValueType::Aggregate(_) => Kind::Aggregate,
+        }
+    }
+}
+
+/// Parameter of [Function]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionParameter {
+    pub name: String,
+    pub ty: ValueType,
+}
+
+/// Parameters and return type of [Function]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub parameters: Vec<FunctionParameter>,
+    pub return_type: ValueType,
+}
+
+impl Signature {
+    /// Returns the construct not representable in Rust as `Err`, e.g. ``parameter `x` of GENERIC``
+    fn new(function: &ast::Function) -> Result<Self, String> {
+        let parameters = function
+            .parameters
+            .iter()
+            .map(|p| {
+                Ok(FunctionParameter {
+                    name: p.name.clone(),
+                    ty: ValueType::new(&p.ty)
+                        .map_err(|ty| format!("parameter `{}` of {}", p.name, ty))?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Signature {
+            parameters,
+            return_type: ValueType::new(&function.return_type)
+                .map_err(|ty| format!("return type {}", ty))?,
+        })
+    }
+}
+
+/// Function declared in schema
+///
+/// A function is translated into Rust only if
+///
+/// - its parameters and return value are simple values or aggregates of them, see [ValueType],
+/// - its body consists of a single `RETURN` statement without local variables, and
+/// - the returned expression consists of literals, parameters, evaluated constants,
+///   arithmetic, comparison, and boolean operators,
+///   built-in functions `SIZEOF`, `HIINDEX`, `LOINDEX`, `EXISTS`, and `ABS`,
+///   and calls of other translated functions.
+///
+/// ```text
+/// FUNCTION is_positive(x : REAL) : BOOLEAN;
+///   RETURN (x > 0.0);
+/// END_FUNCTION;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name: String,
+    /// `Err` describing the parameter or return value whose type cannot be represented in Rust
+    pub signature: Result<Signature, String>,
+    /// Translated expression returned by this function, or `None` if not translated
+    pub body: Option<Expression>,
+}
+
+impl Function {
+    /// Legalize all functions declared in a schema,
+    /// since a function is translated only when the functions it calls are translated.
+    pub fn legalize_all(functions: &[ast::Function], constants: &[Constant]) -> Vec<Self> {
+        let signatures: Vec<Result<Signature, String>> =
+            functions.iter().map(Signature::new).collect();
+        let mut ir: Vec<Function> = functions
+            .iter()
+            .zip(&signatures)
+            .map(|(function, signature)| Function {
+                name: function.name.clone(),
+                signature: signature.clone(),
+                body: None,
+            })
+            .collect();
+        // Translate repeatedly until no more function is translated,
+        // since a call is translated only if the callee is translated
+        loop {
+            let mut updated = false;
+            for (i, function) in functions.iter().enumerate() {
+                if ir[i].body.is_some() {
+                    continue;
+                }
+                let signature = match &signatures[i] {
+                    Ok(signature) => signature,
+                    Err(_) => continue,
+                };
+                let translator = Translator {
+                    parameters: &signature.parameters,
+                    constants,
+                    functions: &ir,
+                };
+                if let Some(body) = translator.body(function, &signature.return_type) {
+                    ir[i].body = Some(body);
+                    updated = true;
+                }
+            }
+            if !updated {
+                return ir;
+            }
+        }
+    }
+}
+
+/// Translate the expression of an attribute derived in a subtype, e.g. `SELF\base.y : REAL := 2.0 * z;`,
+/// into a value of `ty`
+///
+/// The expression may refer `attributes` of the subtype in addition to what a [Function] body may refer.
+pub(super) fn translate_derived(
+    expr: &ast::Expression,
+    ty: &ValueType,
+    attributes: &[FunctionParameter],
+    constants: &[Constant],
+    functions: &[Function],
+) -> Option<Expression> {
+    let translator = Translator {
+        parameters: attributes,
+        constants,
+        functions,
+    };
+    translator.value(expr, ty)
+}
+
+/// Expression in the subset of EXPRESS translatable into Rust, see [Function]
+///
+/// Numbers are evaluated as `REAL` regardless of `INTEGER` or `REAL`.
+#[derive(Debug, Clone)]
+pub enum Expression {
+    Number(f64),
+    Boolean(bool),
+    String(String),
+    /// Reference to a parameter of the function
+    Parameter {
+        name: String,
+        ty: ValueType,
+    },
+    Unary {
+        op: ast::UnaryOperator,
+        arg: Box<Expression>,
+    },
+    Binary {
+        op: ast::BinaryOperator,
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+    },
+    Relation {
+        op: ast::RelationOperator,
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+    },
+    /// `SIZEOF`, `HIINDEX`, `LOINDEX`, `EXISTS`, or `ABS`
+    BuiltIn {
+        function: ast::BuiltInFunction,
+        arg: Box<Expression>,
+    },
+    /// Call of another translated function
+    Call {
+        function: String,
+        parameters: Vec<ValueType>,
+        args: Vec<Expression>,
+        return_type: ValueType,
+    },
+}
+
+// Real value is compared bitwise to satisfy `Eq`
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        use Expression::*;
+        match (self, other) {
+            (Number(a), Number(b)) => a.to_bits() == b.to_bits(),
+            (Boolean(a), Boolean(b)) => a == b,
+            (String(a), String(b)) => a == b,
+            (Parameter { name: a, ty: s }, Parameter { name: b, ty: t }) => a == b && s == t,
+            (Unary { op: o, arg: a }, Unary { op: p, arg: b }) => o == p && a == b,
+            (
+                Binary {
+                    op: o,
+                    lhs: a1,
+                    rhs: a2,
+                },
+                Binary {
+                    op: p,
+                    lhs: b1,
+                    rhs: b2,
+                },
+            ) => o == p && a1 == b1 && a2 == b2,
+            (
+                Relation {
+                    op: o,
+                    lhs: a1,
+                    rhs: a2,
+                },
+                Relation {
+                    op: p,
+                    lhs: b1,
+                    rhs: b2,
+                },
+            ) => o == p && a1 == b1 && a2 == b2,
+            (
+                BuiltIn {
+                    function: f,
+                    arg: a,
+                },
+                BuiltIn {
+                    function: g,
+                    arg: b,
+                },
+            ) => f == g && a == b,
+            (
+                Call {
+                    function: f,
+                    args: a,
+                    ..
+                },
+                Call {
+                    function: g,
+                    args: b,
+                    ..
+                },
+            ) => f == g && a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Expression {}
+
+/// Kind of values of [Expression]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Boolean,
+    Logical,
+    String,
+    Aggregate,
+}
+
+impl Expression {
+    fn kind(&self) -> Kind {
+        use ast::{BinaryOperator::*, BuiltInFunction::*};
+        match self {
+            Expression::Number(_) => Kind::Number,
+            Expression::Boolean(_) | Expression::Relation { .. } => Kind::Boolean,
+            Expression::String(_) => Kind::String,
+            Expression::Parameter { ty, .. } => ty.kind(),
+            Expression::Unary { arg, .. } => arg.kind(),
+            Expression::Binary { op, .. } => match op {
+                And | Or | Xor => Kind::Boolean,
+                _ => Kind::Number,
+            },
+            Expression::BuiltIn { function, .. } => match function {
+                EXISTS => Kind::Boolean,
+                _ => Kind::Number,
+            },
+            Expression::Call { return_type, .. } => return_type.kind(),
+        }
+    }
+}
+
+struct Translator<'a> {
+    parameters: &'a [FunctionParameter],
+    constants: &'a [Constant],
+    functions: &'a [Function],
+}
+
+impl<'a> Translator<'a> {
+    fn body(&self, function: &ast::Function, return_type: &ValueType) -> Option<Expression> {
+        if !function.declarations.is_empty()
+            || !function.constants.is_empty()
+            || !function.variables.is_empty()
+        {
+            return None;
+        }
+        match function.statements.as_slice() {
+            [ast::Statement::Return { value: Some(expr) }] => self.value(expr, return_type),
+            _ => None,
+        }
+    }
+
+    /// Expression evaluated as a value of `ty`
+    fn value(&self, expr: &ast::Expression, ty: &ValueType) -> Option<Expression> {
+        let expr = self.expression(expr)?;
+        match (expr.kind(), ty.kind()) {
+            (Kind::Number, Kind::Number) | (Kind::String, Kind::String) => Some(expr),
+            (Kind::Boolean, Kind::Boolean | Kind::Logical) => Some(expr),
+            _ => None,
+        }
+    }
+
+    fn expression(&self, expr: &ast::Expression) -> Option<Expression> {
+        use ast::{BinaryOperator::*, RelationOperator::*, UnaryOperator::*};
+        match expr {
+            ast::Expression::Literal(literal) => match literal {
+                ast::Literal::Real(value) => Some(Expression::Number(*value)),
+                ast::Literal::String(value) => Some(Expression::String(value.clone())),
+                ast::Literal::Logial(ast::Logical::True) => Some(Expression::Boolean(true)),
+                ast::Literal::Logial(ast::Logical::False) => Some(Expression::Boolean(false)),
+                _ => None,
+            },
+            ast::Expression::Unary { op, arg } => {
+                let arg = self.expression(arg)?;
+                match (op, arg.kind()) {
+                    (Plus | Minus, Kind::Number) | (Not, Kind::Boolean) => {
+                        Some(Expression::Unary {
+                            op: *op,
+                            arg: Box::new(arg),
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            ast::Expression::Binary { op, arg1, arg2 } => {
+                let lhs = self.expression(arg1)?;
+                let rhs = self.expression(arg2)?;
+                let kind = match op {
+                    Add | Sub | Mul | RealDiv | IntegerDiv | Power => Kind::Number,
+                    And | Or | Xor => Kind::Boolean,
+                    _ => return None,
+                };
+                if lhs.kind() != kind || rhs.kind() != kind {
+                    return None;
+                }
+                Some(Expression::Binary {
+                    op: *op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                })
+            }
+            ast::Expression::Relation { op, lhs, rhs } => {
+                let lhs = self.expression(lhs)?;
+                let rhs = self.expression(rhs)?;
+                let supported = match (op, lhs.kind(), rhs.kind()) {
+                    (Equal | NotEqual, Kind::Number | Kind::Boolean | Kind::String, _) => {
+                        lhs.kind() == rhs.kind()
+                    }
+                    (Lt | Gt | Leq | Geq, Kind::Number, Kind::Number) => true,
+                    _ => false,
+                };
+                if !supported {
+                    return None;
+                }
+                Some(Expression::Relation {
+                    op: *op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                })
+            }
+            ast::Expression::QualifiableFactor { factor, qualifiers } if qualifiers.is_empty() => {
+                self.factor(factor)
+            }
+            _ => None,
+        }
+    }
+
+    fn factor(&self, factor: &ast::QualifiableFactor) -> Option<Expression> {
+        use ast::BuiltInFunction::*;
+        match factor {
+            ast::QualifiableFactor::BuiltInConstant(ast::BuiltInConstant::Pi) => {
+                Some(Expression::Number(std::f64::consts::PI))
+            }
+            ast::QualifiableFactor::BuiltInConstant(ast::BuiltInConstant::Napier) => {
+                Some(Expression::Number(std::f64::consts::E))
+            }
+            ast::QualifiableFactor::BuiltInConstant(_) => None,
+            // EXPRESS identifiers are case insensitive
+            ast::QualifiableFactor::Reference(name) => {
+                if let Some(p) = self
+                    .parameters
+                    .iter()
+                    .find(|p| p.name.eq_ignore_ascii_case(name))
+                {
+                    return Some(Expression::Parameter {
+                        name: p.name.clone(),
+                        ty: p.ty.clone(),
+                    });
+                }
+                let constant = self
+                    .constants
+                    .iter()
+                    .find(|c| c.name.eq_ignore_ascii_case(name))?;
+                match constant.value.as_ref()? {
+                    ConstantValue::Real(value) => Some(Expression::Number(*value)),
+                    ConstantValue::Integer(value) => Some(Expression::Number(*value as f64)),
+                    ConstantValue::String(value) => Some(Expression::String(value.clone())),
+                    ConstantValue::Boolean(value) => Some(Expression::Boolean(*value)),
+                    ConstantValue::Logical(_) => None,
+                }
+            }
+            ast::QualifiableFactor::FunctionCall { name, args } => match name {
+                ast::FunctionCallName::BuiltInFunction(function) => {
+                    let arg = match args.as_slice() {
+                        [arg] => self.expression(arg)?,
+                        _ => return None,
+                    };
+                    let supported = match function {
+                        SIZEOF | HIINDEX | LOINDEX => arg.kind() == Kind::Aggregate,
+                        ABS => arg.kind() == Kind::Number,
+                        EXISTS => matches!(arg, Expression::Parameter { .. }),
+                        _ => false,
+                    };
+                    if !supported {
+                        return None;
+                    }
+                    Some(Expression::BuiltIn {
+                        function: function.clone(),
+                        arg: Box::new(arg),
+                    })
+                }
+                ast::FunctionCallName::Reference(name) => {
+                    let callee = self
+                        .functions
+                        .iter()
+                        .find(|f| f.name.eq_ignore_ascii_case(name))?;
+                    callee.body.as_ref()?;
+                    let signature = callee.signature.as_ref().ok()?;
+                    if signature.parameters.len() != args.len() {
+                        return None;
+                    }
+                    let args = args
+                        .iter()
+                        .zip(&signature.parameters)
+                        .map(|(arg, p)| {
+                            let arg = self.expression(arg)?;
+                            let compatible = match (&arg, &p.ty) {
+                                // Aggregates are passed through only from parameters of the same type
+                                (Expression::Parameter { ty, .. }, ValueType::Aggregate(_)) => {
+                                    ty == &p.ty
+                                }
+                                (_, ValueType::Logical) => false,
+                                _ => arg.kind() == p.ty.kind(),
+                            };
+                            if compatible {
+                                Some(arg)
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+                    Some(Expression::Call {
+                        function: callee.name.clone(),
+                        parameters: signature.parameters.iter().map(|p| p.ty.clone()).collect(),
+                        args,
+                        return_type: signature.return_type.clone(),
+                    })
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn functions(input: &str) -> Vec<Function> {
+        let st = SyntaxTree::parse(&format!(
+            r#"
+            SCHEMA s;
+              CONSTANT
+                max_len : INTEGER := 80;
+              END_CONSTANT;
+              ENTITY a; END_ENTITY;
+              {}
+            END_SCHEMA;
+            "#,
+            input
+        ))
+        .unwrap();
+        let ir = IR::from_syntax_tree(&st).unwrap();
+        ir.schemas[0].functions.clone()
+    }
+
+    fn param(name: &str) -> Box<Expression> {
+        Box::new(Expression::Parameter {
+            name: name.to_string(),
+            ty: ValueType::Real,
+        })
+    }
+
+    #[test]
+    fn translated() {
+        let f = functions(
+            r#"
+            FUNCTION is_short(items : LIST OF REAL; x : REAL) : BOOLEAN;
+              RETURN ((SIZEOF(items) < max_len) AND (ABS(x) > 0.5));
+            END_FUNCTION;
+            "#,
+        );
+        assert_eq!(
+            f[0].signature,
+            Ok(Signature {
+                parameters: vec![
+                    FunctionParameter {
+                        name: "items".to_string(),
+                        ty: ValueType::Aggregate(Box::new(ValueType::Real)),
+                    },
+                    FunctionParameter {
+                        name: "x".to_string(),
+                        ty: ValueType::Real,
+                    },
+                ],
+                return_type: ValueType::Boolean,
+            })
+        );
+        assert_eq!(
+            f[0].body,
+            Some(Expression::Binary {
+                op: ast::BinaryOperator::And,
+                lhs: Box::new(Expression::Relation {
+                    op: ast::RelationOperator::Lt,
+                    lhs: Box::new(Expression::BuiltIn {
+                        function: ast::BuiltInFunction::SIZEOF,
+                        arg: Box::new(Expression::Parameter {
+                            name: "items".to_string(),
+                            ty: ValueType::Aggregate(Box::new(ValueType::Real)),
+                        }),
+                    }),
+                    rhs: Box::new(Expression::Number(80.0)),
+                }),
+                rhs: Box::new(Expression::Relation {
+                    op: ast::RelationOperator::Gt,
+                    lhs: Box::new(Expression::BuiltIn {
+                        function: ast::BuiltInFunction::ABS,
+                        arg: param("x"),
+                    }),
+                    rhs: Box::new(Expression::Number(0.5)),
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn call() {
+        // Declared after the caller
+        let f = functions(
+            r#"
+            FUNCTION twice(x : REAL) : REAL;
+              RETURN (double(x));
+            END_FUNCTION;
+            FUNCTION double(x : REAL) : REAL;
+              RETURN (2.0 * x);
+            END_FUNCTION;
+            FUNCTION broken(x : REAL) : REAL;
+              RETURN (unknown(x));
+            END_FUNCTION;
+            FUNCTION calls_broken(x : REAL) : REAL;
+              RETURN (broken(x));
+            END_FUNCTION;
+            "#,
+        );
+        assert_eq!(
+            f[0].body,
+            Some(Expression::Call {
+                function: "double".to_string(),
+                parameters: vec![ValueType::Real],
+                args: vec![*param("x")],
+                return_type: ValueType::Real,
+            })
+        );
+        assert!(f[1].body.is_some());
+        assert!(f[2].signature.is_ok());
+        assert_eq!(f[2].body, None);
+        assert_eq!(f[3].body, None);
+    }
+
+    #[test]
+    fn not_translated() {
+        let f = functions(
+            r#"
+            FUNCTION any_value(x : GENERIC) : BOOLEAN;
+              RETURN (TRUE);
+            END_FUNCTION;
+            FUNCTION with_local(x : REAL) : REAL;
+              LOCAL
+                y : REAL := 1.0;
+              END_LOCAL;
+              RETURN (x + y);
+            END_FUNCTION;
+            FUNCTION mismatch(x : REAL) : BOOLEAN;
+              RETURN (x + 1.0);
+            END_FUNCTION;
+            FUNCTION unknown_logical(x : REAL) : LOGICAL;
+              RETURN (UNKNOWN);
+            END_FUNCTION;
+            "#,
+        );
+        assert_eq!(f[0].signature, Err("parameter `x` of GENERIC".to_string()));
+        assert!(f.iter().all(|f| f.body.is_none()));
+    }
+}
@@ -166,8 +166,10 @@
 //!
 
 mod complex_entity;
+mod constant;
 mod constraints;
 mod entity;
+mod function;
 mod namespace;
 mod schema;
 mod scope;
@@ -175,8 +177,10 @@ mod type_decl;
 mod type_ref;
 
 pub use complex_entity::*;
+pub use constant::*;
 pub use constraints::*;
 pub use entity::*;
+pub use function::*;
 pub use namespace::*;
 pub use schema::*;
 pub use scope::*;
@@ -197,6 +201,32 @@ pub enum SemanticError {
 
     #[error("Same item ({0}) is declared multiple times")]
     DuplicatedDeclaration(Path),
+
+    #[error("Reference {name} in scope {scope} is ambiguous between {candidates:?}")]
+    AmbiguousReference {
+        name: String,
+        scope: Scope,
+        candidates: Vec<Path>,
+    },
+}
+
+/// Semantic warnings, which do not prevent code generation
+/// but indicate that a part of the schema is not translated
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SemanticWarning {
+    #[error("WHERE rule {} of {entity} depends on function {function} which is not implemented", rule.as_deref().unwrap_or("(unlabeled)"))]
+    RuleDependsOnFunction {
+        entity: String,
+        rule: Option<String>,
+        function: String,
+    },
+
+    #[error("UNIQUE rule {} of {entity} is not checked since it refers {attribute}", rule.as_deref().unwrap_or("(unlabeled)"))]
+    UniqueRuleNotChecked {
+        entity: String,
+        rule: Option<String>,
+        attribute: String,
+    },
 }
 
 /// Legalize partial AST input into corresponding intermediate representation
@@ -225,6 +255,41 @@ impl IR {
         let ir = Self::legalize(&ns, &ss, &Scope::root(), st)?;
         Ok(ir)
     }
+
+    /// Warnings about parts of the schema which are not translated
+    pub fn warnings(&self) -> Vec<SemanticWarning> {
+        let mut warnings = Vec::new();
+        for schema in &self.schemas {
+            // Rules calling translated functions can be checked
+            let translated = |name: &String| {
+                schema
+                    .functions
+                    .iter()
+                    .any(|f| f.name.eq_ignore_ascii_case(name) && f.body.is_some())
+            };
+            for entity in &schema.entities {
+                for rule in &entity.rules {
+                    for function in rule.functions.iter().filter(|f| !translated(f)) {
+                        warnings.push(SemanticWarning::RuleDependsOnFunction {
+                            entity: entity.name.clone(),
+                            rule: rule.label.clone(),
+                            function: function.clone(),
+                        });
+                    }
+                }
+                for rule in &entity.uniques {
+                    for attribute in &rule.unsupported {
+                        warnings.push(SemanticWarning::UniqueRuleNotChecked {
+                            entity: entity.name.clone(),
+                            rule: rule.label.clone(),
+                            attribute: attribute.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        warnings
+    }
 }
 
 impl Legalize for IR {
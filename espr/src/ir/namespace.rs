@@ -79,24 +79,69 @@ impl<'st> Namespace<'st> {
 
     /// Resolve a `name` referred in a `scope` into the full path.
     ///
+    /// The name is looked up from the innermost scope to the outermost one.
+    ///
     /// Error
     /// ------
     /// - If no corresponding definition found.
+    /// - If the name is declared multiple times in the same scope, e.g. both `TYPE` and `ENTITY`.
+    ///   Use [Namespace::resolve_as] if the kind of declaration is known from the context.
+    /// - If the name declared in an inner scope shadows a declaration in outer scopes,
+    ///   since it is not clear which one is intended.
     ///
     pub fn resolve(&self, scope: &Scope, name: &str) -> Result<(Path, usize), SemanticError> {
-        let mut scope = scope.clone();
-        loop {
+        self.resolve_filtered(scope, name, |_| true)
+    }
+
+    /// Resolve a `name` referred in a `scope` into the full path of declaration of `kind`,
+    /// e.g. [ScopeType::Entity] for `SUBTYPE OF (name)`.
+    ///
+    /// Declarations of other kinds are ignored even if they have the same name.
+    ///
+    /// Error
+    /// ------
+    /// - If no corresponding definition of `kind` found.
+    ///
+    pub fn resolve_as(
+        &self,
+        scope: &Scope,
+        name: &str,
+        kind: ScopeType,
+    ) -> Result<(Path, usize), SemanticError> {
+        self.resolve_filtered(scope, name, |ty| ty == kind)
+    }
+
+    fn resolve_filtered(
+        &self,
+        scope: &Scope,
+        name: &str,
+        filter: impl Fn(ScopeType) -> bool,
+    ) -> Result<(Path, usize), SemanticError> {
+        // Declarations matched in the scopes from the innermost one
+        let mut found = Vec::new();
+        let mut current = Some(scope.clone());
+        while let Some(scope) = current {
             if let Some(names) = self.names.get(&scope) {
-                for (ty, n, index) in names {
-                    if name == n {
-                        return Ok((Path::new(&scope, *ty, n), *index));
-                    }
-                }
+                found.extend(
+                    names
+                        .iter()
+                        .filter(|(ty, n, _index)| name == n && filter(*ty))
+                        .map(|(ty, n, index)| (Path::new(&scope, *ty, n), *index)),
+                );
             }
-            scope = scope.popped().ok_or_else(|| SemanticError::TypeNotFound {
+            current = scope.popped();
+        }
+        match found.len() {
+            0 => Err(SemanticError::TypeNotFound {
                 scope: scope.clone(),
                 name: name.to_string(),
-            })?;
+            }),
+            1 => Ok(found.pop().unwrap()),
+            _ => Err(SemanticError::AmbiguousReference {
+                name: name.to_string(),
+                scope: scope.clone(),
+                candidates: found.into_iter().map(|(path, _index)| path).collect(),
+            }),
         }
     }
 
@@ -116,6 +161,103 @@ impl<'st> Namespace<'st> {
     }
 }
 
+/// Read-only view of declarations in EXPRESS schemas, created by [SyntaxTree::namespace]
+///
+/// Unlike [Namespace], this does not borrow the syntax tree,
+/// and lists functions, procedures, and rules in addition to entities and types.
+/// Declarations are listed in the order of schemas, and then in the order of
+/// types, entities, functions, procedures, and rules in each schema.
+///
+/// ```
+/// use espr::{ast::SyntaxTree, ir::*};
+///
+/// let st = SyntaxTree::parse(r#"
+/// SCHEMA s;
+///   TYPE label = STRING;
+///   END_TYPE;
+///   ENTITY point;
+///     x : REAL;
+///   END_ENTITY;
+/// END_SCHEMA;
+/// "#).unwrap();
+/// let ns = st.namespace();
+///
+/// let names: Vec<_> = ns.iter().map(|path| path.to_string()).collect();
+/// assert_eq!(names, vec!["s.label", "s.point"]);
+/// assert_eq!(ns.entities().count(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NamespaceView {
+    declarations: Vec<Path>,
+}
+
+impl NamespaceView {
+    pub fn new(st: &SyntaxTree) -> Self {
+        let root = Scope::root();
+        let mut declarations = Vec::new();
+        for schema in &st.schemas {
+            let here = root.pushed(ScopeType::Schema, &schema.name);
+            let mut push = |ty, name: &str| declarations.push(Path::new(&here, ty, name));
+            for ty in &schema.types {
+                push(ScopeType::Type, &ty.type_id);
+            }
+            for entity in &schema.entities {
+                push(ScopeType::Entity, &entity.name);
+            }
+            for function in &schema.functions {
+                push(ScopeType::Function, &function.name);
+            }
+            for procedure in &schema.procedures {
+                push(ScopeType::Procedure, &procedure.name);
+            }
+            for rule in &schema.rules {
+                push(ScopeType::Rule, &rule.name);
+            }
+        }
+        NamespaceView { declarations }
+    }
+
+    /// All declarations with their scopes and kinds
+    pub fn iter(&self) -> impl Iterator<Item = &Path> {
+        self.declarations.iter()
+    }
+
+    /// Declarations of `kind`, e.g. [ScopeType::Entity]
+    pub fn of_kind(&self, kind: ScopeType) -> impl Iterator<Item = &Path> {
+        self.iter().filter(move |path| path.ty == kind)
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = &Path> {
+        self.of_kind(ScopeType::Entity)
+    }
+
+    pub fn types(&self) -> impl Iterator<Item = &Path> {
+        self.of_kind(ScopeType::Type)
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &Path> {
+        self.of_kind(ScopeType::Function)
+    }
+
+    /// Declarations directly in `scope`, e.g. in a schema
+    pub fn in_scope<'a>(&'a self, scope: &'a Scope) -> impl Iterator<Item = &'a Path> {
+        self.iter().filter(move |path| &path.scope == scope)
+    }
+
+    /// Declarations named `name` in any scope
+    pub fn find<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Path> {
+        self.iter().filter(move |path| path.name == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.declarations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.declarations.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,6 +299,85 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ambiguous_reference() {
+        let st = SyntaxTree::parse(
+            r#"
+            SCHEMA s;
+              TYPE label = STRING;
+              END_TYPE;
+              ENTITY label;
+                x : REAL;
+              END_ENTITY;
+            END_SCHEMA;
+            "#
+            .trim(),
+        )
+        .unwrap();
+        let ns = Namespace::new(&st);
+        let scope = Scope::root().pushed(ScopeType::Schema, "s");
+
+        assert!(matches!(
+            ns.resolve(&scope, "label"),
+            Err(SemanticError::AmbiguousReference { candidates, .. }) if candidates.len() == 2
+        ));
+
+        let (path, _) = ns.resolve_as(&scope, "label", ScopeType::Entity).unwrap();
+        assert_eq!(path, Path::new(&scope, ScopeType::Entity, "label"));
+        let (path, _) = ns.resolve_as(&scope, "label", ScopeType::Type).unwrap();
+        assert_eq!(path, Path::new(&scope, ScopeType::Type, "label"));
+        assert!(matches!(
+            ns.resolve_as(&scope, "label", ScopeType::Function),
+            Err(SemanticError::TypeNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn ambiguous_reference_across_scopes() {
+        let st = SyntaxTree::parse(
+            r#"
+            SCHEMA s;
+              TYPE label = STRING;
+              END_TYPE;
+              ENTITY point;
+                x : REAL;
+              END_ENTITY;
+            END_SCHEMA;
+            "#
+            .trim(),
+        )
+        .unwrap();
+        let mut ns = Namespace::new(&st);
+        let schema = Scope::root().pushed(ScopeType::Schema, "s");
+        let inner = schema.pushed(ScopeType::Entity, "point");
+
+        // `label` in the entity scope shadows `TYPE label` in the schema scope
+        let (_, index) = ns.resolve(&schema, "point").unwrap();
+        ns.names.insert(
+            inner.clone(),
+            vec![(ScopeType::Entity, "label".to_string(), index)],
+        );
+
+        match ns.resolve(&inner, "label") {
+            Err(SemanticError::AmbiguousReference { candidates, .. }) => assert_eq!(
+                candidates,
+                vec![
+                    Path::new(&inner, ScopeType::Entity, "label"),
+                    Path::new(&schema, ScopeType::Type, "label"),
+                ]
+            ),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // Not ambiguous if the kind is known
+        let (path, _) = ns.resolve_as(&inner, "label", ScopeType::Type).unwrap();
+        assert_eq!(path, Path::new(&schema, ScopeType::Type, "label"));
+
+        // Outer declaration is not shadowed when referred from the outer scope
+        let (path, _) = ns.resolve(&schema, "label").unwrap();
+        assert_eq!(path, Path::new(&schema, ScopeType::Type, "label"));
+    }
+
     #[test]
     fn namespace_debug() {
         let st = ast::SyntaxTree::parse(
@@ -215,6 +436,7 @@ mod tests {
                                         Real,
                                     ),
                                     optional: false,
+                                    remarks: [],
                                 },
                             ],
                             constraint: Some(
@@ -236,6 +458,7 @@ mod tests {
                             inverse_clause: None,
                             unique_clause: None,
                             where_clause: None,
+                            remarks: [],
                         },
                     ),
                 ),
@@ -253,6 +476,7 @@ mod tests {
                                         Real,
                                     ),
                                     optional: false,
+                                    remarks: [],
                                 },
                             ],
                             constraint: None,
@@ -267,6 +491,7 @@ mod tests {
                             inverse_clause: None,
                             unique_clause: None,
                             where_clause: None,
+                            remarks: [],
                         },
                     ),
                 ),
@@ -284,6 +509,7 @@ mod tests {
                                         Real,
                                     ),
                                     optional: false,
+                                    remarks: [],
                                 },
                             ],
                             constraint: None,
@@ -298,6 +524,7 @@ mod tests {
                             inverse_clause: None,
                             unique_clause: None,
                             where_clause: None,
+                            remarks: [],
                         },
                     ),
                 ),
@@ -305,4 +532,57 @@ mod tests {
         }
         "###);
     }
+
+    #[test]
+    fn namespace_view() {
+        let st = SyntaxTree::parse(
+            r#"
+            SCHEMA one;
+              TYPE label = STRING;
+              END_TYPE;
+              ENTITY first;
+                fattr : label;
+              END_ENTITY;
+              FUNCTION double(x : REAL) : REAL;
+                RETURN (2.0 * x);
+              END_FUNCTION;
+            END_SCHEMA;
+
+            SCHEMA two;
+              ENTITY label;
+                x : REAL;
+              END_ENTITY;
+            END_SCHEMA;
+            "#
+            .trim(),
+        )
+        .unwrap();
+        let ns = st.namespace();
+        let one = Scope::root().pushed(ScopeType::Schema, "one");
+        let two = Scope::root().pushed(ScopeType::Schema, "two");
+
+        assert_eq!(ns.len(), 4);
+        assert_eq!(
+            ns.iter().cloned().collect::<Vec<_>>(),
+            vec![
+                Path::new(&one, ScopeType::Type, "label"),
+                Path::new(&one, ScopeType::Entity, "first"),
+                Path::new(&one, ScopeType::Function, "double"),
+                Path::new(&two, ScopeType::Entity, "label"),
+            ]
+        );
+        assert_eq!(ns.entities().count(), 2);
+        assert_eq!(ns.types().count(), 1);
+        assert_eq!(
+            ns.functions()
+                .map(|path| path.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["double"]
+        );
+        assert_eq!(ns.in_scope(&one).count(), 3);
+        assert_eq!(
+            ns.find("label").map(|path| path.ty).collect::<Vec<_>>(),
+            vec![ScopeType::Type, ScopeType::Entity]
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use super::{entity::*, namespace::*, scope::*, type_decl::*, *};
+use super::{constant::*, entity::*, function::*, namespace::*, scope::*, type_decl::*, *};
 use crate::ast;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -6,6 +6,11 @@ pub struct Schema {
     pub name: String,
     pub entities: Vec<Entity>,
     pub types: Vec<TypeDecl>,
+    /// Combinations of subtypes instantiable as complex entity instances
+    pub complex_entities: Vec<ComplexEntity>,
+    pub constants: Vec<Constant>,
+    /// Functions declared in the schema, translated into Rust if possible
+    pub functions: Vec<Function>,
 }
 
 impl Legalize for Schema {
@@ -18,22 +23,175 @@ impl Legalize for Schema {
     ) -> Result<Self, SemanticError> {
         let name = schema.name.clone();
         let here = scope.pushed(ScopeType::Schema, &name);
-        let entities = schema
+        let mut entities = schema
             .entities
             .iter()
-            .map(|entity| Entity::legalize(ns, ss, &here, entity))
+            .map(|entity| {
+                let mut ir = Entity::legalize(ns, ss, &here, entity)?;
+                if let Some(clause) = &entity.where_clause {
+                    ir.rules = clause
+                        .rules
+                        .iter()
+                        .map(|rule| WhereRule::new(rule, schema))
+                        .collect();
+                }
+                Ok(ir)
+            })
             .collect::<Result<Vec<Entity>, _>>()?;
+        mark_derived_in_subtype(&here, &mut entities);
         let types = schema
             .types
             .iter()
             .map(|entity| TypeDecl::legalize(ns, ss, &here, entity))
             .collect::<Result<Vec<TypeDecl>, _>>()?;
+        let constants = schema
+            .constants
+            .iter()
+            .map(|constant| Constant::legalize(ns, ss, &here, constant, &schema.constants))
+            .collect::<Result<Vec<Constant>, _>>()?;
+        let functions = Function::legalize_all(&schema.functions, &constants);
+        translate_derived_values(&here, schema, &mut entities, &constants, &functions);
+        let mut complex_entities = Vec::new();
+        for entity in &schema.entities {
+            let path = Path::entity(&here, &entity.name);
+            if let Some(instantiables) = ss.instantiables.get(&path) {
+                for pce in instantiables.iter().filter(|pce| pce.len() > 1) {
+                    let complex = ComplexEntity::from_paths(ns, ss, pce)?;
+                    if !complex_entities.contains(&complex) {
+                        complex_entities.push(complex);
+                    }
+                }
+            }
+        }
         Ok(Schema {
             name,
             entities,
             types,
+            complex_entities,
+            constants,
+            functions,
+        })
+    }
+}
+
+/// Mark attributes redeclared in `DERIVE` clause of subtypes in this schema
+fn mark_derived_in_subtype(here: &Scope, entities: &mut [Entity]) {
+    let derived: Vec<(String, String)> = entities
+        .iter()
+        .flat_map(|entity| entity.redeclared.iter().filter(|attr| attr.derived))
+        .filter_map(|attr| match &attr.supertype {
+            TypeRef::Entity { name, scope, .. } if scope == here => {
+                Some((name.clone(), attr.name.clone()))
+            }
+            _ => None,
         })
+        .collect();
+    for entity in entities {
+        for attr in &mut entity.attributes {
+            if derived.contains(&(entity.name.clone(), attr.name.clone())) {
+                attr.derived_in_subtype = true;
+            }
+        }
+    }
+}
+
+/// Translate values of attributes derived in subtypes into [RedeclaredAttribute::value]
+fn translate_derived_values(
+    here: &Scope,
+    schema: &ast::Schema,
+    entities: &mut [Entity],
+    constants: &[Constant],
+    functions: &[Function],
+) {
+    for (i, entity) in schema.entities.iter().enumerate() {
+        let derive = match &entity.derive_clause {
+            Some(derive) => derive,
+            None => continue,
+        };
+        // Simple attributes of the subtype whose values are given in its instances
+        let attributes: Vec<FunctionParameter> = entity
+            .attributes
+            .iter()
+            .filter(|attr| !attr.optional)
+            .filter_map(|attr| match &attr.name {
+                ast::AttributeDecl::Reference(name) => Some(FunctionParameter {
+                    name: name.clone(),
+                    ty: ValueType::new(&attr.ty).ok()?,
+                }),
+                _ => None,
+            })
+            .filter(|p| {
+                entities[i]
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.name == p.name && !attr.derived_in_subtype)
+            })
+            .collect();
+        for derived in &derive.attributes {
+            let name = match &derived.attr {
+                ast::AttributeDecl::Qualified { attribute, .. } => attribute,
+                ast::AttributeDecl::Reference(_) => continue,
+            };
+            let position = entities[i]
+                .redeclared
+                .iter()
+                .position(|attr| attr.derived && &attr.name == name);
+            let value = position.and_then(|j| {
+                let supertype = match &entities[i].redeclared[j].supertype {
+                    TypeRef::Entity { name, scope, .. } if scope == here => name,
+                    _ => return None,
+                };
+                let original = schema
+                    .entities
+                    .iter()
+                    .find(|entity| &entity.name == supertype)?
+                    .attributes
+                    .iter()
+                    .find(
+                        |attr| matches!(&attr.name, ast::AttributeDecl::Reference(n) if n == name),
+                    )?;
+                let ty = ValueType::new(&original.ty).ok()?;
+                let expr =
+                    translate_derived(&derived.expr, &ty, &attributes, constants, functions)?;
+                Some(DerivedValue {
+                    path: supertype_path(here, entities, &entities[i], supertype)?,
+                    ty,
+                    optional: original.optional,
+                    expr,
+                })
+            });
+            if let Some(j) = position {
+                entities[i].redeclared[j].value = value;
+            }
+        }
+    }
+}
+
+/// Supertypes from the direct supertype of `entity` to `target`
+fn supertype_path(
+    here: &Scope,
+    entities: &[Entity],
+    entity: &Entity,
+    target: &str,
+) -> Option<Vec<String>> {
+    for ty in &entity.supertypes {
+        let name = match ty {
+            TypeRef::Entity { name, scope, .. } if scope == here => name,
+            _ => continue,
+        };
+        if name == target {
+            return Some(vec![name.clone()]);
+        }
+        let supertype = match entities.iter().find(|e| &e.name == name) {
+            Some(supertype) => supertype,
+            None => continue,
+        };
+        if let Some(mut path) = supertype_path(here, entities, supertype, target) {
+            path.insert(0, name.clone());
+            return Some(path);
+        }
     }
+    None
 }
 
 #[cfg(test)]
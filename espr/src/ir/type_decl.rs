@@ -129,6 +129,10 @@ impl Legalize for TypeDecl {
                     },
                 })
             }
+            ty @ Type::Array { .. } => TypeDecl::Rename(Rename {
+                id,
+                ty: TypeRef::legalize(ns, ss, scope, ty)?,
+            }),
             _ => panic!(),
         })
     }
@@ -16,8 +16,59 @@ impl Legalize for SimpleType {
     }
 }
 
+/// Bound of aggregation types, e.g. `[1:3]` of `LIST [1:3] OF REAL`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Bound {}
+pub struct Bound {
+    pub lower: BoundValue,
+    pub upper: BoundValue,
+}
+
+/// Lower or upper value of [Bound]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoundValue {
+    /// Integer literal, e.g. `3`
+    Constant(i64),
+    /// Indeterminate `?`, i.e. unbounded
+    Indeterminate,
+    /// Other expression which is not evaluated at compile time, e.g. a reference to attribute
+    Expression,
+}
+
+impl BoundValue {
+    fn from_expression(expr: &ast::Expression) -> Self {
+        match expr {
+            ast::Expression::Literal(ast::Literal::Real(value)) if value.fract() == 0.0 => {
+                BoundValue::Constant(*value as i64)
+            }
+            ast::Expression::Unary { op, arg } => match (op, Self::from_expression(arg)) {
+                (ast::UnaryOperator::Plus, BoundValue::Constant(value)) => {
+                    BoundValue::Constant(value)
+                }
+                (ast::UnaryOperator::Minus, BoundValue::Constant(value)) => {
+                    BoundValue::Constant(-value)
+                }
+                _ => BoundValue::Expression,
+            },
+            ast::Expression::QualifiableFactor {
+                factor: ast::QualifiableFactor::BuiltInConstant(ast::BuiltInConstant::Indeterminate),
+                qualifiers,
+            } if qualifiers.is_empty() => BoundValue::Indeterminate,
+            _ => BoundValue::Expression,
+        }
+    }
+}
+
+impl Bound {
+    /// Number of elements if both bounds are constant, e.g. `3` for `[1:3]`
+    pub fn fixed_size(&self) -> Option<usize> {
+        match (self.lower, self.upper) {
+            (BoundValue::Constant(lower), BoundValue::Constant(upper)) if lower <= upper => {
+                Some((upper - lower + 1) as usize)
+            }
+            _ => None,
+        }
+    }
+}
 
 impl Legalize for Bound {
     type Input = ast::Bound;
@@ -25,10 +76,18 @@ impl Legalize for Bound {
         _ns: &Namespace,
         _ss: &Constraints,
         _scope: &Scope,
-        _input: &Self::Input,
+        input: &Self::Input,
     ) -> Result<Self, SemanticError> {
-        // FIXME
-        Ok(Bound {})
+        Ok(Bound::from(input))
+    }
+}
+
+impl From<&ast::Bound> for Bound {
+    fn from(input: &ast::Bound) -> Self {
+        Bound {
+            lower: BoundValue::from_expression(&input.lower),
+            upper: BoundValue::from_expression(&input.upper),
+        }
     }
 }
 
@@ -72,18 +131,26 @@ pub enum TypeRef {
         bound: Option<Bound>,
         unique: bool,
     },
+    Array {
+        base: Box<TypeRef>,
+        bound: Option<Bound>,
+        unique: bool,
+        optional: bool,
+    },
 }
 
 impl TypeRef {
     /// Returns `true` iff `self` is:
     /// - a simple type,
     /// - a named type whose underlying type is simple, or,
-    /// - a set or list of a type `x` such that `x.is_simple() == true`.
+    /// - a set, list or array of a type `x` such that `x.is_simple() == true`.
     pub fn is_simple(&self) -> bool {
         match self {
             TypeRef::SimpleType(..) => true,
             TypeRef::Named { is_simple, .. } => *is_simple,
-            TypeRef::Set { base, .. } | TypeRef::List { base, .. } => base.is_simple(),
+            TypeRef::Set { base, .. }
+            | TypeRef::List { base, .. }
+            | TypeRef::Array { base, .. } => base.is_simple(),
             _ => false,
         }
     }
@@ -196,7 +263,66 @@ impl Legalize for TypeRef {
                     unique: *unique,
                 }
             }
+            Array {
+                base,
+                bound,
+                unique,
+                optional,
+            } => {
+                let base = TypeRef::legalize(ns, ss, scope, base.as_ref())?;
+                let bound = if let Some(bound) = bound {
+                    Some(Legalize::legalize(ns, ss, scope, bound)?)
+                } else {
+                    None
+                };
+                Self::Array {
+                    base: Box::new(base),
+                    bound,
+                    unique: *unique,
+                    optional: *optional,
+                }
+            }
             _ => todo!(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::Finish;
+
+    fn bound(input: &str) -> Bound {
+        let (residual, (bound, _remarks)) = crate::parser::bound_spec(input).finish().unwrap();
+        assert_eq!(residual, "");
+        Bound::from(&bound)
+    }
+
+    #[test]
+    fn constant_bound() {
+        let b = bound("[1:3]");
+        assert_eq!(b.lower, BoundValue::Constant(1));
+        assert_eq!(b.upper, BoundValue::Constant(3));
+        assert_eq!(b.fixed_size(), Some(3));
+
+        let b = bound("[-1:1]");
+        assert_eq!(b.lower, BoundValue::Constant(-1));
+        assert_eq!(b.fixed_size(), Some(3));
+    }
+
+    #[test]
+    fn indeterminate_bound() {
+        let b = bound("[0:?]");
+        assert_eq!(b.lower, BoundValue::Constant(0));
+        assert_eq!(b.upper, BoundValue::Indeterminate);
+        assert_eq!(b.fixed_size(), None);
+    }
+
+    #[test]
+    fn expression_bound() {
+        let b = bound("[1:n + 1]");
+        assert_eq!(b.lower, BoundValue::Constant(1));
+        assert_eq!(b.upper, BoundValue::Expression);
+        assert_eq!(b.fixed_size(), None);
+    }
+}
@@ -166,6 +166,40 @@ pub fn many0<'a, O>(f: impl EsprParser<'a, O>) -> impl EsprParser<'a, Vec<O>> {
     }
 }
 
+/// Parse `f` followed by `;`
+///
+/// Unlike `tuple((f, char(';')))`, spaces and remarks after `;` are not consumed,
+/// and they are left for the next item, e.g. to be associated by [many0_with_leading_remarks].
+pub fn semicolon_terminated<'a, O>(f: impl EsprParser<'a, O>) -> impl EsprParser<'a, O> {
+    move |input| {
+        let (input, (out, remarks)) = nom::Parser::parse(&mut f.clone(), input)?;
+        let (input, _semicolon) = nom::character::complete::char(';')(input)?;
+        Ok((input, (out, remarks)))
+    }
+}
+
+/// Same as [many0], but also keeps remarks just before each output
+/// to associate them with the output, e.g. remarks written on `ENTITY` declaration.
+pub fn many0_with_leading_remarks<'a, O>(
+    f: impl EsprParser<'a, O>,
+) -> impl EsprParser<'a, Vec<(O, Vec<Remark>)>> {
+    use nom::Parser;
+    move |input| {
+        nom::multi::many0(pair(spaces_or_remarks, f.clone()))
+            .map(|pairs| {
+                let mut outputs = Vec::new();
+                let mut remarks = Vec::new();
+                for (r1, (out, mut r2)) in pairs {
+                    remarks.extend(r1.iter().cloned());
+                    remarks.append(&mut r2);
+                    outputs.push((out, r1));
+                }
+                (outputs, remarks)
+            })
+            .parse(input)
+    }
+}
+
 pub fn many1<'a, O>(f: impl EsprParser<'a, O>) -> impl EsprParser<'a, Vec<O>> {
     use nom::Parser;
     move |input| {
@@ -1,47 +1,49 @@
 use super::{attribute::*, derive::*, domain::*, inverse::*, unique::*};
 use crate::{
     ast::*,
-    parser::{combinator::*, identifier::*, subsuper::*, types::*},
+    parser::{combinator::*, identifier::*, remark::*, subsuper::*, types::*},
 };
 
 /// 215 explicit_attr = [attribute_decl] { `,` [attribute_decl] } `:` \[ OPTIONAL \] [parameter_type] `;` .
+///
+/// A remark in the same line after `;` is kept in [EntityAttribute::remarks].
 pub fn explicit_attr(input: &str) -> ParseResult<Vec<EntityAttribute>> {
-    tuple((
+    let (input, ((attrs, _colon, optional, ty), mut remarks)) = semicolon_terminated(tuple((
         comma_separated(attribute_decl),
         char(':'),
         opt(tag("OPTIONAL")),
         parameter_type,
-        char(';'),
-    ))
-    .map(|(attrs, _colon, optional, ty, _semicolon)| {
-        attrs
-            .into_iter()
-            .map(|name| EntityAttribute {
-                name,
-                ty: ty.clone(),
-                optional: optional.is_some(),
-            })
-            .collect()
-    })
-    .parse(input)
+    )))
+    .parse(input)?;
+    let (input, trailing) = nom::combinator::opt(trailing_remark)(input)?;
+    remarks.extend(trailing.iter().cloned());
+    let attrs = attrs
+        .into_iter()
+        .map(|name| EntityAttribute {
+            name,
+            ty: ty.clone(),
+            optional: optional.is_some(),
+            remarks: trailing.iter().cloned().collect(),
+        })
+        .collect();
+    Ok((input, (attrs, remarks)))
 }
 
 /// 207 entity_head = ENTITY [entity_id] [subsuper] `;` .
 pub fn entity_head(input: &str) -> ParseResult<(String, Option<Constraint>, Option<SubTypeDecl>)> {
-    tuple((
+    semicolon_terminated(tuple((
         tag("ENTITY"), // parse with trailing space
         entity_id,
         subsuper,
-        char(';'),
-    ))
-    .map(|(_start, id, (constraint, subtype), _semicolon)| (id, constraint, subtype))
+    )))
+    .map(|(_start, id, (constraint, subtype))| (id, constraint, subtype))
     .parse(input)
 }
 
 /// 204 entity_body = { [explicit_attr] } \[ [derive_clause] \] \[ [inverse_clause] \] \[ [unique_clause] \] \[ [where_clause] \] .
 pub fn entity_body(input: &str) -> ParseResult<EntityBody> {
     tuple((
-        many0(explicit_attr),
+        many0_with_leading_remarks(explicit_attr),
         opt(derive_clause),
         opt(inverse_clause),
         opt(unique_clause),
@@ -49,7 +51,17 @@ pub fn entity_body(input: &str) -> ParseResult<EntityBody> {
     ))
     .map(
         |(attributes, derive_clause, inverse_clause, unique_clause, where_clause)| EntityBody {
-            attributes: attributes.into_iter().flatten().collect(),
+            attributes: attributes
+                .into_iter()
+                .flat_map(|(attrs, leading)| {
+                    attrs.into_iter().map(move |mut attr| {
+                        let mut remarks = leading.clone();
+                        remarks.append(&mut attr.remarks);
+                        attr.remarks = remarks;
+                        attr
+                    })
+                })
+                .collect(),
             derive_clause,
             inverse_clause,
             unique_clause,
@@ -60,32 +72,41 @@ pub fn entity_body(input: &str) -> ParseResult<EntityBody> {
 }
 
 /// 206 entity_decl = [entity_head] [entity_body] END_ENTITY `;` .
+///
+/// A remark in the same line after [entity_head] is kept in [Entity::remarks].
+/// Remarks before the entity are associated by [schema_body](crate::parser::schema_body).
 pub fn entity_decl(input: &str) -> ParseResult<Entity> {
-    tuple((entity_head, entity_body, tag("END_ENTITY"), char(';')))
-        .map(
-            |(
-                (name, constraint, subtype_of),
-                EntityBody {
-                    attributes,
-                    derive_clause,
-                    inverse_clause,
-                    unique_clause,
-                    where_clause,
-                },
-                _end,
-                _semicolon,
-            )| Entity {
-                name,
+    let (input, ((name, constraint, subtype_of), mut remarks)) = entity_head(input)?;
+    let (input, head_remark) = nom::combinator::opt(trailing_remark)(input)?;
+    remarks.extend(head_remark.iter().cloned());
+    let (
+        input,
+        (
+            EntityBody {
                 attributes,
-                constraint,
-                subtype_of,
                 derive_clause,
                 inverse_clause,
                 unique_clause,
                 where_clause,
             },
-        )
-        .parse(input)
+            mut body_remarks,
+        ),
+    ) = semicolon_terminated(tuple((entity_body, tag("END_ENTITY"))))
+        .map(|(body, _end)| body)
+        .parse(input)?;
+    remarks.append(&mut body_remarks);
+    let entity = Entity {
+        name,
+        attributes,
+        constraint,
+        subtype_of,
+        derive_clause,
+        inverse_clause,
+        unique_clause,
+        where_clause,
+        remarks: head_remark.into_iter().collect(),
+    };
+    Ok((input, (entity, remarks)))
 }
 
 #[cfg(test)]
@@ -158,6 +179,63 @@ mod tests {
         assert_eq!(residual, "");
     }
 
+    #[test]
+    fn abstract_supertype_subtype_of() {
+        let (residual, (entity, _remark)) = super::entity_decl(
+            r#"
+            ENTITY pet
+              ABSTRACT SUPERTYPE OF (ONEOF(cat, dog) ANDOR guard)
+              SUBTYPE OF (animal, property);
+              name : STRING;
+            END_ENTITY;
+            "#
+            .trim(),
+        )
+        .finish()
+        .unwrap();
+        assert_eq!(residual, "");
+        assert_eq!(entity.name, "pet");
+        assert!(entity.is_abstract());
+        assert!(entity.has_supertype_decl());
+        assert_eq!(
+            entity.supertype_expression(),
+            Some(&SuperTypeExpression::AndOr {
+                factors: vec![
+                    SuperTypeExpression::OneOf {
+                        exprs: vec![
+                            SuperTypeExpression::Reference("cat".to_string()),
+                            SuperTypeExpression::Reference("dog".to_string()),
+                        ]
+                    },
+                    SuperTypeExpression::Reference("guard".to_string()),
+                ]
+            })
+        );
+        assert_eq!(entity.supertypes(), ["animal", "property"]);
+        assert_eq!(entity.attributes.len(), 1);
+
+        // Non-abstract supertype
+        let (_residual, (entity, _remark)) =
+            super::entity_decl("ENTITY pet SUPERTYPE OF (ONEOF(cat, dog)); END_ENTITY;")
+                .finish()
+                .unwrap();
+        assert!(!entity.is_abstract());
+        assert!(entity.supertype_expression().is_some());
+        assert!(entity.supertypes().is_empty());
+    }
+
+    #[test]
+    fn entity_without_attributes() {
+        let (residual, (entity, _remark)) =
+            super::entity_decl("ENTITY geometric_representation_item; END_ENTITY;")
+                .finish()
+                .unwrap();
+        assert_eq!(residual, "");
+        assert_eq!(entity.name, "geometric_representation_item");
+        assert!(entity.attributes.is_empty());
+        assert!(entity.derive_clause.is_none());
+    }
+
     #[test]
     fn explicit_attr() {
         let (residual, (attrs, _remark)) = super::explicit_attr("x : REAL;").finish().unwrap();
@@ -326,4 +326,50 @@ mod tests {
         dbg!(expr);
         assert_eq!(residual, "");
     }
+
+    #[test]
+    fn query() {
+        let (residual, (expr, _remarks)) = super::expression("QUERY(x <* SELF.points | x.z > 0.0)")
+            .finish()
+            .unwrap();
+        assert_eq!(residual, "");
+        match expr {
+            Expression::Query {
+                variable,
+                source,
+                expr,
+            } => {
+                assert_eq!(variable, "x");
+                assert!(matches!(
+                    *source,
+                    Expression::QualifiableFactor { ref qualifiers, .. }
+                        if qualifiers == &[Qualifier::Attribute("points".to_string())]
+                ));
+                assert!(matches!(
+                    *expr,
+                    Expression::Relation {
+                        op: RelationOperator::Gt,
+                        ..
+                    }
+                ));
+            }
+            _ => panic!("Must be QUERY"),
+        }
+    }
+
+    #[test]
+    fn query_in_function_call() {
+        let (residual, (expr, _remarks)) =
+            super::expression("SIZEOF(QUERY(e <* edges | NOT (e.closed))) = 0")
+                .finish()
+                .unwrap();
+        assert_eq!(residual, "");
+        assert!(matches!(
+            expr,
+            Expression::Relation {
+                op: RelationOperator::Equal,
+                ..
+            }
+        ));
+    }
 }
@@ -6,8 +6,8 @@ use crate::ast::*;
 /// Integer value, e.g. `23`, will be recognized as a real number `23.0`.
 /// Use [integer_literal] if you wish to parse it as an integer.
 pub fn literal(input: &str) -> ParseResult<Literal> {
-    // FIXME binary_literal,
     alt((
+        binary_literal.map(Literal::Binary),
         logical_literal.map(Literal::Logial),
         real_literal.map(Literal::Real),
         string_literal.map(Literal::String),
@@ -17,18 +17,41 @@ pub fn literal(input: &str) -> ParseResult<Literal> {
 
 /// 255 logical_literal = `FALSE` | `TRUE` | `UNKNOWN` .
 pub fn logical_literal(input: &str) -> ParseResult<Logical> {
-    tuple((
-        alt((
-            value(Logical::True, tag("TRUE")),
-            value(Logical::False, tag("FALSE")),
-            value(Logical::Unknown, tag("UNKNOWN")),
-        )),
-        peek(not(remarked(nom::character::complete::alpha1))),
+    remarked(logical_keyword).parse(input)
+}
+
+/// Keyword must not be followed by identifier characters, e.g. `TRUE_VALUE`.
+/// This check must be done without skipping spaces, otherwise `TRUE THEN` is rejected.
+fn logical_keyword(input: &str) -> RawParseResult<Logical> {
+    use nom::{
+        branch::alt, bytes::complete::tag_no_case, character::complete::satisfy, combinator::*,
+        sequence::terminated, Parser,
+    };
+    let keyword = |keyword: &'static str| {
+        terminated(
+            tag_no_case(keyword),
+            not(satisfy(|c: char| c.is_alphanumeric() || c == '_')),
+        )
+    };
+    alt((
+        value(Logical::True, keyword("TRUE")),
+        value(Logical::False, keyword("FALSE")),
+        value(Logical::Unknown, keyword("UNKNOWN")),
     ))
-    .map(|(logical, _non_alpha)| logical)
     .parse(input)
 }
 
+/// 139 binary_literal = `%` bit { bit } .
+pub fn binary_literal(input: &str) -> ParseResult<Vec<bool>> {
+    tuple((char('%'), remarked(bits)))
+        .map(|(_percent, bits)| bits.chars().map(|c| c == '1').collect())
+        .parse(input)
+}
+
+fn bits(input: &str) -> RawParseResult<&str> {
+    nom::bytes::complete::is_a("01")(input)
+}
+
 /// 141 integer_literal = digits .
 ///
 /// Negative integer, e.g. `-23`,
@@ -57,6 +80,25 @@ pub fn string_literal(input: &str) -> ParseResult<String> {
 mod tests {
     use nom::Finish;
 
+    #[test]
+    fn logical_literal() {
+        let (residual, (value, _remarks)) = super::logical_literal("TRUE THEN").finish().unwrap();
+        assert_eq!(value, crate::ast::Logical::True);
+        assert_eq!(residual, " THEN");
+
+        assert!(super::logical_literal("TRUE_VALUE").finish().is_err());
+        assert!(super::logical_literal("FALSEHOOD").finish().is_err());
+    }
+
+    #[test]
+    fn binary_literal() {
+        let (residual, (value, _remarks)) = super::binary_literal("%0101").finish().unwrap();
+        assert_eq!(value, vec![false, true, false, true]);
+        assert_eq!(residual, "");
+
+        assert!(super::binary_literal("%").finish().is_err());
+    }
+
     #[test]
     fn integer_literal() {
         let (residual, (value, _remarks)) = super::integer_literal("123").finish().unwrap();
@@ -111,6 +111,11 @@ pub fn remark_tag(input: &str) -> RawParseResult<Vec<String>> {
     delimited(char('"'), separated_list1(char('.'), simple_id), char('"')).parse(input)
 }
 
+/// Remark following other tokens in the same line, e.g. `-- coordinate` in `x : REAL; -- coordinate`
+pub fn trailing_remark(input: &str) -> RawParseResult<Remark> {
+    preceded(space0, alt((embedded_remark, tail_remark))).parse(input)
+}
+
 /// Match to spaces or remarks
 pub fn spaces_or_remarks(input: &str) -> RawParseResult<Vec<Remark>> {
     tuple((
@@ -243,6 +248,20 @@ mod tests {
         assert_eq!(remark.remark, "");
     }
 
+    #[test]
+    fn trailing_remark() {
+        let (res, remark) = super::trailing_remark(" -- aaa\nbbb").finish().unwrap();
+        assert_eq!(res, "bbb");
+        assert_eq!(remark.remark, "aaa");
+
+        let (res, remark) = super::trailing_remark("\t(* aaa *)\nbbb").finish().unwrap();
+        assert_eq!(res, "\nbbb");
+        assert_eq!(remark.remark, "aaa");
+
+        // Remark in the next line is not a trailing remark
+        assert!(super::trailing_remark("\n-- aaa\n").finish().is_err());
+    }
+
     #[test]
     fn remark_tag() {
         let (res, tag) = super::remark_tag(r#""some.name.space""#).finish().unwrap();
@@ -50,7 +50,20 @@ pub fn schema_body(
     tuple((
         many0(interface_specification),
         opt(constant_decl).map(|opt| opt.unwrap_or_default()),
-        many0(alt((declaration, rule_decl.map(Declaration::Rule)))),
+        many0_with_leading_remarks(alt((declaration, rule_decl.map(Declaration::Rule)))).map(
+            |decls| {
+                decls
+                    .into_iter()
+                    .map(|(decl, leading)| match decl {
+                        Declaration::Entity(mut entity) => {
+                            entity.remarks.splice(0..0, leading);
+                            Declaration::Entity(entity)
+                        }
+                        _ => decl,
+                    })
+                    .collect()
+            },
+        ),
     ))
     .parse(input)
 }
@@ -68,28 +81,30 @@ pub fn declaration(input: &str) -> ParseResult<Declaration> {
 }
 
 /// 271 procedure_decl = [procedure_head] [algorithm_head] { [stmt] } END_PROCEDURE `;` .
+///
+/// Statements which cannot be parsed are kept as [Statement::Opaque].
 pub fn procedure_decl(input: &str) -> ParseResult<Procedure> {
-    tuple((
+    semicolon_terminated(tuple((
         procedure_head,
         algorithm_head,
-        many0(stmt),
-        tag("END_PROCEDURE"),
-        char(';'),
-    ))
+        alt((
+            tuple((many0(stmt), tag("END_PROCEDURE"))),
+            tuple((
+                opaque_body("PROCEDURE", "END_PROCEDURE").map(|body| vec![body]),
+                tag("END_PROCEDURE"),
+            )),
+        )),
+    )))
     .map(
-        |(
-            (name, parameters),
-            (declarations, constants, variables),
-            statements,
-            _end,
-            _semicolon,
-        )| Procedure {
-            name,
-            parameters,
-            declarations,
-            constants,
-            variables,
-            statements,
+        |((name, parameters), (declarations, constants, variables), (statements, _end))| {
+            Procedure {
+                name,
+                parameters,
+                declarations,
+                constants,
+                variables,
+                statements,
+            }
         },
     )
     .parse(input)
@@ -126,21 +141,25 @@ pub fn procedure_head(input: &str) -> ParseResult<(String, Vec<FormalParameter>)
 }
 
 /// 220 function_decl = [function_head] [algorithm_head] [stmt] { [stmt] } END_FUNCTION `;` .
+///
+/// Statements which cannot be parsed are kept as [Statement::Opaque].
 pub fn function_decl(input: &str) -> ParseResult<Function> {
-    tuple((
+    semicolon_terminated(tuple((
         function_head,
         algorithm_head,
-        many1(stmt),
-        tag("END_FUNCTION"),
-        char(';'),
-    ))
+        alt((
+            tuple((many1(stmt), tag("END_FUNCTION"))),
+            tuple((
+                opaque_body("FUNCTION", "END_FUNCTION").map(|body| vec![body]),
+                tag("END_FUNCTION"),
+            )),
+        )),
+    )))
     .map(
         |(
             (name, parameters, return_type),
             (declarations, constants, variables),
-            statements,
-            _end,
-            _semicolon,
+            (statements, _end),
         )| Function {
             name,
             parameters,
@@ -221,14 +240,13 @@ pub fn constant_body(input: &str) -> ParseResult<Constant> {
 
 /// 291 rule_decl = [rule_head] [algorithm_head] { [stmt] } [where_clause] END_RULE `;` .
 pub fn rule_decl(input: &str) -> ParseResult<Rule> {
-    tuple((
+    semicolon_terminated(tuple((
         rule_head,
         algorithm_head,
         many0(stmt),
         where_clause,
         tag("END_RULE"),
-        char(';'),
-    ))
+    )))
     .map(
         |(
             (name, references),
@@ -236,7 +254,6 @@ pub fn rule_decl(input: &str) -> ParseResult<Rule> {
             statements,
             where_clause,
             _end,
-            _semicolon,
         )| Rule {
             name,
             references,
@@ -430,6 +447,51 @@ mod tests {
         assert_eq!(residual, "");
     }
 
+    #[test]
+    fn schema_remarks() {
+        let exp_str = r#"
+        SCHEMA my_first_schema;
+          TYPE label = STRING;
+          END_TYPE;
+
+          (* The first entity,
+             with remarks *)
+          ENTITY first; -- in head
+            -- leading
+            fattr : STRING; -- trailing
+            sattr : STRING;
+          END_ENTITY;
+          -- The second entity
+          ENTITY second;
+          END_ENTITY;
+        END_SCHEMA;
+        "#
+        .trim();
+
+        let (residual, (schema, remarks)) = super::schema_decl(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        // All remarks are still collected
+        assert_eq!(remarks.len(), 5);
+
+        let texts = |remarks: &[Remark]| -> Vec<String> {
+            remarks.iter().map(|r| r.remark.clone()).collect()
+        };
+        let first = &schema.entities[0];
+        assert_eq!(
+            texts(&first.remarks),
+            vec!["The first entity,\n             with remarks", "in head"]
+        );
+        assert_eq!(
+            texts(&first.attributes[0].remarks),
+            vec!["leading", "trailing"]
+        );
+        assert!(first.attributes[1].remarks.is_empty());
+        assert_eq!(
+            texts(&schema.entities[1].remarks),
+            vec!["The second entity"]
+        );
+    }
+
     #[test]
     fn constant() {
         let exp_str = r#"
@@ -569,6 +631,96 @@ mod tests {
         assert_eq!(residual, "");
     }
 
+    #[test]
+    fn function_opaque() {
+        // `lo <= x <= hi` without braces is not an interval expression
+        let exp_str = r#"
+        FUNCTION in_range(lo, x, hi : REAL) : BOOLEAN;
+            IF TRUE THEN
+                RETURN (lo <= x <= hi); -- END_FUNCTION;
+            END_IF;
+        END_FUNCTION;
+        "#
+        .trim();
+        let (residual, (f, _remark)) = super::function_decl(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        assert_eq!(f.name, "in_range");
+        assert_eq!(f.parameters.len(), 3);
+        assert!(matches!(
+            f.statements.as_slice(),
+            [crate::ast::Statement::Opaque(body)]
+                if body.starts_with("IF TRUE THEN") && body.ends_with("END_IF;")
+        ));
+    }
+
+    #[test]
+    fn function_case() {
+        let exp_str = r#"
+        FUNCTION dimension_of(dim : dimension_kind) : INTEGER;
+            CASE dim OF
+                planar  : RETURN (2);
+                spatial : RETURN (3);
+                OTHERWISE : RETURN (?);
+            END_CASE;
+        END_FUNCTION;
+        "#
+        .trim();
+        let (residual, (f, _remark)) = super::function_decl(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        assert!(matches!(
+            f.statements.as_slice(),
+            [crate::ast::Statement::Case { actions, otherwise: Some(_), .. }] if actions.len() == 2
+        ));
+    }
+
+    #[test]
+    fn function_repeat() {
+        let exp_str = r#"
+        FUNCTION sum_of(values : LIST OF REAL) : REAL;
+            LOCAL
+                total : REAL := 0.0;
+            END_LOCAL;
+            REPEAT i := 1 TO HIINDEX(values);
+                total := total + values[i];
+            END_REPEAT;
+            RETURN (total);
+        END_FUNCTION;
+        "#
+        .trim();
+        let (residual, (f, _remark)) = super::function_decl(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        assert!(matches!(
+            f.statements.as_slice(),
+            [
+                crate::ast::Statement::Repeat { .. },
+                crate::ast::Statement::Return { .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn schema_with_opaque_function() {
+        let exp_str = r#"
+        SCHEMA opaque;
+            ENTITY point;
+                x : REAL;
+            END_ENTITY;
+            FUNCTION f(p : point) : REAL;
+                RETURN (0.0 < p.x < 1.0);
+            END_FUNCTION;
+            PROCEDURE g(VAR p : point);
+                p.x := 1.0 1.0;
+            END_PROCEDURE;
+        END_SCHEMA;
+        "#
+        .trim();
+        let (residual, (schema, _remark)) = super::schema_decl(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        assert_eq!(schema.entities.len(), 1);
+        assert_eq!(schema.functions.len(), 1);
+        assert_eq!(schema.procedures.len(), 1);
+    }
+
     #[test]
     fn function_generic() {
         // From ISO-10303-11 p.67-68
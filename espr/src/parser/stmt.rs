@@ -19,6 +19,89 @@ pub fn stmt(input: &str) -> ParseResult<Statement> {
     .parse(input)
 }
 
+/// Fallback for statements which [stmt] cannot parse
+///
+/// This consumes any text until the `end` keyword, e.g. `END_FUNCTION`, appears
+/// as a token at the same nesting level, i.e. nested `begin` and `end` pairs are skipped.
+/// String literals and remarks are skipped so that keywords in them are not counted.
+pub fn opaque_body<'a>(begin: &'static str, end: &'static str) -> impl EsprParser<'a, Statement> {
+    move |input: &'a str| {
+        let bytes = input.as_bytes();
+        let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+        let is_keyword_at = |i: usize, keyword: &str| {
+            let last = i + keyword.len();
+            last <= bytes.len()
+                && bytes[i..last].eq_ignore_ascii_case(keyword.as_bytes())
+                && (i == 0 || !is_ident(bytes[i - 1]))
+                && bytes.get(last).map_or(true, |b| !is_ident(*b))
+        };
+        // Work on bytes since `i` may not be a char boundary in multi-byte characters
+        let skip_until = |i: usize, pattern: &[u8]| {
+            bytes[i.min(bytes.len())..]
+                .windows(pattern.len())
+                .position(|w| w == pattern)
+                .map_or(bytes.len(), |pos| i + pos + pattern.len())
+        };
+
+        let mut depth = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'(' if bytes.get(i + 1) == Some(&b'*') => {
+                    // embedded remarks can be nested
+                    let mut level = 0;
+                    while i < bytes.len() {
+                        if bytes[i..].starts_with(b"(*") {
+                            level += 1;
+                            i += 2;
+                        } else if bytes[i..].starts_with(b"*)") {
+                            level -= 1;
+                            i += 2;
+                            if level == 0 {
+                                break;
+                            }
+                        } else {
+                            i += 1;
+                        }
+                    }
+                }
+                b'-' if bytes.get(i + 1) == Some(&b'-') => i = skip_until(i, b"\n"),
+                b'\'' => {
+                    // `''` in simple string literal is a escaped apostrophe,
+                    // and it is processed as two adjacent literals here.
+                    i = skip_until(i + 1, b"'");
+                }
+                b'"' => i = skip_until(i + 1, b"\""),
+                _ if is_keyword_at(i, begin) => {
+                    depth += 1;
+                    i += begin.len();
+                }
+                _ if is_keyword_at(i, end) => {
+                    if depth == 0 {
+                        let body = input[..i].trim();
+                        if body.is_empty() {
+                            break;
+                        }
+                        return Ok((
+                            &input[i..],
+                            (Statement::Opaque(body.to_string()), Vec::new()),
+                        ));
+                    }
+                    depth -= 1;
+                    i += end.len();
+                }
+                _ => i += 1,
+            }
+        }
+        Err(nom::Err::Error(nom::error::VerboseError {
+            errors: vec![(
+                input,
+                nom::error::VerboseErrorKind::Context("opaque statements"),
+            )],
+        }))
+    }
+}
+
 /// 174 alias_stmt = ALIAS [variable_id] FOR [general_ref] { [qualifier] } `;` [stmt] { [stmt] } END_ALIAS `;` .
 pub fn alias_stmt(input: &str) -> ParseResult<Statement> {
     tuple((
@@ -265,6 +348,7 @@ pub fn skip_stmt(input: &str) -> ParseResult<Statement> {
 
 #[cfg(test)]
 mod tests {
+    use crate::ast::Statement;
     use nom::Finish;
 
     #[test]
@@ -283,6 +367,57 @@ mod tests {
         assert_eq!(residual, "");
     }
 
+    #[test]
+    fn case() {
+        // From ISO-10303-11 p.116
+        let exp_str = r#"
+        CASE a OF
+            1     : x := SIN(a) ;
+            2     : x := EXP(a) ;
+            3     : x := SQRT(a) ;
+            4, 5  : x := LOG(a) ;
+            OTHERWISE : x := 0 ;
+        END_CASE ;
+        "#
+        .trim();
+        let (residual, (result, _remark)) = super::case_stmt(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        match result {
+            super::Statement::Case {
+                actions, otherwise, ..
+            } => {
+                assert_eq!(actions.len(), 4);
+                assert_eq!(actions[3].0.len(), 2);
+                assert!(otherwise.is_some());
+            }
+            _ => panic!("Must be CASE"),
+        }
+    }
+
+    #[test]
+    fn case_enumeration() {
+        let exp_str = r#"
+        CASE side OF
+            left  : BEGIN
+                      RETURN (-1);
+                    END;
+            right : RETURN (1);
+        END_CASE;
+        "#
+        .trim();
+        let (residual, (result, _remark)) = super::stmt(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        match result {
+            super::Statement::Case {
+                actions, otherwise, ..
+            } => {
+                assert_eq!(actions.len(), 2);
+                assert!(otherwise.is_none());
+            }
+            _ => panic!("Must be CASE"),
+        }
+    }
+
     #[test]
     fn if_then() {
         let exp_str = r#"
@@ -314,6 +449,54 @@ mod tests {
         assert_eq!(residual, "");
     }
 
+    #[test]
+    fn if_else_if() {
+        let exp_str = r#"
+        IF a < 0 THEN
+            c := -1;
+        ELSE
+            IF a = 0 THEN
+                c := 0;
+            ELSE
+                IF ifc THEN
+                    c := 2;
+                END_IF;
+                c := 1;
+            END_IF;
+        END_IF;
+        "#
+        .trim();
+        let (residual, (result, _remark)) = super::if_stmt(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        let else_branch = match result {
+            Statement::If {
+                else_branch: Some(else_branch),
+                ..
+            } => else_branch,
+            _ => panic!("{:?}", result),
+        };
+        match else_branch.as_slice() {
+            [Statement::If {
+                then_branch,
+                else_branch: Some(nested_else),
+                ..
+            }] => {
+                assert_eq!(then_branch.len(), 1);
+                assert!(matches!(
+                    nested_else.as_slice(),
+                    [
+                        Statement::If {
+                            else_branch: None,
+                            ..
+                        },
+                        Statement::Assignment { .. }
+                    ]
+                ));
+            }
+            _ => panic!("{:?}", else_branch),
+        }
+    }
+
     #[test]
     fn procedure_call() {
         // From ISO-10303-11 p.128
@@ -403,4 +586,100 @@ mod tests {
         dbg!(&result);
         assert_eq!(residual, "");
     }
+
+    #[test]
+    fn repeat_by() {
+        let exp_str = r#"
+        REPEAT i := 10 TO 1 BY -2;
+            x := x + i;
+        END_REPEAT;
+        "#
+        .trim();
+        let (residual, (result, _remark)) = super::repeat_stmt(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        match result {
+            super::Statement::Repeat {
+                control,
+                statements,
+            } => {
+                let increment = control.increment.unwrap();
+                assert_eq!(increment.variable, "i");
+                assert!(increment.increment.is_some());
+                assert!(control.while_.is_none());
+                assert!(control.until.is_none());
+                assert_eq!(statements.len(), 1);
+            }
+            _ => panic!("Must be REPEAT"),
+        }
+    }
+
+    #[test]
+    fn repeat_while() {
+        let exp_str = r#"
+        REPEAT WHILE (i < 10);
+            i := i + 1;
+        END_REPEAT;
+        "#
+        .trim();
+        let (residual, (result, _remark)) = super::repeat_stmt(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        match result {
+            super::Statement::Repeat { control, .. } => {
+                assert!(control.increment.is_none());
+                assert!(control.while_.is_some());
+                assert!(control.until.is_none());
+            }
+            _ => panic!("Must be REPEAT"),
+        }
+    }
+
+    #[test]
+    fn repeat_all_controls() {
+        let exp_str = r#"
+        REPEAT i := 1 TO 10 BY 2 WHILE (x > 0) UNTIL (x < 1.0E-6);
+            x := x / 2;
+            IF x = 0 THEN
+                ESCAPE;
+            END_IF;
+        END_REPEAT;
+        "#
+        .trim();
+        let (residual, (result, _remark)) = super::repeat_stmt(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        match result {
+            super::Statement::Repeat {
+                control,
+                statements,
+            } => {
+                assert!(control.increment.unwrap().increment.is_some());
+                assert!(control.while_.is_some());
+                assert!(control.until.is_some());
+                assert_eq!(statements.len(), 2);
+            }
+            _ => panic!("Must be REPEAT"),
+        }
+    }
+
+    #[test]
+    fn opaque_body() {
+        use crate::parser::combinator::EsprParser;
+        let opaque = |input| super::opaque_body("FUNCTION", "END_FUNCTION").parse(input);
+        let exp_str = r#"
+        FUNCTION g : REAL; RETURN (1 < 2 < 3); END_FUNCTION;
+        x := 'END_FUNCTION'; (* END_FUNCTION (* nested *) *)
+        y := "00000041"; -- END_FUNCTION
+        END_FUNCTION;
+        "#
+        .trim();
+        let (residual, (result, _remark)) = opaque(exp_str).finish().unwrap();
+        assert_eq!(residual, "END_FUNCTION;");
+        assert!(
+            matches!(result, crate::ast::Statement::Opaque(body) if body.ends_with("-- END_FUNCTION"))
+        );
+
+        // Empty body is not opaque statements
+        assert!(opaque("END_FUNCTION;").finish().is_err());
+        // `end` keyword is required
+        assert!(opaque("x := 1;").finish().is_err());
+    }
 }
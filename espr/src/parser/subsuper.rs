@@ -115,14 +115,13 @@ pub fn one_of(input: &str) -> ParseResult<SuperTypeExpression> {
 
 /// 315 subtype_constraint_decl = [subtype_constraint_head] [subtype_constraint_body] END_SUBTYPE_CONSTRAINT `;` .
 pub fn subtype_constraint_decl(input: &str) -> ParseResult<SubTypeConstraint> {
-    tuple((
+    semicolon_terminated(tuple((
         subtype_constraint_head,
         subtype_constraint_body,
         tag("END_SUBTYPE_CONSTRAINT"),
-        char(';'),
-    ))
+    )))
     .map(
-        |((name, entity), (is_abstract, total_over, expr), _end, _semicolon)| SubTypeConstraint {
+        |((name, entity), (is_abstract, total_over, expr), _end)| SubTypeConstraint {
             name,
             entity,
             is_abstract,
@@ -163,7 +162,7 @@ pub fn total_over(input: &str) -> ParseResult<Vec<String>> {
     tuple((
         tag("TOTAL_OVER"),
         char('('),
-        many1(entity_ref),
+        comma_separated(entity_ref),
         char(')'),
         char(';'),
     ))
@@ -180,6 +179,7 @@ pub fn abstract_supertype(input: &str) -> ParseResult<()> {
 
 #[cfg(test)]
 mod tests {
+    use crate::ast::*;
     use nom::Finish;
 
     #[test]
@@ -196,5 +196,134 @@ mod tests {
             super::subtype_constraint_decl(exp_str).finish().unwrap();
         dbg!(&entity);
         assert_eq!(residual, "");
+        assert_eq!(
+            entity,
+            SubTypeConstraint {
+                name: "separate_species".to_string(),
+                entity: "pet".to_string(),
+                is_abstract: true,
+                total_over: None,
+                expr: Some(SuperTypeExpression::OneOf {
+                    exprs: vec![
+                        SuperTypeExpression::Reference("cat".to_string()),
+                        SuperTypeExpression::Reference("rabbit".to_string()),
+                        SuperTypeExpression::Reference("dog".to_string()),
+                    ]
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn subtype_constraint_total_over() {
+        let exp_str = r#"
+        SUBTYPE_CONSTRAINT person_sc FOR person;
+          TOTAL_OVER(male, female);
+          ONEOF(male, female) AND ONEOF(citizen, alien);
+        END_SUBTYPE_CONSTRAINT;
+        "#
+        .trim();
+
+        let (residual, (entity, _remark)) =
+            super::subtype_constraint_decl(exp_str).finish().unwrap();
+        dbg!(&entity);
+        assert_eq!(residual, "");
+        assert_eq!(
+            entity,
+            SubTypeConstraint {
+                name: "person_sc".to_string(),
+                entity: "person".to_string(),
+                is_abstract: false,
+                total_over: Some(vec!["male".to_string(), "female".to_string()]),
+                expr: Some(SuperTypeExpression::And {
+                    terms: vec![
+                        SuperTypeExpression::OneOf {
+                            exprs: vec![
+                                SuperTypeExpression::Reference("male".to_string()),
+                                SuperTypeExpression::Reference("female".to_string()),
+                            ]
+                        },
+                        SuperTypeExpression::OneOf {
+                            exprs: vec![
+                                SuperTypeExpression::Reference("citizen".to_string()),
+                                SuperTypeExpression::Reference("alien".to_string()),
+                            ]
+                        },
+                    ]
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn subtype_constraint_empty_body() {
+        let exp_str = r#"
+        SUBTYPE_CONSTRAINT sc FOR person;
+        END_SUBTYPE_CONSTRAINT;
+        "#
+        .trim();
+
+        let (residual, (entity, _remark)) =
+            super::subtype_constraint_decl(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        assert!(!entity.is_abstract);
+        assert_eq!(entity.total_over, None);
+        assert_eq!(entity.expr, None);
+    }
+
+    #[test]
+    fn supertype_expression_nested() {
+        use SuperTypeExpression::*;
+        let r = |name: &str| Reference(name.to_string());
+
+        let (residual, (expr, _remarks)) = super::supertype_expression("ONEOF(a, b AND c) ANDOR d")
+            .finish()
+            .unwrap();
+        assert_eq!(residual, "");
+        assert_eq!(
+            expr,
+            AndOr {
+                factors: vec![
+                    OneOf {
+                        exprs: vec![
+                            r("a"),
+                            And {
+                                terms: vec![r("b"), r("c")]
+                            }
+                        ]
+                    },
+                    r("d"),
+                ]
+            }
+        );
+
+        let (residual, (expr, _remarks)) =
+            super::supertype_expression("(a ANDOR b) AND ONEOF(c, ONEOF(d, e) ANDOR f)")
+                .finish()
+                .unwrap();
+        assert_eq!(residual, "");
+        assert_eq!(
+            expr,
+            And {
+                terms: vec![
+                    AndOr {
+                        factors: vec![r("a"), r("b")]
+                    },
+                    OneOf {
+                        exprs: vec![
+                            r("c"),
+                            AndOr {
+                                factors: vec![
+                                    OneOf {
+                                        exprs: vec![r("d"), r("e")]
+                                    },
+                                    r("f"),
+                                ]
+                            },
+                        ]
+                    },
+                ]
+            }
+        );
     }
 }
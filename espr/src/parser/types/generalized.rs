@@ -176,4 +176,53 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn generic() {
+        let (res, (ty, _remarks)) = super::parameter_type("GENERIC").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(ty, Type::Generic(None));
+
+        let (res, (ty, _remarks)) = super::parameter_type("GENERIC : gen").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(ty, Type::Generic(Some("gen".to_string())));
+
+        let (res, (ty, _remarks)) = super::parameter_type("GENERIC_ENTITY:ent")
+            .finish()
+            .unwrap();
+        assert_eq!(res, "");
+        assert_eq!(ty, Type::GenericEntity(Some("ent".to_string())));
+    }
+
+    #[test]
+    fn aggregate() {
+        let (res, (ty, _remarks)) = super::parameter_type("AGGREGATE OF GENERIC")
+            .finish()
+            .unwrap();
+        assert_eq!(res, "");
+        assert_eq!(
+            ty,
+            Type::Aggregate {
+                base: Box::new(Type::Generic(None)),
+                label: None,
+            }
+        );
+
+        let (res, (ty, _remarks)) =
+            super::parameter_type("AGGREGATE:intype OF LIST OF GENERIC:gen")
+                .finish()
+                .unwrap();
+        assert_eq!(res, "");
+        assert_eq!(
+            ty,
+            Type::Aggregate {
+                base: Box::new(Type::List {
+                    base: Box::new(Type::Generic(Some("gen".to_string()))),
+                    bound: None,
+                    unique: false,
+                }),
+                label: Some("intype".to_string()),
+            }
+        );
+    }
 }
@@ -25,7 +25,7 @@ pub fn underlying_type(input: &str) -> ParseResult<Type> {
 
 /// 327 type_decl = TYPE [type_id] `=` [underlying_type] `;` \[ [where_clause] \] END_TYPE `;` .
 pub fn type_decl(input: &str) -> ParseResult<TypeDecl> {
-    tuple((
+    semicolon_terminated(tuple((
         tag("TYPE"),
         type_id,
         char('='),
@@ -33,24 +33,12 @@ pub fn type_decl(input: &str) -> ParseResult<TypeDecl> {
         char(';'),
         opt(where_clause),
         tag("END_TYPE"),
-        char(';'),
-    ))
+    )))
     .map(
-        |(
-            _start,
+        |(_start, type_id, _equal, underlying_type, _semicolon1, where_clause, _end)| TypeDecl {
             type_id,
-            _equal,
             underlying_type,
-            _semicolon1,
             where_clause,
-            _end,
-            _semicolon2,
-        )| {
-            TypeDecl {
-                type_id,
-                underlying_type,
-                where_clause,
-            }
         },
     )
     .parse(input)
@@ -26,9 +26,13 @@ fn any() {
 
     insta::assert_snapshot!(tt, @r###"
     pub mod test_schema {
-        use ruststep::{as_holder, derive_more::*, primitive::*, Holder, TableInit};
+        use ruststep::{
+            as_holder, derive_more::*, express_builtins::TypeOf, primitive::*, ApproxEq, HashBits,
+            Holder, Serialize, TableInit,
+        };
         use std::collections::HashMap;
         #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        #[table_init(schema = "test_schema")]
         pub struct Tables {
             base: HashMap<u64, as_holder!(Base)>,
             sub1: HashMap<u64, as_holder!(Sub1)>,
@@ -44,15 +48,126 @@ fn any() {
             pub fn sub2_holders(&self) -> &HashMap<u64, as_holder!(Sub2)> {
                 &self.sub2
             }
+            #[doc = r" Ids of all entities in arbitrary order, including complex entity instances"]
+            pub fn all_ids(&self) -> impl Iterator<Item = u64> + '_ {
+                let ids: Vec<Box<dyn Iterator<Item = u64> + '_>> = vec![
+                    Box::new(self.base.keys().cloned()),
+                    Box::new(self.sub1.keys().cloned()),
+                    Box::new(self.sub2.keys().cloned()),
+                ];
+                ids.into_iter().flatten()
+            }
+            #[doc = r" Records of all entities in arbitrary order, with references kept as references"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not included yet."]
+            pub fn all_records(
+                &self,
+            ) -> impl Iterator<Item = ::ruststep::error::Result<(u64, ::ruststep::ast::Record)>> + '_
+            {
+                let records: Vec<Box<dyn Iterator<Item = _> + '_>> = vec![
+                    ::ruststep::tables::records(&self.base),
+                    ::ruststep::tables::records(&self.sub1),
+                    ::ruststep::tables::records(&self.sub2),
+                ];
+                records.into_iter().flatten()
+            }
+            #[doc = r" Check `UNIQUE` rules, and returns entities sharing the same values"]
+            #[doc = r""]
+            #[doc = r" Rules are checked within the table of each entity,"]
+            #[doc = r" i.e. instances of its subtypes are not checked yet."]
+            pub fn check_unique(
+                &self,
+            ) -> ::ruststep::error::Result<Vec<::ruststep::tables::UniqueViolation>> {
+                let violations: Vec<Vec<::ruststep::tables::UniqueViolation>> = vec![];
+                Ok(violations.into_iter().flatten().collect())
+            }
+            #[doc = r" Compare entities with another tables by their ids,"]
+            #[doc = r" where `REAL` values differing at most `tol` are regarded as equal"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not compared yet."]
+            pub fn diff(
+                &self,
+                other: &Self,
+                tol: f64,
+            ) -> ::ruststep::error::Result<::ruststep::tables::TablesDiff> {
+                ::ruststep::tables::diff_records(self.all_records(), other.all_records(), tol)
+            }
+            #[doc = r" Write all entities into a whole exchange structure with `header`,"]
+            #[doc = r" where references are kept as references."]
+            #[doc = r""]
+            #[doc = r" The output is read back by `TableInit::from_exchange` into the same tables."]
+            pub fn to_step_string(
+                &self,
+                header: ::ruststep::header::HeaderInfo,
+            ) -> ::ruststep::error::Result<String> {
+                let mut entities = Vec::new();
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.base)?);
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.sub1)?);
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.sub2)?);
+                ::ruststep::tables::write_exchange::<Self>(&header, entities)
+            }
+            #[doc = r" Store each record with its entity id into the table of its entity name,"]
+            #[doc = r" where records whose entity names are not in this schema are skipped."]
+            #[doc = r""]
+            #[doc = r" Use `TableInit::from_records_lossy` to get the skipped records."]
+            pub fn try_from_records(
+                records: impl IntoIterator<Item = (u64, ::ruststep::ast::Record)>,
+            ) -> ::ruststep::error::Result<Self> {
+                let (tables, _unknowns) =
+                    <Self as ::ruststep::tables::TableInit>::from_records_lossy(records)?;
+                Ok(tables)
+            }
+        }
+        #[doc = r" Store each record with its entity id into the table of its entity name,"]
+        #[doc = r" where records which cannot be stored are skipped, see `TableInit::from_records_skipping`."]
+        #[doc = r""]
+        #[doc = r" Use `Tables::try_from_records` to get errors instead."]
+        impl ::std::iter::FromIterator<(u64, ::ruststep::ast::Record)> for Tables {
+            fn from_iter<I: IntoIterator<Item = (u64, ::ruststep::ast::Record)>>(iter: I) -> Self {
+                <Self as ::ruststep::tables::TableInit>::from_records_skipping(iter)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Base {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Base)>(record)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Sub1 {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Sub1)>(record)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Sub2 {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Sub2)>(record)
+            }
         }
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            Default,
+        )]
         # [holder (table = Tables)]
         # [holder (field = base)]
         #[holder(generate_deserialize)]
         pub struct Base {
             pub x: f64,
         }
-        #[derive(Debug, Clone, PartialEq, Holder)]
+        impl TypeOf for Base {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.BASE"]
+            }
+        }
+        #[derive(Debug, Clone, PartialEq, Holder, Serialize, ApproxEq, HashBits)]
         # [holder (table = Tables)]
         #[holder(generate_deserialize)]
         pub enum BaseAny {
@@ -87,8 +202,55 @@ fn any() {
                 }
             }
         }
+        impl ::std::convert::TryFrom<BaseAny> for Base {
+            type Error = BaseAny;
+            fn try_from(any: BaseAny) -> ::std::result::Result<Self, Self::Error> {
+                match any {
+                    BaseAny::Base(x) => Ok(*x),
+                    _ => Err(any),
+                }
+            }
+        }
+        impl ::std::convert::TryFrom<BaseAny> for Sub1 {
+            type Error = BaseAny;
+            fn try_from(any: BaseAny) -> ::std::result::Result<Self, Self::Error> {
+                match any {
+                    BaseAny::Sub1(x) => Ok(*x),
+                    _ => Err(any),
+                }
+            }
+        }
+        impl ::std::convert::TryFrom<BaseAny> for Sub2 {
+            type Error = BaseAny;
+            fn try_from(any: BaseAny) -> ::std::result::Result<Self, Self::Error> {
+                match any {
+                    BaseAny::Sub2(x) => Ok(*x),
+                    _ => Err(any),
+                }
+            }
+        }
+        impl TypeOf for BaseAny {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                match self {
+                    BaseAny::Base(value) => value.r#typeof(),
+                    BaseAny::Sub1(value) => value.r#typeof(),
+                    BaseAny::Sub2(value) => value.r#typeof(),
+                }
+            }
+        }
         #[derive(
-            Debug, Clone, PartialEq, :: derive_new :: new, Holder, AsRef, AsMut, Deref, DerefMut,
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            AsRef,
+            AsMut,
+            Deref,
+            DerefMut,
         )]
         # [holder (table = Tables)]
         # [holder (field = sub1)]
@@ -102,8 +264,34 @@ fn any() {
             pub base: Base,
             pub y1: f64,
         }
+        impl TypeOf for Sub1 {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.SUB1", "TEST_SCHEMA.BASE"]
+            }
+        }
+        impl From<Sub1> for Base {
+            fn from(sub: Sub1) -> Self {
+                sub.base
+            }
+        }
+        impl From<&Sub1> for Base {
+            fn from(sub: &Sub1) -> Self {
+                sub.base.clone()
+            }
+        }
         #[derive(
-            Debug, Clone, PartialEq, :: derive_new :: new, Holder, AsRef, AsMut, Deref, DerefMut,
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            AsRef,
+            AsMut,
+            Deref,
+            DerefMut,
         )]
         # [holder (table = Tables)]
         # [holder (field = sub2)]
@@ -117,6 +305,21 @@ fn any() {
             pub base: Base,
             pub y2: f64,
         }
+        impl TypeOf for Sub2 {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.SUB2", "TEST_SCHEMA.BASE"]
+            }
+        }
+        impl From<Sub2> for Base {
+            fn from(sub: Sub2) -> Self {
+                sub.base
+            }
+        }
+        impl From<&Sub2> for Base {
+            fn from(sub: &Sub2) -> Self {
+                sub.base.clone()
+            }
+        }
     }
     "###);
 }
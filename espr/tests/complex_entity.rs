@@ -0,0 +1,369 @@
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  ENTITY person SUPERTYPE OF (employee ANDOR student);
+    name: STRING;
+  END_ENTITY;
+
+  ENTITY employee SUBTYPE OF (person);
+    pay: INTEGER;
+  END_ENTITY;
+
+  ENTITY student SUBTYPE OF (person);
+    school_name: STRING;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn complex_entity() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ruststep::{
+            as_holder, derive_more::*, express_builtins::TypeOf, primitive::*, ApproxEq, HashBits,
+            Holder, Serialize, TableInit,
+        };
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        #[table_init(schema = "test_schema")]
+        pub struct Tables {
+            person: HashMap<u64, as_holder!(Person)>,
+            employee: HashMap<u64, as_holder!(Employee)>,
+            student: HashMap<u64, as_holder!(Student)>,
+            #[table_init(complex)]
+            employee_student: HashMap<u64, as_holder!(EmployeeStudent)>,
+        }
+        impl Tables {
+            pub fn person_holders(&self) -> &HashMap<u64, as_holder!(Person)> {
+                &self.person
+            }
+            pub fn employee_holders(&self) -> &HashMap<u64, as_holder!(Employee)> {
+                &self.employee
+            }
+            pub fn student_holders(&self) -> &HashMap<u64, as_holder!(Student)> {
+                &self.student
+            }
+            pub fn employee_student_holders(&self) -> &HashMap<u64, as_holder!(EmployeeStudent)> {
+                &self.employee_student
+            }
+            #[doc = r" Ids of all entities in arbitrary order, including complex entity instances"]
+            pub fn all_ids(&self) -> impl Iterator<Item = u64> + '_ {
+                let ids: Vec<Box<dyn Iterator<Item = u64> + '_>> = vec![
+                    Box::new(self.person.keys().cloned()),
+                    Box::new(self.employee.keys().cloned()),
+                    Box::new(self.student.keys().cloned()),
+                    Box::new(self.employee_student.keys().cloned()),
+                ];
+                ids.into_iter().flatten()
+            }
+            #[doc = r" Records of all entities in arbitrary order, with references kept as references"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not included yet."]
+            pub fn all_records(
+                &self,
+            ) -> impl Iterator<Item = ::ruststep::error::Result<(u64, ::ruststep::ast::Record)>> + '_
+            {
+                let records: Vec<Box<dyn Iterator<Item = _> + '_>> = vec![
+                    ::ruststep::tables::records(&self.person),
+                    ::ruststep::tables::records(&self.employee),
+                    ::ruststep::tables::records(&self.student),
+                ];
+                records.into_iter().flatten()
+            }
+            #[doc = r" Check `UNIQUE` rules, and returns entities sharing the same values"]
+            #[doc = r""]
+            #[doc = r" Rules are checked within the table of each entity,"]
+            #[doc = r" i.e. instances of its subtypes are not checked yet."]
+            pub fn check_unique(
+                &self,
+            ) -> ::ruststep::error::Result<Vec<::ruststep::tables::UniqueViolation>> {
+                let violations: Vec<Vec<::ruststep::tables::UniqueViolation>> = vec![];
+                Ok(violations.into_iter().flatten().collect())
+            }
+            #[doc = r" Compare entities with another tables by their ids,"]
+            #[doc = r" where `REAL` values differing at most `tol` are regarded as equal"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not compared yet."]
+            pub fn diff(
+                &self,
+                other: &Self,
+                tol: f64,
+            ) -> ::ruststep::error::Result<::ruststep::tables::TablesDiff> {
+                ::ruststep::tables::diff_records(self.all_records(), other.all_records(), tol)
+            }
+            #[doc = r" Write all entities into a whole exchange structure with `header`,"]
+            #[doc = r" where references are kept as references."]
+            #[doc = r""]
+            #[doc = r" The output is read back by `TableInit::from_exchange` into the same tables."]
+            pub fn to_step_string(
+                &self,
+                header: ::ruststep::header::HeaderInfo,
+            ) -> ::ruststep::error::Result<String> {
+                let mut entities = Vec::new();
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.person)?);
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.employee)?);
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.student)?);
+                entities.append(&mut ::ruststep::tables::complex_entity_instances(
+                    &self.employee_student,
+                    &["EMPLOYEE", "PERSON", "STUDENT"],
+                )?);
+                ::ruststep::tables::write_exchange::<Self>(&header, entities)
+            }
+            #[doc = r" Store each record with its entity id into the table of its entity name,"]
+            #[doc = r" where records whose entity names are not in this schema are skipped."]
+            #[doc = r""]
+            #[doc = r" Use `TableInit::from_records_lossy` to get the skipped records."]
+            pub fn try_from_records(
+                records: impl IntoIterator<Item = (u64, ::ruststep::ast::Record)>,
+            ) -> ::ruststep::error::Result<Self> {
+                let (tables, _unknowns) =
+                    <Self as ::ruststep::tables::TableInit>::from_records_lossy(records)?;
+                Ok(tables)
+            }
+        }
+        #[doc = r" Store each record with its entity id into the table of its entity name,"]
+        #[doc = r" where records which cannot be stored are skipped, see `TableInit::from_records_skipping`."]
+        #[doc = r""]
+        #[doc = r" Use `Tables::try_from_records` to get errors instead."]
+        impl ::std::iter::FromIterator<(u64, ::ruststep::ast::Record)> for Tables {
+            fn from_iter<I: IntoIterator<Item = (u64, ::ruststep::ast::Record)>>(iter: I) -> Self {
+                <Self as ::ruststep::tables::TableInit>::from_records_skipping(iter)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Person {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Person)>(record)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Employee {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Employee)>(record)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Student {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Student)>(record)
+            }
+        }
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            Default,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = person)]
+        #[holder(generate_deserialize)]
+        pub struct Person {
+            pub name: String,
+        }
+        impl TypeOf for Person {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.PERSON"]
+            }
+        }
+        #[derive(Debug, Clone, PartialEq, Holder, Serialize, ApproxEq, HashBits)]
+        # [holder (table = Tables)]
+        #[holder(generate_deserialize)]
+        pub enum PersonAny {
+            #[holder(use_place_holder)]
+            Person(Box<Person>),
+            #[holder(use_place_holder)]
+            Employee(Box<Employee>),
+            #[holder(use_place_holder)]
+            Student(Box<Student>),
+        }
+        impl Into<PersonAny> for Person {
+            fn into(self) -> PersonAny {
+                PersonAny::Person(Box::new(self))
+            }
+        }
+        impl Into<PersonAny> for Employee {
+            fn into(self) -> PersonAny {
+                PersonAny::Employee(Box::new(self.into()))
+            }
+        }
+        impl Into<PersonAny> for Student {
+            fn into(self) -> PersonAny {
+                PersonAny::Student(Box::new(self.into()))
+            }
+        }
+        impl AsRef<Person> for PersonAny {
+            fn as_ref(&self) -> &Person {
+                match self {
+                    PersonAny::Person(x) => x.as_ref(),
+                    PersonAny::Employee(x) => (**x).as_ref(),
+                    PersonAny::Student(x) => (**x).as_ref(),
+                }
+            }
+        }
+        impl ::std::convert::TryFrom<PersonAny> for Person {
+            type Error = PersonAny;
+            fn try_from(any: PersonAny) -> ::std::result::Result<Self, Self::Error> {
+                match any {
+                    PersonAny::Person(x) => Ok(*x),
+                    _ => Err(any),
+                }
+            }
+        }
+        impl ::std::convert::TryFrom<PersonAny> for Employee {
+            type Error = PersonAny;
+            fn try_from(any: PersonAny) -> ::std::result::Result<Self, Self::Error> {
+                match any {
+                    PersonAny::Employee(x) => Ok(*x),
+                    _ => Err(any),
+                }
+            }
+        }
+        impl ::std::convert::TryFrom<PersonAny> for Student {
+            type Error = PersonAny;
+            fn try_from(any: PersonAny) -> ::std::result::Result<Self, Self::Error> {
+                match any {
+                    PersonAny::Student(x) => Ok(*x),
+                    _ => Err(any),
+                }
+            }
+        }
+        impl TypeOf for PersonAny {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                match self {
+                    PersonAny::Person(value) => value.r#typeof(),
+                    PersonAny::Employee(value) => value.r#typeof(),
+                    PersonAny::Student(value) => value.r#typeof(),
+                }
+            }
+        }
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            AsRef,
+            AsMut,
+            Deref,
+            DerefMut,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = employee)]
+        #[holder(generate_deserialize)]
+        pub struct Employee {
+            #[as_ref]
+            #[as_mut]
+            #[deref]
+            #[deref_mut]
+            #[holder(use_place_holder)]
+            pub person: Person,
+            pub pay: i64,
+        }
+        impl TypeOf for Employee {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.EMPLOYEE", "TEST_SCHEMA.PERSON"]
+            }
+        }
+        impl From<Employee> for Person {
+            fn from(sub: Employee) -> Self {
+                sub.person
+            }
+        }
+        impl From<&Employee> for Person {
+            fn from(sub: &Employee) -> Self {
+                sub.person.clone()
+            }
+        }
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            AsRef,
+            AsMut,
+            Deref,
+            DerefMut,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = student)]
+        #[holder(generate_deserialize)]
+        pub struct Student {
+            #[as_ref]
+            #[as_mut]
+            #[deref]
+            #[deref_mut]
+            #[holder(use_place_holder)]
+            pub person: Person,
+            pub school_name: String,
+        }
+        impl TypeOf for Student {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.STUDENT", "TEST_SCHEMA.PERSON"]
+            }
+        }
+        impl From<Student> for Person {
+            fn from(sub: Student) -> Self {
+                sub.person
+            }
+        }
+        impl From<&Student> for Person {
+            fn from(sub: &Student) -> Self {
+                sub.person.clone()
+            }
+        }
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            AsRef,
+            AsMut,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = employee_student)]
+        #[holder(generate_deserialize)]
+        pub struct EmployeeStudent {
+            #[as_ref]
+            #[as_mut]
+            #[holder(use_place_holder)]
+            pub employee: Employee,
+            #[as_ref]
+            #[as_mut]
+            #[holder(use_place_holder)]
+            pub student: Student,
+        }
+        impl TypeOf for EmployeeStudent {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &[
+                    "TEST_SCHEMA.EMPLOYEE",
+                    "TEST_SCHEMA.PERSON",
+                    "TEST_SCHEMA.STUDENT",
+                ]
+            }
+        }
+    }
+    "###);
+}
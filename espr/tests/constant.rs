@@ -0,0 +1,275 @@
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  CONSTANT
+    pref_name_len : INTEGER := 70;
+    syn_name_len : INTEGER := pref_name_len;
+    offset : REAL := -0.5;
+    sep_id : STRING := '.';
+    strict : BOOLEAN := FALSE;
+    dummy_gri : geometric_representation_item := representation_item('') || geometric_representation_item();
+  END_CONSTANT;
+
+  ENTITY representation_item;
+    name : STRING;
+  END_ENTITY;
+
+  ENTITY geometric_representation_item SUBTYPE OF (representation_item);
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn constant() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ruststep::{
+            as_holder, derive_more::*, express_builtins::TypeOf, primitive::*, ApproxEq, HashBits,
+            Holder, Serialize, TableInit,
+        };
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        #[table_init(schema = "test_schema")]
+        pub struct Tables {
+            representation_item: HashMap<u64, as_holder!(RepresentationItem)>,
+            geometric_representation_item: HashMap<u64, as_holder!(GeometricRepresentationItem)>,
+        }
+        impl Tables {
+            pub fn representation_item_holders(&self) -> &HashMap<u64, as_holder!(RepresentationItem)> {
+                &self.representation_item
+            }
+            pub fn geometric_representation_item_holders(
+                &self,
+            ) -> &HashMap<u64, as_holder!(GeometricRepresentationItem)> {
+                &self.geometric_representation_item
+            }
+            #[doc = r" Ids of all entities in arbitrary order, including complex entity instances"]
+            pub fn all_ids(&self) -> impl Iterator<Item = u64> + '_ {
+                let ids: Vec<Box<dyn Iterator<Item = u64> + '_>> = vec![
+                    Box::new(self.representation_item.keys().cloned()),
+                    Box::new(self.geometric_representation_item.keys().cloned()),
+                ];
+                ids.into_iter().flatten()
+            }
+            #[doc = r" Records of all entities in arbitrary order, with references kept as references"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not included yet."]
+            pub fn all_records(
+                &self,
+            ) -> impl Iterator<Item = ::ruststep::error::Result<(u64, ::ruststep::ast::Record)>> + '_
+            {
+                let records: Vec<Box<dyn Iterator<Item = _> + '_>> = vec![
+                    ::ruststep::tables::records(&self.representation_item),
+                    ::ruststep::tables::records(&self.geometric_representation_item),
+                ];
+                records.into_iter().flatten()
+            }
+            #[doc = r" Check `UNIQUE` rules, and returns entities sharing the same values"]
+            #[doc = r""]
+            #[doc = r" Rules are checked within the table of each entity,"]
+            #[doc = r" i.e. instances of its subtypes are not checked yet."]
+            pub fn check_unique(
+                &self,
+            ) -> ::ruststep::error::Result<Vec<::ruststep::tables::UniqueViolation>> {
+                let violations: Vec<Vec<::ruststep::tables::UniqueViolation>> = vec![];
+                Ok(violations.into_iter().flatten().collect())
+            }
+            #[doc = r" Compare entities with another tables by their ids,"]
+            #[doc = r" where `REAL` values differing at most `tol` are regarded as equal"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not compared yet."]
+            pub fn diff(
+                &self,
+                other: &Self,
+                tol: f64,
+            ) -> ::ruststep::error::Result<::ruststep::tables::TablesDiff> {
+                ::ruststep::tables::diff_records(self.all_records(), other.all_records(), tol)
+            }
+            #[doc = r" Write all entities into a whole exchange structure with `header`,"]
+            #[doc = r" where references are kept as references."]
+            #[doc = r""]
+            #[doc = r" The output is read back by `TableInit::from_exchange` into the same tables."]
+            pub fn to_step_string(
+                &self,
+                header: ::ruststep::header::HeaderInfo,
+            ) -> ::ruststep::error::Result<String> {
+                let mut entities = Vec::new();
+                entities.append(&mut ::ruststep::tables::entity_instances(
+                    &self.representation_item,
+                )?);
+                entities.append(&mut ::ruststep::tables::entity_instances(
+                    &self.geometric_representation_item,
+                )?);
+                ::ruststep::tables::write_exchange::<Self>(&header, entities)
+            }
+            #[doc = r" Store each record with its entity id into the table of its entity name,"]
+            #[doc = r" where records whose entity names are not in this schema are skipped."]
+            #[doc = r""]
+            #[doc = r" Use `TableInit::from_records_lossy` to get the skipped records."]
+            pub fn try_from_records(
+                records: impl IntoIterator<Item = (u64, ::ruststep::ast::Record)>,
+            ) -> ::ruststep::error::Result<Self> {
+                let (tables, _unknowns) =
+                    <Self as ::ruststep::tables::TableInit>::from_records_lossy(records)?;
+                Ok(tables)
+            }
+        }
+        #[doc = r" Store each record with its entity id into the table of its entity name,"]
+        #[doc = r" where records which cannot be stored are skipped, see `TableInit::from_records_skipping`."]
+        #[doc = r""]
+        #[doc = r" Use `Tables::try_from_records` to get errors instead."]
+        impl ::std::iter::FromIterator<(u64, ::ruststep::ast::Record)> for Tables {
+            fn from_iter<I: IntoIterator<Item = (u64, ::ruststep::ast::Record)>>(iter: I) -> Self {
+                <Self as ::ruststep::tables::TableInit>::from_records_skipping(iter)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for RepresentationItem {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(RepresentationItem)>(record)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for GeometricRepresentationItem {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(GeometricRepresentationItem)>(record)
+            }
+        }
+        #[doc = "`CONSTANT pref_name_len` declared in EXPRESS schema"]
+        pub const PREF_NAME_LEN: i64 = 70;
+        #[doc = "`CONSTANT syn_name_len` declared in EXPRESS schema"]
+        pub const SYN_NAME_LEN: i64 = 70;
+        #[doc = "`CONSTANT offset` declared in EXPRESS schema"]
+        pub const OFFSET: f64 = -0.5;
+        #[doc = "`CONSTANT sep_id` declared in EXPRESS schema"]
+        pub const SEP_ID: &str = ".";
+        #[doc = "`CONSTANT strict` declared in EXPRESS schema"]
+        pub const STRICT: bool = false;
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            Default,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = representation_item)]
+        #[holder(generate_deserialize)]
+        pub struct RepresentationItem {
+            pub name: String,
+        }
+        impl TypeOf for RepresentationItem {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.REPRESENTATION_ITEM"]
+            }
+        }
+        #[derive(Debug, Clone, PartialEq, Holder, Serialize, ApproxEq, HashBits)]
+        # [holder (table = Tables)]
+        #[holder(generate_deserialize)]
+        pub enum RepresentationItemAny {
+            #[holder(use_place_holder)]
+            RepresentationItem(Box<RepresentationItem>),
+            #[holder(use_place_holder)]
+            GeometricRepresentationItem(Box<GeometricRepresentationItem>),
+        }
+        impl Into<RepresentationItemAny> for RepresentationItem {
+            fn into(self) -> RepresentationItemAny {
+                RepresentationItemAny::RepresentationItem(Box::new(self))
+            }
+        }
+        impl Into<RepresentationItemAny> for GeometricRepresentationItem {
+            fn into(self) -> RepresentationItemAny {
+                RepresentationItemAny::GeometricRepresentationItem(Box::new(self.into()))
+            }
+        }
+        impl AsRef<RepresentationItem> for RepresentationItemAny {
+            fn as_ref(&self) -> &RepresentationItem {
+                match self {
+                    RepresentationItemAny::RepresentationItem(x) => x.as_ref(),
+                    RepresentationItemAny::GeometricRepresentationItem(x) => (**x).as_ref(),
+                }
+            }
+        }
+        impl ::std::convert::TryFrom<RepresentationItemAny> for RepresentationItem {
+            type Error = RepresentationItemAny;
+            fn try_from(any: RepresentationItemAny) -> ::std::result::Result<Self, Self::Error> {
+                match any {
+                    RepresentationItemAny::RepresentationItem(x) => Ok(*x),
+                    _ => Err(any),
+                }
+            }
+        }
+        impl ::std::convert::TryFrom<RepresentationItemAny> for GeometricRepresentationItem {
+            type Error = RepresentationItemAny;
+            fn try_from(any: RepresentationItemAny) -> ::std::result::Result<Self, Self::Error> {
+                match any {
+                    RepresentationItemAny::GeometricRepresentationItem(x) => Ok(*x),
+                    _ => Err(any),
+                }
+            }
+        }
+        impl TypeOf for RepresentationItemAny {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                match self {
+                    RepresentationItemAny::RepresentationItem(value) => value.r#typeof(),
+                    RepresentationItemAny::GeometricRepresentationItem(value) => value.r#typeof(),
+                }
+            }
+        }
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            AsRef,
+            AsMut,
+            Deref,
+            DerefMut,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = geometric_representation_item)]
+        #[holder(generate_deserialize)]
+        pub struct GeometricRepresentationItem {
+            #[as_ref]
+            #[as_mut]
+            #[deref]
+            #[deref_mut]
+            #[holder(use_place_holder)]
+            pub representation_item: RepresentationItem,
+        }
+        impl TypeOf for GeometricRepresentationItem {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &[
+                    "TEST_SCHEMA.GEOMETRIC_REPRESENTATION_ITEM",
+                    "TEST_SCHEMA.REPRESENTATION_ITEM",
+                ]
+            }
+        }
+        impl From<GeometricRepresentationItem> for RepresentationItem {
+            fn from(sub: GeometricRepresentationItem) -> Self {
+                sub.representation_item
+            }
+        }
+        impl From<&GeometricRepresentationItem> for RepresentationItem {
+            fn from(sub: &GeometricRepresentationItem) -> Self {
+                sub.representation_item.clone()
+            }
+        }
+    }
+    "###);
+}
@@ -0,0 +1,202 @@
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  CONSTANT
+    max_len : INTEGER := 80;
+  END_CONSTANT;
+
+  ENTITY label;
+    name : STRING;
+    items : LIST OF REAL;
+  WHERE
+    wr1: is_short(items);
+  END_ENTITY;
+
+  FUNCTION is_short(items : LIST OF REAL) : BOOLEAN;
+    RETURN (SIZEOF(items) <= max_len);
+  END_FUNCTION;
+
+  FUNCTION norm(x : REAL; y : REAL) : REAL;
+    RETURN ((x ** 2 + y ** 2) ** 0.5);
+  END_FUNCTION;
+
+  FUNCTION half(n : INTEGER) : INTEGER;
+    RETURN (n DIV 2);
+  END_FUNCTION;
+
+  FUNCTION is_unit(x : REAL; y : REAL) : LOGICAL;
+    RETURN (ABS(norm(x, y) - 1.0) < 1.0e-6);
+  END_FUNCTION;
+
+  FUNCTION same_name(a : STRING; b : STRING) : BOOLEAN;
+    RETURN ((a = b) AND NOT (a = ''));
+  END_FUNCTION;
+
+  FUNCTION last_index(items : LIST OF INTEGER) : INTEGER;
+    LOCAL
+      n : INTEGER := HIINDEX(items);
+    END_LOCAL;
+    RETURN (n);
+  END_FUNCTION;
+
+  FUNCTION first_item(agg : AGGREGATE OF GENERIC:gen) : GENERIC:gen;
+    RETURN (agg[1]);
+  END_FUNCTION;
+
+  FUNCTION label_size(l : label) : INTEGER;
+    RETURN (SIZEOF(l.items));
+  END_FUNCTION;
+END_SCHEMA;
+"#;
+
+#[test]
+fn function() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    assert!(ir.warnings().is_empty());
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ruststep::{
+            as_holder, derive_more::*, express_builtins::TypeOf, primitive::*, ApproxEq, HashBits,
+            Holder, Serialize, TableInit,
+        };
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        #[table_init(schema = "test_schema")]
+        pub struct Tables {
+            label: HashMap<u64, as_holder!(Label)>,
+        }
+        impl Tables {
+            pub fn label_holders(&self) -> &HashMap<u64, as_holder!(Label)> {
+                &self.label
+            }
+            #[doc = r" Ids of all entities in arbitrary order, including complex entity instances"]
+            pub fn all_ids(&self) -> impl Iterator<Item = u64> + '_ {
+                let ids: Vec<Box<dyn Iterator<Item = u64> + '_>> =
+                    vec![Box::new(self.label.keys().cloned())];
+                ids.into_iter().flatten()
+            }
+            #[doc = r" Records of all entities in arbitrary order, with references kept as references"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not included yet."]
+            pub fn all_records(
+                &self,
+            ) -> impl Iterator<Item = ::ruststep::error::Result<(u64, ::ruststep::ast::Record)>> + '_
+            {
+                let records: Vec<Box<dyn Iterator<Item = _> + '_>> =
+                    vec![::ruststep::tables::records(&self.label)];
+                records.into_iter().flatten()
+            }
+            #[doc = r" Check `UNIQUE` rules, and returns entities sharing the same values"]
+            #[doc = r""]
+            #[doc = r" Rules are checked within the table of each entity,"]
+            #[doc = r" i.e. instances of its subtypes are not checked yet."]
+            pub fn check_unique(
+                &self,
+            ) -> ::ruststep::error::Result<Vec<::ruststep::tables::UniqueViolation>> {
+                let violations: Vec<Vec<::ruststep::tables::UniqueViolation>> = vec![];
+                Ok(violations.into_iter().flatten().collect())
+            }
+            #[doc = r" Compare entities with another tables by their ids,"]
+            #[doc = r" where `REAL` values differing at most `tol` are regarded as equal"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not compared yet."]
+            pub fn diff(
+                &self,
+                other: &Self,
+                tol: f64,
+            ) -> ::ruststep::error::Result<::ruststep::tables::TablesDiff> {
+                ::ruststep::tables::diff_records(self.all_records(), other.all_records(), tol)
+            }
+            #[doc = r" Write all entities into a whole exchange structure with `header`,"]
+            #[doc = r" where references are kept as references."]
+            #[doc = r""]
+            #[doc = r" The output is read back by `TableInit::from_exchange` into the same tables."]
+            pub fn to_step_string(
+                &self,
+                header: ::ruststep::header::HeaderInfo,
+            ) -> ::ruststep::error::Result<String> {
+                let mut entities = Vec::new();
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.label)?);
+                ::ruststep::tables::write_exchange::<Self>(&header, entities)
+            }
+            #[doc = r" Store each record with its entity id into the table of its entity name,"]
+            #[doc = r" where records whose entity names are not in this schema are skipped."]
+            #[doc = r""]
+            #[doc = r" Use `TableInit::from_records_lossy` to get the skipped records."]
+            pub fn try_from_records(
+                records: impl IntoIterator<Item = (u64, ::ruststep::ast::Record)>,
+            ) -> ::ruststep::error::Result<Self> {
+                let (tables, _unknowns) =
+                    <Self as ::ruststep::tables::TableInit>::from_records_lossy(records)?;
+                Ok(tables)
+            }
+        }
+        #[doc = r" Store each record with its entity id into the table of its entity name,"]
+        #[doc = r" where records which cannot be stored are skipped, see `TableInit::from_records_skipping`."]
+        #[doc = r""]
+        #[doc = r" Use `Tables::try_from_records` to get errors instead."]
+        impl ::std::iter::FromIterator<(u64, ::ruststep::ast::Record)> for Tables {
+            fn from_iter<I: IntoIterator<Item = (u64, ::ruststep::ast::Record)>>(iter: I) -> Self {
+                <Self as ::ruststep::tables::TableInit>::from_records_skipping(iter)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Label {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Label)>(record)
+            }
+        }
+        #[doc = "`CONSTANT max_len` declared in EXPRESS schema"]
+        pub const MAX_LEN: i64 = 80;
+        #[doc = "`FUNCTION is_short` declared in EXPRESS schema"]
+        pub fn is_short(items: &[f64]) -> bool {
+            (::ruststep::express_builtins::sizeof(items) as f64) <= 80.0
+        }
+        #[doc = "`FUNCTION norm` declared in EXPRESS schema"]
+        pub fn norm(x: f64, y: f64) -> f64 {
+            f64::powf(f64::powf(x, 2.0) + f64::powf(y, 2.0), 0.5)
+        }
+        #[doc = "`FUNCTION half` declared in EXPRESS schema"]
+        pub fn half(n: i64) -> i64 {
+            f64::trunc((n as f64) / 2.0) as i64
+        }
+        #[doc = "`FUNCTION is_unit` declared in EXPRESS schema"]
+        pub fn is_unit(x: f64, y: f64) -> Logical {
+            Logical::from(::ruststep::express_builtins::abs(norm(x, y) - 1.0) < 0.000001)
+        }
+        #[doc = "`FUNCTION same_name` declared in EXPRESS schema"]
+        pub fn same_name(a: &str, b: &str) -> bool {
+            (a == b) && !(a == "")
+        }
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            Default,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = label)]
+        #[holder(generate_deserialize)]
+        pub struct Label {
+            pub name: String,
+            pub items: Vec<f64>,
+        }
+        impl TypeOf for Label {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.LABEL"]
+            }
+        }
+    }
+    "###);
+}
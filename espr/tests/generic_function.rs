@@ -0,0 +1,43 @@
+//! Functions with generalized parameter types, e.g. `GENERIC` and `AGGREGATE`,
+//! are parsed and then skipped in code generation.
+
+use espr::{ast::*, codegen::rust::*, ir::IR};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  ENTITY a;
+    x: REAL;
+  END_ENTITY;
+
+  FUNCTION first_item(agg : AGGREGATE:intype OF GENERIC:gen) : GENERIC:gen;
+    RETURN (agg[1]);
+  END_FUNCTION;
+
+  FUNCTION count_items(agg : AGGREGATE OF GENERIC; item : GENERIC_ENTITY) : INTEGER;
+    RETURN (SIZEOF(agg));
+  END_FUNCTION;
+END_SCHEMA;
+"#;
+
+#[test]
+fn generic_function() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let functions = &st.schemas[0].functions;
+    assert_eq!(functions.len(), 2);
+    assert_eq!(
+        functions[0].parameters[0].ty,
+        Type::Aggregate {
+            base: Box::new(Type::Generic(Some("gen".to_string()))),
+            label: Some("intype".to_string()),
+        }
+    );
+    assert_eq!(
+        functions[0].return_type,
+        Type::Generic(Some("gen".to_string()))
+    );
+    assert_eq!(functions[1].parameters[1].ty, Type::GenericEntity(None));
+
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+    assert!(tt.contains("pub struct A"));
+}
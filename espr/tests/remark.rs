@@ -0,0 +1,212 @@
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  TYPE label = STRING;
+  END_TYPE;
+
+  (* A point in 3D space,
+     given by Cartesian coordinates *)
+  ENTITY point;
+    x : REAL; -- x coordinate
+    -- y coordinate
+    y : REAL;
+    z : REAL;
+  END_ENTITY;
+
+  -- Named item
+  ENTITY named; -- with a label
+    name : label;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn remark() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ruststep::{
+            as_holder, derive_more::*, express_builtins::TypeOf, primitive::*, ApproxEq, HashBits,
+            Holder, Serialize, TableInit,
+        };
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        #[table_init(schema = "test_schema")]
+        pub struct Tables {
+            point: HashMap<u64, as_holder!(Point)>,
+            named: HashMap<u64, as_holder!(Named)>,
+            label: HashMap<u64, as_holder!(Label)>,
+        }
+        impl Tables {
+            pub fn point_holders(&self) -> &HashMap<u64, as_holder!(Point)> {
+                &self.point
+            }
+            pub fn named_holders(&self) -> &HashMap<u64, as_holder!(Named)> {
+                &self.named
+            }
+            pub fn label_holders(&self) -> &HashMap<u64, as_holder!(Label)> {
+                &self.label
+            }
+            #[doc = r" Ids of all entities in arbitrary order, including complex entity instances"]
+            pub fn all_ids(&self) -> impl Iterator<Item = u64> + '_ {
+                let ids: Vec<Box<dyn Iterator<Item = u64> + '_>> = vec![
+                    Box::new(self.point.keys().cloned()),
+                    Box::new(self.named.keys().cloned()),
+                    Box::new(self.label.keys().cloned()),
+                ];
+                ids.into_iter().flatten()
+            }
+            #[doc = r" Records of all entities in arbitrary order, with references kept as references"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not included yet."]
+            pub fn all_records(
+                &self,
+            ) -> impl Iterator<Item = ::ruststep::error::Result<(u64, ::ruststep::ast::Record)>> + '_
+            {
+                let records: Vec<Box<dyn Iterator<Item = _> + '_>> = vec![
+                    ::ruststep::tables::records(&self.point),
+                    ::ruststep::tables::records(&self.named),
+                    ::ruststep::tables::records(&self.label),
+                ];
+                records.into_iter().flatten()
+            }
+            #[doc = r" Check `UNIQUE` rules, and returns entities sharing the same values"]
+            #[doc = r""]
+            #[doc = r" Rules are checked within the table of each entity,"]
+            #[doc = r" i.e. instances of its subtypes are not checked yet."]
+            pub fn check_unique(
+                &self,
+            ) -> ::ruststep::error::Result<Vec<::ruststep::tables::UniqueViolation>> {
+                let violations: Vec<Vec<::ruststep::tables::UniqueViolation>> = vec![];
+                Ok(violations.into_iter().flatten().collect())
+            }
+            #[doc = r" Compare entities with another tables by their ids,"]
+            #[doc = r" where `REAL` values differing at most `tol` are regarded as equal"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not compared yet."]
+            pub fn diff(
+                &self,
+                other: &Self,
+                tol: f64,
+            ) -> ::ruststep::error::Result<::ruststep::tables::TablesDiff> {
+                ::ruststep::tables::diff_records(self.all_records(), other.all_records(), tol)
+            }
+            #[doc = r" Write all entities into a whole exchange structure with `header`,"]
+            #[doc = r" where references are kept as references."]
+            #[doc = r""]
+            #[doc = r" The output is read back by `TableInit::from_exchange` into the same tables."]
+            pub fn to_step_string(
+                &self,
+                header: ::ruststep::header::HeaderInfo,
+            ) -> ::ruststep::error::Result<String> {
+                let mut entities = Vec::new();
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.point)?);
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.named)?);
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.label)?);
+                ::ruststep::tables::write_exchange::<Self>(&header, entities)
+            }
+            #[doc = r" Store each record with its entity id into the table of its entity name,"]
+            #[doc = r" where records whose entity names are not in this schema are skipped."]
+            #[doc = r""]
+            #[doc = r" Use `TableInit::from_records_lossy` to get the skipped records."]
+            pub fn try_from_records(
+                records: impl IntoIterator<Item = (u64, ::ruststep::ast::Record)>,
+            ) -> ::ruststep::error::Result<Self> {
+                let (tables, _unknowns) =
+                    <Self as ::ruststep::tables::TableInit>::from_records_lossy(records)?;
+                Ok(tables)
+            }
+        }
+        #[doc = r" Store each record with its entity id into the table of its entity name,"]
+        #[doc = r" where records which cannot be stored are skipped, see `TableInit::from_records_skipping`."]
+        #[doc = r""]
+        #[doc = r" Use `Tables::try_from_records` to get errors instead."]
+        impl ::std::iter::FromIterator<(u64, ::ruststep::ast::Record)> for Tables {
+            fn from_iter<I: IntoIterator<Item = (u64, ::ruststep::ast::Record)>>(iter: I) -> Self {
+                <Self as ::ruststep::tables::TableInit>::from_records_skipping(iter)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Point {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Point)>(record)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Named {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Named)>(record)
+            }
+        }
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            AsRef,
+            Deref,
+            DerefMut,
+            Into,
+            From,
+            :: ruststep_derive :: Holder,
+            :: ruststep_derive :: Serialize,
+            :: ruststep_derive :: ApproxEq,
+            :: ruststep_derive :: HashBits,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = label)]
+        #[holder(generate_deserialize)]
+        pub struct Label(pub String);
+        #[doc = " A point in 3D space,"]
+        #[doc = " given by Cartesian coordinates"]
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            Default,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = point)]
+        #[holder(generate_deserialize)]
+        pub struct Point {
+            #[doc = " x coordinate"]
+            pub x: f64,
+            #[doc = " y coordinate"]
+            pub y: f64,
+            pub z: f64,
+        }
+        impl TypeOf for Point {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.POINT"]
+            }
+        }
+        #[doc = " Named item"]
+        #[doc = " with a label"]
+        #[derive(
+            Debug, Clone, PartialEq, :: derive_new :: new, Holder, Serialize, ApproxEq, HashBits,
+        )]
+        # [holder (table = Tables)]
+        # [holder (field = named)]
+        #[holder(generate_deserialize)]
+        pub struct Named {
+            #[holder(use_place_holder)]
+            pub name: Label,
+        }
+        impl TypeOf for Named {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.NAMED"]
+            }
+        }
+    }
+    "###);
+}
@@ -30,9 +30,13 @@ fn reserved_keyword() {
 
     insta::assert_snapshot!(tt, @r###"
     pub mod test_schema {
-        use ruststep::{as_holder, derive_more::*, primitive::*, Holder, TableInit};
+        use ruststep::{
+            as_holder, derive_more::*, express_builtins::TypeOf, primitive::*, ApproxEq, HashBits,
+            Holder, Serialize, TableInit,
+        };
         use std::collections::HashMap;
         #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        #[table_init(schema = "test_schema")]
         pub struct Tables {
             r#loop: HashMap<u64, as_holder!(Loop)>,
             a: HashMap<u64, as_holder!(A)>,
@@ -52,22 +56,149 @@ fn reserved_keyword() {
             pub fn b_holders(&self) -> &HashMap<u64, as_holder!(B)> {
                 &self.b
             }
+            #[doc = r" Ids of all entities in arbitrary order, including complex entity instances"]
+            pub fn all_ids(&self) -> impl Iterator<Item = u64> + '_ {
+                let ids: Vec<Box<dyn Iterator<Item = u64> + '_>> = vec![
+                    Box::new(self.r#loop.keys().cloned()),
+                    Box::new(self.a.keys().cloned()),
+                    Box::new(self.c.keys().cloned()),
+                    Box::new(self.b.keys().cloned()),
+                ];
+                ids.into_iter().flatten()
+            }
+            #[doc = r" Records of all entities in arbitrary order, with references kept as references"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not included yet."]
+            pub fn all_records(
+                &self,
+            ) -> impl Iterator<Item = ::ruststep::error::Result<(u64, ::ruststep::ast::Record)>> + '_
+            {
+                let records: Vec<Box<dyn Iterator<Item = _> + '_>> = vec![
+                    ::ruststep::tables::records(&self.r#loop),
+                    ::ruststep::tables::records(&self.a),
+                    ::ruststep::tables::records(&self.c),
+                    ::ruststep::tables::records(&self.b),
+                ];
+                records.into_iter().flatten()
+            }
+            #[doc = r" Check `UNIQUE` rules, and returns entities sharing the same values"]
+            #[doc = r""]
+            #[doc = r" Rules are checked within the table of each entity,"]
+            #[doc = r" i.e. instances of its subtypes are not checked yet."]
+            pub fn check_unique(
+                &self,
+            ) -> ::ruststep::error::Result<Vec<::ruststep::tables::UniqueViolation>> {
+                let violations: Vec<Vec<::ruststep::tables::UniqueViolation>> = vec![];
+                Ok(violations.into_iter().flatten().collect())
+            }
+            #[doc = r" Compare entities with another tables by their ids,"]
+            #[doc = r" where `REAL` values differing at most `tol` are regarded as equal"]
+            #[doc = r""]
+            #[doc = r" Complex entity instances are not compared yet."]
+            pub fn diff(
+                &self,
+                other: &Self,
+                tol: f64,
+            ) -> ::ruststep::error::Result<::ruststep::tables::TablesDiff> {
+                ::ruststep::tables::diff_records(self.all_records(), other.all_records(), tol)
+            }
+            #[doc = r" Write all entities into a whole exchange structure with `header`,"]
+            #[doc = r" where references are kept as references."]
+            #[doc = r""]
+            #[doc = r" The output is read back by `TableInit::from_exchange` into the same tables."]
+            pub fn to_step_string(
+                &self,
+                header: ::ruststep::header::HeaderInfo,
+            ) -> ::ruststep::error::Result<String> {
+                let mut entities = Vec::new();
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.r#loop)?);
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.a)?);
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.c)?);
+                entities.append(&mut ::ruststep::tables::entity_instances(&self.b)?);
+                ::ruststep::tables::write_exchange::<Self>(&header, entities)
+            }
+            #[doc = r" Store each record with its entity id into the table of its entity name,"]
+            #[doc = r" where records whose entity names are not in this schema are skipped."]
+            #[doc = r""]
+            #[doc = r" Use `TableInit::from_records_lossy` to get the skipped records."]
+            pub fn try_from_records(
+                records: impl IntoIterator<Item = (u64, ::ruststep::ast::Record)>,
+            ) -> ::ruststep::error::Result<Self> {
+                let (tables, _unknowns) =
+                    <Self as ::ruststep::tables::TableInit>::from_records_lossy(records)?;
+                Ok(tables)
+            }
+        }
+        #[doc = r" Store each record with its entity id into the table of its entity name,"]
+        #[doc = r" where records which cannot be stored are skipped, see `TableInit::from_records_skipping`."]
+        #[doc = r""]
+        #[doc = r" Use `Tables::try_from_records` to get errors instead."]
+        impl ::std::iter::FromIterator<(u64, ::ruststep::ast::Record)> for Tables {
+            fn from_iter<I: IntoIterator<Item = (u64, ::ruststep::ast::Record)>>(iter: I) -> Self {
+                <Self as ::ruststep::tables::TableInit>::from_records_skipping(iter)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Loop {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(Loop)>(record)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for A {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(A)>(record)
+            }
+        }
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for C {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::owned_from_record::<as_holder!(C)>(record)
+            }
         }
         #[derive(
-            Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, :: ruststep_derive :: Holder,
+            Clone,
+            Debug,
+            PartialEq,
+            AsRef,
+            Deref,
+            DerefMut,
+            Into,
+            From,
+            :: ruststep_derive :: Holder,
+            :: ruststep_derive :: Serialize,
+            :: ruststep_derive :: ApproxEq,
+            :: ruststep_derive :: HashBits,
         )]
         # [holder (table = Tables)]
         # [holder (field = b)]
         #[holder(generate_deserialize)]
         pub struct B(#[holder(use_place_holder)] pub Loop);
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            :: derive_new :: new,
+            Holder,
+            Serialize,
+            ApproxEq,
+            HashBits,
+            Default,
+        )]
         # [holder (table = Tables)]
         # [holder (field = r#loop)]
         #[holder(generate_deserialize)]
         pub struct Loop {
             pub a: f64,
         }
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
+        impl TypeOf for Loop {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.LOOP"]
+            }
+        }
+        #[derive(
+            Debug, Clone, PartialEq, :: derive_new :: new, Holder, Serialize, ApproxEq, HashBits,
+        )]
         # [holder (table = Tables)]
         # [holder (field = a)]
         #[holder(generate_deserialize)]
@@ -76,7 +207,14 @@ fn reserved_keyword() {
             #[holder(use_place_holder)]
             pub a_loop: Loop,
         }
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
+        impl TypeOf for A {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.A"]
+            }
+        }
+        #[derive(
+            Debug, Clone, PartialEq, :: derive_new :: new, Holder, Serialize, ApproxEq, HashBits,
+        )]
         # [holder (table = Tables)]
         # [holder (field = c)]
         #[holder(generate_deserialize)]
@@ -84,6 +222,11 @@ fn reserved_keyword() {
             #[holder(use_place_holder)]
             pub r#loop: B,
         }
+        impl TypeOf for C {
+            fn r#typeof(&self) -> &'static [&'static str] {
+                &["TEST_SCHEMA.C"]
+            }
+        }
     }
     "###);
 }
@@ -0,0 +1,131 @@
+//! Benchmark for resolving entity references in AP203 tables
+//!
+//! ```shell
+//! cargo bench -p ruststep-bench --bench into_owned
+//! ```
+//!
+//! The sample file `benches/ap203_grid.step` is a grid of `CARTESIAN_POINT`s
+//! connected by `POLYLINE`s along rows and columns,
+//! i.e. every point is referenced from two polylines.
+//! Supertype instances, e.g. `POINT`, are also shared by reference.
+//!
+//! Each path is timed in the `resolve` group by [criterion::Bencher::iter_custom],
+//! which also measures its peak heap usage by a counting allocator.
+//! Criterion only reports the time, and the peak heap usage is printed after the group.
+
+use criterion::{black_box, criterion_group, criterion_main, Bencher, Criterion};
+use ruststep::{
+    ap203::config_control_design::*,
+    parser,
+    tables::{EntityTable, SharedTable, TableInit},
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    path::PathBuf,
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Allocator recording the peak of allocated heap size
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAlloc = PeakAlloc;
+
+/// Peak heap size in bytes increased while running `f`
+fn peak_memory<T>(f: impl FnOnce() -> T) -> usize {
+    let base = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(base, Ordering::Relaxed);
+    let value = f();
+    let peak = PEAK.load(Ordering::Relaxed) - base;
+    drop(value);
+    peak
+}
+
+fn load_tables() -> Tables {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("benches/ap203_grid.step");
+    let step_str = std::fs::read_to_string(path).unwrap();
+    let exchange = parser::parse(&step_str).unwrap();
+    Tables::from_data_section(&exchange.data[0]).unwrap()
+}
+
+/// Resolve every polyline by [EntityTable::owned_iter], i.e. [ruststep::tables::IntoOwned::into_owned]
+/// where references are resolved recursively
+fn into_owned(table: &Tables) -> Vec<Polyline> {
+    EntityTable::<PolylineHolder>::owned_iter(table)
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+/// Resolve every polyline by [SharedTable], i.e. once for each entity id
+fn shared_table(table: &Tables) -> Vec<Rc<Polyline>> {
+    let shared = SharedTable::new(table);
+    table
+        .polyline_holders()
+        .keys()
+        .map(|&id| shared.get_shared::<PolylineHolder>(id).unwrap())
+        .collect()
+}
+
+/// Time `path` for each iteration, and record its peak heap usage into `peak`
+///
+/// The resolved value is dropped out of the timed region.
+fn bench_path<T>(b: &mut Bencher, table: &Tables, path: fn(&Tables) -> T, peak: &mut usize) {
+    b.iter_custom(|iters| {
+        let mut elapsed = Duration::ZERO;
+        for _ in 0..iters {
+            let memory = peak_memory(|| {
+                let start = Instant::now();
+                let value = black_box(path(table));
+                elapsed += start.elapsed();
+                value
+            });
+            *peak = (*peak).max(memory);
+        }
+        elapsed
+    })
+}
+
+fn resolve(c: &mut Criterion) {
+    let table = load_tables();
+    let mut into_owned_peak = 0;
+    let mut shared_table_peak = 0;
+    let mut group = c.benchmark_group("resolve");
+    group.bench_function("into_owned", |b| {
+        bench_path(b, &table, into_owned, &mut into_owned_peak)
+    });
+    group.bench_function("SharedTable", |b| {
+        bench_path(b, &table, shared_table, &mut shared_table_peak)
+    });
+    group.finish();
+
+    for (name, peak) in [
+        ("into_owned", into_owned_peak),
+        ("SharedTable", shared_table_peak),
+    ] {
+        println!("resolve/{:<12} peak memory: {} bytes", name, peak);
+    }
+}
+
+criterion_group!(benches, resolve);
+criterion_main!(benches);
@@ -0,0 +1,56 @@
+use super::*;
+use quote::format_ident;
+
+pub fn derive_approx_eq(ast: &syn::DeriveInput) -> TokenStream2 {
+    let ident = &ast.ident;
+    let ruststep = ruststep_crate();
+    let body = match &ast.data {
+        syn::Data::Struct(st) => {
+            let members = st.fields.iter().enumerate().map(|(i, f)| match &f.ident {
+                Some(ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(i.into()),
+            });
+            conjunction(members.map(|member| {
+                quote! { #ruststep::primitive::ApproxEq::approx_eq(&self.#member, &other.#member, tol) }
+            }))
+        }
+        syn::Data::Enum(e) => {
+            let arms = e.variants.iter().map(|var| {
+                let variant = &var.ident;
+                let (lhs, rhs): (Vec<_>, Vec<_>) = (0..var.fields.len())
+                    .map(|i| (format_ident!("a_{}", i), format_ident!("b_{}", i)))
+                    .unzip();
+                match var.fields {
+                    syn::Fields::Named(_) => abort_call_site!("Struct variant is not supported"),
+                    syn::Fields::Unnamed(_) => {
+                        let body = conjunction(lhs.iter().zip(&rhs).map(|(a, b)| {
+                            quote! { #ruststep::primitive::ApproxEq::approx_eq(#a, #b, tol) }
+                        }));
+                        quote! {
+                            (#ident::#variant(#(#lhs),*), #ident::#variant(#(#rhs),*)) => #body,
+                        }
+                    }
+                    syn::Fields::Unit => quote! {
+                        (#ident::#variant, #ident::#variant) => true,
+                    },
+                }
+            });
+            quote! {
+                #[allow(unreachable_patterns)]
+                match (self, other) {
+                    #( #arms )*
+                    _ => false,
+                }
+            }
+        }
+        _ => abort_call_site!("Only struct and enum are supported"),
+    };
+    quote! {
+        #[automatically_derived]
+        impl #ruststep::primitive::ApproxEq for #ident {
+            fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+                #body
+            }
+        }
+    }
+}
@@ -1,10 +1,10 @@
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream};
 use proc_macro_crate::{crate_name, FoundCrate};
 use proc_macro_error::ResultExt;
-use quote::format_ident;
+use quote::{format_ident, quote};
 use std::convert::*;
 
-use super::field_type::*;
+use super::{field_type::*, holder_attr::*};
 
 pub fn as_holder_visitor(input: &syn::Ident) -> syn::Ident {
     format_ident!("{}HolderVisitor", input)
@@ -14,6 +14,26 @@ pub fn as_holder_ident(input: &syn::Ident) -> syn::Ident {
     format_ident!("{}Holder", input)
 }
 
+pub fn as_builder_ident(input: &syn::Ident) -> syn::Ident {
+    format_ident!("{}Builder", input)
+}
+
+/// Types implementing `Serialize` by `#[derive(Serialize)]`
+///
+/// Holder is also serialized into the same record if `#[holder(table = ...)]` is specified,
+/// where references are kept as they are.
+/// The input type itself is skipped with `#[holder(serialize_holder_only)]`.
+pub fn serialize_idents(input: &syn::Ident, attr: &HolderAttr) -> Vec<syn::Ident> {
+    let mut idents = Vec::new();
+    if !attr.serialize_holder_only {
+        idents.push(input.clone());
+    }
+    if attr.table.is_some() {
+        idents.push(as_holder_ident(input));
+    }
+    idents
+}
+
 pub fn as_holder_path(input: &syn::Type) -> syn::Type {
     let ft: FieldType = input
         .clone()
@@ -82,6 +102,16 @@ pub fn ruststep_crate() -> syn::Path {
     }
 }
 
+/// Join comparisons by `&&`, or `true` if empty
+pub fn conjunction(exprs: impl Iterator<Item = TokenStream>) -> TokenStream {
+    let exprs: Vec<_> = exprs.collect();
+    if exprs.is_empty() {
+        quote! { true }
+    } else {
+        quote! { #( #exprs )&&* }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
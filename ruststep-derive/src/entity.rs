@@ -1,7 +1,7 @@
 use inflector::Inflector;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use proc_macro_error::OptionExt;
-use quote::quote;
+use quote::{format_ident, quote};
 use std::convert::*;
 
 use super::*;
@@ -16,12 +16,48 @@ pub fn derive_deserialize(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStre
     } // quote!
 }
 
+pub fn derive_serialize(
+    ident: &syn::Ident,
+    st: &syn::DataStruct,
+    attr: &HolderAttr,
+) -> TokenStream2 {
+    let name = attr.name(ident);
+    let FieldEntries { attributes, .. } = FieldEntries::parse(st);
+    let attr_len = attributes.len();
+    let serde = serde_crate();
+    serialize_idents(ident, attr)
+        .into_iter()
+        .map(|ident| {
+            let keys = attributes.iter().map(|attr| attr.to_string());
+            quote! {
+                #[automatically_derived]
+                impl #serde::ser::Serialize for #ident {
+                    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+                    where
+                        S: #serde::ser::Serializer,
+                    {
+                        use #serde::ser::SerializeStruct;
+                        let mut st = serializer.serialize_struct(#name, #attr_len)?;
+                        #( st.serialize_field(#keys, &self.#attributes)?; )*
+                        st.end()
+                    }
+                }
+            } // quote!
+        })
+        .collect()
+}
+
 pub fn derive_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr) -> TokenStream2 {
-    let name = ident.to_string().to_screaming_snake_case();
+    let name = attr.name(ident);
     let holder_ident = as_holder_ident(ident);
     let def_holder_tt = def_holder(ident, st);
     let impl_holder_tt = impl_holder(ident, attr, st);
     let impl_entity_table_tt = impl_entity_table(ident, attr);
+    let def_builder_tt = if attr.generate_builder {
+        def_builder(ident, st)
+    } else {
+        TokenStream2::new()
+    };
     if attr.generate_deserialize {
         let def_visitor_tt = def_visitor(&holder_ident, &name, st);
         let impl_deserialize_tt = impl_deserialize(&holder_ident, &name, st);
@@ -30,6 +66,7 @@ pub fn derive_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr
             #def_holder_tt
             #impl_holder_tt
             #impl_entity_table_tt
+            #def_builder_tt
             #def_visitor_tt
             #impl_deserialize_tt
             #impl_with_visitor_tt
@@ -39,28 +76,103 @@ pub fn derive_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr
             #def_holder_tt
             #impl_holder_tt
             #impl_entity_table_tt
+            #def_builder_tt
         }
     }
 }
 
+/// Methods of traits implemented for holders, which accessors must not shadow
+const HOLDER_METHODS: &[&str] = &[
+    "name",
+    "attr_len",
+    "attr_names",
+    "accepts_keyword",
+    "validate",
+    "from_record",
+    "into_owned",
+    "into_owned_with",
+    "visit_refs",
+    "remap_refs",
+    "visitor_new",
+    "serialize",
+    "deserialize",
+    "clone",
+    "clone_from",
+    "eq",
+    "ne",
+    "fmt",
+];
+
+/// Name of the accessor for an attribute, prefixed by `resolve_` if it collides with [HOLDER_METHODS]
+fn accessor_ident(attr: &syn::Ident) -> syn::Ident {
+    let name = attr.to_string().trim_start_matches("r#").to_string();
+    if HOLDER_METHODS.contains(&name.as_str()) {
+        format_ident!("resolve_{}", name)
+    } else {
+        attr.clone()
+    }
+}
+
 /// This must be same between codegens
 fn table_arg() -> syn::Ident {
     syn::Ident::new("table", Span::call_site())
 }
 
+/// This must be same between codegens
+fn resolver_arg() -> syn::Ident {
+    syn::Ident::new("resolver", Span::call_site())
+}
+
 struct FieldEntries {
     attributes: Vec<syn::Ident>,
     holder_types: Vec<syn::Type>,
     into_owned: Vec<TokenStream2>,
+    /// Fields using place holder, which may contain references
+    place_holder_attributes: Vec<syn::Ident>,
+    /// Checks of `#[holder(bound(..))]` for `Holder::validate`
+    bound_checks: Vec<TokenStream2>,
+    /// Methods resolving fields using place holder on demand
+    accessors: Vec<TokenStream2>,
+    /// Statements filling attributes of supertypes derived in this subtype by `#[holder(derive(..))]`
+    derive_fills: Vec<TokenStream2>,
+}
+
+/// Statement to fill the attribute at `path` in the supertype held in `field` if it is omitted as `*`
+fn derive_fill(field: &syn::Ident, path: &[syn::Ident], value: &syn::Expr) -> TokenStream2 {
+    let ruststep = ruststep_crate();
+    let (attr, supertypes) = path.split_last().expect_or_abort("Empty attribute path");
+    let mut fill = quote! {
+        if matches!(holder.#attr, #ruststep::tables::Derived::Omitted) {
+            holder.#attr = #ruststep::tables::Derived::Value(#value);
+        }
+    };
+    for supertype in supertypes.iter().rev() {
+        fill = quote! {
+            if let #ruststep::tables::PlaceHolder::Owned(holder) = &mut holder.#supertype {
+                #fill
+            }
+        };
+    }
+    quote! {
+        if let #ruststep::tables::PlaceHolder::Owned(holder) = &mut self.#field {
+            #fill
+        }
+    }
 }
 
 impl FieldEntries {
     fn parse(st: &syn::DataStruct) -> Self {
         let table_arg = table_arg();
+        let resolver_arg = resolver_arg();
 
         let mut attributes = Vec::new();
         let mut holder_types = Vec::new();
         let mut into_owned = Vec::new();
+        let mut place_holder_attributes = Vec::new();
+        let mut bound_checks = Vec::new();
+        let mut accessors = Vec::new();
+        let mut derive_fills = Vec::new();
+        let ruststep = ruststep_crate();
 
         for field in &st.fields {
             let ident = field.ident.as_ref().expect_or_abort("st is not struct");
@@ -68,33 +180,124 @@ impl FieldEntries {
 
             let ft: FieldType = field.ty.clone().try_into().unwrap();
 
-            let HolderAttr { place_holder, .. } = HolderAttr::parse(&field.attrs);
-            if place_holder {
-                match &ft {
-                    FieldType::Path(_) => {
-                        into_owned.push(quote! { #ident.into_owned(#table_arg)? });
+            let HolderAttr {
+                place_holder,
+                bound,
+                derived_in_subtype,
+                derive,
+                ..
+            } = HolderAttr::parse(&field.attrs);
+            let attr_name = ident.to_string().trim_start_matches("r#").to_string();
+            // The value omitted as `*` is read only through the subtype deriving it
+            let (target, omitted) = if derived_in_subtype {
+                (
+                    quote! { value },
+                    quote! {
+                        return Err(#ruststep::error::Error::DerivedAttribute {
+                            entity_name: <Self as #ruststep::tables::Holder>::name().to_string(),
+                            attribute: #attr_name.to_string(),
+                        })
+                    },
+                )
+            } else {
+                (quote! { self.#ident }, TokenStream2::new())
+            };
+            for (path, value) in &derive {
+                derive_fills.push(derive_fill(ident, path, value));
+            }
+            if let Some((lower, upper)) = bound {
+                let found = match &ft {
+                    FieldType::List(_) => quote! { Some(#target.len()) },
+                    FieldType::Optional(inner) if matches!(inner.as_ref(), FieldType::List(_)) => {
+                        quote! { #target.as_ref().map(|v| v.len()) }
                     }
+                    _ => abort_call_site!("#[holder(bound(..))] requires Vec<T> or Option<Vec<T>>"),
+                };
+                let upper = match upper {
+                    Some(upper) => quote! { Some(#upper) },
+                    None => quote! { None },
+                };
+                let check = quote! {
+                    #ruststep::tables::check_bound(#attr_name, #found, #lower, #upper)?;
+                };
+                if derived_in_subtype {
+                    bound_checks.push(quote! {
+                        if let #ruststep::tables::Derived::Value(value) = &self.#ident {
+                            #check
+                        }
+                    });
+                } else {
+                    bound_checks.push(check);
+                }
+            }
+            let (owned, holder_ty): (_, syn::Type) = if place_holder {
+                place_holder_attributes.push(ident.clone());
+                let owned = match &ft {
                     FieldType::Optional(_) => {
-                        into_owned.push(quote! { #ident.map(|holder| holder.into_owned(#table_arg)).transpose()? });
+                        quote! { #ident.map(|holder| holder.into_owned_with(#resolver_arg)).transpose()? }
+                    }
+                    FieldType::Path(_)
+                    | FieldType::List(_)
+                    | FieldType::Array(..)
+                    | FieldType::Shared(_) => {
+                        quote! { #ident.into_owned_with(#resolver_arg)? }
                     }
-                    FieldType::List(_) => into_owned.push(quote! {
-                        #ident
-                            .into_iter()
-                            .map(|v| v.into_owned(#table_arg))
-                            .collect::<::std::result::Result<Vec<_>, _>>()?
-                    }),
                     FieldType::Boxed(_) => abort_call_site!("Unexpected Box<T>"),
-                }
-                holder_types.push(ft.into_holder().into_place_holder().into());
+                };
+                let resolve = match &ft {
+                    FieldType::Optional(_) => quote! {
+                        #target.clone().map(|holder| holder.into_owned(#table_arg)).transpose()
+                    },
+                    FieldType::List(_) => quote! {
+                        #target.iter().map(|v| v.clone().into_owned(#table_arg)).collect()
+                    },
+                    _ => quote! { #target.clone().into_owned(#table_arg) },
+                };
+                let resolve = if derived_in_subtype {
+                    quote! {
+                        let value = match &self.#ident {
+                            #ruststep::tables::Derived::Value(value) => value,
+                            #ruststep::tables::Derived::Omitted => #omitted,
+                        };
+                        #resolve
+                    }
+                } else {
+                    resolve
+                };
+                let owned_ty = &field.ty;
+                let accessor = accessor_ident(ident);
+                accessors.push(quote! {
+                    /// Resolve this attribute into its owned value, looking up references in `table`
+                    pub fn #accessor(&self, #table_arg: &<Self as #ruststep::tables::IntoOwned>::Table) -> #ruststep::error::Result<#owned_ty> {
+                        use #ruststep::tables::IntoOwned;
+                        #resolve
+                    }
+                });
+                (owned, ft.into_holder().into_place_holder().into())
+            } else {
+                (quote! { #ident }, ft.into())
+            };
+            if derived_in_subtype {
+                into_owned.push(quote! {
+                    match #ident {
+                        #ruststep::tables::Derived::Value(#ident) => #owned,
+                        #ruststep::tables::Derived::Omitted => #omitted,
+                    }
+                });
+                holder_types.push(syn::parse_quote! { #ruststep::tables::Derived<#holder_ty> });
             } else {
-                into_owned.push(quote! { #ident });
-                holder_types.push(ft.into());
+                into_owned.push(owned);
+                holder_types.push(holder_ty);
             }
         }
         FieldEntries {
             attributes,
             holder_types,
             into_owned,
+            place_holder_attributes,
+            bound_checks,
+            accessors,
+            derive_fills,
         }
     }
 }
@@ -116,24 +319,50 @@ pub fn def_holder(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
 }
 
 pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct) -> TokenStream2 {
-    let name = ident.to_string().to_screaming_snake_case();
+    let name = table.name(ident);
     let holder_ident = as_holder_ident(ident);
     let FieldEntries {
         attributes,
         into_owned,
+        place_holder_attributes,
+        bound_checks,
+        accessors,
+        derive_fills,
         ..
     } = FieldEntries::parse(st);
     let attr_len = attributes.len();
-    let HolderAttr { table, .. } = table;
-    let table_arg = table_arg();
     let ruststep = ruststep_crate();
+    let validate = if bound_checks.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn validate(&self) -> #ruststep::error::Result<()> {
+                #( #bound_checks )*
+                Ok(())
+            }
+        }
+    };
+    let attr_names = attributes
+        .iter()
+        .map(|attr| attr.to_string().trim_start_matches("r#").to_string());
+    let HolderAttr { table, .. } = table;
+    let resolver_arg = resolver_arg();
+    let receiver = if derive_fills.is_empty() {
+        quote! { self }
+    } else {
+        quote! { mut self }
+    };
 
     quote! {
+        impl #holder_ident {
+            #( #accessors )*
+        }
         #[automatically_derived]
         impl #ruststep::tables::IntoOwned for #holder_ident {
             type Table = #table;
             type Owned = #ident;
-            fn into_owned(self, #table_arg: &Self::Table) -> #ruststep::error::Result<Self::Owned> {
+            fn into_owned_with(#receiver, #resolver_arg: &mut #ruststep::tables::Resolver<'_, Self::Table>) -> #ruststep::error::Result<Self::Owned> {
+                #( #derive_fills )*
                 let #holder_ident { #(#attributes),* } = self;
                 Ok(#ident { #(#attributes: #into_owned),* })
             }
@@ -146,6 +375,19 @@ pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct)
             fn attr_len() -> usize {
                 #attr_len
             }
+            fn attr_names() -> &'static [&'static str] {
+                &[#(#attr_names),*]
+            }
+            #validate
+        }
+        #[automatically_derived]
+        impl #ruststep::tables::VisitRefs for #holder_ident {
+            fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+                #( #ruststep::tables::VisitRefs::visit_refs(&self.#place_holder_attributes, visitor); )*
+            }
+            fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+                #( #ruststep::tables::VisitRefs::remap_refs(&mut self.#place_holder_attributes, map); )*
+            }
         }
     } // quote!
 }
@@ -161,6 +403,12 @@ pub fn impl_entity_table(ident: &syn::Ident, table: &HolderAttr) -> TokenStream2
             fn get_owned(&self, entity_id: u64) -> #ruststep::error::Result<#ident> {
                 #ruststep::tables::get_owned(self, &self.#field, entity_id)
             }
+            fn contains(&self, entity_id: u64) -> bool {
+                #ruststep::tables::IdMap::get(&self.#field, entity_id).is_some()
+            }
+            fn get_holder(&self, entity_id: u64) -> Option<::std::borrow::Cow<'_, #holder_ident>> {
+                #ruststep::tables::IdMap::get(&self.#field, entity_id).map(::std::borrow::Cow::Borrowed)
+            }
             fn owned_iter<'table>(&'table self) -> Box<dyn Iterator<Item = #ruststep::error::Result<#ident>> + 'table> {
                 #ruststep::tables::owned_iter(self, &self.#field)
             }
@@ -168,6 +416,68 @@ pub fn impl_entity_table(ident: &syn::Ident, table: &HolderAttr) -> TokenStream2
     }
 }
 
+/// Builder of the owned struct, which takes non-optional attributes in `new`
+/// and optional attributes through setters defaulting to `None`
+pub fn def_builder(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
+    let builder_ident = as_builder_ident(ident);
+    let mut attributes = Vec::new();
+    let mut types = Vec::new();
+    let mut required = Vec::new();
+    let mut required_types = Vec::new();
+    let mut optional = Vec::new();
+    let mut optional_types = Vec::new();
+    for field in &st.fields {
+        let ident = field.ident.as_ref().expect_or_abort("st is not struct");
+        attributes.push(ident);
+        types.push(&field.ty);
+        let ft: FieldType = field.ty.clone().try_into().unwrap();
+        match ft {
+            FieldType::Optional(inner) => {
+                optional.push(ident);
+                optional_types.push(syn::Type::from(*inner));
+            }
+            _ => {
+                required.push(ident);
+                required_types.push(&field.ty);
+            }
+        }
+    }
+    let doc = format!(
+        " Builder of [{}] auto-generated by `#[derive(Holder)]`",
+        ident
+    );
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct #builder_ident {
+            #( #attributes: #types ),*
+        }
+
+        #[automatically_derived]
+        impl #builder_ident {
+            /// Start building with non-optional attributes. Optional attributes are `None`.
+            #[allow(clippy::too_many_arguments)]
+            pub fn new(#( #required: #required_types ),*) -> Self {
+                #builder_ident {
+                    #( #required, )*
+                    #( #optional: None, )*
+                }
+            }
+
+            #(
+            pub fn #optional(mut self, #optional: #optional_types) -> Self {
+                self.#optional = Some(#optional);
+                self
+            }
+            )*
+
+            pub fn build(self) -> #ident {
+                #ident { #( #attributes: self.#attributes ),* }
+            }
+        }
+    } // quote!
+}
+
 // `name` may be different from `ident`
 // because this will be used for both Entity struct and its `*Holder` struct.
 fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStream2 {
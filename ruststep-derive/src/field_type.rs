@@ -14,6 +14,10 @@ pub enum FieldType {
     List(Box<FieldType>),
     /// Like `Box<T>`
     Boxed(Box<FieldType>),
+    /// Like `Rc<T>`, an entity shared among referrers
+    Shared(Box<FieldType>),
+    /// Like `[T; N]`
+    Array(Box<FieldType>, syn::Expr),
 }
 
 impl FieldType {
@@ -48,6 +52,14 @@ impl FieldType {
                 let holder = ty.into_holder();
                 FieldType::Boxed(Box::new(holder))
             }
+            FieldType::Shared(ty) => {
+                let holder = ty.into_holder();
+                FieldType::Shared(Box::new(holder))
+            }
+            FieldType::Array(ty, len) => {
+                let holder = ty.into_holder();
+                FieldType::Array(Box::new(holder), len)
+            }
         }
     }
 
@@ -70,6 +82,17 @@ impl FieldType {
                 let place_holder = ty.into_place_holder();
                 FieldType::Boxed(Box::new(place_holder))
             }
+            FieldType::Shared(ty) => match *ty {
+                FieldType::Path(path) => {
+                    let path = syn::parse_quote! { #ruststep::tables::SharedPlaceHolder<#path> };
+                    FieldType::Path(path)
+                }
+                _ => abort_call_site!("Rc<T> is supported only for an entity"),
+            },
+            FieldType::Array(ty, len) => {
+                let place_holder = ty.into_place_holder();
+                FieldType::Array(Box::new(place_holder), len)
+            }
         }
     }
 }
@@ -102,6 +125,14 @@ impl From<FieldType> for syn::Type {
                 let ty: syn::Type = (*ty).into();
                 syn::parse_quote! { Box<#ty> }
             }
+            FieldType::Shared(ty) => {
+                let ty: syn::Type = (*ty).into();
+                syn::parse_quote! { ::std::rc::Rc<#ty> }
+            }
+            FieldType::Array(ty, len) => {
+                let ty: syn::Type = (*ty).into();
+                return syn::parse_quote! { [#ty; #len] };
+            }
         };
         syn::Type::Path(syn::TypePath { qself: None, path })
     }
@@ -111,10 +142,13 @@ impl TryFrom<syn::Type> for FieldType {
     type Error = UnsupportedTypeError;
 
     fn try_from(ty: syn::Type) -> Result<Self, Self::Error> {
-        let path = if let syn::Type::Path(syn::TypePath { path, .. }) = ty {
-            path
-        } else {
-            return Err(UnsupportedTypeError {});
+        let path = match ty {
+            syn::Type::Path(syn::TypePath { path, .. }) => path,
+            syn::Type::Array(syn::TypeArray { elem, len, .. }) => {
+                let ty = Box::new((*elem).try_into()?);
+                return Ok(FieldType::Array(ty, len));
+            }
+            _ => return Err(UnsupportedTypeError {}),
         };
 
         let syn::Path { segments, .. } = &path;
@@ -138,6 +172,9 @@ impl TryFrom<syn::Type> for FieldType {
                     if last_seg.ident == "Box" {
                         return Ok(FieldType::Boxed(ty));
                     }
+                    if last_seg.ident == "Rc" {
+                        return Ok(FieldType::Shared(ty));
+                    }
                 }
                 Err(UnsupportedTypeError {})
             }
@@ -175,6 +212,15 @@ mod tests {
             panic!()
         }
         assert_eq!(<FieldType as Into<syn::Type>>::into(f), ty);
+
+        let ty: syn::Type = syn::parse_str("[[T; 3]; 2]").unwrap();
+        let f = ty.clone().try_into().unwrap();
+        if let FieldType::Array(ty, _) = &f {
+            assert!(matches!(**ty, FieldType::Array(..)));
+        } else {
+            panic!()
+        }
+        assert_eq!(<FieldType as Into<syn::Type>>::into(f), ty);
     }
 
     #[test]
@@ -202,6 +248,12 @@ mod tests {
         let holder = f.into_holder();
         let ans: syn::Type = syn::parse_str("Option<Vec<THolder>>").unwrap();
         assert_eq!(<FieldType as Into<syn::Type>>::into(holder), ans);
+
+        let ty: syn::Type = syn::parse_str("[T; 3]").unwrap();
+        let f: FieldType = ty.try_into().unwrap();
+        let holder = f.into_holder();
+        let ans: syn::Type = syn::parse_str("[THolder; 3]").unwrap();
+        assert_eq!(<FieldType as Into<syn::Type>>::into(holder), ans);
     }
 
     #[test]
@@ -232,5 +284,12 @@ mod tests {
         let ans: syn::Type =
             syn::parse_str("Option<Vec<::ruststep::tables::PlaceHolder<THolder>>>").unwrap();
         assert_eq!(<FieldType as Into<syn::Type>>::into(place_holder), ans);
+
+        let ty: syn::Type = syn::parse_str("[T; 3]").unwrap();
+        let f: FieldType = ty.try_into().unwrap();
+        let place_holder = f.into_holder().into_place_holder();
+        let ans: syn::Type =
+            syn::parse_str("[::ruststep::tables::PlaceHolder<THolder>; 3]").unwrap();
+        assert_eq!(<FieldType as Into<syn::Type>>::into(place_holder), ans);
     }
 }
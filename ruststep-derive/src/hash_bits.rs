@@ -0,0 +1,86 @@
+use super::*;
+use quote::format_ident;
+
+pub fn derive_hash_bits(ast: &syn::DeriveInput) -> TokenStream2 {
+    let ident = &ast.ident;
+    let ruststep = ruststep_crate();
+    let (hash, eq) = match &ast.data {
+        syn::Data::Struct(st) => {
+            let members: Vec<_> = st
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| match &f.ident {
+                    Some(ident) => syn::Member::Named(ident.clone()),
+                    None => syn::Member::Unnamed(i.into()),
+                })
+                .collect();
+            let hash = quote! {
+                #( #ruststep::primitive::HashBits::hash_bits(&self.#members, state); )*
+            };
+            let eq = conjunction(members.iter().map(|member| {
+                quote! { #ruststep::primitive::HashBits::eq_bits(&self.#member, &other.#member) }
+            }));
+            (hash, eq)
+        }
+        syn::Data::Enum(e) => {
+            let mut hash_arms = Vec::new();
+            let mut eq_arms = Vec::new();
+            for var in &e.variants {
+                let variant = &var.ident;
+                let (lhs, rhs): (Vec<_>, Vec<_>) = (0..var.fields.len())
+                    .map(|i| (format_ident!("a_{}", i), format_ident!("b_{}", i)))
+                    .unzip();
+                match var.fields {
+                    syn::Fields::Named(_) => abort_call_site!("Struct variant is not supported"),
+                    syn::Fields::Unnamed(_) => {
+                        hash_arms.push(quote! {
+                            #ident::#variant(#(#lhs),*) => {
+                                #( #ruststep::primitive::HashBits::hash_bits(#lhs, state); )*
+                            }
+                        });
+                        let body = conjunction(lhs.iter().zip(&rhs).map(|(a, b)| {
+                            quote! { #ruststep::primitive::HashBits::eq_bits(#a, #b) }
+                        }));
+                        eq_arms.push(quote! {
+                            (#ident::#variant(#(#lhs),*), #ident::#variant(#(#rhs),*)) => #body,
+                        });
+                    }
+                    syn::Fields::Unit => {
+                        hash_arms.push(quote! { #ident::#variant => {} });
+                        eq_arms.push(quote! {
+                            (#ident::#variant, #ident::#variant) => true,
+                        });
+                    }
+                }
+            }
+            let hash = quote! {
+                ::core::hash::Hash::hash(&::core::mem::discriminant(self), state);
+                match self {
+                    #( #hash_arms )*
+                }
+            };
+            let eq = quote! {
+                #[allow(unreachable_patterns)]
+                match (self, other) {
+                    #( #eq_arms )*
+                    _ => false,
+                }
+            };
+            (hash, eq)
+        }
+        _ => abort_call_site!("Only struct and enum are supported"),
+    };
+    quote! {
+        #[automatically_derived]
+        impl #ruststep::primitive::HashBits for #ident {
+            fn hash_bits<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                #hash
+            }
+
+            fn eq_bits(&self, other: &Self) -> bool {
+                #eq
+            }
+        }
+    }
+}
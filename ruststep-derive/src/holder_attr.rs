@@ -1,19 +1,40 @@
 //! Parse the associated attribute `#[holder(...)]` with `#[derive(Holder)]`
 //!
-//! There are three options:
+//! There are following options:
 //!
 //! - `#[holder(table = {path::to::table::struct})]`
 //! - `#[holder(field = {field_ident})]`
 //! - `#[holder(use_place_holder)]`
 //! - `#[holder(generate_deserialize)]`
+//! - `#[holder(generate_builder)]`
+//! - `#[holder(rename = "RECORD_NAME")]`
+//! - `#[holder(bound({lower}))]` or `#[holder(bound({lower}, {upper}))]` for aggregate fields
+//! - `#[holder(derived_in_subtype)]` for attributes which subtypes derive, omitted as `*`
+//! - `#[holder(derive({attr} = {expr}, {supertype}.{attr} = {expr}, ...))]` for supertype fields
+//!   to fill the attributes derived in this subtype
 //!
 
+use inflector::Inflector;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct HolderAttr {
     pub table: Option<syn::Path>,
     pub field: Option<syn::Ident>,
     pub place_holder: bool,
     pub generate_deserialize: bool,
+    /// Generate `XxxBuilder` for the entity struct
+    pub generate_builder: bool,
+    pub rename: Option<String>,
+    /// Lower and upper bound of the number of elements, `None` upper bound for `?`
+    pub bound: Option<(usize, Option<usize>)>,
+    /// `#[derive(Serialize)]` implements only for the holder,
+    /// since the type itself derives `serde::Serialize`
+    pub serialize_holder_only: bool,
+    /// Holder keeps the attribute as `Derived<T>` to accept `*`
+    pub derived_in_subtype: bool,
+    /// Attributes of the supertype derived by this subtype,
+    /// as pairs of the path to the attribute and its value
+    pub derive: Vec<(Vec<syn::Ident>, syn::Expr)>,
 }
 
 impl HolderAttr {
@@ -22,6 +43,12 @@ impl HolderAttr {
         let mut field = None;
         let mut place_holder = false;
         let mut generate_deserialize = false;
+        let mut generate_builder = false;
+        let mut rename = None;
+        let mut bound = None;
+        let mut serialize_holder_only = false;
+        let mut derived_in_subtype = false;
+        let mut derive = Vec::new();
 
         for attr in attrs {
             // Only read `#[holder(...)]`
@@ -46,6 +73,24 @@ impl HolderAttr {
                 Attr::GenerateDeserialize => {
                     generate_deserialize = true;
                 }
+                Attr::GenerateBuilder => {
+                    generate_builder = true;
+                }
+                Attr::Rename(name) => {
+                    rename = Some(name);
+                }
+                Attr::Bound(lower, upper) => {
+                    bound = Some((lower, upper));
+                }
+                Attr::SerializeHolderOnly => {
+                    serialize_holder_only = true;
+                }
+                Attr::DerivedInSubtype => {
+                    derived_in_subtype = true;
+                }
+                Attr::Derive(values) => {
+                    derive.extend(values);
+                }
             }
         }
         HolderAttr {
@@ -53,6 +98,20 @@ impl HolderAttr {
             field,
             place_holder,
             generate_deserialize,
+            generate_builder,
+            rename,
+            bound,
+            serialize_holder_only,
+            derived_in_subtype,
+            derive,
+        }
+    }
+
+    /// Record name in exchange structure, `ident` in SCREAMING_SNAKE_CASE if not renamed
+    pub fn name(&self, ident: &syn::Ident) -> String {
+        match &self.rename {
+            Some(name) => name.clone(),
+            None => ident.to_string().to_screaming_snake_case(),
         }
     }
 }
@@ -63,6 +122,29 @@ enum Attr {
     Field(syn::Ident),
     PlaceHolder,
     GenerateDeserialize,
+    GenerateBuilder,
+    Rename(String),
+    Bound(usize, Option<usize>),
+    SerializeHolderOnly,
+    DerivedInSubtype,
+    Derive(Vec<(Vec<syn::Ident>, syn::Expr)>),
+}
+
+/// Path of fields to an attribute, e.g. `[base, y]` for `base.y`
+fn attribute_path(expr: &syn::Expr) -> Option<Vec<syn::Ident>> {
+    match expr {
+        syn::Expr::Path(path) => Some(vec![path.path.get_ident()?.clone()]),
+        syn::Expr::Field(syn::ExprField {
+            base,
+            member: syn::Member::Named(ident),
+            ..
+        }) => {
+            let mut path = attribute_path(base)?;
+            path.push(ident.clone());
+            Some(path)
+        }
+        _ => None,
+    }
 }
 
 impl syn::parse::Parse for Attr {
@@ -81,9 +163,49 @@ impl syn::parse::Parse for Attr {
             }
             "use_place_holder" => Ok(Attr::PlaceHolder),
             "generate_deserialize" => Ok(Attr::GenerateDeserialize),
+            "generate_builder" => Ok(Attr::GenerateBuilder),
+            "serialize_holder_only" => Ok(Attr::SerializeHolderOnly),
+            "derived_in_subtype" => Ok(Attr::DerivedInSubtype),
+            "derive" => {
+                let content;
+                syn::parenthesized!(content in input);
+                content
+                    .parse_terminated(<syn::ExprAssign as syn::parse::Parse>::parse, syn::Token![,])?
+                    .into_iter()
+                    .map(|assign| {
+                        let path = attribute_path(&assign.left).ok_or_else(|| {
+                            syn::parse::Error::new(ident.span(), "expected `attr = expr` or `supertype.attr = expr`")
+                        })?;
+                        Ok((path, *assign.right))
+                    })
+                    .collect::<syn::parse::Result<_>>()
+                    .map(Attr::Derive)
+            }
+            "rename" => {
+                let _eq: syn::Token![=] = input.parse()?;
+                let name: syn::LitStr = input.parse()?;
+                Ok(Attr::Rename(name.value()))
+            }
+            "bound" => {
+                let content;
+                syn::parenthesized!(content in input);
+                let bounds = content
+                    .parse_terminated(<syn::LitInt as syn::parse::Parse>::parse, syn::Token![,])?
+                    .iter()
+                    .map(syn::LitInt::base10_parse)
+                    .collect::<syn::parse::Result<Vec<usize>>>()?;
+                match bounds.as_slice() {
+                    [lower] => Ok(Attr::Bound(*lower, None)),
+                    [lower, upper] => Ok(Attr::Bound(*lower, Some(*upper))),
+                    _ => Err(syn::parse::Error::new(
+                        ident.span(),
+                        "expected `bound(lower)` or `bound(lower, upper)`",
+                    )),
+                }
+            }
             _ => Err(syn::parse::Error::new(
                 ident.span(),
-                "expected `table`, `field`, or `use_place_holder`",
+                "expected `table`, `field`, `use_place_holder`, `generate_deserialize`, `generate_builder`, `rename`, `bound`, `serialize_holder_only`, `derived_in_subtype`, or `derive`",
             )),
         }
     }
@@ -132,4 +254,71 @@ mod tests {
         // typo
         assert!(syn::parse_str::<Attr>("use_place_helder").is_err());
     }
+
+    #[test]
+    fn parse_attr_generate_builder() {
+        let attr: Attr = syn::parse_str("generate_builder").unwrap();
+        assert_eq!(attr, Attr::GenerateBuilder);
+
+        assert!(syn::parse_str::<Attr>("generate_builder = true").is_err());
+    }
+
+    #[test]
+    fn parse_attr_serialize_holder_only() {
+        let attr: Attr = syn::parse_str("serialize_holder_only").unwrap();
+        assert_eq!(attr, Attr::SerializeHolderOnly);
+
+        assert!(syn::parse_str::<Attr>("serialize_holder_only = true").is_err());
+    }
+
+    #[test]
+    fn parse_attr_rename() {
+        let attr: Attr = syn::parse_str(r#"rename = "B_SPLINE_CURVE""#).unwrap();
+        assert_eq!(attr, Attr::Rename("B_SPLINE_CURVE".to_string()));
+
+        // rename must take string literal
+        assert!(syn::parse_str::<Attr>("rename = B_SPLINE_CURVE").is_err());
+        assert!(syn::parse_str::<Attr>("rename").is_err());
+    }
+
+    #[test]
+    fn parse_attr_derive() {
+        let attr: Attr = syn::parse_str("derived_in_subtype").unwrap();
+        assert_eq!(attr, Attr::DerivedInSubtype);
+
+        let attr: Attr = syn::parse_str("derive(w = 1.0, base.y = Some(2.0 * z))").unwrap();
+        assert_eq!(
+            attr,
+            Attr::Derive(vec![
+                (
+                    vec![syn::parse_str("w").unwrap()],
+                    syn::parse_str("1.0").unwrap()
+                ),
+                (
+                    vec![
+                        syn::parse_str("base").unwrap(),
+                        syn::parse_str("y").unwrap()
+                    ],
+                    syn::parse_str("Some(2.0 * z)").unwrap()
+                ),
+            ])
+        );
+
+        assert!(syn::parse_str::<Attr>("derive").is_err());
+        assert!(syn::parse_str::<Attr>("derive(1.0)").is_err());
+        assert!(syn::parse_str::<Attr>("derive(f(x) = 1.0)").is_err());
+    }
+
+    #[test]
+    fn parse_attr_bound() {
+        let attr: Attr = syn::parse_str("bound(1)").unwrap();
+        assert_eq!(attr, Attr::Bound(1, None));
+        let attr: Attr = syn::parse_str("bound(2, 3)").unwrap();
+        assert_eq!(attr, Attr::Bound(2, Some(3)));
+
+        assert!(syn::parse_str::<Attr>("bound").is_err());
+        assert!(syn::parse_str::<Attr>("bound()").is_err());
+        assert!(syn::parse_str::<Attr>("bound(1, 2, 3)").is_err());
+        assert!(syn::parse_str::<Attr>("bound(-1)").is_err());
+    }
 }
@@ -27,9 +27,11 @@ use proc_macro_error::{abort_call_site, proc_macro_error};
 use quote::quote;
 use std::convert::*;
 
+mod approx_eq;
 mod common;
 mod entity;
 mod field_type;
+mod hash_bits;
 mod holder_attr;
 mod select;
 mod table_init;
@@ -42,6 +44,22 @@ use table_init::*;
 
 /// Derive `TableInit` for tables
 ///
+/// Each field stores the records whose name is `Holder::name` of its holders,
+/// i.e. the name in SCREAMING_SNAKE_CASE or the one specified by `#[holder(rename = "RECORD_NAME")]`.
+/// The type of fields can be any container implementing `ruststep::tables::IdMap`,
+/// e.g. `BTreeMap` to iterate entities in ascending id order.
+/// A field with `#[table_init(complex)]` attribute is a table of combined holders
+/// for complex entity instances, see `ruststep::ast::SubSuperRecord::deserialize_combined`.
+///
+/// `#[table_init(schema = "schema_name")]` on the struct specifies the EXPRESS schema,
+/// which is checked against `FILE_SCHEMA` when creating tables from a whole exchange structure
+/// by `TableInit::from_exchange` or `FromStr`.
+/// `FromStr` accepts both a whole exchange structure starting with `ISO-10303-21;` and a data section.
+///
+/// `ruststep::tables::EntityGraph` is also implemented to walk references between tables.
+/// Tables can be filled in parallel by `TableInit::from_data_section_parallel`
+/// with `rayon` feature of ruststep.
+///
 /// ```
 /// use ruststep_derive::{as_holder, Holder, TableInit};
 /// use std::collections::HashMap;
@@ -72,7 +90,7 @@ use table_init::*;
 /// }
 /// ```
 #[proc_macro_error]
-#[proc_macro_derive(TableInit)]
+#[proc_macro_derive(TableInit, attributes(table_init))]
 pub fn derive_table_init_entry(input: TokenStream) -> TokenStream {
     derive_table_init(&syn::parse(input).unwrap()).into()
 }
@@ -93,6 +111,104 @@ fn derive_deserialize(ast: &syn::DeriveInput) -> TokenStream2 {
     }
 }
 
+/// Generate `impl Serialize` for entity structs, defined types, and select enums
+///
+/// The fields are serialized in the same order as `#[derive(Holder)]` deserializes them,
+/// i.e. the output of `ruststep::ast::ser::to_record` is deserialized back into the same value.
+/// With `#[holder(table = ...)]`, `Serialize` is also implemented for the holder generated by `#[derive(Holder)]`,
+/// which writes references as they are.
+/// With `#[holder(serialize_holder_only)]`, it is implemented only for the holder,
+/// e.g. when the type itself derives `serde::Serialize`.
+#[proc_macro_error]
+#[proc_macro_derive(Serialize, attributes(holder))]
+pub fn derive_serialize_entry(input: TokenStream) -> TokenStream {
+    derive_serialize(&syn::parse(input).unwrap()).into()
+}
+
+fn derive_serialize(ast: &syn::DeriveInput) -> TokenStream2 {
+    let attr = HolderAttr::parse(&ast.attrs);
+    let ident = &ast.ident;
+    match &ast.data {
+        syn::Data::Struct(st) => match st.fields {
+            syn::Fields::Named(_) => entity::derive_serialize(ident, st, &attr),
+            syn::Fields::Unnamed(_) => type_decl::derive_serialize(ident, st, &attr),
+            syn::Fields::Unit => abort_call_site!("Unit struct is not supported."),
+        },
+        syn::Data::Enum(e) => select::derive_serialize(ident, e, &attr),
+        _ => abort_call_site!("Only struct and enum are supported"),
+    }
+}
+
+/// Generate `impl ApproxEq` for entity structs, defined types, and select enums
+///
+/// Fields are compared by `ruststep::primitive::ApproxEq` in order,
+/// and values of different variants are not equal.
+///
+/// ```
+/// use ruststep::primitive::ApproxEq;
+/// use ruststep_derive::ApproxEq;
+///
+/// #[derive(ApproxEq)]
+/// pub struct A {
+///     pub x: f64,
+///     pub name: String,
+/// }
+///
+/// #[derive(ApproxEq)]
+/// pub enum S {
+///     A(Box<A>),
+///     P(f64),
+/// }
+///
+/// let a = A { x: 1.0, name: "a".to_string() };
+/// let b = A { x: 1.0 + 1e-9, name: "a".to_string() };
+/// assert!(a.approx_eq(&b, 1e-6));
+/// assert!(!S::A(Box::new(a)).approx_eq(&S::P(1.0), 1e-6));
+/// ```
+#[proc_macro_error]
+#[proc_macro_derive(ApproxEq)]
+pub fn derive_approx_eq_entry(input: TokenStream) -> TokenStream {
+    approx_eq::derive_approx_eq(&syn::parse(input).unwrap()).into()
+}
+
+/// Generate `impl HashBits` for entity structs, defined types, and select enums
+///
+/// Fields are hashed and compared by `ruststep::primitive::HashBits` in order,
+/// and values of different variants are not equal.
+///
+/// ```
+/// use ruststep::primitive::{BitHashed, HashBits};
+/// use ruststep_derive::HashBits;
+/// use std::collections::HashSet;
+///
+/// #[derive(HashBits)]
+/// pub struct A {
+///     pub x: f64,
+///     pub name: String,
+/// }
+///
+/// #[derive(HashBits)]
+/// pub enum S {
+///     A(Box<A>),
+///     P(f64),
+/// }
+///
+/// let a = A { x: 1.0, name: "a".to_string() };
+/// let b = A { x: 1.0, name: "a".to_string() };
+/// assert!(a.eq_bits(&b));
+///
+/// let set: HashSet<_> = [S::A(Box::new(a)), S::A(Box::new(b)), S::P(1.0)]
+///     .into_iter()
+///     .map(BitHashed)
+///     .collect();
+/// assert_eq!(set.len(), 2);
+/// ```
+#[proc_macro_error]
+#[proc_macro_derive(HashBits)]
+pub fn derive_hash_bits_entry(input: TokenStream) -> TokenStream {
+    hash_bits::derive_hash_bits(&syn::parse(input).unwrap()).into()
+}
+
 /// Generates `Holder` struct and related implementation for each `ENTITY` struct
 ///
 /// `#[holder]` attribute
@@ -128,9 +244,28 @@ fn derive_deserialize(ast: &syn::DeriveInput) -> TokenStream2 {
 /// - `#[holder(generate_deserialize)]`
 ///   - This must be a container attribute
 ///   - Flag for generating `impl Deserialize for XxxHolder`
+/// - `#[holder(generate_builder)]`
+///   - This must be a container attribute
+///   - Flag for generating `XxxBuilder`, see below
 /// - `#[holder(use_place_holder)]`
 ///   - This can be both in field or variant attribute
 ///   - Specify the field is not a simple type
+/// - `#[holder(rename = "RECORD_NAME")]`
+///   - This must be a container attribute
+///   - Record name used in exchange structure instead of the struct name in SCREAMING_SNAKE_CASE,
+///     e.g. `#[holder(rename = "B_SPLINE_CURVE")]` for `struct BSplineCurve`
+///   - `#[derive(TableInit)]` also stores records of this name into the corresponding field
+///
+/// Builder
+/// --------
+/// For an entity struct `A` with `#[holder(generate_builder)]`, a builder `ABuilder` is also generated.
+/// This is opt-in to keep generated code small for large schemas.
+/// `ABuilder::new` takes non-optional fields, and each `Option<T>` field
+/// is `None` by default and set by a setter of the same name taking `T`.
+///
+/// ```ignore
+/// let a = ABuilder::new(1.0).name("a".to_string()).build();
+/// ```
 ///
 #[proc_macro_error]
 #[proc_macro_derive(Holder, attributes(holder))]
@@ -197,10 +332,13 @@ mod snapshot_tests {
         impl ::ruststep::tables::IntoOwned for S1Holder {
             type Owned = S1;
             type Table = Table;
-            fn into_owned(self, table: &Self::Table) -> ::ruststep::error::Result<Self::Owned> {
+            fn into_owned_with(
+                self,
+                resolver: &mut ::ruststep::tables::Resolver<'_, Self::Table>,
+            ) -> ::ruststep::error::Result<Self::Owned> {
                 Ok(match self {
-                    S1Holder::A(sub) => S1::A(Box::new(sub.into_owned(table)?)),
-                    S1Holder::B(sub) => S1::B(Box::new(sub.into_owned(table)?)),
+                    S1Holder::A(sub) => S1::A(Box::new(sub.into_owned_with(resolver)?)),
+                    S1Holder::B(sub) => S1::B(Box::new(sub.into_owned_with(resolver)?)),
                 })
             }
         }
@@ -211,6 +349,25 @@ mod snapshot_tests {
             fn attr_len() -> usize {
                 0
             }
+            fn accepts_keyword(keyword: &str) -> bool {
+                false
+                    || <AHolder as ::ruststep::tables::Holder>::accepts_keyword(keyword)
+                    || <BHolder as ::ruststep::tables::Holder>::accepts_keyword(keyword)
+            }
+        }
+        impl ::ruststep::tables::VisitRefs for S1Holder {
+            fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+                match self {
+                    S1Holder::A(sub) => ::ruststep::tables::VisitRefs::visit_refs(sub, visitor),
+                    S1Holder::B(sub) => ::ruststep::tables::VisitRefs::visit_refs(sub, visitor),
+                }
+            }
+            fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+                match self {
+                    S1Holder::A(sub) => ::ruststep::tables::VisitRefs::remap_refs(sub, map),
+                    S1Holder::B(sub) => ::ruststep::tables::VisitRefs::remap_refs(sub, map),
+                }
+            }
         }
         impl<'de> ::ruststep::serde::de::Deserialize<'de> for S1Holder {
             fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
@@ -243,11 +400,18 @@ mod snapshot_tests {
                         let owned = map.next_value()?;
                         return Ok(S1Holder::B(Box::new(owned)));
                     }
-                    _ => {
-                        use ruststep::serde::de::{Error, Unexpected};
-                        return Err(A::Error::invalid_value(Unexpected::Other(&key), &self));
-                    }
+                    _ => {}
                 }
+                if <AHolder as ::ruststep::tables::Holder>::accepts_keyword(&key) {
+                    let owned: AHolder = map.next_value_seed(::ruststep::tables::KeywordSeed::new(key))?;
+                    return Ok(S1Holder::A(Box::new(owned)));
+                }
+                if <BHolder as ::ruststep::tables::Holder>::accepts_keyword(&key) {
+                    let owned: BHolder = map.next_value_seed(::ruststep::tables::KeywordSeed::new(key))?;
+                    return Ok(S1Holder::B(Box::new(owned)));
+                }
+                use ruststep::serde::de::{Error, Unexpected};
+                Err(A::Error::invalid_value(Unexpected::Other(&key), &self))
             }
         }
         impl ::ruststep::tables::WithVisitor for S1Holder {
@@ -258,13 +422,41 @@ mod snapshot_tests {
         }
         impl ::ruststep::tables::EntityTable<S1Holder> for Table {
             fn get_owned(&self, entity_id: u64) -> ::ruststep::error::Result<S1> {
-                if let Ok(owned) = ::ruststep::tables::EntityTable::<AHolder>::get_owned(self, entity_id) {
-                    return Ok(S1::A(Box::new(owned.into())));
+                if ::ruststep::tables::EntityTable::<AHolder>::contains(self, entity_id) {
+                    return ::ruststep::tables::EntityTable::<AHolder>::get_owned(self, entity_id)
+                        .map(|owned| S1::A(Box::new(owned.into())));
+                }
+                if ::ruststep::tables::EntityTable::<BHolder>::contains(self, entity_id) {
+                    return ::ruststep::tables::EntityTable::<BHolder>::get_owned(self, entity_id)
+                        .map(|owned| S1::B(Box::new(owned.into())));
                 }
-                if let Ok(owned) = ::ruststep::tables::EntityTable::<BHolder>::get_owned(self, entity_id) {
-                    return Ok(S1::B(Box::new(owned.into())));
+                Err(::ruststep::error::Error::UnexpectedEntityType {
+                    entity_id,
+                    expected: vec![
+                        <AHolder as ::ruststep::tables::Holder>::name().to_string(),
+                        <BHolder as ::ruststep::tables::Holder>::name().to_string(),
+                    ],
+                })
+            }
+            fn contains(&self, entity_id: u64) -> bool {
+                false
+                    || ::ruststep::tables::EntityTable::<AHolder>::contains(self, entity_id)
+                    || ::ruststep::tables::EntityTable::<BHolder>::contains(self, entity_id)
+            }
+            fn get_holder(&self, entity_id: u64) -> Option<::std::borrow::Cow<'_, S1Holder>> {
+                if let Some(holder) =
+                    ::ruststep::tables::EntityTable::<AHolder>::get_holder(self, entity_id)
+                {
+                    let owned = holder.into_owned();
+                    return Some(::std::borrow::Cow::Owned(S1Holder::A(Box::new(owned))));
+                }
+                if let Some(holder) =
+                    ::ruststep::tables::EntityTable::<BHolder>::get_holder(self, entity_id)
+                {
+                    let owned = holder.into_owned();
+                    return Some(::std::borrow::Cow::Owned(S1Holder::B(Box::new(owned))));
                 }
-                Err(::ruststep::error::Error::UnknownEntity(entity_id))
+                None
             }
             fn owned_iter<'table>(
                 &'table self,
@@ -311,10 +503,13 @@ mod snapshot_tests {
         impl ::ruststep::tables::IntoOwned for BaseAnyHolder {
             type Owned = BaseAny;
             type Table = Tables;
-            fn into_owned(self, table: &Self::Table) -> ::ruststep::error::Result<Self::Owned> {
+            fn into_owned_with(
+                self,
+                resolver: &mut ::ruststep::tables::Resolver<'_, Self::Table>,
+            ) -> ::ruststep::error::Result<Self::Owned> {
                 Ok(match self {
-                    BaseAnyHolder::Base(sub) => BaseAny::Base(Box::new(sub.into_owned(table)?)),
-                    BaseAnyHolder::Sub(sub) => BaseAny::Sub(Box::new(sub.into_owned(table)?)),
+                    BaseAnyHolder::Base(sub) => BaseAny::Base(Box::new(sub.into_owned_with(resolver)?)),
+                    BaseAnyHolder::Sub(sub) => BaseAny::Sub(Box::new(sub.into_owned_with(resolver)?)),
                 })
             }
         }
@@ -325,6 +520,25 @@ mod snapshot_tests {
             fn attr_len() -> usize {
                 0
             }
+            fn accepts_keyword(keyword: &str) -> bool {
+                false
+                    || <BaseHolder as ::ruststep::tables::Holder>::accepts_keyword(keyword)
+                    || <SubAnyHolder as ::ruststep::tables::Holder>::accepts_keyword(keyword)
+            }
+        }
+        impl ::ruststep::tables::VisitRefs for BaseAnyHolder {
+            fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+                match self {
+                    BaseAnyHolder::Base(sub) => ::ruststep::tables::VisitRefs::visit_refs(sub, visitor),
+                    BaseAnyHolder::Sub(sub) => ::ruststep::tables::VisitRefs::visit_refs(sub, visitor),
+                }
+            }
+            fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+                match self {
+                    BaseAnyHolder::Base(sub) => ::ruststep::tables::VisitRefs::remap_refs(sub, map),
+                    BaseAnyHolder::Sub(sub) => ::ruststep::tables::VisitRefs::remap_refs(sub, map),
+                }
+            }
         }
         impl<'de> ::ruststep::serde::de::Deserialize<'de> for BaseAnyHolder {
             fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
@@ -357,11 +571,20 @@ mod snapshot_tests {
                         let owned = map.next_value()?;
                         return Ok(BaseAnyHolder::Sub(Box::new(owned)));
                     }
-                    _ => {
-                        use ruststep::serde::de::{Error, Unexpected};
-                        return Err(A::Error::invalid_value(Unexpected::Other(&key), &self));
-                    }
+                    _ => {}
+                }
+                if <BaseHolder as ::ruststep::tables::Holder>::accepts_keyword(&key) {
+                    let owned: BaseHolder =
+                        map.next_value_seed(::ruststep::tables::KeywordSeed::new(key))?;
+                    return Ok(BaseAnyHolder::Base(Box::new(owned)));
+                }
+                if <SubAnyHolder as ::ruststep::tables::Holder>::accepts_keyword(&key) {
+                    let owned: SubAnyHolder =
+                        map.next_value_seed(::ruststep::tables::KeywordSeed::new(key))?;
+                    return Ok(BaseAnyHolder::Sub(Box::new(owned)));
                 }
+                use ruststep::serde::de::{Error, Unexpected};
+                Err(A::Error::invalid_value(Unexpected::Other(&key), &self))
             }
         }
         impl ::ruststep::tables::WithVisitor for BaseAnyHolder {
@@ -372,16 +595,45 @@ mod snapshot_tests {
         }
         impl ::ruststep::tables::EntityTable<BaseAnyHolder> for Tables {
             fn get_owned(&self, entity_id: u64) -> ::ruststep::error::Result<BaseAny> {
-                if let Ok(owned) = ::ruststep::tables::EntityTable::<BaseHolder>::get_owned(self, entity_id)
+                if ::ruststep::tables::EntityTable::<BaseHolder>::contains(self, entity_id) {
+                    return ::ruststep::tables::EntityTable::<BaseHolder>::get_owned(self, entity_id)
+                        .map(|owned| BaseAny::Base(Box::new(owned.into())));
+                }
+                if ::ruststep::tables::EntityTable::<SubAnyHolder>::contains(self, entity_id) {
+                    return ::ruststep::tables::EntityTable::<SubAnyHolder>::get_owned(self, entity_id)
+                        .map(|owned| BaseAny::Sub(Box::new(owned.into())));
+                }
+                Err(::ruststep::error::Error::UnexpectedEntityType {
+                    entity_id,
+                    expected: vec![
+                        <BaseHolder as ::ruststep::tables::Holder>::name().to_string(),
+                        <SubAnyHolder as ::ruststep::tables::Holder>::name().to_string(),
+                    ],
+                })
+            }
+            fn contains(&self, entity_id: u64) -> bool {
+                false
+                    || ::ruststep::tables::EntityTable::<BaseHolder>::contains(self, entity_id)
+                    || ::ruststep::tables::EntityTable::<SubAnyHolder>::contains(self, entity_id)
+            }
+            fn get_holder(&self, entity_id: u64) -> Option<::std::borrow::Cow<'_, BaseAnyHolder>> {
+                if let Some(holder) =
+                    ::ruststep::tables::EntityTable::<BaseHolder>::get_holder(self, entity_id)
                 {
-                    return Ok(BaseAny::Base(Box::new(owned.into())));
+                    let owned = holder.into_owned();
+                    return Some(::std::borrow::Cow::Owned(BaseAnyHolder::Base(Box::new(
+                        owned,
+                    ))));
                 }
-                if let Ok(owned) =
-                    ::ruststep::tables::EntityTable::<SubAnyHolder>::get_owned(self, entity_id)
+                if let Some(holder) =
+                    ::ruststep::tables::EntityTable::<SubAnyHolder>::get_holder(self, entity_id)
                 {
-                    return Ok(BaseAny::Sub(Box::new(owned.into())));
+                    let owned = holder.into_owned();
+                    return Some(::std::borrow::Cow::Owned(BaseAnyHolder::Sub(Box::new(
+                        owned,
+                    ))));
                 }
-                Err(::ruststep::error::Error::UnknownEntity(entity_id))
+                None
             }
             fn owned_iter<'table>(
                 &'table self,
@@ -430,14 +682,27 @@ mod snapshot_tests {
             pub base: ::ruststep::tables::PlaceHolder<BaseHolder>,
             pub y1: f64,
         }
+        impl Sub1Holder {
+            #[doc = r" Resolve this attribute into its owned value, looking up references in `table`"]
+            pub fn base(
+                &self,
+                table: &<Self as ::ruststep::tables::IntoOwned>::Table,
+            ) -> ::ruststep::error::Result<Base> {
+                use ruststep::tables::IntoOwned;
+                self.base.clone().into_owned(table)
+            }
+        }
         #[automatically_derived]
         impl ::ruststep::tables::IntoOwned for Sub1Holder {
             type Table = Tables;
             type Owned = Sub1;
-            fn into_owned(self, table: &Self::Table) -> ::ruststep::error::Result<Self::Owned> {
+            fn into_owned_with(
+                self,
+                resolver: &mut ::ruststep::tables::Resolver<'_, Self::Table>,
+            ) -> ::ruststep::error::Result<Self::Owned> {
                 let Sub1Holder { base, y1 } = self;
                 Ok(Sub1 {
-                    base: base.into_owned(table)?,
+                    base: base.into_owned_with(resolver)?,
                     y1: y1,
                 })
             }
@@ -450,12 +715,30 @@ mod snapshot_tests {
             fn attr_len() -> usize {
                 2usize
             }
+            fn attr_names() -> &'static [&'static str] {
+                &["base", "y1"]
+            }
+        }
+        #[automatically_derived]
+        impl ::ruststep::tables::VisitRefs for Sub1Holder {
+            fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+                ::ruststep::tables::VisitRefs::visit_refs(&self.base, visitor);
+            }
+            fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+                ::ruststep::tables::VisitRefs::remap_refs(&mut self.base, map);
+            }
         }
         #[automatically_derived]
         impl ::ruststep::tables::EntityTable<Sub1Holder> for Tables {
             fn get_owned(&self, entity_id: u64) -> ::ruststep::error::Result<Sub1> {
                 ::ruststep::tables::get_owned(self, &self.sub1, entity_id)
             }
+            fn contains(&self, entity_id: u64) -> bool {
+                ::ruststep::tables::IdMap::get(&self.sub1, entity_id).is_some()
+            }
+            fn get_holder(&self, entity_id: u64) -> Option<::std::borrow::Cow<'_, Sub1Holder>> {
+                ::ruststep::tables::IdMap::get(&self.sub1, entity_id).map(::std::borrow::Cow::Borrowed)
+            }
             fn owned_iter<'table>(
                 &'table self,
             ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<Sub1>> + 'table> {
@@ -50,7 +50,7 @@ impl Input {
                     if place_holder {
                         // ENTITY case
                         holder_types.push(as_holder_path(&f.ty));
-                        holder_exprs.push(quote! { Box::new(sub.into_owned(table)?) });
+                        holder_exprs.push(quote! { Box::new(sub.into_owned_with(resolver)?) });
                         variant_exprs.push(quote! { Box::new(owned) });
                         variant_into_exprs.push(quote! { Box::new(owned.into()) });
                     } else {
@@ -62,7 +62,7 @@ impl Input {
                     if place_holder {
                         // *Any case
                         holder_types.push(as_holder_path(&f.ty));
-                        holder_exprs.push(quote! { sub.into_owned(table)? });
+                        holder_exprs.push(quote! { sub.into_owned_with(resolver)? });
                     } else {
                         // SimpleType case
                         holder_types.push(f.ty.clone());
@@ -88,6 +88,23 @@ impl Input {
         }
     }
 
+    /// Variants using place holder, with their holder types unboxed
+    fn place_holder_variants(&self) -> Vec<(usize, syn::Type)> {
+        self.holder_types
+            .iter()
+            .zip(&self.place_holders)
+            .enumerate()
+            .filter(|(_, (_, place_holder))| **place_holder)
+            .map(|(i, (holder, _))| {
+                if let FieldType::Boxed(path) = holder.clone().try_into().unwrap() {
+                    (i, path.as_ref().clone().into())
+                } else {
+                    (i, holder.clone())
+                }
+            })
+            .collect()
+    }
+
     fn def_holder(&self) -> TokenStream2 {
         let Input {
             holder_ident,
@@ -112,15 +129,35 @@ impl Input {
             variants,
             table,
             holder_exprs,
+            place_holders,
             ..
         } = self;
         let ruststep = ruststep_crate();
+        let members: Vec<_> = self
+            .place_holder_variants()
+            .into_iter()
+            .map(|(_, holder)| holder)
+            .collect();
+        let visit_refs = place_holders.iter().map(|place_holder| {
+            if *place_holder {
+                quote! { #ruststep::tables::VisitRefs::visit_refs(sub, visitor) }
+            } else {
+                quote! { {} }
+            }
+        });
+        let remap_refs = place_holders.iter().map(|place_holder| {
+            if *place_holder {
+                quote! { #ruststep::tables::VisitRefs::remap_refs(sub, map) }
+            } else {
+                quote! { {} }
+            }
+        });
 
         quote! {
             impl #ruststep::tables::IntoOwned for #holder_ident {
                 type Owned = #ident;
                 type Table = #table;
-                fn into_owned(self, table: &Self::Table) -> #ruststep::error::Result<Self::Owned> {
+                fn into_owned_with(self, resolver: &mut #ruststep::tables::Resolver<'_, Self::Table>) -> #ruststep::error::Result<Self::Owned> {
                     Ok(match self {
                         #(#holder_ident::#variants(sub) => #ident::#variants(#holder_exprs)),*
                     })
@@ -133,6 +170,21 @@ impl Input {
                 fn attr_len() -> usize {
                     0
                 }
+                fn accepts_keyword(keyword: &str) -> bool {
+                    false #(|| <#members as #ruststep::tables::Holder>::accepts_keyword(keyword))*
+                }
+            }
+            impl #ruststep::tables::VisitRefs for #holder_ident {
+                fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+                    match self {
+                        #(#holder_ident::#variants(sub) => #visit_refs),*
+                    }
+                }
+                fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+                    match self {
+                        #(#holder_ident::#variants(sub) => #remap_refs),*
+                    }
+                }
             }
         } // quote!
     }
@@ -169,6 +221,14 @@ impl Input {
         } = self;
         let ruststep = ruststep_crate();
         let serde = serde_crate();
+        let mut member_variants = Vec::new();
+        let mut member_holders = Vec::new();
+        let mut member_exprs = Vec::new();
+        for (i, holder) in self.place_holder_variants() {
+            member_variants.push(&variants[i]);
+            member_holders.push(holder);
+            member_exprs.push(&variant_exprs[i]);
+        }
 
         quote! {
             #[doc(hidden)]
@@ -195,11 +255,18 @@ impl Input {
                             return Ok(#holder_ident::#variants(#variant_exprs));
                         }
                         )*
-                        _ => {
-                            use #serde::de::{Error, Unexpected};
-                            return Err(A::Error::invalid_value(Unexpected::Other(&key), &self));
-                        }
+                        _ => {}
+                    }
+                    // Keyword of a member of nested SELECT, e.g. `LENGTH_MEASURE(1.0)`
+                    // for `SELECT (measure_value, ...)` where `measure_value = SELECT (length_measure, ...)`
+                    #(
+                    if <#member_holders as #ruststep::tables::Holder>::accepts_keyword(&key) {
+                        let owned: #member_holders = map.next_value_seed(#ruststep::tables::KeywordSeed::new(key))?;
+                        return Ok(#holder_ident::#member_variants(#member_exprs));
                     }
+                    )*
+                    use #serde::de::{Error, Unexpected};
+                    Err(A::Error::invalid_value(Unexpected::Other(&key), &self))
                 }
             }
 
@@ -217,43 +284,52 @@ impl Input {
             ident,
             holder_ident,
             variants,
-            holder_types,
             table,
+            variant_exprs,
             variant_into_exprs,
-            place_holders,
             ..
         } = self;
         let ruststep = ruststep_crate();
         let itertools = itertools_crate();
         let mut vars = Vec::new();
-        let mut holders = Vec::<syn::Type>::new();
+        let mut holders = Vec::new();
+        let mut holder_exprs = Vec::new();
         let mut exprs = Vec::new();
-        for (((var, holder), expr), place_holder) in variants
-            .iter()
-            .zip(holder_types)
-            .zip(variant_into_exprs)
-            .zip(place_holders)
-        {
-            if *place_holder {
-                vars.push(var);
-                exprs.push(expr);
-                if let FieldType::Boxed(path) = holder.clone().try_into().unwrap() {
-                    holders.push(path.as_ref().clone().into());
-                } else {
-                    holders.push(holder.clone());
-                }
-            }
+        for (i, holder) in self.place_holder_variants() {
+            vars.push(&variants[i]);
+            holders.push(holder);
+            holder_exprs.push(&variant_exprs[i]);
+            exprs.push(&variant_into_exprs[i]);
         }
 
         quote! {
             impl #ruststep::tables::EntityTable<#holder_ident> for #table {
                 fn get_owned(&self, entity_id: u64) -> #ruststep::error::Result<#ident> {
+                    // Dispatch by the table containing the entity,
+                    // and report errors while resolving it as they are
                     #(
-                    if let Ok(owned) = #ruststep::tables::EntityTable::<#holders>::get_owned(self, entity_id) {
-                        return Ok(#ident::#vars(#exprs));
+                    if #ruststep::tables::EntityTable::<#holders>::contains(self, entity_id) {
+                        return #ruststep::tables::EntityTable::<#holders>::get_owned(self, entity_id)
+                            .map(|owned| #ident::#vars(#exprs));
                     }
                     )*
-                    Err(#ruststep::error::Error::UnknownEntity(entity_id))
+                    Err(#ruststep::error::Error::UnexpectedEntityType {
+                        entity_id,
+                        expected: vec![#(<#holders as #ruststep::tables::Holder>::name().to_string()),*],
+                    })
+                }
+                fn contains(&self, entity_id: u64) -> bool {
+                    false #(|| #ruststep::tables::EntityTable::<#holders>::contains(self, entity_id))*
+                }
+                fn get_holder(&self, entity_id: u64) -> Option<::std::borrow::Cow<'_, #holder_ident>> {
+                    // Holder of the member containing the entity is wrapped into this SELECT holder
+                    #(
+                    if let Some(holder) = #ruststep::tables::EntityTable::<#holders>::get_holder(self, entity_id) {
+                        let owned = holder.into_owned();
+                        return Some(::std::borrow::Cow::Owned(#holder_ident::#vars(#holder_exprs)));
+                    }
+                    )*
+                    None
                 }
                 fn owned_iter<'table>(&'table self) -> Box<dyn Iterator<Item = #ruststep::error::Result<#ident>> + 'table> {
                     Box::new(#itertools::chain![
@@ -292,6 +368,39 @@ pub fn derive_holder(ident: &syn::Ident, e: &syn::DataEnum, attr: &HolderAttr) -
     }
 }
 
+pub fn derive_serialize(ident: &syn::Ident, e: &syn::DataEnum, attr: &HolderAttr) -> TokenStream2 {
+    let name = ident.to_string().to_screaming_snake_case();
+    let variants: Vec<syn::Ident> = e.variants.iter().map(|var| var.ident.clone()).collect();
+    let variant_names: Vec<_> = variants
+        .iter()
+        .map(|id| id.to_string().to_screaming_snake_case())
+        .collect();
+    let serde = serde_crate();
+    serialize_idents(ident, attr)
+        .into_iter()
+        .map(|ident| {
+            let indices = 0..variants.len() as u32;
+            quote! {
+                #[automatically_derived]
+                impl #serde::ser::Serialize for #ident {
+                    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+                    where
+                        S: #serde::ser::Serializer,
+                    {
+                        match self {
+                            #(
+                            #ident::#variants(value) => {
+                                serializer.serialize_newtype_variant(#name, #indices, #variant_names, value)
+                            }
+                            )*
+                        }
+                    }
+                }
+            } // quote!
+        })
+        .collect()
+}
+
 pub fn derive_deserialize(_ident: &syn::Ident, _e: &syn::DataEnum) -> TokenStream2 {
     unimplemented!()
 }
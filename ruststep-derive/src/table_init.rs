@@ -7,56 +7,268 @@ use crate::common::ruststep_crate;
 
 pub fn derive_table_init(ast: &syn::DeriveInput) -> TokenStream2 {
     let ident = &ast.ident;
+    let schema = table_init_attrs(&ast.attrs)
+        .into_iter()
+        .find_map(|attr| match attr {
+            TableInitAttr::Schema(name) => Some(name),
+            _ => None,
+        });
     match &ast.data {
         syn::Data::Struct(st) => match st.fields {
-            syn::Fields::Named(_) => entity_impl_table_init(ident, st),
-            syn::Fields::Unnamed(_) => tuple_impl_table_init(ident, st),
+            syn::Fields::Named(_) => entity_impl_table_init(ident, st, schema),
+            syn::Fields::Unnamed(_) => tuple_impl_table_init(ident, st, schema),
             syn::Fields::Unit => panic!("Unit struct is not supported."),
         },
         _ => abort_call_site!("Only struct is supprted currently"),
     }
 }
 
-fn entity_impl_table_init(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
+/// Implementation of `TableInit::schema_name` if `#[table_init(schema = "...")]` is specified
+fn impl_schema_name(schema: Option<String>) -> TokenStream2 {
+    match schema {
+        Some(schema) => quote! {
+            fn schema_name() -> Option<&'static str> {
+                Some(#schema)
+            }
+        },
+        None => quote! {},
+    }
+}
+
+/// Attribute `#[table_init(...)]`
+enum TableInitAttr {
+    /// `#[table_init(complex)]` marks a table for combined holders
+    Complex,
+    /// `#[table_init(schema = "schema_name")]` on the struct specifies the EXPRESS schema
+    Schema(String),
+}
+
+impl syn::parse::Parse for TableInitAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::parse::Result<Self> {
+        let ident: syn::Ident = input.parse()?;
+        match ident.to_string().as_str() {
+            "complex" => Ok(TableInitAttr::Complex),
+            "schema" => {
+                let _eq: syn::Token![=] = input.parse()?;
+                let name: syn::LitStr = input.parse()?;
+                Ok(TableInitAttr::Schema(name.value()))
+            }
+            _ => Err(syn::parse::Error::new(
+                ident.span(),
+                "expected `complex` or `schema`",
+            )),
+        }
+    }
+}
+
+fn table_init_attrs(attrs: &[syn::Attribute]) -> Vec<TableInitAttr> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("table_init"))
+        .map(|attr| {
+            attr.parse_args().unwrap_or_else(|_| {
+                abort_call_site!(
+                    "Expected `#[table_init(complex)]` or `#[table_init(schema = \"...\")]`"
+                )
+            })
+        })
+        .collect()
+}
+
+fn entity_impl_table_init(
+    ident: &syn::Ident,
+    st: &syn::DataStruct,
+    schema: Option<String>,
+) -> TokenStream2 {
     let mut table_names = Vec::new();
-    let mut entity_names = Vec::new();
+    let mut complex_table_names = Vec::new();
     for field in &st.fields {
         let ident = field.ident.as_ref().expect_or_abort("unreachable!");
-        let name = ident.to_string().to_screaming_snake_case();
-        table_names.push(ident);
-        entity_names.push(name);
+        let attrs = table_init_attrs(&field.attrs);
+        if attrs
+            .iter()
+            .any(|attr| matches!(attr, TableInitAttr::Complex))
+        {
+            complex_table_names.push(ident);
+        } else {
+            table_names.push(ident);
+        }
     }
-    assert_eq!(table_names.len(), entity_names.len());
+    let table_indices = (0..table_names.len()).map(syn::Index::from);
+    let table_indices2 = table_indices.clone();
+    let table_indices3 = table_indices.clone();
 
     let ruststep = ruststep_crate();
+    let schema_name = impl_schema_name(schema);
+
+    // Holders are required to be `Send` only when `rayon` feature is enabled,
+    // since checking it may exceed the recursion limit for large schemas.
+    let table_tasks = if cfg!(feature = "rayon") {
+        quote! {
+            fn table_tasks<'t, 'r: 't>(
+                &'t mut self,
+                records: &mut ::std::collections::HashMap<&'r str, ::std::vec::Vec<(u64, &'r #ruststep::ast::Record)>>,
+            ) -> ::std::vec::Vec<#ruststep::tables::TableTask<'t>> {
+                use #ruststep::tables::{holder_name, insert_records};
+                let Self { #(#table_names,)* .. } = self;
+                let mut tasks: ::std::vec::Vec<#ruststep::tables::TableTask<'t>> = ::std::vec::Vec::new();
+                #(
+                if let Some(records) = records.remove(holder_name(&*#table_names)) {
+                    tasks.push(::std::boxed::Box::new(move || insert_records(#table_names, records)));
+                }
+                )*
+                tasks
+            }
+        }
+    } else {
+        quote! {}
+    };
 
     quote! {
         #[automatically_derived]
         impl #ruststep::tables::TableInit for #ident {
-            fn append_data_section(
+            #schema_name
+
+            fn unknown_records(
+                &self,
+                data_sec: &#ruststep::ast::DataSection
+            ) -> #ruststep::error::Result<::std::vec::Vec<#ruststep::tables::UnknownRecord>> {
+                use #ruststep::{
+                    tables::{holder_name, matches_subsuper_record, matches_combined_record, UnknownRecord},
+                    ast::EntityInstance,
+                };
+                let tables: ::std::collections::HashMap<&'static str, usize> = [#(holder_name(&self.#table_names)),*]
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| (*name, index))
+                    .collect();
+                let mut unknowns = ::std::vec::Vec::new();
+                for entity in &data_sec.entities {
+                    let known = match entity {
+                        EntityInstance::Simple { record, .. } => tables.contains_key(record.name.as_str()),
+                        EntityInstance::Complex { subsuper, .. } => {
+                            let mut known = false;
+                            for component in subsuper {
+                                let name = component.name.as_str();
+                                known = match tables.get(name) {
+                                    #(
+                                    Some(#table_indices) => matches_subsuper_record(&self.#table_names, subsuper, name)?,
+                                    )*
+                                    _ => false,
+                                };
+                                if known {
+                                    break;
+                                }
+                            }
+                            #(
+                            if !known {
+                                known = matches_combined_record(&self.#complex_table_names, subsuper)?;
+                            }
+                            )*
+                            known
+                        }
+                    };
+                    if !known {
+                        unknowns.push(UnknownRecord::new(entity));
+                    }
+                }
+                Ok(unknowns)
+            }
+
+            fn append_data_section_lossy(
                 &mut self,
                 data_sec: &#ruststep::ast::DataSection
-            ) -> #ruststep::error::Result<()> {
-                use #ruststep::{error::Error, tables::insert_record, ast::EntityInstance};
+            ) -> #ruststep::error::Result<::std::vec::Vec<#ruststep::tables::UnknownRecord>> {
+                use #ruststep::{
+                    tables::{holder_name, check_duplicated_ids, insert_record, insert_subsuper_record, insert_combined_record, IdMap, TableInit},
+                    ast::EntityInstance,
+                };
+                // Ids must be unique over all tables, including ones appended before
+                check_duplicated_ids(data_sec, |id| {
+                    false #(|| IdMap::get(&self.#table_names, id).is_some())* #(|| IdMap::get(&self.#complex_table_names, id).is_some())*
+                })?;
+                let unknowns = <Self as TableInit>::unknown_records(self, data_sec)?;
+                let skipped: ::std::collections::HashSet<u64> = unknowns.iter().map(|unknown| unknown.id).collect();
+                // Records are dispatched by `Holder::name` of each table
+                let tables: ::std::collections::HashMap<&'static str, usize> = [#(holder_name(&self.#table_names)),*]
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| (*name, index))
+                    .collect();
                 for entity in &data_sec.entities {
+                    if skipped.contains(&entity.id()) {
+                        continue;
+                    }
                     match entity {
-                        EntityInstance::Simple { id, record } => match record.name.as_str() {
+                        EntityInstance::Simple { id, record } => match tables.get(record.name.as_str()) {
                             #(
-                            #entity_names => insert_record(&mut self.#table_names, *id, record)?,
+                            Some(#table_indices2) => insert_record(&mut self.#table_names, *id, record)?,
                             )*
-                            _ => {
-                                return Err(Error::UnknownEntityName {
-                                    entity_name: record.name.clone(),
-                                    schema: "".to_string(),
-                                });
-                            }
+                            // Unknown records are skipped above
+                            _ => {}
                         },
-                        EntityInstance::Complex { .. } => {
-                            unimplemented!("Complex entity is not supported")
+                        EntityInstance::Complex { id, subsuper } => {
+                            // Insert into the table of the most derived component
+                            let mut inserted = false;
+                            for component in subsuper {
+                                let name = component.name.as_str();
+                                inserted = match tables.get(name) {
+                                    #(
+                                    Some(#table_indices3) => insert_subsuper_record(&mut self.#table_names, *id, subsuper, name)?,
+                                    )*
+                                    _ => false,
+                                };
+                                if inserted {
+                                    break;
+                                }
+                            }
+                            // Insert into the table of combined holder
+                            #(
+                            if !inserted {
+                                inserted = insert_combined_record(&mut self.#complex_table_names, *id, subsuper)?;
+                            }
+                            )*
+                            debug_assert!(inserted, "Unknown records are skipped above");
                         }
                     }
                 }
-                Ok(())
+                Ok(unknowns)
+            }
+
+            #table_tasks
+        }
+
+        #[automatically_derived]
+        impl #ruststep::tables::EntityGraph for #ident {
+            fn references(&self) -> ::std::collections::HashMap<u64, ::std::vec::Vec<u64>> {
+                let mut references = ::std::collections::HashMap::new();
+                #( #ruststep::tables::collect_references(&self.#table_names, &mut references); )*
+                #( #ruststep::tables::collect_references(&self.#complex_table_names, &mut references); )*
+                references
+            }
+
+            fn retain_ids(&mut self, ids: &::std::collections::HashSet<u64>) -> usize {
+                0 #( + #ruststep::tables::retain_ids(&mut self.#table_names, ids) )*
+                  #( + #ruststep::tables::retain_ids(&mut self.#complex_table_names, ids) )*
+            }
+
+            fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+                #( #ruststep::tables::remap_refs(&mut self.#table_names, map); )*
+                #( #ruststep::tables::remap_refs(&mut self.#complex_table_names, map); )*
+            }
+
+            fn type_of(&self, id: u64) -> ::std::option::Option<&'static str> {
+                ::std::option::Option::None
+                    #( .or_else(|| #ruststep::tables::type_of(&self.#table_names, id)) )*
+                    #( .or_else(|| #ruststep::tables::type_of(&self.#complex_table_names, id)) )*
+            }
+
+            fn validate(&self) -> ::std::vec::Vec<(u64, #ruststep::error::Error)> {
+                let mut errors = ::std::vec::Vec::new();
+                #( #ruststep::tables::validate(&self.#table_names, &mut errors); )*
+                #( #ruststep::tables::validate(&self.#complex_table_names, &mut errors); )*
+                errors.sort_by_key(|(id, _err)| *id);
+                errors
             }
         }
 
@@ -64,15 +276,17 @@ fn entity_impl_table_init(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStre
         impl ::std::str::FromStr for #ident {
             type Err = #ruststep::error::Error;
             fn from_str(input: &str) -> #ruststep::error::Result<Self> {
-                use #ruststep::{tables::TableInit, ast::DataSection};
-                let data_sec = DataSection::from_str(input)?;
-                Ok(Self::from_data_section(&data_sec)?)
+                #ruststep::tables::tables_from_str(input)
             }
         }
     }
 }
 
-fn tuple_impl_table_init(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
+fn tuple_impl_table_init(
+    ident: &syn::Ident,
+    st: &syn::DataStruct,
+    schema: Option<String>,
+) -> TokenStream2 {
     let mut table_names = Vec::new();
     let mut entity_names = Vec::new();
     for field in &st.fields {
@@ -84,34 +298,91 @@ fn tuple_impl_table_init(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStrea
     assert_eq!(table_names.len(), entity_names.len());
 
     let ruststep = ruststep_crate();
+    let schema_name = impl_schema_name(schema);
 
     quote! {
         #[automatically_derived]
         impl #ruststep::tables::TableInit for #ident {
-            fn append_data_section(
+            #schema_name
+
+            fn unknown_records(
+                &self,
+                data_sec: &#ruststep::ast::DataSection
+            ) -> #ruststep::error::Result<::std::vec::Vec<#ruststep::tables::UnknownRecord>> {
+                use #ruststep::{
+                    tables::{matches_subsuper_record, UnknownRecord},
+                    ast::EntityInstance,
+                };
+                let mut unknowns = ::std::vec::Vec::new();
+                for entity in &data_sec.entities {
+                    let known = match entity {
+                        EntityInstance::Simple { record, .. } => match record.name.as_str() {
+                            #( #entity_names => true, )*
+                            _ => false,
+                        },
+                        EntityInstance::Complex { subsuper, .. } => {
+                            let mut known = false;
+                            for component in subsuper {
+                                known = match component.name.as_str() {
+                                    #(
+                                    #entity_names => matches_subsuper_record(&self.#table_names, subsuper, #entity_names)?,
+                                    )*
+                                    _ => false,
+                                };
+                                if known {
+                                    break;
+                                }
+                            }
+                            known
+                        }
+                    };
+                    if !known {
+                        unknowns.push(UnknownRecord::new(entity));
+                    }
+                }
+                Ok(unknowns)
+            }
+
+            fn append_data_section_lossy(
                 &mut self,
                 data_sec: &#ruststep::ast::DataSection
-            ) -> #ruststep::error::Result<()> {
-                use #ruststep::{error::Error, tables::insert_record, ast::EntityInstance};
+            ) -> #ruststep::error::Result<::std::vec::Vec<#ruststep::tables::UnknownRecord>> {
+                use #ruststep::{
+                    tables::{check_duplicated_ids, insert_record, insert_subsuper_record, TableInit},
+                    ast::EntityInstance,
+                };
+                check_duplicated_ids(data_sec, |_| false)?;
+                let unknowns = <Self as TableInit>::unknown_records(self, data_sec)?;
+                let skipped: ::std::collections::HashSet<u64> = unknowns.iter().map(|unknown| unknown.id).collect();
                 for entity in &data_sec.entities {
+                    if skipped.contains(&entity.id()) {
+                        continue;
+                    }
                     match entity {
                         EntityInstance::Simple { id, record } => match record.name.as_str() {
                             #(
                             #entity_names => insert_record(&mut self.#table_names, *id, record)?,
                             )*
-                            _ => {
-                                return Err(Error::UnknownEntityName {
-                                    entity_name: record.name.clone(),
-                                    schema: "".to_string(),
-                                });
-                            }
+                            // Unknown records are skipped above
+                            _ => {}
                         },
-                        EntityInstance::Complex { .. } => {
-                            unimplemented!("Complex entity is not supported")
+                        EntityInstance::Complex { id, subsuper } => {
+                            // Insert into the table of the most derived component
+                            for component in subsuper {
+                                let inserted = match component.name.as_str() {
+                                    #(
+                                    #entity_names => insert_subsuper_record(&mut self.#table_names, *id, subsuper, #entity_names)?,
+                                    )*
+                                    _ => false,
+                                };
+                                if inserted {
+                                    break;
+                                }
+                            }
                         }
                     }
                 }
-                Ok(())
+                Ok(unknowns)
             }
         }
 
@@ -119,9 +390,7 @@ fn tuple_impl_table_init(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStrea
         impl ::std::str::FromStr for #ident {
             type Err = #ruststep::error::Error;
             fn from_str(input: &str) -> #ruststep::error::Result<Self> {
-                use #ruststep::{tables::TableInit, ast::DataSection};
-                let data_sec = DataSection::from_str(input)?;
-                Ok(Self::from_data_section(&data_sec)?)
+                #ruststep::tables::tables_from_str(input)
             }
         }
     }
@@ -1,4 +1,3 @@
-use inflector::Inflector;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 use std::convert::*;
@@ -6,7 +5,7 @@ use std::convert::*;
 use super::*;
 
 pub fn derive_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr) -> TokenStream2 {
-    let name = ident.to_string().to_screaming_snake_case();
+    let name = attr.name(ident);
     let holder_ident = as_holder_ident(ident);
     let def_holder_tt = def_holder(ident, st);
     let impl_holder_tt = impl_holder(ident, attr, st);
@@ -32,6 +31,36 @@ pub fn derive_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr
     }
 }
 
+pub fn derive_serialize(
+    ident: &syn::Ident,
+    st: &syn::DataStruct,
+    attr: &HolderAttr,
+) -> TokenStream2 {
+    let name = attr.name(ident);
+    let len = st.fields.len();
+    let indices: Vec<_> = (0..len).map(syn::Index::from).collect();
+    let serde = serde_crate();
+    serialize_idents(ident, attr)
+        .into_iter()
+        .map(|ident| {
+            quote! {
+                #[automatically_derived]
+                impl #serde::ser::Serialize for #ident {
+                    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+                    where
+                        S: #serde::ser::Serializer,
+                    {
+                        use #serde::ser::SerializeTupleStruct;
+                        let mut st = serializer.serialize_tuple_struct(#name, #len)?;
+                        #( st.serialize_field(&self.#indices)?; )*
+                        st.end()
+                    }
+                }
+            } // quote!
+        })
+        .collect()
+}
+
 pub fn def_holder(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
     let holder_ident = as_holder_ident(ident);
     let FieldEntries { holder_types, .. } = FieldEntries::parse(st);
@@ -43,15 +72,16 @@ pub fn def_holder(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
 }
 
 pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct) -> TokenStream2 {
-    let name = ident.to_string().to_screaming_snake_case();
+    let name = table.name(ident);
     let holder_ident = as_holder_ident(ident);
     let FieldEntries {
         holder_types,
         into_owned,
+        place_holder_indices,
     } = FieldEntries::parse(st);
     let HolderAttr { table, .. } = table;
     let tuple_len = holder_types.len();
-    let table_arg = table_arg();
+    let resolver_arg = resolver_arg();
     let ruststep = ruststep_crate();
 
     quote! {
@@ -59,7 +89,7 @@ pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct)
         impl #ruststep::tables::IntoOwned for #holder_ident {
             type Table = #table;
             type Owned = #ident;
-            fn into_owned(self, #table_arg: &Self::Table) -> #ruststep::error::Result<Self::Owned> {
+            fn into_owned_with(self, #resolver_arg: &mut #ruststep::tables::Resolver<'_, Self::Table>) -> #ruststep::error::Result<Self::Owned> {
                 Ok(#ident ( #(#into_owned),* ))
             }
         }
@@ -72,6 +102,15 @@ pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct)
                 #tuple_len
             }
         }
+        #[automatically_derived]
+        impl #ruststep::tables::VisitRefs for #holder_ident {
+            fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+                #( #ruststep::tables::VisitRefs::visit_refs(&self.#place_holder_indices, visitor); )*
+            }
+            fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+                #( #ruststep::tables::VisitRefs::remap_refs(&mut self.#place_holder_indices, map); )*
+            }
+        }
     } // quote!
 }
 
@@ -86,6 +125,12 @@ pub fn impl_entity_table(ident: &syn::Ident, table: &HolderAttr) -> TokenStream2
             fn get_owned(&self, entity_id: u64) -> #ruststep::error::Result<#ident> {
                 #ruststep::tables::get_owned(self, &self.#field, entity_id)
             }
+            fn contains(&self, entity_id: u64) -> bool {
+                #ruststep::tables::IdMap::get(&self.#field, entity_id).is_some()
+            }
+            fn get_holder(&self, entity_id: u64) -> Option<::std::borrow::Cow<'_, #holder_ident>> {
+                #ruststep::tables::IdMap::get(&self.#field, entity_id).map(::std::borrow::Cow::Borrowed)
+            }
             fn owned_iter<'table>(&'table self) -> Box<dyn Iterator<Item = #ruststep::error::Result<#ident>> + 'table> {
                 #ruststep::tables::owned_iter(self, &self.#field)
             }
@@ -103,6 +148,37 @@ fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStr
         .map(|i| format_ident!("a_{}", i))
         .collect::<Vec<_>>();
     let serde = serde_crate();
+    // A newtype is deserialized transparently from a bare parameter, e.g. `1.0` for `TYPE length = REAL;`,
+    // by forwarding it to the inner type.
+    let forward = if attr_len == 1 {
+        quote! {
+            fn visit_i64<E>(self, value: i64) -> ::std::result::Result<Self::Value, E>
+            where
+                E: #serde::de::Error,
+            {
+                use #serde::de::IntoDeserializer;
+                Ok(#ident(#serde::de::Deserialize::deserialize(value.into_deserializer())?))
+            }
+
+            fn visit_f64<E>(self, value: f64) -> ::std::result::Result<Self::Value, E>
+            where
+                E: #serde::de::Error,
+            {
+                use #serde::de::IntoDeserializer;
+                Ok(#ident(#serde::de::Deserialize::deserialize(value.into_deserializer())?))
+            }
+
+            fn visit_str<E>(self, value: &str) -> ::std::result::Result<Self::Value, E>
+            where
+                E: #serde::de::Error,
+            {
+                use #serde::de::IntoDeserializer;
+                Ok(#ident(#serde::de::Deserialize::deserialize(value.into_deserializer())?))
+            }
+        }
+    } else {
+        quote! {}
+    };
     quote! {
         #[doc(hidden)]
         pub struct #visitor_ident;
@@ -143,6 +219,8 @@ fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStr
                 let value = map.next_value()?; // send to Self::visit_seq
                 Ok(value)
             }
+
+            #forward
         }
     } // quote!
 }
@@ -187,14 +265,17 @@ fn impl_with_visitor(ident: &syn::Ident) -> TokenStream2 {
 struct FieldEntries {
     holder_types: Vec<syn::Type>,
     into_owned: Vec<TokenStream2>,
+    /// Indices of fields using place holder, which may contain references
+    place_holder_indices: Vec<syn::Index>,
 }
 
 impl FieldEntries {
     fn parse(st: &syn::DataStruct) -> Self {
-        let table_arg = table_arg();
+        let resolver_arg = resolver_arg();
 
         let mut holder_types = Vec::new();
         let mut into_owned = Vec::new();
+        let mut place_holder_indices = Vec::new();
 
         for (i, field) in st.fields.iter().enumerate() {
             let ft: FieldType = field.ty.clone().try_into().unwrap();
@@ -202,19 +283,17 @@ impl FieldEntries {
 
             let HolderAttr { place_holder, .. } = HolderAttr::parse(&field.attrs);
             if place_holder {
+                place_holder_indices.push(index.clone());
                 match &ft {
-                    FieldType::Path(_) => {
-                        into_owned.push(quote! { self.#index.into_owned(#table_arg)? });
-                    }
                     FieldType::Optional(_) => {
-                        into_owned.push(quote! { self.#index.map(|holder| holder.into_owned(#table_arg)).transpose()? },);
+                        into_owned.push(quote! { self.#index.map(|holder| holder.into_owned_with(#resolver_arg)).transpose()? },);
+                    }
+                    FieldType::Path(_)
+                    | FieldType::List(_)
+                    | FieldType::Array(..)
+                    | FieldType::Shared(_) => {
+                        into_owned.push(quote! { self.#index.into_owned_with(#resolver_arg)? });
                     }
-                    FieldType::List(_) => into_owned.push(quote! {
-                        self.#index
-                            .into_iter()
-                            .map(|v| v.into_owned(#table_arg))
-                            .collect::<::std::result::Result<Vec<_>, _>>()?
-                    }),
                     FieldType::Boxed(_) => abort_call_site!("Unexpected Box<T>"),
                 }
                 holder_types.push(ft.into_holder().into_place_holder().into());
@@ -226,11 +305,12 @@ impl FieldEntries {
         FieldEntries {
             holder_types,
             into_owned,
+            place_holder_indices,
         }
     }
 }
 
 /// This must be same between codegens
-fn table_arg() -> syn::Ident {
-    syn::Ident::new("table", Span::call_site())
+fn resolver_arg() -> syn::Ident {
+    syn::Ident::new("resolver", Span::call_site())
 }
@@ -3,6 +3,7 @@ pub mod explicit_draughting {
     use crate::{as_holder, derive_more::*, primitive::*, Holder, TableInit};
     use std::collections::HashMap;
     #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+    #[table_init(schema = "explicit_draughting")]
     pub struct Tables {
         address: HashMap<u64, as_holder!(Address)>,
         angular_dimension: HashMap<u64, as_holder!(AngularDimension)>,
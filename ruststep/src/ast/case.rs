@@ -0,0 +1,74 @@
+//! Case conversion of identifiers used without `std` feature
+//!
+//! This is a subset of [Inflector](https://docs.rs/Inflector) crate
+//! enough for keywords and enumerations in exchange structure, e.g. `CURVE_2D`,
+//! since it depends on `std`.
+
+use crate::prelude::*;
+
+/// Same method names as `inflector::Inflector` to switch by `std` feature
+pub trait Inflector {
+    /// `CARTESIAN_POINT` to `CartesianPoint`, and `CURVE_2D` to `Curve2D`
+    fn to_pascal_case(&self) -> String;
+    /// `CartesianPoint` to `CARTESIAN_POINT`, and `Curve2d` to `CURVE_2D`
+    fn to_screaming_snake_case(&self) -> String;
+}
+
+impl Inflector for str {
+    fn to_pascal_case(&self) -> String {
+        let mut out = String::with_capacity(self.len());
+        let mut prev: Option<char> = None;
+        for c in self.chars() {
+            if c != '_' {
+                match prev {
+                    None | Some('_') => out.push(c.to_ascii_uppercase()),
+                    Some(p) if p.is_ascii_digit() => out.push(c.to_ascii_uppercase()),
+                    _ => out.push(c.to_ascii_lowercase()),
+                }
+            }
+            prev = Some(c);
+        }
+        out
+    }
+
+    fn to_screaming_snake_case(&self) -> String {
+        let mut out = String::with_capacity(self.len() + 4);
+        let mut prev: Option<char> = None;
+        for c in self.chars() {
+            if let Some(p) = prev {
+                let boundary = (c.is_ascii_uppercase() && p.is_ascii_lowercase())
+                    || (c.is_ascii_digit() && p.is_ascii_alphabetic());
+                if boundary {
+                    out.push('_');
+                }
+            }
+            out.push(c.to_ascii_uppercase());
+            prev = Some(c);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Inflector;
+
+    #[test]
+    fn pascal_case() {
+        assert_eq!("CARTESIAN_POINT".to_pascal_case(), "CartesianPoint");
+        assert_eq!("CURVE_2D".to_pascal_case(), "Curve2D");
+        assert_eq!("TRUE".to_pascal_case(), "True");
+        assert_eq!("A_B".to_pascal_case(), "AB");
+    }
+
+    #[test]
+    fn screaming_snake_case() {
+        assert_eq!(
+            "CartesianPoint".to_screaming_snake_case(),
+            "CARTESIAN_POINT"
+        );
+        assert_eq!("Curve2d".to_screaming_snake_case(), "CURVE_2D");
+        assert_eq!("True".to_screaming_snake_case(), "TRUE");
+        assert_eq!("CURVE_2D".to_screaming_snake_case(), "CURVE_2D");
+    }
+}
@@ -1,11 +1,37 @@
 use super::*;
+#[cfg(not(feature = "std"))]
+use crate::ast::case::Inflector;
 use crate::ast::*;
+#[cfg(feature = "std")]
 use inflector::Inflector;
 use serde::{
     de::{self, IntoDeserializer},
     forward_to_deserialize_any,
 };
 
+/// Implement `deserialize_*` for integer types, which accepts `REAL` only if it is a whole number
+macro_rules! deserialize_integer {
+    ($($method:ident)*) => {
+        $(
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            match self {
+                Parameter::Real(val) => match self.as_integer() {
+                    Some(val) => visitor.visit_i64(val),
+                    None => Err(de::Error::invalid_type(
+                        de::Unexpected::Float(*val),
+                        &"integer or whole number real",
+                    )),
+                },
+                _ => self.deserialize_any(visitor),
+            }
+        }
+        )*
+    };
+}
+
 impl<'de, 'param> de::Deserializer<'de> for &'param Parameter {
     type Error = crate::error::Error;
 
@@ -57,10 +83,129 @@ impl<'de, 'param> de::Deserializer<'de> for &'param Parameter {
         }
     }
 
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            // `2` may appear where `REAL` is expected
+            Parameter::Integer(val) => visitor.visit_f64(*val as f64),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_f64(visitor)
+    }
+
+    /// List is deserialized into a struct positionally,
+    /// and the length of list must be equal to the number of fields
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Parameter::List(params) => {
+                check_arity(name, fields, params)?;
+                visitor.visit_map(RecordStructDeserializer::new(fields, params))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /// Fixed-size arrays, e.g. `[f64; 3]` for `ARRAY [1:3] OF REAL`,
+    /// are deserialized as tuples, and the length of list must match exactly
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Parameter::List(params) if params.len() != len => {
+                Err(de::Error::invalid_length(params.len(), &visitor))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /// Enumeration value, e.g. `.RED.`, is checked against the variants of the enum,
+    /// and [crate::error::Error::UnknownEnumVariant] is returned if no variant matches
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Parameter::Enumeration(variant) => {
+                let variant_pascal = variant.to_pascal_case();
+                if !variants.contains(&variant_pascal.as_str()) {
+                    return Err(crate::error::Error::UnknownEnumVariant {
+                        type_name: name,
+                        value: variant.clone(),
+                        expected: variants,
+                    });
+                }
+                visitor.visit_enum(variant_pascal.into_deserializer())
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /// Reference to an entity instance, e.g. `#12`, is also deserialized into its id
+    /// so that `LIST OF entity` can be read as `Vec<u64>`
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Parameter::Ref(Name::Entity(id)) => visitor.visit_u64(*id),
+            Parameter::Real(val) => match self.as_integer() {
+                Some(val) => visitor.visit_i64(val),
+                None => Err(de::Error::invalid_type(
+                    de::Unexpected::Float(*val),
+                    &"integer, whole number real, or entity reference",
+                )),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /// `*` is read as unit only for an attribute derived in subtypes, see [DERIVED_TOKEN]
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Parameter::Omitted if name == DERIVED_TOKEN => visitor.visit_unit(),
+            _ if name == DERIVED_TOKEN => visitor.visit_newtype_struct(self),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_integer! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u128
+    }
+
     forward_to_deserialize_any! {
-        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf unit unit_struct newtype_struct seq tuple
-        struct tuple_struct map enum identifier ignored_any
+        char str string
+        bytes byte_buf unit unit_struct seq
+        tuple_struct map identifier ignored_any
     }
 }
 
@@ -25,6 +25,7 @@ impl<'de, 'record> de::Deserializer<'de> for &'record Record {
     {
         if name == self.name {
             if let Parameter::List(ref parameters) = self.parameter {
+                check_arity(name, fields, parameters)?;
                 return visitor.visit_map(RecordStructDeserializer::new(fields, parameters));
             }
         }
@@ -97,7 +98,28 @@ impl<'de, 'record> de::MapAccess<'de> for RecordDeserializer<'record> {
     }
 }
 
-/// Deserialize [Record] as a struct
+/// Check the number of parameters is equal to the number of fields of struct `name`
+///
+/// Parameters are mapped to fields positionally,
+/// i.e. they must be in the order of attributes in EXPRESS schema.
+pub(crate) fn check_arity(
+    name: &str,
+    fields: &[&str],
+    parameters: &[Parameter],
+) -> Result<(), crate::error::Error> {
+    if parameters.len() == fields.len() {
+        Ok(())
+    } else {
+        let expected = format!("{} parameters for {}", fields.len(), name);
+        Err(de::Error::invalid_length(
+            parameters.len(),
+            &expected.as_str(),
+        ))
+    }
+}
+
+/// Deserialize [Record] or [Parameter::List] as a struct,
+/// whose fields are taken from the parameters by their position
 #[derive(Debug)]
 pub struct RecordStructDeserializer<'a> {
     cursor: usize,
@@ -1,4 +1,5 @@
 use crate::ast::*;
+use core::cell::Cell;
 use serde::{
     de::{self, IntoDeserializer},
     forward_to_deserialize_any,
@@ -65,3 +66,351 @@ impl<'de, 'record> de::MapAccess<'de> for SubSuperRecordDeserializer<'record> {
         }
     }
 }
+
+impl SubSuperRecord {
+    /// Deserialize complex entity instance as the component `name`
+    ///
+    /// Supertype fields of the holder are filled by other components,
+    /// i.e. `(BASE(1.0) SUB(2.0))` is deserialized as `SUB`
+    /// in the same manner as `SUB(BASE((1.0)), 2.0)`.
+    /// Returns `Ok(None)` if `name` is not a component,
+    /// or if some components are not consumed as its supertypes.
+    pub fn deserialize_component<'de, T: de::Deserialize<'de>>(
+        &self,
+        name: &str,
+    ) -> crate::error::Result<Option<T>> {
+        let index = match self.0.iter().position(|record| record.name == name) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let components = Components::new(&self.0);
+        components.used[index].set(true);
+        let value = T::deserialize(ComponentDeserializer {
+            components: &components,
+            index,
+        })?;
+        if components.used.iter().all(|used| used.get()) {
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Deserialize complex entity instance as a combination of components,
+    /// e.g. `(PERSON('Nizika Iziti') EMPLOYEE(15) STUDENT('Simokitazawa'))` into
+    /// a holder whose fields are `EMPLOYEE` and `STUDENT` in this order.
+    ///
+    /// Each field is deserialized by [SubSuperRecord::deserialize_component],
+    /// and thus supertypes, e.g. `PERSON`, can be shared among fields.
+    /// Returns `Ok(None)` if a field does not match any component,
+    /// or if some components are not consumed by any fields.
+    pub fn deserialize_combined<'de, T: de::Deserialize<'de>>(
+        &self,
+    ) -> crate::error::Result<Option<T>> {
+        let combined = Combined {
+            records: &self.0,
+            used: self.0.iter().map(|_| Cell::new(false)).collect(),
+            missing: Cell::new(false),
+        };
+        match T::deserialize(&combined) {
+            Ok(value) => {
+                if combined.used.iter().all(|used| used.get()) {
+                    Ok(Some(value))
+                } else {
+                    Ok(None)
+                }
+            }
+            Err(_) if combined.missing.get() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// State for [SubSuperRecord::deserialize_combined]
+struct Combined<'record> {
+    records: &'record [Record],
+    /// Whether components are consumed by some fields
+    used: Vec<Cell<bool>>,
+    /// Set if some field does not match any component
+    missing: Cell<bool>,
+}
+
+impl<'de, 'a, 'record> de::Deserializer<'de> for &'a Combined<'record> {
+    type Error = crate::error::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(CombinedSeqAccess { combined: self })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        struct tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct CombinedSeqAccess<'a, 'record> {
+    combined: &'a Combined<'record>,
+}
+
+impl<'de, 'a, 'record> de::SeqAccess<'de> for CombinedSeqAccess<'a, 'record> {
+    type Error = crate::error::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(CombinedElementDeserializer {
+            combined: self.combined,
+        })
+        .map(Some)
+    }
+}
+
+/// Deserializer for a field of combined holder, which must be a component
+struct CombinedElementDeserializer<'a, 'record> {
+    combined: &'a Combined<'record>,
+}
+
+impl<'de, 'a, 'record> de::Deserializer<'de> for CombinedElementDeserializer<'a, 'record> {
+    type Error = crate::error::Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.combined.missing.set(true);
+        Err(de::Error::custom(
+            "Field of combined entity must be a component",
+        ))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let records = self.combined.records;
+        let index = match records.iter().position(|record| record.name == name) {
+            Some(index) => index,
+            None => {
+                self.combined.missing.set(true);
+                return Err(de::Error::custom(format!(
+                    "Component '{}' does not exist",
+                    name
+                )));
+            }
+        };
+        // Supertypes can be shared among fields
+        let components = Components::new(records);
+        components.used[index].set(true);
+        let value = ComponentDeserializer {
+            components: &components,
+            index,
+        }
+        .deserialize_tuple_struct(name, len, visitor)?;
+        for (total, used) in self.combined.used.iter().zip(&components.used) {
+            if used.get() {
+                total.set(true);
+            }
+        }
+        Ok(value)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        struct map enum identifier ignored_any
+    }
+}
+
+/// Components of complex entity instance and flags whether they are already consumed
+struct Components<'record> {
+    records: &'record [Record],
+    used: Vec<Cell<bool>>,
+}
+
+impl<'record> Components<'record> {
+    fn new(records: &'record [Record]) -> Self {
+        Components {
+            records,
+            used: records.iter().map(|_| Cell::new(false)).collect(),
+        }
+    }
+
+    /// Take a component not consumed yet, or a consumed one
+    /// since a supertype is shared by several supertypes in multiple inheritance,
+    /// e.g. `SHAPE` in `(COLORED_SHAPE(..) SHAPE(..) STYLED_SHAPE(..) TEXTURED_SHAPE(..))`
+    /// is a supertype of both `COLORED_SHAPE` and `TEXTURED_SHAPE`.
+    fn take(&self, name: &str) -> Option<usize> {
+        let index = self
+            .records
+            .iter()
+            .zip(&self.used)
+            .position(|(record, used)| !used.get() && record.name == name)
+            .or_else(|| self.records.iter().position(|record| record.name == name))?;
+        self.used[index].set(true);
+        Some(index)
+    }
+}
+
+/// Deserializer for a component in complex entity instance
+struct ComponentDeserializer<'a, 'record> {
+    components: &'a Components<'record>,
+    index: usize,
+}
+
+impl<'de, 'a, 'record> de::Deserializer<'de> for ComponentDeserializer<'a, 'record> {
+    type Error = crate::error::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.components.records[self.index].deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let record = &self.components.records[self.index];
+        let parameters = match &record.parameter {
+            Parameter::List(parameters) => parameters,
+            _ => return record.deserialize_any(visitor),
+        };
+        let cursor = Cell::new(0);
+        let value = visitor.visit_seq(ComponentSeqAccess {
+            components: self.components,
+            parameters,
+            cursor: &cursor,
+        })?;
+        if cursor.get() != parameters.len() {
+            return Err(de::Error::custom(format!(
+                "{} parameters are remaining in '{}'",
+                parameters.len() - cursor.get(),
+                record.name
+            )));
+        }
+        Ok(value)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        struct map enum identifier ignored_any
+    }
+}
+
+/// Supertypes in other components come first, and then own parameters of the component
+///
+/// Every element is looked up in other components until the first own parameter is consumed,
+/// and thus all supertypes in multiple inheritance are filled by components.
+struct ComponentSeqAccess<'a, 'record> {
+    components: &'a Components<'record>,
+    parameters: &'record [Parameter],
+    cursor: &'a Cell<usize>,
+}
+
+impl<'de, 'a, 'record> de::SeqAccess<'de> for ComponentSeqAccess<'a, 'record> {
+    type Error = crate::error::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.cursor.get() == 0 {
+            seed.deserialize(ElementDeserializer {
+                components: self.components,
+                parameters: self.parameters,
+                cursor: self.cursor,
+            })
+            .map(Some)
+        } else {
+            seed.deserialize(next_parameter(self.parameters, self.cursor)?)
+                .map(Some)
+        }
+    }
+}
+
+fn next_parameter<'record>(
+    parameters: &'record [Parameter],
+    cursor: &Cell<usize>,
+) -> crate::error::Result<&'record Parameter> {
+    let parameter = parameters.get(cursor.get()).ok_or_else(|| {
+        <crate::error::Error as de::Error>::custom("Too few parameters in component")
+    })?;
+    cursor.set(cursor.get() + 1);
+    Ok(parameter)
+}
+
+/// Deserializer for an element which may be a supertype in another component
+struct ElementDeserializer<'a, 'record> {
+    components: &'a Components<'record>,
+    parameters: &'record [Parameter],
+    cursor: &'a Cell<usize>,
+}
+
+impl<'de, 'a, 'record> de::Deserializer<'de> for ElementDeserializer<'a, 'record> {
+    type Error = crate::error::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        next_parameter(self.parameters, self.cursor)?.deserialize_any(visitor)
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        next_parameter(self.parameters, self.cursor)?.deserialize_bool(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        next_parameter(self.parameters, self.cursor)?.deserialize_option(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Some(index) = self.components.take(name) {
+            ComponentDeserializer {
+                components: self.components,
+                index,
+            }
+            .deserialize_tuple_struct(name, len, visitor)
+        } else {
+            next_parameter(self.parameters, self.cursor)?
+                .deserialize_tuple_struct(name, len, visitor)
+        }
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        struct map enum identifier ignored_any
+    }
+}
@@ -0,0 +1,422 @@
+//! Write AST into exchange structure
+//!
+//! [std::fmt::Display] is implemented so that its output can be parsed back
+//! by the corresponding parser in [crate::parser]:
+//!
+//! ```
+//! use ruststep::ast::*;
+//! use std::str::FromStr;
+//!
+//! let p = Parameter::from_str("A((1, 2.0, 'It''s', .T., $, *, #3))").unwrap();
+//! assert_eq!(p.to_string(), "A((1, 2., 'It''s', .T., $, *, #3))");
+//! assert_eq!(Parameter::from_str(&p.to_string()).unwrap(), p);
+//! ```
+//!
+//! Strings are written with control directives in ISO-10303-21 7.3.3,
+//! i.e. apostrophe and reverse solidus are doubled, and other characters out of
+//! the basic alphabet are encoded, e.g. `α` into `\X2\03B1\X0\`.
+//! They are decoded by the parser if [crate::parser::ParseOptions::decode_string_escapes] is set.
+//!
+//! Non-finite `REAL` values, i.e. NaN and infinity, cannot be written in exchange structure.
+//! [fmt::Display] writes them as `{:?}` does, e.g. `NaN`, which cannot be read back,
+//! and [to_step_string] returns an error for them instead.
+
+use crate::{ast::*, error::*, prelude::*};
+use core::fmt::{self, Write};
+use itertools::Itertools;
+
+/// Write `value` into [String] in exchange structure notation
+///
+/// ```
+/// use ruststep::{ast::*, error::Error};
+///
+/// assert_eq!(to_step_string(&Parameter::Real(1.0)).unwrap(), "1.");
+/// assert!(matches!(
+///     to_step_string(&Parameter::Real(f64::NAN)),
+///     Err(Error::NonFiniteReal)
+/// ));
+/// ```
+///
+/// Errors
+/// -------
+/// - [Error::NonFiniteReal] if `value` contains NaN or infinity as a `REAL` value
+///
+pub fn to_step_string(value: &impl fmt::Display) -> Result<String> {
+    let mut buf = String::new();
+    // The alternate flag makes `write_real` fail for non-finite values
+    write!(buf, "{:#}", value).map_err(|_| Error::NonFiniteReal)?;
+    Ok(buf)
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Name::Entity(id) => write!(f, "#{}", id),
+            Name::Value(id) => write!(f, "@{}", id),
+            Name::ConstantEntity(name) => write!(f, "#{}", name),
+            Name::ConstantValue(name) => write!(f, "@{}", name),
+        }
+    }
+}
+
+/// Write `REAL` value in the form `[sign] digit {digit} . {digit} [E [sign] digit {digit}]`
+///
+/// The shortest representation which round-trips is taken from `{:?}`,
+/// and then a decimal point is always inserted so that `1.0` is written as `1.`
+/// instead of being read back as an integer. Exponent is written by upper case `E`.
+///
+/// Non-finite values cannot be represented in exchange structure,
+/// and they are written as `{:?}` does, or [fmt::Error] is returned with the alternate flag `{:#}`.
+/// The formatter is passed through nested values so that [to_step_string] detects them.
+fn write_real(f: &mut fmt::Formatter, val: f64) -> fmt::Result {
+    if !val.is_finite() {
+        if f.alternate() {
+            return Err(fmt::Error);
+        }
+        return write!(f, "{:?}", val);
+    }
+    let repr = format!("{:?}", val);
+    let (mantissa, exponent) = match repr.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (repr.as_str(), None),
+    };
+    let mantissa = mantissa.strip_suffix(".0").unwrap_or(mantissa);
+    write!(f, "{}", mantissa)?;
+    if !mantissa.contains('.') {
+        write!(f, ".")?;
+    }
+    if let Some(exponent) = exponent {
+        write!(f, "E{}", exponent)?;
+    }
+    Ok(())
+}
+
+/// Control directive encoding a character, see [write_string]
+#[derive(Clone, Copy, PartialEq)]
+enum Directive {
+    /// Written as is, or doubled for apostrophe and reverse solidus
+    None,
+    /// `\X\hh` for U+0000 to U+00FF
+    X,
+    /// `\X2\hhhh...\X0\` for the basic multilingual plane
+    X2,
+    /// `\X4\hhhhhhhh...\X0\` for other planes
+    X4,
+}
+
+impl Directive {
+    fn new(c: char) -> Self {
+        match c {
+            ' '..='~' => Directive::None,
+            '\u{0}'..='\u{ff}' => Directive::X,
+            '\u{100}'..='\u{ffff}' => Directive::X2,
+            _ => Directive::X4,
+        }
+    }
+}
+
+/// Write string with apostrophes, where consecutive characters
+/// in `\X2\` or `\X4\` directive are encoded together, e.g. `'\X2\03B103B2\X0\'` for `αβ`
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('\'')?;
+    let mut open = Directive::None;
+    for c in s.chars() {
+        let directive = Directive::new(c);
+        if open != directive && open != Directive::None {
+            f.write_str("\\X0\\")?;
+            open = Directive::None;
+        }
+        match directive {
+            Directive::None => match c {
+                '\'' => f.write_str("''")?,
+                '\\' => f.write_str("\\\\")?,
+                _ => f.write_char(c)?,
+            },
+            Directive::X => write!(f, "\\X\\{:02X}", c as u32)?,
+            Directive::X2 => {
+                if open != directive {
+                    f.write_str("\\X2\\")?;
+                    open = directive;
+                }
+                write!(f, "{:04X}", c as u32)?;
+            }
+            Directive::X4 => {
+                if open != directive {
+                    f.write_str("\\X4\\")?;
+                    open = directive;
+                }
+                write!(f, "{:08X}", c as u32)?;
+            }
+        }
+    }
+    if open != Directive::None {
+        f.write_str("\\X0\\")?;
+    }
+    f.write_char('\'')
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Parameter::Typed { keyword, parameter } => {
+                write!(f, "{}(", keyword)?;
+                parameter.fmt(f)?;
+                write!(f, ")")
+            }
+            Parameter::Integer(val) => write!(f, "{}", val),
+            Parameter::Real(val) => write_real(f, *val),
+            Parameter::String(val) => write_string(f, val),
+            Parameter::Enumeration(val) => write!(f, ".{}.", val),
+            Parameter::List(params) => {
+                write!(f, "(")?;
+                params.iter().format(", ").fmt(f)?;
+                write!(f, ")")
+            }
+            Parameter::Ref(name) => write!(f, "{}", name),
+            Parameter::NotProvided => write!(f, "$"),
+            Parameter::Omitted => write!(f, "*"),
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}(", self.name)?;
+        match &self.parameter {
+            Parameter::List(params) => params.iter().format(", ").fmt(f)?,
+            parameter => parameter.fmt(f)?,
+        }
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for SubSuperRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        self.0.iter().format(" ").fmt(f)?;
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for EntityInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntityInstance::Simple { id, record } => {
+                write!(f, "#{} = ", id)?;
+                record.fmt(f)?;
+            }
+            EntityInstance::Complex { id, subsuper } => {
+                write!(f, "#{} = ", id)?;
+                subsuper.fmt(f)?;
+            }
+        }
+        write!(f, ";")
+    }
+}
+
+impl fmt::Display for DataSection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.meta.is_empty() {
+            writeln!(f, "DATA;")?;
+        } else {
+            write!(f, "DATA(")?;
+            self.meta.iter().format(", ").fmt(f)?;
+            writeln!(f, ");")?;
+        }
+        for entity in &self.entities {
+            entity.fmt(f)?;
+            writeln!(f)?;
+        }
+        write!(f, "ENDSEC;")
+    }
+}
+
+impl fmt::Display for AnchorItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnchorItem::Integer(val) => write!(f, "{}", val),
+            AnchorItem::Real(val) => write_real(f, *val),
+            AnchorItem::String(val) => write_string(f, val),
+            AnchorItem::Enumeration(val) => write!(f, ".{}.", val),
+            AnchorItem::NotProvided => write!(f, "$"),
+            AnchorItem::Name(name) => write!(f, "{}", name),
+            AnchorItem::List(items) => {
+                write!(f, "(")?;
+                items.iter().format(", ").fmt(f)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}> = ", self.name)?;
+        self.item.fmt(f)?;
+        for (name, item) in &self.tags {
+            write!(f, "{{{}: ", name)?;
+            item.fmt(f)?;
+            write!(f, "}}")?;
+        }
+        write!(f, ";")
+    }
+}
+
+impl fmt::Display for ReferenceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} = <{}>;", self.name, self.resource.0)
+    }
+}
+
+/// `HEADER` section consisting of `records`
+pub(crate) struct HeaderSection<'a>(pub &'a [Record]);
+
+impl fmt::Display for HeaderSection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "HEADER;")?;
+        for record in self.0 {
+            record.fmt(f)?;
+            writeln!(f, ";")?;
+        }
+        write!(f, "ENDSEC;")
+    }
+}
+
+impl fmt::Display for Exchange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "ISO-10303-21;")?;
+        HeaderSection(&self.header).fmt(f)?;
+        writeln!(f)?;
+        if !self.anchor.is_empty() {
+            writeln!(f, "ANCHOR;")?;
+            for anchor in &self.anchor {
+                anchor.fmt(f)?;
+                writeln!(f)?;
+            }
+            writeln!(f, "ENDSEC;")?;
+        }
+        if !self.reference.is_empty() {
+            writeln!(f, "REFERENCE;")?;
+            for reference in &self.reference {
+                writeln!(f, "{}", reference)?;
+            }
+            writeln!(f, "ENDSEC;")?;
+        }
+        for data in &self.data {
+            data.fmt(f)?;
+            writeln!(f)?;
+        }
+        write!(f, "END-ISO-10303-21;")?;
+        for signature in &self.signature {
+            write!(f, "\nSIGNATURE\n{}\nENDSEC;", signature)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::*,
+        error::Error,
+        parser::{token, ParseOptions},
+    };
+    use nom::{Finish, Parser};
+
+    fn round_trip(val: f64) -> String {
+        let s = Parameter::Real(val).to_string();
+        let (res, parsed) = token::real(&s).finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(parsed.to_bits(), val.to_bits(), "{} is not read back", s);
+        s
+    }
+
+    #[test]
+    fn real() {
+        assert_eq!(round_trip(1.0), "1.");
+        assert_eq!(round_trip(-2.0), "-2.");
+        assert_eq!(round_trip(0.0), "0.");
+        assert_eq!(round_trip(1.5), "1.5");
+        assert_eq!(round_trip(1e-7), "1.E-7");
+        assert_eq!(round_trip(1.25e20), "1.25E20");
+    }
+
+    #[test]
+    fn real_extreme() {
+        assert_eq!(round_trip(f64::MAX), "1.7976931348623157E308");
+        assert_eq!(round_trip(f64::MIN), "-1.7976931348623157E308");
+        assert_eq!(round_trip(f64::MIN_POSITIVE), "2.2250738585072014E-308");
+        // subnormals
+        assert_eq!(round_trip(5e-324), "5.E-324");
+        assert_eq!(round_trip(-1.5e-320), "-1.5E-320");
+    }
+
+    #[test]
+    fn negative_zero() {
+        assert_eq!(round_trip(-0.0), "-0.");
+    }
+
+    #[test]
+    fn string_escapes() {
+        for (val, answer) in [
+            ("It's", r"'It''s'"),
+            (r"C:\tmp", r"'C:\\tmp'"),
+            ("\u{e9}t\u{e9}", r"'\X\E9t\X\E9'"),
+            ("αβ γ", r"'\X2\03B103B2\X0\ \X2\03B3\X0\'"),
+            ("α\u{1F600}", r"'\X2\03B1\X0\\X4\0001F600\X0\'"),
+        ] {
+            let s = Parameter::String(val.to_string()).to_string();
+            assert_eq!(s, answer);
+            let options = ParseOptions {
+                decode_string_escapes: true,
+                ..Default::default()
+            };
+            let (res, parsed) = token::string_with(options).parse(&s).finish().unwrap();
+            assert_eq!(res, "");
+            assert_eq!(parsed, val);
+        }
+    }
+
+    #[test]
+    fn non_finite() {
+        for val in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                to_step_string(&Parameter::Real(val)),
+                Err(Error::NonFiniteReal)
+            ));
+            let record = Record {
+                name: "A".to_string(),
+                parameter: vec![Parameter::Real(1.0), Parameter::Real(val)].into(),
+            };
+            assert!(matches!(to_step_string(&record), Err(Error::NonFiniteReal)));
+            // Display does not fail for them
+            assert_eq!(record.to_string(), format!("A(1., {:?})", val));
+        }
+    }
+
+    #[test]
+    fn exchange() {
+        let input = r#"
+        ISO-10303-21;
+        HEADER;
+          FILE_DESCRIPTION(('a'), '2;1');
+          FILE_NAME('test.stp', '', (''), (''), '', '', '');
+          FILE_SCHEMA(('TEST_SCHEMA'));
+        ENDSEC;
+        ANCHOR;
+          <a1> = #1{tag: 'x'};
+        ENDSEC;
+        REFERENCE;
+          #2 = <other.stp#1>;
+        ENDSEC;
+        DATA(('meta'));
+          #1 = A(1.0, #2);
+          #3 = (B(1) C('c'));
+        ENDSEC;
+        END-ISO-10303-21;
+        "#;
+        let exchange: Exchange = input.parse().unwrap();
+        let output = exchange.to_string();
+        assert_eq!(output.parse::<Exchange>().unwrap(), exchange);
+    }
+}
@@ -33,12 +33,28 @@
 //! │ Other Rust struct │
 //! └───────────────────┘
 //! ```
+//!
+//! Serialize
+//! ----------
+//!
+//! [ser::to_record] and [ser::to_parameter] use [serde::Serialize] in the reverse direction.
+//! espr-generated structs implement it by `ruststep_derive::Serialize`,
+//! and the output is deserialized back into the same struct.
 
 pub mod de;
 pub mod ser;
 
-use crate::parser;
-use std::str::FromStr;
+#[cfg(not(feature = "std"))]
+mod case;
+mod display;
+
+pub use display::to_step_string;
+#[cfg(feature = "std")]
+pub(crate) use display::HeaderSection;
+
+use crate::{parser, prelude::*};
+use alloc::borrow::Cow;
+use core::str::FromStr;
 
 /// AST portion
 pub trait AST: FromStr<Err = crate::error::Error> {
@@ -47,14 +63,14 @@ pub trait AST: FromStr<Err = crate::error::Error> {
 
 macro_rules! derive_ast_from_str {
     ($ast:ty, $parse:path) => {
-        impl std::str::FromStr for $ast {
+        impl core::str::FromStr for $ast {
             type Err = $crate::error::Error;
             fn from_str(input: &str) -> $crate::error::Result<Self> {
                 use nom::Finish;
                 let input = input.trim();
                 let (residual, record) = AST::parse(input)
                     .finish()
-                    .map_err(|err| $crate::error::TokenizeFailed::new(input, err))?;
+                    .map_err(|err| $crate::error::Error::tokenize_failed(input, err))?;
                 if !residual.is_empty() {
                     return Err($crate::error::Error::ExtraInputRemaining(input.to_string()));
                 }
@@ -93,7 +109,7 @@ derive_ast_from_str!(Name, parser::token::rhs_occurrence_name);
 ///
 /// ```
 /// use ruststep::ast::{Record, Parameter};
-/// use std::str::FromStr;
+/// use core::str::FromStr;
 ///
 /// let record = Record::from_str("A(1, 2)").unwrap();
 /// assert_eq!(
@@ -175,6 +191,31 @@ derive_ast_from_str!(Name, parser::token::rhs_occurrence_name);
 /// assert!(B::deserialize(&p).is_err());
 /// ```
 ///
+/// Parameters are mapped to the fields by their position, not by their names,
+/// i.e. the order of fields must be the order of attributes in EXPRESS schema.
+/// The number of parameters must be equal to the number of fields:
+///
+/// ```
+/// use core::str::FromStr;
+/// use ruststep::ast::*;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Clone, PartialEq, Deserialize)]
+/// #[serde(rename = "DATA_KEYWORD")]
+/// struct A {
+///     x: i32,
+///     y: i32,
+///     z: i32,
+/// }
+///
+/// let p = Record::from_str("DATA_KEYWORD(1, 2)").unwrap();
+/// let err = A::deserialize(&p).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "Error while deserialize STEP struct: invalid length 2, expected 3 parameters for DATA_KEYWORD"
+/// );
+/// ```
+///
 /// Internal mapping to complex entity instance
 /// --------------------------------------------
 ///
@@ -235,6 +276,45 @@ pub struct Record {
 }
 derive_ast_from_str!(Record, parser::exchange::simple_record);
 
+impl Record {
+    /// Parameters of this record, e.g. `[1.0, 2.0]` for `A(1.0, 2.0)`
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use core::str::FromStr;
+    ///
+    /// let record = Record::from_str("A(1.0, (2, 3))").unwrap();
+    /// assert_eq!(
+    ///     record.parameters(),
+    ///     &[Parameter::real(1.0), [Parameter::integer(2), Parameter::integer(3)].iter().collect()]
+    /// );
+    /// ```
+    pub fn parameters(&self) -> &[Parameter] {
+        match &self.parameter {
+            Parameter::List(params) => params,
+            parameter => core::slice::from_ref(parameter),
+        }
+    }
+
+    /// Check if the name is a user-defined keyword starting with `!`, e.g. `!MY_ENTITY(1.0)`
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use core::str::FromStr;
+    ///
+    /// let record = Record::from_str("!MY_ENTITY(1.0)").unwrap();
+    /// assert_eq!(record.name, "!MY_ENTITY");
+    /// assert!(record.is_user_defined());
+    /// assert_eq!(record.to_string(), "!MY_ENTITY(1.)");
+    ///
+    /// let record = Record::from_str("A(1.0)").unwrap();
+    /// assert!(!record.is_user_defined());
+    /// ```
+    pub fn is_user_defined(&self) -> bool {
+        self.name.starts_with('!')
+    }
+}
+
 /// A set of [Record] mapping to complex entity instance,
 /// e.g. `(A(1) B(2.0) C("3"))`
 ///
@@ -243,7 +323,7 @@ derive_ast_from_str!(Record, parser::exchange::simple_record);
 ///
 /// ```
 /// use ruststep::ast::*;
-/// use std::str::FromStr;
+/// use core::str::FromStr;
 ///
 /// let record = SubSuperRecord::from_str("(A(1, 2) B(3, 4))").unwrap();
 /// assert_eq!(
@@ -356,7 +436,7 @@ derive_ast_from_str!(SubSuperRecord, parser::exchange::subsuper_record);
 
 impl IntoIterator for SubSuperRecord {
     type Item = Record;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type IntoIter = alloc::vec::IntoIter<Self::Item>;
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
     }
@@ -364,7 +444,7 @@ impl IntoIterator for SubSuperRecord {
 
 impl<'a> IntoIterator for &'a SubSuperRecord {
     type Item = &'a Record;
-    type IntoIter = std::slice::Iter<'a, Record>;
+    type IntoIter = core::slice::Iter<'a, Record>;
     fn into_iter(self) -> Self::IntoIter {
         self.0.iter()
     }
@@ -386,7 +466,7 @@ impl<'a> FromIterator<&'a Record> for SubSuperRecord {
 ///
 /// ```
 /// use ruststep::ast::DataSection;
-/// use std::str::FromStr;
+/// use core::str::FromStr;
 ///
 /// let input = r#"
 /// DATA;
@@ -407,6 +487,91 @@ pub struct DataSection {
 }
 derive_ast_from_str!(DataSection, parser::exchange::data_section);
 
+impl DataSection {
+    /// Iterate all records with its entity instance id
+    ///
+    /// A complex entity instance yields each of its components with the same id.
+    /// This is useful to inspect a file without schema:
+    ///
+    /// ```
+    /// use ruststep::ast::DataSection;
+    /// use core::str::FromStr;
+    ///
+    /// let input = r#"
+    /// DATA;
+    ///   #1 = A(1.0, 2.0);
+    ///   #2 = (B(3.0) C(#1));
+    ///   #3 = A(4.0, 5.0);
+    /// ENDSEC;
+    /// "#;
+    /// let data_section = DataSection::from_str(input).unwrap();
+    /// let records: Vec<_> = data_section
+    ///     .records()
+    ///     .map(|(id, record)| (id, record.name.as_str()))
+    ///     .collect();
+    /// assert_eq!(records, vec![(1, "A"), (2, "B"), (2, "C"), (3, "A")]);
+    /// ```
+    pub fn records(&self) -> impl Iterator<Item = (u64, &Record)> {
+        self.entities.iter().flat_map(|instance| {
+            let id = instance.id();
+            instance.records().iter().map(move |record| (id, record))
+        })
+    }
+
+    /// Entity instance of given id, or `None` if no instance has the id
+    ///
+    /// This searches the instances linearly.
+    pub fn get_instance(&self, id: u64) -> Option<&EntityInstance> {
+        self.entities.iter().find(|instance| instance.id() == id)
+    }
+
+    /// Record of the simple entity instance with given id
+    ///
+    /// This returns `None` for a dangling reference, i.e. there is no instance with the id,
+    /// and also for a complex entity instance since it consists of several records.
+    /// Use [DataSection::get_instance] to get the records of a complex entity instance.
+    ///
+    /// ```
+    /// use ruststep::ast::{DataSection, Name, Parameter};
+    /// use core::str::FromStr;
+    ///
+    /// let input = r#"
+    /// DATA;
+    ///   #1 = A(1.0, 2.0);
+    ///   #2 = B(#1, #3);
+    ///   #4 = (C(1.0) D(2.0));
+    /// ENDSEC;
+    /// "#;
+    /// let data_section = DataSection::from_str(input).unwrap();
+    ///
+    /// let b = data_section.get(2).unwrap();
+    /// let ids: Vec<_> = b
+    ///     .parameter
+    ///     .as_list()
+    ///     .unwrap()
+    ///     .iter()
+    ///     .map(|p| match p {
+    ///         Parameter::Ref(Name::Entity(id)) => *id,
+    ///         _ => unreachable!(),
+    ///     })
+    ///     .collect();
+    /// assert_eq!(ids, vec![1, 3]);
+    ///
+    /// assert_eq!(data_section.get(1).unwrap().name, "A");
+    /// // dangling reference
+    /// assert!(data_section.get(3).is_none());
+    /// // complex entity instance
+    /// assert!(data_section.get(4).is_none());
+    /// assert_eq!(data_section.get_instance(4).unwrap().records().len(), 2);
+    /// ```
+    pub fn get(&self, id: u64) -> Option<&Record> {
+        match self.get_instance(id)? {
+            EntityInstance::Simple { record, .. } => Some(record),
+            EntityInstance::Complex { .. } => None,
+        }
+    }
+}
+
 /// Primitive value type in STEP data
 ///
 /// Inline struct or list can be nested, i.e. `Parameter` can be a tree.
@@ -482,13 +647,26 @@ pub enum Parameter {
     /// FromStr
     /// --------
     /// ```
-    /// use std::str::FromStr;
+    /// use core::str::FromStr;
     /// use ruststep::ast::Parameter;
     ///
     /// let p = Parameter::from_str("FILE_NAME('ruststep')").unwrap();
     /// assert!(matches!(p, Parameter::Typed { .. }));
     /// ```
     ///
+    /// User-defined keyword keeps its leading `!` in `keyword`
+    /// so that it is written back as it is:
+    ///
+    /// ```
+    /// use core::str::FromStr;
+    /// use ruststep::ast::Parameter;
+    ///
+    /// let p = Parameter::from_str("!MY_LENGTH(2.5)").unwrap();
+    /// assert!(matches!(&p, Parameter::Typed { keyword, .. } if keyword == "!MY_LENGTH"));
+    /// assert!(p.is_user_defined());
+    /// assert_eq!(p.to_string(), "!MY_LENGTH(2.5)");
+    /// ```
+    ///
     /// Deserialize
     /// ------------
     /// ```
@@ -542,7 +720,7 @@ pub enum Parameter {
     /// FromStr
     /// --------
     /// ```
-    /// use std::str::FromStr;
+    /// use core::str::FromStr;
     /// use ruststep::ast::Parameter;
     ///
     /// let p = Parameter::from_str("10").unwrap();
@@ -580,7 +758,7 @@ pub enum Parameter {
     /// FromStr
     /// --------
     /// ```
-    /// use std::str::FromStr;
+    /// use core::str::FromStr;
     /// use ruststep::ast::Parameter;
     ///
     /// let p = Parameter::from_str("1.0").unwrap();
@@ -594,7 +772,7 @@ pub enum Parameter {
     /// FromStr
     /// --------
     /// ```
-    /// use std::str::FromStr;
+    /// use core::str::FromStr;
     /// use ruststep::ast::Parameter;
     ///
     /// let p = Parameter::from_str("'EXAMPLE STRING'").unwrap();
@@ -608,7 +786,7 @@ pub enum Parameter {
     /// FromStr
     /// --------
     /// ```
-    /// # use std::str::FromStr;
+    /// # use core::str::FromStr;
     /// # use ruststep::ast::Parameter;
     /// let p = Parameter::from_str(".TRUE.").unwrap();
     /// assert_eq!(p, Parameter::Enumeration("TRUE".to_string()));
@@ -619,7 +797,7 @@ pub enum Parameter {
     /// ```
     /// use ruststep::ast::*;
     /// use serde::Deserialize;
-    /// use std::str::FromStr;
+    /// use core::str::FromStr;
     ///
     /// let p = Parameter::from_str(".A.").unwrap();
     ///
@@ -638,7 +816,7 @@ pub enum Parameter {
     /// FromStr
     /// --------
     /// ```
-    /// use std::str::FromStr;
+    /// use core::str::FromStr;
     /// use ruststep::ast::Parameter;
     ///
     /// let p = Parameter::from_str("(1.0, 2, 'STRING')").unwrap();
@@ -652,7 +830,7 @@ pub enum Parameter {
     /// Deserialize
     /// ------------
     /// ```
-    /// use std::str::FromStr;
+    /// use core::str::FromStr;
     /// use ruststep::ast::*;
     /// use serde::Deserialize;
     ///
@@ -671,6 +849,10 @@ pub enum Parameter {
     /// }
     /// let a = A::deserialize(&p).unwrap();
     /// assert_eq!(a, A { x: 1, y: 2, z: 3 });
+    ///
+    /// // Length of list must match the number of fields
+    /// let p = Parameter::from_str("(1, 2, 3, 4)").unwrap();
+    /// assert!(A::deserialize(&p).is_err());
     /// ```
     #[from]
     List(Vec<Parameter>),
@@ -682,7 +864,7 @@ pub enum Parameter {
     /// ```
     /// use ruststep::ast::*;
     /// use serde::Deserialize;
-    /// use std::str::FromStr;
+    /// use core::str::FromStr;
     ///
     /// let p = Parameter::from_str("#12").unwrap();
     ///
@@ -726,6 +908,12 @@ pub enum Parameter {
     Omitted,
 }
 
+/// Newtype struct name marking an attribute derived in subtypes, i.e. `tables::Derived`
+///
+/// Only this type is deserialized from and serialized into [Parameter::Omitted]
+/// distinguished from [Parameter::NotProvided].
+pub(crate) const DERIVED_TOKEN: &str = "$ruststep::Derived";
+
 impl Parameter {
     pub fn integer(i: i64) -> Self {
         Parameter::Integer(i)
@@ -738,15 +926,172 @@ impl Parameter {
     pub fn string(s: &str) -> Self {
         Parameter::String(s.to_string())
     }
+
+    /// Check if this is a typed parameter with a user-defined keyword starting with `!`
+    pub fn is_user_defined(&self) -> bool {
+        matches!(self, Parameter::Typed { keyword, .. } if keyword.starts_with('!'))
+    }
+
+    /// Get the value of `REAL`, or `INTEGER` as a real number
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    ///
+    /// assert_eq!(Parameter::real(1.5).as_real(), Some(1.5));
+    /// assert_eq!(Parameter::integer(2).as_real(), Some(2.0));
+    /// assert_eq!(Parameter::string("1.5").as_real(), None);
+    /// ```
+    pub fn as_real(&self) -> Option<f64> {
+        match self {
+            Parameter::Real(val) => Some(*val),
+            Parameter::Integer(val) => Some(*val as f64),
+            _ => None,
+        }
+    }
+
+    /// Get the value of `INTEGER`, or `REAL` if it is a whole number
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    ///
+    /// assert_eq!(Parameter::integer(2).as_integer(), Some(2));
+    /// assert_eq!(Parameter::real(2.0).as_integer(), Some(2));
+    /// assert_eq!(Parameter::real(2.5).as_integer(), None);
+    /// ```
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Parameter::Integer(val) => Some(*val),
+            // `f64::fract` is not available in `core`
+            Parameter::Real(val)
+                if *val >= i64::MIN as f64
+                    && *val < i64::MAX as f64
+                    && (*val as i64) as f64 == *val =>
+            {
+                Some(*val as i64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the string literal
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use core::str::FromStr;
+    ///
+    /// let p = Parameter::from_str("'It''s'").unwrap();
+    /// assert_eq!(p.as_str(), Some("It's"));
+    /// assert_eq!(Parameter::integer(1).as_str(), None);
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Parameter::String(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Get the elements of list
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use core::str::FromStr;
+    ///
+    /// let p = Parameter::from_str("(1, 'a')").unwrap();
+    /// assert_eq!(
+    ///     p.as_list(),
+    ///     Some([Parameter::integer(1), Parameter::string("a")].as_slice())
+    /// );
+    /// assert_eq!(Parameter::integer(1).as_list(), None);
+    /// ```
+    pub fn as_list(&self) -> Option<&[Parameter]> {
+        match self {
+            Parameter::List(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    /// Get the entity id of a reference, e.g. `12` for `#12`
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use core::str::FromStr;
+    ///
+    /// assert_eq!(Parameter::from_str("#12").unwrap().as_ref_id(), Some(12));
+    /// // value instance is not an entity
+    /// assert_eq!(Parameter::from_str("@12").unwrap().as_ref_id(), None);
+    /// ```
+    pub fn as_ref_id(&self) -> Option<u64> {
+        match self {
+            Parameter::Ref(Name::Entity(id)) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Get a flat list of real numbers, e.g. coordinates of `CARTESIAN_POINT`
+    ///
+    /// Returns `None` if `self` is not a list or some element is not a number.
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use core::str::FromStr;
+    ///
+    /// let p = Parameter::from_str("(1.0, 2, 3.5)").unwrap();
+    /// assert_eq!(p.as_real_vec(), Some(vec![1.0, 2.0, 3.5]));
+    ///
+    /// let p = Parameter::from_str("(1.0, $)").unwrap();
+    /// assert_eq!(p.as_real_vec(), None);
+    /// ```
+    pub fn as_real_vec(&self) -> Option<Vec<f64>> {
+        self.as_list()?.iter().map(Parameter::as_real).collect()
+    }
+
+    /// Compare two parameters regarding `INTEGER` and `REAL` of the same value as equal
+    ///
+    /// Lists and typed parameters are compared recursively.
+    /// The derived [PartialEq] remains strict, i.e. `1` and `1.0` are different.
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use core::str::FromStr;
+    ///
+    /// let a = Parameter::from_str("A((1, 2.5, 'x'))").unwrap();
+    /// let b = Parameter::from_str("A((1.0, 2.5, 'x'))").unwrap();
+    /// assert!(a.numeric_eq(&b));
+    /// assert_ne!(a, b);
+    ///
+    /// assert!(!Parameter::integer(1).numeric_eq(&Parameter::real(1.5)));
+    /// assert!(!Parameter::integer(1).numeric_eq(&Parameter::string("1")));
+    /// ```
+    pub fn numeric_eq(&self, other: &Self) -> bool {
+        use Parameter::*;
+        match (self, other) {
+            (Integer(a), Integer(b)) => a == b,
+            (Integer(_) | Real(_), Integer(_) | Real(_)) => self.as_real() == other.as_real(),
+            (List(a), List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.numeric_eq(b))
+            }
+            (
+                Typed {
+                    keyword: k1,
+                    parameter: p1,
+                },
+                Typed {
+                    keyword: k2,
+                    parameter: p2,
+                },
+            ) => k1 == k2 && p1.numeric_eq(p2),
+            _ => self == other,
+        }
+    }
 }
 
-impl std::iter::FromIterator<Parameter> for Parameter {
+impl core::iter::FromIterator<Parameter> for Parameter {
     fn from_iter<Iter: IntoIterator<Item = Parameter>>(iter: Iter) -> Self {
         Parameter::List(iter.into_iter().collect())
     }
 }
 
-impl<'a> std::iter::FromIterator<&'a Parameter> for Parameter {
+impl<'a> core::iter::FromIterator<&'a Parameter> for Parameter {
     fn from_iter<Iter: IntoIterator<Item = &'a Parameter>>(iter: Iter) -> Self {
         iter.into_iter().cloned().collect()
     }
@@ -754,6 +1099,89 @@ impl<'a> std::iter::FromIterator<&'a Parameter> for Parameter {
 
 derive_ast_from_str!(Parameter, parser::exchange::parameter);
 
+/// Borrowed variant of [Parameter] for hot paths
+///
+/// Strings borrow the input of [parser::exchange::parameter_ref_with] unless they contain
+/// something to be unescaped, see [parser::token::string_cow_with].
+/// Convert into [Parameter] by [ParameterRef::into_owned] to use the public API, e.g. deserialization.
+///
+/// ```
+/// use nom::{Finish, Parser};
+/// use ruststep::{ast::*, parser::{exchange::parameter_ref_with, ParseOptions}};
+/// use std::borrow::Cow;
+///
+/// let (_, p) = parameter_ref_with(ParseOptions::default())
+///     .parse("('vim', 'It''s')")
+///     .finish()
+///     .unwrap();
+/// assert_eq!(
+///     p,
+///     ParameterRef::List(vec![
+///         ParameterRef::String(Cow::Borrowed("vim")),
+///         ParameterRef::String(Cow::Owned("It's".to_string())),
+///     ])
+/// );
+/// assert_eq!(
+///     p.into_owned(),
+///     Parameter::List(vec![
+///         Parameter::String("vim".to_string()),
+///         Parameter::String("It's".to_string()),
+///     ])
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterRef<'a> {
+    /// Same as [Parameter::Typed]
+    Typed {
+        keyword: String,
+        parameter: Box<ParameterRef<'a>>,
+    },
+    /// Same as [Parameter::Integer]
+    Integer(i64),
+    /// Same as [Parameter::Real]
+    Real(f64),
+    /// Same as [Parameter::String], but borrowed if possible
+    String(Cow<'a, str>),
+    /// Same as [Parameter::Enumeration]
+    Enumeration(String),
+    /// Same as [Parameter::List]
+    List(Vec<ParameterRef<'a>>),
+    /// Same as [Parameter::Ref]
+    Ref(Name),
+    /// Same as [Parameter::NotProvided]
+    NotProvided,
+    /// Same as [Parameter::Omitted]
+    Omitted,
+}
+
+impl<'a> ParameterRef<'a> {
+    /// Convert into [Parameter], allocating borrowed strings
+    pub fn into_owned(self) -> Parameter {
+        match self {
+            ParameterRef::Typed { keyword, parameter } => Parameter::Typed {
+                keyword,
+                parameter: Box::new(parameter.into_owned()),
+            },
+            ParameterRef::Integer(i) => Parameter::Integer(i),
+            ParameterRef::Real(x) => Parameter::Real(x),
+            ParameterRef::String(s) => Parameter::String(s.into_owned()),
+            ParameterRef::Enumeration(e) => Parameter::Enumeration(e),
+            ParameterRef::List(list) => {
+                Parameter::List(list.into_iter().map(ParameterRef::into_owned).collect())
+            }
+            ParameterRef::Ref(name) => Parameter::Ref(name),
+            ParameterRef::NotProvided => Parameter::NotProvided,
+            ParameterRef::Omitted => Parameter::Omitted,
+        }
+    }
+}
+
+impl<'a> From<ParameterRef<'a>> for Parameter {
+    fn from(p: ParameterRef<'a>) -> Self {
+        p.into_owned()
+    }
+}
+
 /// Entire exchange structure
 #[derive(Debug, Clone, PartialEq)]
 pub struct Exchange {
@@ -778,6 +1206,23 @@ pub enum EntityInstance {
 }
 derive_ast_from_str!(EntityInstance, parser::exchange::entity_instance);
 
+impl EntityInstance {
+    /// Entity instance id, e.g. `1` for `#1 = A(1.0);`
+    pub fn id(&self) -> u64 {
+        match self {
+            EntityInstance::Simple { id, .. } | EntityInstance::Complex { id, .. } => *id,
+        }
+    }
+
+    /// Records in this instance. Complex entity instance has several records.
+    pub fn records(&self) -> &[Record] {
+        match self {
+            EntityInstance::Simple { record, .. } => core::slice::from_ref(record),
+            EntityInstance::Complex { subsuper, .. } => &subsuper.0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ReferenceEntry {
     pub name: Name,
@@ -1,18 +1,139 @@
-use crate::{ast::*, error::*};
+#[cfg(not(feature = "std"))]
+use crate::ast::case::Inflector;
+use crate::{ast::*, error::*, prelude::*};
+use core::{convert::TryFrom, mem, str::FromStr};
+#[cfg(feature = "std")]
+use inflector::Inflector;
 use serde::ser;
-use std::convert::TryFrom;
 
 /// Serialize struct into STEP [Record]
+///
+/// This is the inverse of deserialization, i.e. the [Record] is deserialized into the same value.
 pub fn to_record(obj: &impl ser::Serialize) -> Result<Record> {
     let mut ser = RecordSerializer::default();
     obj.serialize(&mut ser)?;
     assert!(ser.stack.is_empty()); // should panic because this must be bug, not a valid input
+    if ser.name.is_empty() {
+        // Value of SELECT type is serialized into a typed parameter
+        if let [Parameter::Typed { keyword, parameter }] = ser.parameters.as_slice() {
+            return Ok(Record {
+                name: keyword.clone(),
+                parameter: parameter.as_ref().clone(),
+            });
+        }
+    }
     Ok(Record {
         name: ser.name,
         parameter: ser.parameters.iter().collect(),
     })
 }
 
+/// Serialize value into STEP [Parameter]
+///
+/// A struct is serialized into [Parameter::Typed] with the same content as [to_record],
+/// e.g. `A((1.0, 2.0))` for `A(1.0, 2.0)`.
+pub fn to_parameter(obj: &impl ser::Serialize) -> Result<Parameter> {
+    let mut ser = RecordSerializer::default();
+    obj.serialize(&mut ser)?;
+    assert!(ser.stack.is_empty()); // should panic because this must be bug, not a valid input
+    if !ser.name.is_empty() {
+        return Ok(Parameter::Typed {
+            keyword: ser.name,
+            parameter: Box::new(ser.parameters.iter().collect()),
+        });
+    }
+    let mut parameters = ser.parameters;
+    if parameters.len() == 1 {
+        Ok(parameters.pop().unwrap())
+    } else {
+        Ok(Parameter::List(parameters))
+    }
+}
+
+/// Serialize a holder of complex entity into [SubSuperRecord]
+///
+/// The holder is serialized by [to_record] into a record combining its components,
+/// e.g. `EMPLOYEE_STUDENT(EMPLOYEE((PERSON(('A')), 15)), STUDENT((PERSON(('A')), 'B')))`,
+/// and then split into the components sorted by name, e.g. `(EMPLOYEE(15) PERSON('A') STUDENT('B'))`.
+/// This is the inverse of [SubSuperRecord::deserialize_combined].
+///
+/// `names` are the names of the components and their supertypes, e.g. `["EMPLOYEE", "PERSON", "STUDENT"]`.
+/// The first parameter of a component is regarded as its supertype if its keyword is in `names`.
+/// Returns [Error::InvalidComplexEntity] if a supertype shared by components has different values.
+pub fn to_subsuper_record(obj: &impl ser::Serialize, names: &[&str]) -> Result<SubSuperRecord> {
+    let record = to_record(obj)?;
+    let components = match record.parameter {
+        Parameter::List(components) => components,
+        component => vec![component],
+    };
+    let mut records = Vec::new();
+    for component in components {
+        match component {
+            Parameter::Typed { keyword, parameter } => {
+                split_component(keyword, *parameter, names, &mut records)?
+            }
+            _ => {
+                return Err(Error::InvalidComplexEntity(format!(
+                    "'{}' has a parameter which is not a component",
+                    record.name
+                )))
+            }
+        }
+    }
+    records.sort_by(|a: &Record, b| a.name.cmp(&b.name));
+    Ok(SubSuperRecord(records))
+}
+
+/// Split supertypes in the leading parameter of a component into separate records
+fn split_component(
+    name: String,
+    parameter: Parameter,
+    names: &[&str],
+    records: &mut Vec<Record>,
+) -> Result<()> {
+    let mut parameters = match parameter {
+        Parameter::List(parameters) => parameters,
+        parameter => vec![parameter],
+    };
+    if let Some(Parameter::Typed { keyword, .. }) = parameters.first() {
+        if names.contains(&keyword.as_str()) {
+            if let Parameter::Typed { keyword, parameter } = parameters.remove(0) {
+                split_component(keyword, *parameter, names, records)?;
+            }
+        }
+    }
+    let record = Record {
+        name,
+        parameter: Parameter::List(parameters),
+    };
+    match records.iter().find(|r| r.name == record.name) {
+        Some(shared) if shared != &record => Err(Error::InvalidComplexEntity(format!(
+            "supertype '{}' differs among components",
+            record.name
+        ))),
+        Some(_) => Ok(()),
+        None => {
+            records.push(record);
+            Ok(())
+        }
+    }
+}
+
+/// Newtype struct name marking a serialized [Name]
+///
+/// Other serializers, e.g. JSON, see [Name] as a string like `#12`,
+/// while [RecordSerializer] writes it as [Parameter::Ref].
+const NAME_TOKEN: &str = "$ruststep::Name";
+
+impl ser::Serialize for Name {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(NAME_TOKEN, &self.to_string())
+    }
+}
+
 #[derive(Default, Debug)]
 struct RecordSerializer {
     name: String,
@@ -21,6 +142,26 @@ struct RecordSerializer {
     stack: Vec<(String, Vec<Parameter>)>,
 }
 
+impl RecordSerializer {
+    /// Start serializing nested value e.g. list or typed parameter
+    fn push_frame(&mut self) {
+        let current_name = mem::take(&mut self.name);
+        let current_params = mem::take(&mut self.parameters);
+        self.stack.push((current_name, current_params));
+    }
+
+    /// Restore the state saved by [RecordSerializer::push_frame],
+    /// and returns the parameters serialized in the frame
+    fn pop_frame(&mut self) -> Vec<Parameter> {
+        let (name, params) = self
+            .stack
+            .pop()
+            .expect("Stack of RecordSerializer is broken"); // this must be a bug
+        self.name = name;
+        mem::replace(&mut self.parameters, params)
+    }
+}
+
 impl<'se> ser::Serializer for &'se mut RecordSerializer {
     type Ok = ();
     type Error = Error;
@@ -115,30 +256,68 @@ impl<'se> ser::Serializer for &'se mut RecordSerializer {
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
-        self.serialize_str(variant)
+        self.parameters
+            .push(Parameter::Enumeration(variant.to_screaming_snake_case()));
+        Ok(())
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + ser::Serialize,
     {
+        if name == NAME_TOKEN {
+            // Reference serialized by `impl Serialize for Name`, e.g. `#12`
+            self.push_frame();
+            value.serialize(&mut *self)?;
+            match self.pop_frame().as_slice() {
+                [Parameter::String(name)] => {
+                    self.parameters.push(Parameter::Ref(Name::from_str(name)?));
+                    return Ok(());
+                }
+                _ => unreachable!("Name is serialized as a string"),
+            }
+        }
+        if name == DERIVED_TOKEN {
+            // Attribute derived in subtypes serialized by `tables::Derived`
+            self.parameters.push(Parameter::Omitted);
+            return Ok(());
+        }
         value.serialize(self)
     }
 
+    // SELECT type value, e.g. `LABEL('a')`
     fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _value: &T,
+        variant: &'static str,
+        value: &T,
     ) -> Result<()>
     where
         T: ?Sized + ser::Serialize,
     {
-        unimplemented!("Newtype variant is not suuported yet.")
+        self.push_frame();
+        value.serialize(&mut *self)?;
+        let mut params = self.pop_frame();
+        if let [Parameter::Typed { .. }] = params.as_slice() {
+            // ENTITY or nested SELECT is already typed
+            self.parameters.append(&mut params);
+        } else {
+            self.parameters.push(Parameter::Typed {
+                keyword: variant.to_screaming_snake_case(),
+                parameter: Box::new(if params.len() == 1 {
+                    params.pop().unwrap()
+                } else {
+                    Parameter::List(params)
+                }),
+            });
+        }
+        Ok(())
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        // Elements are collected into a list in SerializeSeq::end()
+        self.push_frame();
         Ok(self)
     }
 
@@ -146,12 +325,13 @@ impl<'se> ser::Serializer for &'se mut RecordSerializer {
         self.serialize_seq(Some(len))
     }
 
+    // Tuple struct is also a record, e.g. `A('a')` for `TYPE a = STRING;`
     fn serialize_tuple_struct(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        self.serialize_seq(Some(len))
+        self.serialize_struct(name, len)
     }
 
     fn serialize_tuple_variant(
@@ -169,7 +349,7 @@ impl<'se> ser::Serializer for &'se mut RecordSerializer {
     }
 
     fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        if self.name.is_empty() {
+        if self.name.is_empty() && self.stack.is_empty() {
             self.name = name.to_string();
         } else {
             // Entering sub struct e.g.
@@ -183,9 +363,8 @@ impl<'se> ser::Serializer for &'se mut RecordSerializer {
             // and start serializing `A((1.0, 2.0))`.
             // This stack will be popped in SerializeStruct::end()
             //
-            let current_name = std::mem::replace(&mut self.name, name.to_string());
-            let current_params = std::mem::take(&mut self.parameters);
-            self.stack.push((current_name, current_params));
+            self.push_frame();
+            self.name = name.to_string();
         }
         Ok(self)
     }
@@ -211,6 +390,8 @@ impl<'se> ser::SerializeSeq for &'se mut RecordSerializer {
         value.serialize(&mut **self)
     }
     fn end(self) -> Result<()> {
+        let params = self.pop_frame();
+        self.parameters.push(Parameter::List(params));
         Ok(())
     }
 }
@@ -226,7 +407,7 @@ impl<'se> ser::SerializeTuple for &'se mut RecordSerializer {
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        ser::SerializeSeq::end(self)
     }
 }
 
@@ -242,7 +423,7 @@ impl<'se> ser::SerializeTupleStruct for &'se mut RecordSerializer {
     }
 
     fn end(self) -> Result<()> {
-        Ok(())
+        ser::SerializeStruct::end(self)
     }
 }
 
@@ -297,10 +478,10 @@ impl<'se> ser::SerializeStruct for &'se mut RecordSerializer {
     }
 
     fn end(self) -> Result<()> {
-        if let Some((name, params)) = self.stack.pop() {
+        if !self.stack.is_empty() {
             // restore stacked state
-            let name = std::mem::replace(&mut self.name, name);
-            let params = std::mem::replace(&mut self.parameters, params);
+            let name = self.name.clone();
+            let params = self.pop_frame();
             self.parameters.push(Parameter::Typed {
                 keyword: name,
                 parameter: Box::new(params.into_iter().collect()),
@@ -1,27 +1,295 @@
+use crate::{
+    ast::Name,
+    parser::{combinator::UNTERMINATED_COMMENT, token::INTEGER_OVERFLOW},
+    prelude::*,
+};
+use core::fmt;
 use serde::{de, ser};
-use std::fmt;
 
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
-#[derive(Debug, thiserror::Error)]
+/// Error of this crate
+///
+/// Without `std` feature, [fmt::Display] is implemented as same as [fmt::Debug]
+/// since `thiserror` requires `std`.
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
 pub enum Error {
+    #[cfg_attr(feature = "std", error(transparent))]
+    TokenizeFailed(#[cfg_attr(feature = "std", from)] TokenizeFailed),
+
+    #[cfg_attr(
+        feature = "std",
+        error("Unexpected end of input, the exchange structure may be truncated")
+    )]
+    UnexpectedEof,
+
+    #[cfg_attr(
+        feature = "std",
+        error("Comment starting at line {line}, column {column} is not terminated by `*/`")
+    )]
+    UnterminatedComment {
+        /// Line of the opening `/*`, starting from 1
+        line: usize,
+        /// Column of the opening `/*` in characters, starting from 1
+        column: usize,
+    },
+
+    #[cfg_attr(
+        feature = "std",
+        error("Integer '{literal}' at line {line}, column {column} does not fit into 64 bits")
+    )]
+    IntegerOverflow {
+        /// Integer as written, e.g. `#18446744073709551616`
+        literal: String,
+        /// Line of the integer, starting from 1
+        line: usize,
+        /// Column of the integer in characters, starting from 1
+        column: usize,
+    },
+
+    #[cfg(feature = "std")]
     #[error(transparent)]
-    TokenizeFailed(#[from] TokenizeFailed),
+    Io(#[from] std::io::Error),
 
-    #[error("Extra input string remains behind: {0}")]
+    #[cfg_attr(feature = "std", error("Extra input string remains behind: {0}"))]
     ExtraInputRemaining(String),
 
-    #[error("Error while deserialize STEP struct: {0}")]
+    #[cfg_attr(
+        feature = "std",
+        error("Input is not valid UTF-8: invalid byte sequence at offset {valid_up_to}")
+    )]
+    InvalidUtf8 { valid_up_to: usize },
+
+    #[cfg_attr(feature = "std", error("Error while deserialize STEP struct: {0}"))]
     DeserializeFailed(String),
 
-    #[error("Lookup failed for #{0}")]
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "Record '{type_name}' expects {expected} parameters, but {found} parameters found{}",
+            lacking(.missing)
+        )
+    )]
+    ArityMismatch {
+        type_name: String,
+        expected: usize,
+        found: usize,
+        /// Name of the first attribute lacking its parameter, if known
+        missing: Option<String>,
+    },
+
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "Attribute '{attr}' has {found} elements, but its bound is [{min}:{}]",
+            upper(.max)
+        )
+    )]
+    AggregateBounds {
+        /// Name of the aggregate attribute
+        attr: String,
+        min: usize,
+        /// `None` for indeterminate upper bound `?`
+        max: Option<usize>,
+        found: usize,
+    },
+
+    #[cfg_attr(
+        feature = "std",
+        error("Record '{found}' cannot be read as '{expected}'")
+    )]
+    RecordNameMismatch { expected: String, found: String },
+
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "Enumeration value '.{value}.' is not a variant of '{type_name}', expected one of {}",
+            .expected.join(", ")
+        )
+    )]
+    UnknownEnumVariant {
+        /// Name of the Rust enum, e.g. `Color`
+        type_name: &'static str,
+        /// Enumeration value as written in exchange structure, e.g. `FOO` for `.FOO.`
+        value: String,
+        /// Variants of the enum
+        expected: &'static [&'static str],
+    },
+
+    #[cfg_attr(feature = "std", error("Lookup failed for #{0}"))]
     UnknownEntity(u64),
 
-    #[error("Entity ID #{0} is duplicated")]
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "Reference {0} cannot be resolved, only entity instance names like #1 are supported"
+        )
+    )]
+    UnsupportedReference(Name),
+
+    #[cfg_attr(
+        feature = "std",
+        error("Entity #{entity_id} is not an instance of any of {}", .expected.join(", "))
+    )]
+    UnexpectedEntityType {
+        entity_id: u64,
+        /// Names of entities acceptable for the reference, e.g. subtypes of the supertype
+        expected: Vec<String>,
+    },
+
+    #[cfg_attr(feature = "std", error("Entity ID #{0} is duplicated"))]
     DuplicatedEntity(u64),
 
-    #[error("Entity '{entity_name}' is not a member of the schema '{schema}'")]
+    #[cfg_attr(
+        feature = "std",
+        error("Cyclic reference detected while resolving #{entity_id} as '{entity_name}'")
+    )]
+    CyclicReference { entity_id: u64, entity_name: String },
+
+    /// Returned by `IntoOwned::into_owned_with` to let `Resolver` resolve the entity beforehand,
+    /// and thus not returned by `IntoOwned::into_owned`
+    #[cfg_attr(
+        feature = "std",
+        error("#{entity_id} as '{entity_name}' is deferred to be resolved before its referrer")
+    )]
+    Deferred { entity_id: u64, entity_name: String },
+
+    #[cfg_attr(
+        feature = "std",
+        error("Attribute '{attribute}' of '{entity_name}' is omitted as `*`, but the subtype does not derive its value")
+    )]
+    DerivedAttribute {
+        /// Entity declaring the attribute, e.g. `POINT`
+        entity_name: String,
+        /// Attribute derived in subtypes
+        attribute: String,
+    },
+
+    #[cfg_attr(feature = "std", error("Header entity '{0}' is missing"))]
+    MissingHeaderEntity(String),
+
+    #[cfg_attr(
+        feature = "std",
+        error("Entity '{entity_name}' is not a member of the schema '{schema}'")
+    )]
     UnknownEntityName { entity_name: String, schema: String },
+
+    #[cfg_attr(
+        feature = "std",
+        error("Exchange structure is written in the schema {}, not '{expected}'", .found.join(", "))
+    )]
+    SchemaMismatch {
+        /// Schema of tables
+        expected: String,
+        /// Schema names listed in `FILE_SCHEMA` of the header section
+        found: Vec<String>,
+    },
+
+    #[cfg_attr(
+        feature = "std",
+        error("Invalid ISO-10303-28 XML at line {line}: {message}")
+    )]
+    InvalidXml {
+        /// Line of the element, starting from 1
+        line: u32,
+        message: String,
+    },
+
+    #[cfg_attr(feature = "std", error("{0} cannot be written in ISO-10303-28 XML"))]
+    UnsupportedXml(String),
+
+    #[cfg_attr(
+        feature = "std",
+        error("NaN or infinity cannot be written as REAL in exchange structure")
+    )]
+    NonFiniteReal,
+
+    #[cfg_attr(
+        feature = "std",
+        error("Cannot be written as a complex entity instance: {0}")
+    )]
+    InvalidComplexEntity(String),
+}
+
+#[cfg(not(feature = "std"))]
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl From<TokenizeFailed> for Error {
+    fn from(err: TokenizeFailed) -> Self {
+        Error::TokenizeFailed(err)
+    }
+}
+
+#[cfg(feature = "std")]
+fn lacking(missing: &Option<String>) -> String {
+    match missing {
+        Some(name) => format!(", lacking '{}'", name),
+        None => String::new(),
+    }
+}
+
+#[cfg(feature = "std")]
+fn upper(max: &Option<usize>) -> String {
+    match max {
+        Some(max) => max.to_string(),
+        None => "?".to_string(),
+    }
+}
+
+impl Error {
+    /// Create error from the tokenize error of `input`
+    ///
+    /// [Error::UnterminatedComment] is returned if a comment is not terminated,
+    /// [Error::IntegerOverflow] is returned for an integer too large to be represented,
+    /// and [Error::UnexpectedEof] is returned if the tokenizer reaches the end of input
+    /// before finishing, e.g. for a file missing `ENDSEC;` or `END-ISO-10303-21;`.
+    pub fn tokenize_failed(input: &str, err: nom::error::VerboseError<&str>) -> Self {
+        let failed_at = |context: &str| {
+            err.errors.iter().find_map(|(residual, kind)| match kind {
+                nom::error::VerboseErrorKind::Context(c) if *c == context => Some(*residual),
+                _ => None,
+            })
+        };
+        if let Some(residual) = failed_at(UNTERMINATED_COMMENT) {
+            let (line, column) = position(input, residual);
+            Error::UnterminatedComment { line, column }
+        } else if let Some(residual) = failed_at(INTEGER_OVERFLOW) {
+            let (line, column) = position(input, residual);
+            let literal = residual
+                .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '#' | '@' | '+' | '-')))
+                .next()
+                .unwrap_or_default();
+            Error::IntegerOverflow {
+                literal: literal.to_string(),
+                line,
+                column,
+            }
+        } else if err
+            .errors
+            .iter()
+            .any(|(residual, _kind)| residual.trim().is_empty())
+        {
+            Error::UnexpectedEof
+        } else {
+            TokenizeFailed::new(input, err).into()
+        }
+    }
+}
+
+/// Line and column of `residual` in `input`, both starting from 1
+fn position(input: &str, residual: &str) -> (usize, usize) {
+    let consumed = &input[..input.len() - residual.len()];
+    let line_start = consumed.rfind('\n').map_or(0, |pos| pos + 1);
+    (
+        consumed.matches('\n').count() + 1,
+        consumed[line_start..].chars().count() + 1,
+    )
 }
 
 impl de::Error for Error {
@@ -48,7 +316,7 @@ pub struct TokenizeFailed {
 }
 
 impl fmt::Debug for TokenizeFailed {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::result::Result<(), fmt::Error> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> core::result::Result<(), fmt::Error> {
         write!(
             f,
             "Error while tokenizing STEP input\n{}",
@@ -60,11 +328,12 @@ impl fmt::Debug for TokenizeFailed {
 
 // Use same output as Debug
 impl fmt::Display for TokenizeFailed {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::result::Result<(), fmt::Error> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> core::result::Result<(), fmt::Error> {
         fmt::Debug::fmt(self, f)
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for TokenizeFailed {}
 
 impl TokenizeFailed {
@@ -0,0 +1,239 @@
+//! Built-in functions of EXPRESS defined in ISO 10303-11 clause 15
+//!
+//! Functions generated by espr call them,
+//! and they are also useful to write validators by hand.
+//!
+//! ```
+//! use ruststep::express_builtins::*;
+//!
+//! let items = [1.0, 2.0, 3.0];
+//! assert_eq!(sizeof(&items), 3);
+//! assert_eq!(hiindex(&items), 3);
+//! assert_eq!(abs(-1.5), 1.5);
+//! assert!(exists(&Some(1)));
+//! assert!(!exists(&Option::<i64>::None));
+//! ```
+//!
+//! Indeterminate value `?` is represented by `None`,
+//! e.g. [sqrt] returns `None` for negative numbers.
+//!
+//! `FORMAT` is not provided yet.
+
+use crate::{ast::Parameter, prelude::*, primitive::Logical};
+use alloc::rc::Rc;
+
+/// `ABS`, the absolute value of a number (15.1)
+///
+/// ```
+/// use ruststep::express_builtins::abs;
+/// assert_eq!(abs(-10.0), 10.0);
+/// assert_eq!(abs(0.5), 0.5);
+/// ```
+pub fn abs(value: f64) -> f64 {
+    if value.is_sign_negative() {
+        -value
+    } else {
+        value
+    }
+}
+
+/// `BLENGTH`, the number of bits in a binary (15.4)
+///
+/// ```
+/// use ruststep::express_builtins::blength;
+/// // %01010010
+/// let bits = [false, true, false, true, false, false, true, false];
+/// assert_eq!(blength(&bits), 8);
+/// ```
+pub fn blength(bits: &[bool]) -> i64 {
+    bits.len() as i64
+}
+
+/// `EXISTS`, whether the value is not indeterminate (15.9)
+///
+/// ```
+/// use ruststep::{ast::Parameter, express_builtins::exists};
+/// assert!(exists(&Some(1.0)));
+/// assert!(!exists(&Option::<f64>::None));
+/// assert!(exists(&Parameter::Real(1.0)));
+/// assert!(!exists(&Parameter::NotProvided));
+/// ```
+pub fn exists<T: Exists + ?Sized>(value: &T) -> bool {
+    value.exists()
+}
+
+/// `HIINDEX`, the upper index of an aggregate value (15.12)
+///
+/// Every aggregate is indexed from 1 when translated into Rust,
+/// and thus this equals to [sizeof].
+///
+/// ```
+/// use ruststep::express_builtins::hiindex;
+/// assert_eq!(hiindex(&[1, 2, 3]), 3);
+/// assert_eq!(hiindex::<i64>(&[]), 0);
+/// ```
+pub fn hiindex<T>(items: &[T]) -> i64 {
+    items.len() as i64
+}
+
+/// `LOINDEX`, the lower index of an aggregate value (15.17)
+///
+/// This is always 1 since every aggregate is indexed from 1 when translated into Rust.
+///
+/// ```
+/// use ruststep::express_builtins::loindex;
+/// assert_eq!(loindex(&[1, 2, 3]), 1);
+/// assert_eq!(loindex::<i64>(&[]), 1);
+/// ```
+pub fn loindex<T>(_items: &[T]) -> i64 {
+    1
+}
+
+/// `NVL`, the value itself if it exists, otherwise the substitute (15.18)
+///
+/// ```
+/// use ruststep::express_builtins::nvl;
+/// assert_eq!(nvl(Some(1.0), 0.0), 1.0);
+/// assert_eq!(nvl(None, 0.0), 0.0);
+/// ```
+pub fn nvl<T>(value: Option<T>, substitute: T) -> T {
+    value.unwrap_or(substitute)
+}
+
+/// `SIZEOF`, the number of elements in an aggregate value (15.24)
+///
+/// ```
+/// use ruststep::express_builtins::sizeof;
+/// assert_eq!(sizeof(&[1, 2, 3]), 3);
+/// assert_eq!(sizeof(&vec![1.0; 5]), 5);
+/// ```
+pub fn sizeof<T>(items: &[T]) -> i64 {
+    items.len() as i64
+}
+
+/// `SQRT`, the non-negative square root of a number (15.25)
+///
+/// Returns `None`, i.e. indeterminate, for negative numbers.
+///
+/// ```
+/// use ruststep::express_builtins::sqrt;
+/// assert_eq!(sqrt(121.0), Some(11.0));
+/// assert_eq!(sqrt(-1.0), None);
+/// ```
+#[cfg(feature = "std")]
+pub fn sqrt(value: f64) -> Option<f64> {
+    if value < 0.0 {
+        None
+    } else {
+        Some(value.sqrt())
+    }
+}
+
+/// `TYPEOF`, the names of types of which the value is a member (15.27)
+///
+/// Entities generated by espr return their own name and names of all their supertypes
+/// qualified by schema names in upper case, e.g. `'SCHEMA.ENTITY'`.
+/// `typeof` is a reserved keyword in Rust, and thus this is named `r#typeof`.
+pub fn r#typeof<T: TypeOf + ?Sized>(value: &T) -> &'static [&'static str] {
+    value.r#typeof()
+}
+
+/// `VALUE`, the number represented by a string (15.28)
+///
+/// Returns `None`, i.e. indeterminate, if the string does not represent a number.
+///
+/// ```
+/// use ruststep::express_builtins::value;
+/// assert_eq!(value("1.234"), Some(1.234));
+/// assert_eq!(value("1.20E3"), Some(1200.0));
+/// assert_eq!(value("-5"), Some(-5.0));
+/// assert_eq!(value("12ABC"), None);
+/// assert_eq!(value("inf"), None);
+/// ```
+pub fn value(s: &str) -> Option<f64> {
+    let s = s.trim();
+    // Reject `inf` and `NaN` accepted by Rust
+    if !s
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E'))
+    {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Values whose type names are known at runtime, used by `TYPEOF`
+///
+/// This is implemented for every entity and `Any` enum generated by espr.
+pub trait TypeOf {
+    fn r#typeof(&self) -> &'static [&'static str];
+}
+
+impl<T: TypeOf + ?Sized> TypeOf for &T {
+    fn r#typeof(&self) -> &'static [&'static str] {
+        (**self).r#typeof()
+    }
+}
+
+impl<T: TypeOf + ?Sized> TypeOf for Box<T> {
+    fn r#typeof(&self) -> &'static [&'static str] {
+        (**self).r#typeof()
+    }
+}
+
+impl<T: TypeOf + ?Sized> TypeOf for Rc<T> {
+    fn r#typeof(&self) -> &'static [&'static str] {
+        (**self).r#typeof()
+    }
+}
+
+/// Values which may be indeterminate (`?`) in EXPRESS
+///
+/// `OPTIONAL` attributes, i.e. `Option<T>`, and `$` in exchange structure can be indeterminate.
+pub trait Exists {
+    fn exists(&self) -> bool;
+}
+
+impl<T> Exists for Option<T> {
+    fn exists(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T: Exists + ?Sized> Exists for &T {
+    fn exists(&self) -> bool {
+        (**self).exists()
+    }
+}
+
+impl Exists for Parameter {
+    fn exists(&self) -> bool {
+        !matches!(self, Parameter::NotProvided)
+    }
+}
+
+macro_rules! impl_exists {
+    ($($t:ty),*) => {
+        $(
+        impl Exists for $t {
+            fn exists(&self) -> bool {
+                true
+            }
+        }
+        )*
+    };
+}
+
+impl_exists!(f64, i64, bool, str, String, Logical);
+
+impl<T> Exists for [T] {
+    fn exists(&self) -> bool {
+        true
+    }
+}
+
+impl<T> Exists for Vec<T> {
+    fn exists(&self) -> bool {
+        true
+    }
+}
@@ -20,7 +20,10 @@
 //! we write these definitions manually to keep development process simple.
 //!
 
-use crate::{ast::*, error::Result};
+use crate::{
+    ast::{ser::to_record, *},
+    error::*,
+};
 use serde::Deserialize;
 
 /// File description
@@ -34,7 +37,7 @@ use serde::Deserialize;
 ///   implementation_level : STRING (256) ;
 /// END_ENTITY;
 /// ```
-#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize)]
+#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize, ruststep_derive::Serialize)]
 pub struct FileDescription {
     pub description: Vec<String>,
     pub implementation_level: String,
@@ -59,7 +62,7 @@ pub struct FileDescription {
 /// TYPE time_stamp_text = STRING(256);
 /// END_TYPE;
 /// ```
-#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize)]
+#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize, ruststep_derive::Serialize)]
 pub struct FileName {
     pub name: String,
     /// ISO-8601 formatted date and time specifying when the exchange structure was created.
@@ -84,11 +87,131 @@ pub struct FileName {
 /// TYPE schema_name = STRING(1024);
 /// END_TYPE;
 /// ```
-#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize)]
+#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize, ruststep_derive::Serialize)]
 pub struct FileSchema {
     pub schema: Vec<String>,
 }
 
+impl FileSchema {
+    /// Check if the schema is listed, e.g. `AUTOMOTIVE_DESIGN { 1 0 10303 214 1 1 1 1 }` for `automotive_design`
+    ///
+    /// Schema names are compared case-insensitively since EXPRESS identifiers are case insensitive,
+    /// and the object identifier in braces following the name is ignored.
+    pub fn contains(&self, schema: &str) -> bool {
+        self.schema.iter().any(|name| {
+            let name = name.split('{').next().unwrap_or_default().trim();
+            name.eq_ignore_ascii_case(schema)
+        })
+    }
+}
+
+/// Schema population
+///
+/// Following EXPRESS schema is an exerpt from
+/// [ISO-10303-21:2016(E) "8.2.5 schema_population"](https://www.iso.org/standard/63141.html):
+///
+/// ```text
+/// ENTITY schema_population;
+///   external_file_identifications : SET OF external_file_identification;
+/// END_ENTITY;
+///
+/// TYPE external_file_identification = LIST [2:3] OF STRING;
+/// END_TYPE;
+/// ```
+#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize)]
+pub struct SchemaPopulation {
+    pub external_file_identifications: Vec<Vec<String>>,
+}
+
+/// File population
+///
+/// Following EXPRESS schema is an exerpt from
+/// [ISO-10303-21:2016(E) "8.2.6 file_population"](https://www.iso.org/standard/63141.html):
+///
+/// ```text
+/// ENTITY file_population;
+///   governing_schema     : schema_name;
+///   determination_method : exchange_structure_identifier;
+///   governed_sections    : OPTIONAL SET [1:?] OF section_name;
+/// END_ENTITY;
+/// ```
+#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize)]
+pub struct FilePopulation {
+    pub governing_schema: String,
+    pub determination_method: String,
+    pub governed_sections: Option<Vec<String>>,
+}
+
+/// Section language
+///
+/// Following EXPRESS schema is an exerpt from
+/// [ISO-10303-21:2016(E) "8.2.7 section_language"](https://www.iso.org/standard/63141.html):
+///
+/// ```text
+/// ENTITY section_language;
+///   section          : OPTIONAL section_name;
+///   default_language : language_name;
+/// END_ENTITY;
+/// ```
+#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize)]
+pub struct SectionLanguage {
+    pub section: Option<String>,
+    pub default_language: String,
+}
+
+/// Section context
+///
+/// Following EXPRESS schema is an exerpt from
+/// [ISO-10303-21:2016(E) "8.2.8 section_context"](https://www.iso.org/standard/63141.html):
+///
+/// ```text
+/// ENTITY section_context;
+///   section             : OPTIONAL section_name;
+///   context_identifiers : LIST [1:?] OF context_name;
+/// END_ENTITY;
+/// ```
+#[derive(Debug, Clone, PartialEq, ruststep_derive::Deserialize)]
+pub struct SectionContext {
+    pub section: Option<String>,
+    pub context_identifiers: Vec<String>,
+}
+
+/// An entity instance in HEADER section
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderRecord {
+    FileDescription(FileDescription),
+    FileName(FileName),
+    FileSchema(FileSchema),
+    SchemaPopulation(SchemaPopulation),
+    FilePopulation(FilePopulation),
+    SectionLanguage(SectionLanguage),
+    SectionContext(SectionContext),
+    /// Entity not defined in ISO-10303-21, e.g. user defined header entities,
+    /// is kept as it is.
+    Unknown(Record),
+}
+
+impl HeaderRecord {
+    pub fn from_record(record: &Record) -> Result<Self> {
+        Ok(match record.name.as_str() {
+            "FILE_DESCRIPTION" => {
+                HeaderRecord::FileDescription(FileDescription::deserialize(record)?)
+            }
+            "FILE_NAME" => HeaderRecord::FileName(FileName::deserialize(record)?),
+            "FILE_SCHEMA" => HeaderRecord::FileSchema(FileSchema::deserialize(record)?),
+            "SCHEMA_POPULATION" => {
+                HeaderRecord::SchemaPopulation(SchemaPopulation::deserialize(record)?)
+            }
+            "FILE_POPULATION" => HeaderRecord::FilePopulation(FilePopulation::deserialize(record)?),
+            "SECTION_LANGUAGE" => {
+                HeaderRecord::SectionLanguage(SectionLanguage::deserialize(record)?)
+            }
+            "SECTION_CONTEXT" => HeaderRecord::SectionContext(SectionContext::deserialize(record)?),
+            _ => HeaderRecord::Unknown(record.clone()),
+        })
+    }
+}
+
 /// STEP-file HEADER section
 ///
 /// There is a schema for HEADER section,
@@ -99,24 +222,107 @@ pub struct Header {
     pub file_description: FileDescription,
     pub file_name: FileName,
     pub file_schema: FileSchema,
+    /// Other records in the order of appearance,
+    /// e.g. `FILE_POPULATION` of edition 3 or user defined header entities
+    pub others: Vec<HeaderRecord>,
 }
 
 impl Header {
+    /// Read records in HEADER section
+    ///
+    /// The first `FILE_DESCRIPTION`, `FILE_NAME`, and `FILE_SCHEMA` are stored into the typed fields,
+    /// and the remaining records into [Header::others].
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::MissingHeaderEntity] if one of `FILE_DESCRIPTION`, `FILE_NAME`, or `FILE_SCHEMA` does not exist
+    ///
     pub fn from_records(records: &[Record]) -> Result<Self> {
-        assert!(records.len() >= 3);
-        let file_description = FileDescription::deserialize(&records[0])?;
-        let file_name = FileName::deserialize(&records[1])?;
-        let file_schema = FileSchema::deserialize(&records[2])?;
+        let mut file_description = None;
+        let mut file_name = None;
+        let mut file_schema = None;
+        let mut others = Vec::new();
+        for record in records {
+            match HeaderRecord::from_record(record)? {
+                HeaderRecord::FileDescription(r) if file_description.is_none() => {
+                    file_description = Some(r)
+                }
+                HeaderRecord::FileName(r) if file_name.is_none() => file_name = Some(r),
+                HeaderRecord::FileSchema(r) if file_schema.is_none() => file_schema = Some(r),
+                other => others.push(other),
+            }
+        }
+        let missing = |name: &str| Error::MissingHeaderEntity(name.to_string());
         Ok(Header {
-            file_description,
-            file_name,
-            file_schema,
+            file_description: file_description.ok_or_else(|| missing("FILE_DESCRIPTION"))?,
+            file_name: file_name.ok_or_else(|| missing("FILE_NAME"))?,
+            file_schema: file_schema.ok_or_else(|| missing("FILE_SCHEMA"))?,
+            others,
         })
     }
 }
 
+/// Metadata written into HEADER section, e.g. by `Tables::to_step_string` of espr-generated code
+///
+/// [HeaderInfo::default] fills mandatory attributes with empty strings,
+/// and `FILE_SCHEMA` lists the schema of tables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderInfo {
+    pub file_description: FileDescription,
+    pub file_name: FileName,
+    /// Schema names written in `FILE_SCHEMA`. The schema of tables is used if empty.
+    pub schema: Vec<String>,
+    /// HEADER section written verbatim instead of the other fields, from `HEADER;` to `ENDSEC;`
+    ///
+    /// This keeps the header of the original file as it is,
+    /// e.g. taken by [crate::parser::header_span].
+    pub raw: Option<String>,
+}
+
+impl Default for HeaderInfo {
+    fn default() -> Self {
+        HeaderInfo {
+            file_description: FileDescription {
+                description: vec![String::new()],
+                // Conformance class 1 of ISO-10303-21 edition 2
+                implementation_level: "2;1".to_string(),
+            },
+            file_name: FileName {
+                name: String::new(),
+                time_stamp: String::new(),
+                author: vec![String::new()],
+                organization: vec![String::new()],
+                preprocessor_version: concat!("ruststep ", env!("CARGO_PKG_VERSION")).to_string(),
+                originating_system: String::new(),
+                authorization: String::new(),
+            },
+            schema: Vec::new(),
+            raw: None,
+        }
+    }
+}
+
+impl HeaderInfo {
+    /// Records in HEADER section
+    ///
+    /// `default_schema` is written in `FILE_SCHEMA` in upper case if [HeaderInfo::schema] is empty.
+    pub fn to_records(&self, default_schema: Option<&str>) -> Result<Vec<Record>> {
+        let schema = if self.schema.is_empty() {
+            default_schema.map(str::to_uppercase).into_iter().collect()
+        } else {
+            self.schema.clone()
+        };
+        Ok(vec![
+            to_record(&self.file_description)?,
+            to_record(&self.file_name)?,
+            to_record(&FileSchema { schema })?,
+        ])
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use nom::Finish;
 
     #[test]
@@ -133,6 +339,101 @@ mod tests {
             .finish()
             .unwrap();
         let header = super::Header::from_records(&records).unwrap();
-        dbg!(header);
+        assert!(header.file_schema.contains("automotive_design"));
+        assert!(!header.file_schema.contains("config_control_design"));
+    }
+
+    #[test]
+    fn edition3_header() {
+        let header = r#"
+        HEADER;
+            FILE_DESCRIPTION(('ViewDefinition'), '3;1');
+            FILE_NAME('example.stp', '2021-01-01T00:00:00', ('author'), ('org'), 'pre', 'sys', '');
+            FILE_SCHEMA(('AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF'));
+            FILE_POPULATION('AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF', 'SECTION_BOUNDED', $);
+            SECTION_LANGUAGE($, 'en');
+            SECTION_CONTEXT('DATA1', ('context1', 'context2'));
+            USER_DEFINED_HEADER(1, 'custom');
+        ENDSEC;
+        "#.trim();
+        let (_residual, records) = crate::parser::exchange::header_section(header)
+            .finish()
+            .unwrap();
+        let header = super::Header::from_records(&records).unwrap();
+        assert_eq!(header.file_description.implementation_level, "3;1");
+        assert_eq!(header.file_name.name, "example.stp");
+        assert_eq!(
+            header.file_schema.schema,
+            vec!["AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF"]
+        );
+        assert_eq!(header.others.len(), 4);
+        assert_eq!(
+            header.others[0],
+            HeaderRecord::FilePopulation(FilePopulation {
+                governing_schema: "AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF".to_string(),
+                determination_method: "SECTION_BOUNDED".to_string(),
+                governed_sections: None,
+            })
+        );
+        assert_eq!(
+            header.others[1],
+            HeaderRecord::SectionLanguage(SectionLanguage {
+                section: None,
+                default_language: "en".to_string(),
+            })
+        );
+        assert_eq!(
+            header.others[2],
+            HeaderRecord::SectionContext(SectionContext {
+                section: Some("DATA1".to_string()),
+                context_identifiers: vec!["context1".to_string(), "context2".to_string()],
+            })
+        );
+        assert!(
+            matches!(&header.others[3], HeaderRecord::Unknown(record) if record.name == "USER_DEFINED_HEADER")
+        );
+    }
+
+    #[test]
+    fn description_with_special_characters() {
+        // Semicolons and parentheses in strings are not separators
+        let header = r#"
+        HEADER;
+            FILE_DESCRIPTION(('part; rev (A)', 'It''s done;', ');('), '2;1');
+            FILE_NAME('a;b.stp', '2021-01-01T00:00:00', ('author'), ('org'), 'pre', 'sys', '');
+            FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));
+        ENDSEC;
+        "#
+        .trim();
+        let (residual, records) = crate::parser::exchange::header_section(header)
+            .finish()
+            .unwrap();
+        assert_eq!(residual, "");
+        let header = super::Header::from_records(&records).unwrap();
+        assert_eq!(
+            header.file_description.description,
+            vec!["part; rev (A)", "It's done;", ");("]
+        );
+        assert_eq!(header.file_description.implementation_level, "2;1");
+        assert_eq!(header.file_name.name, "a;b.stp");
+        assert!(header.file_schema.contains("config_control_design"));
+
+        // Written back with escaped apostrophes
+        assert_eq!(
+            records[0].to_string(),
+            "FILE_DESCRIPTION(('part; rev (A)', 'It''s done;', ');('), '2;1')"
+        );
+    }
+
+    #[test]
+    fn missing_header_entity() {
+        let header = "HEADER; FILE_DESCRIPTION(('a'), '2;1'); ENDSEC;";
+        let (_residual, records) = crate::parser::exchange::header_section(header)
+            .finish()
+            .unwrap();
+        assert!(matches!(
+            super::Header::from_records(&records),
+            Err(Error::MissingHeaderEntity(name)) if name == "FILE_NAME"
+        ));
     }
 }
@@ -0,0 +1,98 @@
+//! JSON view of records for debugging and web UIs
+//!
+//! This is not the XML mapping defined in ISO 10303-28,
+//! but a pragmatic conversion of [Parameter] tree into [serde_json::Value]:
+//!
+//! | [Parameter]                  | JSON                      |
+//! |:-----------------------------|:--------------------------|
+//! | `A((1, 2))` typed parameter  | `{"A": [1, 2]}`           |
+//! | integer and real             | number                    |
+//! | string                       | string                    |
+//! | list                         | array                     |
+//! | `#12` reference              | `{"$ref": 12}`            |
+//! | `@12`, `#CONST`, `@CONST`    | `{"$ref": "@12"}` etc.    |
+//! | `.TRUE.` enumeration         | `".TRUE."`                |
+//! | `$` and `*`                  | `null`                    |
+//!
+//! ```
+//! use ruststep::{ast::*, json::*};
+//! use std::str::FromStr;
+//!
+//! let record = Record::from_str("A(1, (2.0, #3), .T.)").unwrap();
+//! assert_eq!(
+//!     record_to_json(&record),
+//!     serde_json::json!({ "A": [1, [2.0, { "$ref": 3 }], ".T."] })
+//! );
+//! ```
+
+use crate::{
+    ast::{ser::to_parameter, *},
+    error::*,
+};
+use serde::ser;
+use serde_json::{Map, Value};
+
+/// Key of a JSON object representing a reference, e.g. `{"$ref": 12}` for `#12`
+pub const REF_KEY: &str = "$ref";
+
+/// Convert [Parameter] into JSON value
+///
+/// Non-finite real numbers, which JSON cannot represent, become `null`.
+pub fn parameter_to_json(parameter: &Parameter) -> Value {
+    match parameter {
+        Parameter::Typed { keyword, parameter } => {
+            single_key(keyword.clone(), parameter_to_json(parameter))
+        }
+        Parameter::Integer(i) => Value::from(*i),
+        Parameter::Real(x) => Value::from(*x),
+        Parameter::String(s) => Value::String(s.clone()),
+        Parameter::List(list) => Value::Array(list.iter().map(parameter_to_json).collect()),
+        Parameter::Ref(name) => name_to_json(name),
+        Parameter::Enumeration(e) => Value::String(format!(".{}.", e)),
+        Parameter::NotProvided | Parameter::Omitted => Value::Null,
+    }
+}
+
+/// Convert [Name] into `{"$ref": ...}` object
+///
+/// Entity instance name `#12` becomes `{"$ref": 12}`,
+/// and others are kept as their STEP notation, e.g. `{"$ref": "@12"}`.
+pub fn name_to_json(name: &Name) -> Value {
+    let target = match name {
+        Name::Entity(id) => Value::from(*id),
+        _ => Value::String(name.to_string()),
+    };
+    single_key(REF_KEY.to_string(), target)
+}
+
+/// Convert [Record] into JSON object, e.g. `{"A": [1, 2]}` for `A(1, 2)`
+pub fn record_to_json(record: &Record) -> Value {
+    single_key(record.name.clone(), parameter_to_json(&record.parameter))
+}
+
+/// Serialize a value, e.g. a holder or owned struct, into JSON through [to_parameter]
+///
+/// References kept in holders are mapped into `{"$ref": id}`,
+/// while owned structs are expanded recursively.
+pub fn to_json(obj: &impl ser::Serialize) -> Result<Value> {
+    Ok(parameter_to_json(&to_parameter(obj)?))
+}
+
+/// Collect records into a JSON object keyed by their ids
+///
+/// This is intended to dump a whole exchange structure with `Tables::all_records`
+/// of espr-generated code.
+pub fn records_to_json(records: impl IntoIterator<Item = Result<(u64, Record)>>) -> Result<Value> {
+    let mut map = Map::new();
+    for record in records {
+        let (id, record) = record?;
+        map.insert(id.to_string(), record_to_json(&record));
+    }
+    Ok(Value::Object(map))
+}
+
+fn single_key(key: String, value: Value) -> Value {
+    let mut map = Map::new();
+    map.insert(key, value);
+    Value::Object(map)
+}
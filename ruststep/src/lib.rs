@@ -15,6 +15,20 @@
 //!
 //! These features are not default.
 //!
+//! `no_std` support
+//! -----------------
+//!
+//! [ast], [express_builtins], [parser], and [primitive] modules only require `alloc`,
+//! and they can be used in `no_std` environment by disabling the default `std` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! ruststep = { version = "*", default-features = false }
+//! ```
+//!
+//! `tables` and `header` modules, and thus the code generated by espr,
+//! require `std` since they use `HashMap`.
+//!
 //! ASCII encoding of exchange structure
 //! -------------------------------------
 //!
@@ -103,17 +117,47 @@
 //! STEP implementation using XML(eXtensible Markup Language) is defined in
 //! [ISO-10303-28](https://www.iso.org/standard/40646.html).
 //!
-//! Not supported yet. See [tracking issue](https://github.com/ricosjp/ruststep/issues/215).
+//! Reading and writing a subset of the late binding form is supported with `xml` feature,
+//! see the document of `xml` module.
 //!
 
 #![deny(rustdoc::broken_intra_doc_links)]
+#![cfg_attr(not(feature = "std"), no_std)]
+// Checking holders in ap203 are `Send` for `rayon` feature requires deep recursion
+#![recursion_limit = "256"]
+
+extern crate alloc;
+// Tests use std even without `std` feature
+#[cfg(test)]
+#[macro_use]
+extern crate std;
 
 pub mod ast;
 pub mod error;
+pub mod express_builtins;
+#[cfg(feature = "std")]
 pub mod header;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod parser;
 pub mod primitive;
+#[cfg(feature = "std")]
 pub mod tables;
+#[cfg(feature = "xml")]
+pub mod xml;
+
+/// Items in std prelude which are not in core prelude
+#[allow(unused_imports)]
+mod prelude {
+    pub use alloc::{
+        borrow::ToOwned,
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+}
 
 // To work generated code by ruststep-derive only with ruststep
 pub use derive_more;
@@ -10,12 +10,13 @@
 //!
 //! and combinators in this submodule responsible for handling them.
 
+use crate::{parser::ParseOptions, prelude::*};
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{char, multispace0, multispace1, none_of},
-    combinator::{not, opt, peek, value},
-    error::VerboseError,
+    character::complete::{char, multispace0, multispace1},
+    combinator::{opt, value},
+    error::{VerboseError, VerboseErrorKind},
     multi::{many0, many1},
     sequence::tuple,
     IResult, Parser,
@@ -46,6 +47,20 @@ pub fn tag_<'a>(name: &'static str) -> impl ExchangeParser<'a, &'a str> {
     }
 }
 
+/// Same as [tag_], but spaces and comments are allowed before the semicolon of tag, e.g. `ENDSEC ;`,
+/// if [ParseOptions::tolerate_spaced_keywords] is set.
+pub fn tag_with<'a>(options: ParseOptions, name: &'static str) -> impl ExchangeParser<'a, &'a str> {
+    move |input: &'a str| {
+        if options.tolerate_spaced_keywords {
+            if let Some(keyword) = name.strip_suffix(';') {
+                let (residual, _) = tuple((tag(keyword), ignorable, char(';'))).parse(input)?;
+                return Ok((residual, &input[..input.len() - residual.len()]));
+            }
+        }
+        tag_(name).parse(input)
+    }
+}
+
 pub fn opt_<'a, O>(f: impl ExchangeParser<'a, O>) -> impl ExchangeParser<'a, Option<O>> {
     move |input| {
         let (input, c) = nom::combinator::opt(f.clone())(input)?;
@@ -61,16 +76,24 @@ pub fn opt_<'a, O>(f: impl ExchangeParser<'a, O>) -> impl ExchangeParser<'a, Opt
 ///
 /// These comments are dropped while parsing. Do not passed to following convert step.
 ///
+/// Comments do not nest, i.e. a comment ends at the first `*/` even if it contains `/*`.
+/// An unterminated comment is a failure with the context [UNTERMINATED_COMMENT] at its `/*`,
+/// which is reported as [crate::error::Error::UnterminatedComment],
+/// instead of backtracking to try other tokens.
+///
 pub fn comment(input: &str) -> ParseResult<String> {
-    let internal = alt((
-        none_of("*"),
-        tuple((char('*'), peek(not(char('/'))))).map(|(star, _not_slash)| star),
-    ));
-    tuple((tag("/*"), many0(internal), tag("*/")))
-        .map(|(_start, c, _end)| c.into_iter().collect())
-        .parse(input)
+    let (rest, _start) = tag("/*")(input)?;
+    match rest.find("*/") {
+        Some(end) => Ok((&rest[end + 2..], rest[..end].to_string())),
+        None => Err(nom::Err::Failure(VerboseError {
+            errors: vec![(input, VerboseErrorKind::Context(UNTERMINATED_COMMENT))],
+        })),
+    }
 }
 
+/// Context of the failure for an unterminated comment, see [comment]
+pub const UNTERMINATED_COMMENT: &str = "unterminated-comment";
+
 /// Comments with front/back spaces, or multi-space at least 1 char
 ///
 /// - This never matches to empty string.
@@ -137,6 +160,22 @@ pub fn comma_separated<'a, O>(f: impl ExchangeParser<'a, O>) -> impl ExchangePar
     separated(',', f)
 }
 
+/// Same as [comma_separated], but a trailing comma e.g. `1, 2,` is also accepted
+/// if [ParseOptions::tolerate_trailing_commas] is set.
+pub fn comma_separated_with<'a, O>(
+    options: ParseOptions,
+    f: impl ExchangeParser<'a, O>,
+) -> impl ExchangeParser<'a, Vec<O>> {
+    move |input| {
+        let (input, values) = comma_separated(f.clone()).parse(input)?;
+        if options.tolerate_trailing_commas {
+            let (input, _trailing) = opt(tuple((ignorable, char(',')))).parse(input)?;
+            return Ok((input, values));
+        }
+        Ok((input, values))
+    }
+}
+
 /// Sequence of separated tokens
 pub fn tuple_<'a, O, List: Tuple<'a, O>>(mut l: List) -> impl ExchangeParser<'a, O> {
     move |input| l.parse(input)
@@ -247,6 +286,27 @@ mod tests {
         let (res, c) = super::comment("/* vim * vim */").finish().unwrap();
         assert_eq!(res, "");
         assert_eq!(c, " vim * vim ");
+
+        // Comments do not nest
+        let (res, c) = super::comment("/* a /* b */ c */").finish().unwrap();
+        assert_eq!(res, " c */");
+        assert_eq!(c, " a /* b ");
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        let err = super::comment("/* stray\n#1 = A();").finish().unwrap_err();
+        assert_eq!(
+            err.errors,
+            vec![(
+                "/* stray\n#1 = A();",
+                VerboseErrorKind::Context(UNTERMINATED_COMMENT)
+            )]
+        );
+
+        // Not backtracked
+        let err = super::ignorable(" /* stray").finish().unwrap_err();
+        assert_eq!(err.errors[0].0, "/* stray");
     }
 
     #[test]
@@ -391,4 +451,50 @@ mod tests {
         assert_eq!(res, "");
         assert_eq!(digits, &['1', '2']);
     }
+
+    #[test]
+    fn trailing_comma() {
+        // trailing comma is left in strict mode
+        let (res, digits) = comma_digit("1, 2,").finish().unwrap();
+        assert_eq!(res, ",");
+        assert_eq!(digits, &['1', '2']);
+
+        let options = ParseOptions {
+            tolerate_trailing_commas: true,
+            ..Default::default()
+        };
+        let (res, digits) = comma_separated_with(options, digit)
+            .parse("1, 2,")
+            .finish()
+            .unwrap();
+        assert_eq!(res, "");
+        assert_eq!(digits, &['1', '2']);
+
+        let (res, digits) = comma_separated_with(options, digit)
+            .parse("1, 2 , /* comment */")
+            .finish()
+            .unwrap();
+        assert_eq!(res, " /* comment */");
+        assert_eq!(digits, &['1', '2']);
+    }
+
+    #[test]
+    fn spaced_keyword() {
+        let strict = ParseOptions::default();
+        assert!(tag_with(strict, "ENDSEC;")
+            .parse("ENDSEC ;")
+            .finish()
+            .is_err());
+
+        let options = ParseOptions {
+            tolerate_spaced_keywords: true,
+            ..Default::default()
+        };
+        let (res, tag) = tag_with(options, "ENDSEC;")
+            .parse("ENDSEC /* end */ ;")
+            .finish()
+            .unwrap();
+        assert_eq!(res, "");
+        assert_eq!(tag, "ENDSEC /* end */ ;");
+    }
 }
@@ -1,76 +1,153 @@
 use crate::{
     ast::*,
-    parser::{combinator::*, exchange::*, token::*},
+    parser::{combinator::*, exchange::*, token::*, ParseOptions},
+    prelude::*,
 };
 use nom::{branch::alt, Parser};
 
 /// data_section = `DATA` \[ `(` [parameter_list] `)` \] `;` [entity_instance_list] `ENDSEC;` .
 pub fn data_section(input: &str) -> ParseResult<DataSection> {
-    tuple_((
-        tag_("DATA"),
-        opt_(tuple_((char_('('), parameter_list, char_(')')))),
-        char_(';'),
-        entity_instance_list,
-        tag_("ENDSEC;"),
-    ))
-    .map(|(_start, meta, _semicolon, entities, _end)| DataSection {
-        meta: meta
-            .map(|(_open, params, _close)| params)
-            .unwrap_or_default(),
-        entities,
-    })
-    .parse(input)
+    data_section_with(ParseOptions::default()).parse(input)
+}
+
+/// [data_section] with [ParseOptions]
+pub fn data_section_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, DataSection> {
+    move |input| {
+        tuple_((
+            tag_("DATA"),
+            opt_(tuple_((
+                char_('('),
+                parameter_list_with(options),
+                char_(')'),
+            ))),
+            char_(';'),
+            entity_instance_list_with(options),
+            tag_with(options, "ENDSEC;"),
+        ))
+        .map(|(_start, meta, _semicolon, entities, _end)| DataSection {
+            meta: meta
+                .map(|(_open, params, _close)| params)
+                .unwrap_or_default(),
+            entities,
+        })
+        .parse(input)
+    }
 }
 
 /// entity_instance_list = { [entity_instance] } .
 pub fn entity_instance_list(input: &str) -> ParseResult<Vec<EntityInstance>> {
-    many0_(entity_instance).parse(input)
+    entity_instance_list_with(ParseOptions::default()).parse(input)
+}
+
+/// [entity_instance_list] with [ParseOptions]
+pub fn entity_instance_list_with<'a>(
+    options: ParseOptions,
+) -> impl ExchangeParser<'a, Vec<EntityInstance>> {
+    move |input| many0_(entity_instance_with(options)).parse(input)
 }
 
 /// entity_instance = [simple_entity_instance] | [complex_entity_instance] .
 pub fn entity_instance(input: &str) -> ParseResult<EntityInstance> {
-    alt((simple_entity_instance, complex_entity_instance)).parse(input)
+    entity_instance_with(ParseOptions::default()).parse(input)
+}
+
+/// [entity_instance] with [ParseOptions]
+pub fn entity_instance_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, EntityInstance> {
+    move |input| {
+        alt((
+            simple_entity_instance_with(options),
+            complex_entity_instance_with(options),
+        ))
+        .parse(input)
+    }
 }
 
 /// simple_entity_instance = [entity_instance_name] `=` [simple_record] `;` .
 pub fn simple_entity_instance(input: &str) -> ParseResult<EntityInstance> {
-    tuple_((entity_instance_name, char_('='), simple_record, char_(';')))
+    simple_entity_instance_with(ParseOptions::default()).parse(input)
+}
+
+/// [simple_entity_instance] with [ParseOptions]
+pub fn simple_entity_instance_with<'a>(
+    options: ParseOptions,
+) -> impl ExchangeParser<'a, EntityInstance> {
+    move |input| {
+        tuple_((
+            entity_instance_name,
+            char_('='),
+            simple_record_with(options),
+            char_(';'),
+        ))
         .map(|(id, _eq, record, _semicolon)| EntityInstance::Simple { id, record })
         .parse(input)
+    }
 }
 
 /// complex_entity_instance = [entity_instance_name] `=` [subsuper_record] `;` .
 pub fn complex_entity_instance(input: &str) -> ParseResult<EntityInstance> {
-    tuple_((
-        entity_instance_name,
-        char_('='),
-        subsuper_record,
-        char_(';'),
-    ))
-    .map(|(id, _eq, subsuper, _semicolon)| EntityInstance::Complex { id, subsuper })
-    .parse(input)
+    complex_entity_instance_with(ParseOptions::default()).parse(input)
+}
+
+/// [complex_entity_instance] with [ParseOptions]
+pub fn complex_entity_instance_with<'a>(
+    options: ParseOptions,
+) -> impl ExchangeParser<'a, EntityInstance> {
+    move |input| {
+        tuple_((
+            entity_instance_name,
+            char_('='),
+            subsuper_record_with(options),
+            char_(';'),
+        ))
+        .map(|(id, _eq, subsuper, _semicolon)| EntityInstance::Complex { id, subsuper })
+        .parse(input)
+    }
 }
 
 /// simple_record = [keyword] `(` \[ [parameter_list] \] `)` .
 pub fn simple_record(input: &str) -> ParseResult<Record> {
-    tuple_((keyword, char_('('), opt_(parameter_list), char_(')')))
+    simple_record_with(ParseOptions::default()).parse(input)
+}
+
+/// [simple_record] with [ParseOptions]
+pub fn simple_record_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, Record> {
+    move |input| {
+        tuple_((
+            keyword,
+            char_('('),
+            opt_(parameter_list_with(options)),
+            char_(')'),
+        ))
         .map(|(name, _open, parameter, _close)| Record {
             name,
             parameter: parameter.unwrap_or_default().into_iter().collect(),
         })
         .parse(input)
+    }
 }
 
 /// simple_record_list = [simple_record] { [simple_record] } .
 pub fn simple_record_list(input: &str) -> ParseResult<Vec<Record>> {
-    many0_(simple_record).parse(input)
+    simple_record_list_with(ParseOptions::default()).parse(input)
+}
+
+/// [simple_record_list] with [ParseOptions]
+pub fn simple_record_list_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, Vec<Record>> {
+    move |input| many0_(simple_record_with(options)).parse(input)
 }
 
 /// subsuper_record = `(` [simple_record_list] `)` .
 pub fn subsuper_record(input: &str) -> ParseResult<SubSuperRecord> {
-    tuple_((char_('('), simple_record_list, char_(')')))
-        .map(|(_open, records, _close)| SubSuperRecord(records))
-        .parse(input)
+    subsuper_record_with(ParseOptions::default()).parse(input)
+}
+
+/// [subsuper_record] with [ParseOptions]
+pub fn subsuper_record_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, SubSuperRecord> {
+    move |input| {
+        tuple_((char_('('), simple_record_list_with(options), char_(')')))
+            .map(|(_open, records, _close)| SubSuperRecord(records))
+            .parse(input)
+    }
 }
 
 #[cfg(test)]
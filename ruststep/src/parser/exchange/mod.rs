@@ -14,9 +14,10 @@ pub use reference::*;
 
 use crate::{
     ast::*,
-    parser::{combinator::*, token::*},
+    parser::{combinator::*, token::*, ParseOptions},
+    prelude::*,
 };
-use nom::Parser;
+use nom::{bytes::complete::take_until, combinator::verify, Parser};
 
 /// exchange_file = `ISO-10303-21;`
 ///                 [header_section]
@@ -26,25 +27,71 @@ use nom::Parser;
 ///                 `END-ISO-10303-21;`
 ///               { signature_section } .
 pub fn exchange_file(input: &str) -> ParseResult<Exchange> {
-    tuple_((
-        tag_("ISO-10303-21;"),
-        header_section,
-        opt_(anchor_section),
-        opt_(reference_section),
-        many0_(data_section),
-        tag_("END-ISO-10303-21;"),
-        many0_(signature_section),
-    ))
-    .map(
-        |(_start, header, anchor, reference, data, _end, signature)| Exchange {
-            header,
-            anchor: anchor.unwrap_or_default(),
-            reference: reference.unwrap_or_default(),
-            data,
-            signature,
-        },
-    )
-    .parse(input)
+    exchange_file_with(ParseOptions::default()).parse(input)
+}
+
+/// [exchange_file] with [ParseOptions]
+///
+/// Sections not defined in ISO-10303-21 are skipped
+/// after the header section if [ParseOptions::tolerate_unknown_sections] is set.
+pub fn exchange_file_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, Exchange> {
+    move |input| {
+        tuple_((
+            tag_("ISO-10303-21;"),
+            skip_unknown_sections(options, header_section_with(options)),
+            opt_(skip_unknown_sections(options, anchor_section_with(options))),
+            opt_(skip_unknown_sections(
+                options,
+                reference_section_with(options),
+            )),
+            many0_(skip_unknown_sections(options, data_section_with(options))),
+            tag_("END-ISO-10303-21;"),
+            many0_(signature_section),
+        ))
+        .map(
+            |(_start, header, anchor, reference, data, _end, signature)| Exchange {
+                header,
+                anchor: anchor.unwrap_or_default(),
+                reference: reference.unwrap_or_default(),
+                data,
+                signature,
+            },
+        )
+        .parse(input)
+    }
+}
+
+/// Apply `f` and then skip following unknown sections if [ParseOptions::tolerate_unknown_sections] is set
+fn skip_unknown_sections<'a, O>(
+    options: ParseOptions,
+    f: impl ExchangeParser<'a, O>,
+) -> impl ExchangeParser<'a, O> {
+    move |input| {
+        let (input, out) = f.clone().parse(input)?;
+        if options.tolerate_unknown_sections {
+            let (input, _sep) = ignorable(input)?;
+            let (input, _skipped) = many0_(unknown_section).parse(input)?;
+            return Ok((input, out));
+        }
+        Ok((input, out))
+    }
+}
+
+/// Section not defined in ISO-10303-21, e.g. `VENDOR_SECTION; ... ENDSEC;`
+///
+/// The contents are discarded without parsing.
+fn unknown_section(input: &str) -> ParseResult<String> {
+    let is_unknown = |name: &String| {
+        !matches!(
+            name.as_str(),
+            "HEADER" | "ANCHOR" | "REFERENCE" | "DATA" | "SIGNATURE"
+        )
+    };
+    let (input, name) = verify(standard_keyword, is_unknown).parse(input)?;
+    let (input, _semicolon) = char_(';').parse(input)?;
+    let (input, _contents) = take_until("ENDSEC;").parse(input)?;
+    let (input, _end) = tag_("ENDSEC;").parse(input)?;
+    Ok((input, name))
 }
 
 /// signature_section  = `SIGNATURE` signature_content `ENDSEC;`.
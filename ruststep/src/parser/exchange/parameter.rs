@@ -1,44 +1,82 @@
 use crate::{
     ast::*,
-    parser::{combinator::*, token::*},
+    parser::{combinator::*, token::*, ParseOptions},
+    prelude::*,
 };
 use nom::{branch::alt, combinator::value, Parser};
 
 /// list = `(` \[ [parameter] { `,` [parameter] } \] `)` .
 pub fn list(input: &str) -> ParseResult<Parameter> {
-    tuple_((char_('('), comma_separated(parameter), char_(')')))
-        .map(|(_open, params, _close)| Parameter::List(params))
+    list_with(ParseOptions::default()).parse(input)
+}
+
+/// [list] with [ParseOptions]
+pub fn list_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, Parameter> {
+    move |input| {
+        tuple_((
+            char_('('),
+            opt_(comma_separated_with(options, parameter_with(options))),
+            char_(')'),
+        ))
+        .map(|(_open, params, _close)| Parameter::List(params.unwrap_or_default()))
         .parse(input)
+    }
 }
 
 /// parameter = [typed_parameter] | [untyped_parameter] | [omitted_parameter] .
 pub fn parameter(input: &str) -> ParseResult<Parameter> {
-    alt((typed_parameter, untyped_parameter, omitted_parameter)).parse(input)
+    parameter_with(ParseOptions::default()).parse(input)
+}
+
+/// [parameter] with [ParseOptions]
+pub fn parameter_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, Parameter> {
+    move |input| {
+        alt((
+            typed_parameter_with(options),
+            untyped_parameter_with(options),
+            omitted_parameter,
+        ))
+        .parse(input)
+    }
 }
 
 /// typed_parameter = [keyword] `(` [parameter] `)` .
 pub fn typed_parameter(input: &str) -> ParseResult<Parameter> {
-    tuple_((keyword, char_('('), parameter, char_(')')))
-        .map(|(name, _open, ty, _close)| Parameter::Typed {
-            keyword: name,
-            parameter: Box::new(ty),
-        })
-        .parse(input)
+    typed_parameter_with(ParseOptions::default()).parse(input)
+}
+
+/// [typed_parameter] with [ParseOptions]
+pub fn typed_parameter_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, Parameter> {
+    move |input| {
+        tuple_((keyword, char_('('), parameter_with(options), char_(')')))
+            .map(|(name, _open, ty, _close)| Parameter::Typed {
+                keyword: name,
+                parameter: Box::new(ty),
+            })
+            .parse(input)
+    }
 }
 
 /// untyped_parameter = `$` | [integer] | [real] | [string] | [rhs_occurrence_name] | [enumeration] | binary | [list] .
 pub fn untyped_parameter(input: &str) -> ParseResult<Parameter> {
-    alt((
-        char_('$').map(|_| Parameter::NotProvided),
-        real.map(Parameter::Real),
-        integer.map(Parameter::Integer),
-        string.map(Parameter::String),
-        rhs_occurrence_name.map(Parameter::Ref),
-        enumeration.map(Parameter::Enumeration),
-        // FIXME binary
-        list,
-    ))
-    .parse(input)
+    untyped_parameter_with(ParseOptions::default()).parse(input)
+}
+
+/// [untyped_parameter] with [ParseOptions]
+pub fn untyped_parameter_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, Parameter> {
+    move |input| {
+        alt((
+            char_('$').map(|_| Parameter::NotProvided),
+            real_with(options).map(Parameter::Real),
+            integer.map(Parameter::Integer),
+            string_with(options).map(Parameter::String),
+            rhs_occurrence_name.map(Parameter::Ref),
+            enumeration.map(Parameter::Enumeration),
+            // FIXME binary
+            list_with(options),
+        ))
+        .parse(input)
+    }
 }
 
 /// omitted_parameter = `*` .
@@ -48,13 +86,67 @@ pub fn omitted_parameter(input: &str) -> ParseResult<Parameter> {
 
 /// parameter_list = [parameter] { `,` [parameter] } .
 pub fn parameter_list(input: &str) -> ParseResult<Vec<Parameter>> {
-    comma_separated(parameter).parse(input)
+    parameter_list_with(ParseOptions::default()).parse(input)
+}
+
+/// [parameter_list] with [ParseOptions]
+pub fn parameter_list_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, Vec<Parameter>> {
+    move |input| comma_separated_with(options, parameter_with(options)).parse(input)
+}
+
+/// Same as [parameter_with], but strings borrow the input as [ParameterRef::String] if possible
+pub fn parameter_ref_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, ParameterRef<'a>> {
+    move |input| {
+        alt((
+            tuple_((keyword, char_('('), parameter_ref_with(options), char_(')'))).map(
+                |(name, _open, ty, _close)| ParameterRef::Typed {
+                    keyword: name,
+                    parameter: Box::new(ty),
+                },
+            ),
+            char_('$').map(|_| ParameterRef::NotProvided),
+            real_with(options).map(ParameterRef::Real),
+            integer.map(ParameterRef::Integer),
+            string_cow_with(options).map(ParameterRef::String),
+            rhs_occurrence_name.map(ParameterRef::Ref),
+            enumeration.map(ParameterRef::Enumeration),
+            // FIXME binary
+            list_ref_with(options),
+            char_('*').map(|_| ParameterRef::Omitted),
+        ))
+        .parse(input)
+    }
+}
+
+/// Same as [list_with], but strings borrow the input as [ParameterRef::String] if possible
+pub fn list_ref_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, ParameterRef<'a>> {
+    move |input| {
+        tuple_((
+            char_('('),
+            opt_(parameter_list_ref_with(options)),
+            char_(')'),
+        ))
+        .map(|(_open, params, _close)| ParameterRef::List(params.unwrap_or_default()))
+        .parse(input)
+    }
+}
+
+/// Same as [parameter_list_with], but strings borrow the input as [ParameterRef::String] if possible
+pub fn parameter_list_ref_with<'a>(
+    options: ParseOptions,
+) -> impl ExchangeParser<'a, Vec<ParameterRef<'a>>> {
+    move |input| comma_separated_with(options, parameter_ref_with(options)).parse(input)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ast::Parameter;
-    use nom::Finish;
+    use crate::{
+        ast::{Name, Parameter},
+        parser::ParseOptions,
+        prelude::*,
+    };
+    use nom::{Finish, Parser};
+    use proptest::prelude::*;
 
     #[test]
     fn untyped_parameter() {
@@ -66,4 +158,102 @@ mod tests {
         assert_eq!(res, "");
         assert_eq!(record, Parameter::real(2.0));
     }
+
+    #[test]
+    fn signed_parameter() {
+        let (res, p) = super::parameter("+1").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(p, Parameter::integer(1));
+
+        let (res, p) = super::parameter("+1.5").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(p, Parameter::real(1.5));
+
+        let (res, p) = super::parameter("-1.0E-3").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(p, Parameter::real(-1.0e-3));
+
+        // Sign bit of negative zero is kept through deserialization
+        let (res, p) = super::parameter("-0.0").finish().unwrap();
+        assert_eq!(res, "");
+        let x: f64 = serde::Deserialize::deserialize(&p).unwrap();
+        assert!(x == 0.0 && x.is_sign_negative());
+    }
+
+    #[test]
+    fn empty_list() {
+        let (res, record) = super::list("()").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(record, Parameter::List(Vec::new()));
+    }
+
+    fn keyword() -> impl Strategy<Value = String> {
+        "[A-Z][A-Z0-9]{0,8}"
+    }
+
+    fn real() -> impl Strategy<Value = f64> {
+        use proptest::num::f64::*;
+        // Every finite values including subnormals and negative zero
+        NORMAL | SUBNORMAL | ZERO | NEGATIVE | POSITIVE
+    }
+
+    fn name() -> impl Strategy<Value = Name> {
+        prop_oneof![
+            any::<u64>().prop_map(Name::Entity),
+            any::<u64>().prop_map(Name::Value),
+            keyword().prop_map(Name::ConstantEntity),
+            keyword().prop_map(Name::ConstantValue),
+        ]
+    }
+
+    /// Arbitrary parameter tree with bounded depth
+    fn parameter() -> impl Strategy<Value = Parameter> {
+        let leaf = prop_oneof![
+            ((i64::MIN + 1)..=i64::MAX).prop_map(Parameter::Integer),
+            real().prop_map(Parameter::Real),
+            prop_oneof!["[a-zA-Z0-9 '\\\\]*", any::<String>()].prop_map(Parameter::String),
+            keyword().prop_map(Parameter::Enumeration),
+            name().prop_map(Parameter::Ref),
+            Just(Parameter::NotProvided),
+            Just(Parameter::Omitted),
+        ];
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..8).prop_map(Parameter::List),
+                (keyword(), inner).prop_map(|(keyword, parameter)| Parameter::Typed {
+                    keyword,
+                    parameter: Box::new(parameter)
+                }),
+            ]
+        })
+    }
+
+    /// Strings are written with control directives, which are decoded by these options
+    fn decode_options() -> ParseOptions {
+        ParseOptions {
+            decode_string_escapes: true,
+            ..Default::default()
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(p in parameter()) {
+            let s = p.to_string();
+            let (res, parsed) = super::parameter_with(decode_options()).parse(&s).finish().unwrap();
+            prop_assert_eq!(res, "");
+            prop_assert_eq!(parsed, p);
+        }
+
+        #[test]
+        fn parameter_ref(p in parameter()) {
+            let s = p.to_string();
+            let (res, parsed) = super::parameter_ref_with(decode_options())
+                .parse(&s)
+                .finish()
+                .unwrap();
+            prop_assert_eq!(res, "");
+            prop_assert_eq!(parsed.into_owned(), p);
+        }
+    }
 }
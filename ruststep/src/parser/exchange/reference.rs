@@ -1,14 +1,28 @@
 use crate::{
     ast::*,
-    parser::{combinator::*, token::*},
+    parser::{combinator::*, token::*, ParseOptions},
+    prelude::*,
 };
 use nom::Parser;
 
 /// reference_section = `REFERENCE;` [reference_list] `ENDSEC;` .
 pub fn reference_section(input: &str) -> ParseResult<Vec<ReferenceEntry>> {
-    tuple_((tag_("REFERENCE;"), reference_list, tag_("ENDSEC;")))
+    reference_section_with(ParseOptions::default()).parse(input)
+}
+
+/// [reference_section] with [ParseOptions]
+pub fn reference_section_with<'a>(
+    options: ParseOptions,
+) -> impl ExchangeParser<'a, Vec<ReferenceEntry>> {
+    move |input| {
+        tuple_((
+            tag_with(options, "REFERENCE;"),
+            reference_list,
+            tag_with(options, "ENDSEC;"),
+        ))
         .map(|(_start, list, _end)| list)
         .parse(input)
+    }
 }
 
 /// reference_list = { [reference()] } .
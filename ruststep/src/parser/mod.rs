@@ -36,9 +36,67 @@ pub mod token;
 
 use crate::{
     ast,
-    error::{Result, TokenizeFailed},
+    error::{Error, Result},
+    prelude::*,
 };
-use nom::Finish;
+use nom::{error::VerboseError, Finish, Parser};
+
+/// Options to configure leniency of the parser
+///
+/// [ParseOptions::default] is strict, i.e. only the syntax defined in ISO-10303-21 is accepted.
+/// Each option tolerates a deviation found in files written by non-conformant exporters:
+///
+/// ```
+/// use ruststep::parser::{parse_with_options, ParseOptions};
+///
+/// let step_str = r#"
+/// ISO-10303-21;
+/// HEADER;
+///   FILE_DESCRIPTION((''), '2;1');
+///   FILE_NAME('', '', (''), (''), '', '', '');
+///   FILE_SCHEMA(('TEST'));
+/// ENDSEC;
+/// DATA;
+///   #1 = A((1.0, .5, ));
+/// ENDSEC;
+/// END-ISO-10303-21;
+/// "#.trim();
+/// assert!(parse_with_options(step_str, &ParseOptions::default()).is_err());
+///
+/// let options = ParseOptions {
+///     strict_reals: false,
+///     tolerate_trailing_commas: true,
+///     ..Default::default()
+/// };
+/// let ex = parse_with_options(step_str, &options).unwrap();
+/// assert_eq!(ex.data[0].entities.len(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    /// Reject reals out of the syntax, e.g. `.5` without integral part or `1.0e-3` with lower case exponent
+    pub strict_reals: bool,
+    /// Accept a trailing comma in lists, e.g. `(#1, #2, )`
+    pub tolerate_trailing_commas: bool,
+    /// Accept spaces and comments before the semicolon of section keywords, e.g. `ENDSEC ;`
+    pub tolerate_spaced_keywords: bool,
+    /// Skip sections not defined in ISO-10303-21, e.g. `VENDOR_SECTION; ... ENDSEC;`
+    pub tolerate_unknown_sections: bool,
+    /// Decode control directives in strings, e.g. `\X2\03B1\X0\` into `α`.
+    /// Strings are kept as written if disabled.
+    pub decode_string_escapes: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            strict_reals: true,
+            tolerate_trailing_commas: false,
+            tolerate_spaced_keywords: false,
+            tolerate_unknown_sections: false,
+            decode_string_escapes: false,
+        }
+    }
+}
 
 /// Parse HEADER section
 ///
@@ -68,14 +126,462 @@ use nom::Finish;
 pub fn parse_header(input: &str) -> Result<(&str, Vec<ast::Record>)> {
     match exchange::header_section(input).finish() {
         Ok((input, records)) => Ok((input, records)),
-        Err(e) => Err(TokenizeFailed::new(input, e).into()),
+        Err(e) => Err(Error::tokenize_failed(input, e)),
+    }
+}
+
+/// Raw text of HEADER section in entire STEP file, from `HEADER;` to `ENDSEC;`
+///
+/// Comments and spaces in the section are kept as written,
+/// e.g. to write the original header verbatim by `HeaderInfo::raw`
+/// when only the data section is modified.
+///
+/// ```
+/// let step_str = r#"
+/// ISO-10303-21;
+/// HEADER;
+///   /* exported by some CAD */
+///   FILE_DESCRIPTION((''), '2;1');
+///   FILE_NAME('', '', (''), (''), '', '', '');
+///   FILE_SCHEMA(('TEST'));
+/// ENDSEC;
+/// DATA;
+/// ENDSEC;
+/// END-ISO-10303-21;
+/// "#;
+///
+/// let header = ruststep::parser::header_span(step_str).unwrap();
+/// assert!(header.starts_with("HEADER;\n  /* exported by some CAD */\n"));
+/// assert!(header.ends_with("FILE_SCHEMA(('TEST'));\nENDSEC;"));
+/// ```
+pub fn header_span(input: &str) -> Result<&str> {
+    match file_header(input).finish() {
+        Ok((_residual, (header, _records))) => Ok(header),
+        Err(e) => Err(Error::tokenize_failed(input, e)),
     }
 }
 
+/// Raw text and records of HEADER section following `ISO-10303-21;`
+fn file_header(input: &str) -> combinator::ParseResult<(&str, Vec<ast::Record>)> {
+    use combinator::{ignorable, tag_};
+    let (start, _) =
+        nom::sequence::tuple((ignorable, tag_("ISO-10303-21;"), ignorable)).parse(input)?;
+    let (residual, records) = exchange::header_section(start)?;
+    Ok((residual, (&start[..start.len() - residual.len()], records)))
+}
+
+/// Read only HEADER section of STEP file, and stop reading at its `ENDSEC;`
+///
+/// The input is read line by line until `ENDSEC` appears out of strings and comments,
+/// i.e. DATA section is neither read nor parsed.
+/// This is useful to scan metadata of many large files:
+///
+/// ```
+/// let step_str = r#"
+/// ISO-10303-21;
+/// HEADER;
+///   FILE_DESCRIPTION((''), '2;1');
+///   FILE_NAME('', '', (''), (''), '', '', '');
+///   FILE_SCHEMA(('TEST'));
+/// ENDSEC;
+/// DATA;
+///   #1 = A(1.0);
+///   #2 = this is not parsed
+/// "#.trim();
+///
+/// let header = ruststep::parser::parse_header_only(step_str.as_bytes()).unwrap();
+/// assert_eq!(header.file_schema.schema, vec!["TEST".to_string()]);
+/// ```
+///
+/// Errors
+/// -------
+/// - [Error::Io] if reading fails, or the input is not valid UTF-8
+/// - [Error::UnexpectedEof] if the input ends before HEADER section is closed
+/// - [Error::MissingHeaderEntity] if a mandatory record is missing, see [crate::header::Header::from_records]
+/// - [Error::TokenizeFailed] for other syntax errors
+///
+#[cfg(feature = "std")]
+pub fn parse_header_only(reader: impl std::io::Read) -> Result<crate::header::Header> {
+    use std::io::BufRead;
+    let mut reader = std::io::BufReader::new(reader);
+    let mut input = String::new();
+    loop {
+        let start = input.len();
+        if reader.read_line(&mut input)? == 0 {
+            return Err(Error::UnexpectedEof);
+        }
+        if !input[start..].contains("ENDSEC") || !section_closed(&input) {
+            continue;
+        }
+        return match file_header(&input).finish() {
+            Ok((_residual, (_header, records))) => crate::header::Header::from_records(&records),
+            Err(e) => Err(Error::tokenize_failed(&input, e)),
+        };
+    }
+}
+
+/// Whether `ENDSEC` appears out of strings and comments, i.e. the first section is closed
+#[cfg(feature = "std")]
+fn section_closed(input: &str) -> bool {
+    let mut rest = input;
+    while let Some(pos) = rest.find(['\'', '/', 'E']) {
+        rest = &rest[pos..];
+        if rest.starts_with("ENDSEC") {
+            return true;
+        }
+        let skipped = if let Some(string) = rest.strip_prefix('\'') {
+            string.find('\'').map(|end| end + 2)
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            comment.find("*/").map(|end| end + 4)
+        } else {
+            Some(1)
+        };
+        match skipped {
+            Some(skipped) => rest = &rest[skipped..],
+            None => return false,
+        }
+    }
+    false
+}
+
 /// Parse entire STEP file
+///
+/// Errors
+/// -------
+/// - [Error::UnexpectedEof] if the input is truncated, e.g. `END-ISO-10303-21;` is missing
+/// - [Error::UnterminatedComment] if a comment is not terminated by `*/`
+/// - [Error::IntegerOverflow] if an integer or an instance name does not fit into 64 bits
+/// - [Error::TokenizeFailed] for other syntax errors
+///
 pub fn parse(input: &str) -> Result<ast::Exchange> {
-    match exchange::exchange_file(input).finish() {
+    parse_with_options(input, &ParseOptions::default())
+}
+
+/// Parse entire STEP file with [ParseOptions]
+///
+/// Errors
+/// -------
+/// Same as [parse]
+///
+pub fn parse_with_options(input: &str, options: &ParseOptions) -> Result<ast::Exchange> {
+    match exchange::exchange_file_with(*options).parse(input).finish() {
         Ok((_residual, ex)) => Ok(ex),
-        Err(e) => Err(TokenizeFailed::new(input, e).into()),
+        Err(e) => {
+            let truncated = stopped_at_eof(&e, *options);
+            match Error::tokenize_failed(input, e) {
+                Error::TokenizeFailed(_) if truncated => Err(Error::UnexpectedEof),
+                err => Err(err),
+            }
+        }
+    }
+}
+
+/// Whether the parser stopped at the end of input, i.e. the input is truncated
+///
+/// Since the tokenizer backtracks to the beginning of the section where parsing failed,
+/// the section, and then the record where the section stopped, are parsed again
+/// to find the position where parsing actually failed.
+fn stopped_at_eof(err: &VerboseError<&str>, options: ParseOptions) -> bool {
+    use exchange::*;
+    let residual = match err.errors.first() {
+        Some((residual, _kind)) => *residual,
+        None => return false,
+    };
+    if at_eof(err) {
+        true
+    } else if residual.starts_with("HEADER") {
+        stops_at_eof(header_section_with(options), residual, |record| {
+            fails_at_eof(header_entity_with(options), record)
+        })
+    } else if residual.starts_with("ANCHOR") {
+        stops_at_eof(anchor_section_with(options), residual, |record| {
+            fails_at_eof(anchor_with(options), record)
+        })
+    } else if residual.starts_with("REFERENCE") {
+        stops_at_eof(reference_section_with(options), residual, |record| {
+            fails_at_eof(reference, record)
+        })
+    } else if residual.starts_with("DATA") {
+        stops_at_eof(data_section_with(options), residual, |record| {
+            fails_at_eof(simple_entity_instance_with(options), record)
+                || fails_at_eof(complex_entity_instance_with(options), record)
+        })
+    } else {
+        false
+    }
+}
+
+/// Whether `section` fails at the end of input, or `record` does at the position where `section` stops
+fn stops_at_eof<'a, S>(
+    mut section: impl Parser<&'a str, S, VerboseError<&'a str>>,
+    input: &'a str,
+    record: impl FnOnce(&'a str) -> bool,
+) -> bool {
+    match section.parse(input).finish() {
+        Ok(_) => false,
+        Err(err) => {
+            at_eof(&err)
+                || err
+                    .errors
+                    .first()
+                    .map_or(false, |(residual, _kind)| record(residual))
+        }
+    }
+}
+
+/// Whether `parser` fails at the end of input
+fn fails_at_eof<'a, O>(
+    mut parser: impl Parser<&'a str, O, VerboseError<&'a str>>,
+    input: &'a str,
+) -> bool {
+    match parser.parse(input).finish() {
+        Ok(_) => false,
+        Err(err) => at_eof(&err),
+    }
+}
+
+fn at_eof(err: &VerboseError<&str>) -> bool {
+    err.errors
+        .iter()
+        .any(|(residual, _kind)| residual.trim().is_empty())
+}
+
+/// Parse entire STEP file given as bytes, e.g. a memory-mapped file
+///
+/// The input is validated as UTF-8 once, and then parsed by [parse] without copying
+/// the whole input into a [String]. This avoids reading a large file into the heap before parsing:
+///
+/// ```no_run
+/// use std::fs::File;
+///
+/// let file = File::open("large_assembly.step").unwrap();
+/// let mmap = unsafe { memmap2::Mmap::map(&file) }.unwrap();
+/// let ex = ruststep::parser::parse_bytes(&mmap).unwrap();
+/// ```
+///
+/// The returned [ast::Exchange] owns its records as [parse] does,
+/// i.e. strings and names in the records are allocated while parsing.
+/// Use [exchange::parameter_ref_with] to parse parameters borrowing strings from the input.
+///
+/// Errors
+/// -------
+/// - [Error::InvalidUtf8] if the input is not a valid UTF-8 sequence
+/// - Same as [parse] for the other cases
+///
+pub fn parse_bytes(input: &[u8]) -> Result<ast::Exchange> {
+    let input = core::str::from_utf8(input).map_err(|e| Error::InvalidUtf8 {
+        valid_up_to: e.valid_up_to(),
+    })?;
+    parse(input)
+}
+
+/// Decompress the input if it is compressed by gzip, e.g. `*.stp.gz` or `*.stpZ`
+///
+/// Gzip is detected by its magic bytes `1F 8B` instead of the file extension,
+/// and the other input is returned as it is.
+///
+/// ```
+/// let step = b"DATA;\n#1 = A(1.0);\nENDSEC;\n";
+/// assert_eq!(ruststep::parser::decompress(step).unwrap().as_ref(), step);
+/// ```
+///
+/// Errors
+/// -------
+/// - [Error::Io] if the gzip stream is broken
+///
+#[cfg(feature = "flate2")]
+pub fn decompress(input: &[u8]) -> Result<alloc::borrow::Cow<'_, [u8]>> {
+    use std::io::Read;
+    if !input.starts_with(&[0x1f, 0x8b]) {
+        return Ok(alloc::borrow::Cow::Borrowed(input));
+    }
+    let mut decompressed = Vec::new();
+    flate2_crate::read::MultiGzDecoder::new(input).read_to_end(&mut decompressed)?;
+    Ok(alloc::borrow::Cow::Owned(decompressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+
+    const HEADER: &str = r#"
+HEADER;
+  FILE_DESCRIPTION((''), '2;1');
+  FILE_NAME('', '', (''), (''), '', '', '');
+  FILE_SCHEMA(('TEST'));
+ENDSEC;
+"#;
+
+    #[test]
+    fn complete() {
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n#1 = A(1.0);\nENDSEC;\nEND-ISO-10303-21;\n",
+            HEADER
+        );
+        let ex = super::parse(&input).unwrap();
+        assert_eq!(ex.data.len(), 1);
+    }
+
+    #[test]
+    fn truncated() {
+        // Cut off in a record
+        let input = format!("ISO-10303-21;{}DATA;\n#1 = A(1.0);\n#2 = A(", HEADER);
+        assert!(matches!(super::parse(&input), Err(Error::UnexpectedEof)));
+
+        // Missing ENDSEC;
+        let input = format!("ISO-10303-21;{}DATA;\n#1 = A(1.0);\n", HEADER);
+        assert!(matches!(super::parse(&input), Err(Error::UnexpectedEof)));
+
+        // Missing END-ISO-10303-21;
+        let input = format!("ISO-10303-21;{}DATA;\n#1 = A(1.0);\nENDSEC;\n", HEADER);
+        assert!(matches!(super::parse(&input), Err(Error::UnexpectedEof)));
+    }
+
+    #[test]
+    fn bytes() {
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n#1 = A('é');\nENDSEC;\nEND-ISO-10303-21;\n",
+            HEADER
+        );
+        let ex = super::parse_bytes(input.as_bytes()).unwrap();
+        assert_eq!(ex, super::parse(&input).unwrap());
+
+        let mut input = input.into_bytes();
+        let offset = input.iter().position(|&b| !b.is_ascii()).unwrap();
+        input[offset] = 0xff;
+        assert!(matches!(
+            super::parse_bytes(&input),
+            Err(Error::InvalidUtf8 { valid_up_to }) if valid_up_to == offset
+        ));
+    }
+
+    #[test]
+    fn syntax_error() {
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n#1 = A(1.0)\nENDSEC;\nEND-ISO-10303-21;\n",
+            HEADER
+        );
+        assert!(matches!(
+            super::parse(&input),
+            Err(Error::TokenizeFailed(_))
+        ));
+
+        // Syntax error in the middle of a file without the terminator is not regarded as truncated
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n#1 = A(1.0)\n#2 = A(2.0);\nENDSEC;\n",
+            HEADER
+        );
+        assert!(matches!(
+            super::parse(&input),
+            Err(Error::TokenizeFailed(_))
+        ));
+    }
+
+    #[test]
+    fn integer_overflow() {
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n#1 = A(1, 9223372036854775808);\nENDSEC;\nEND-ISO-10303-21;\n",
+            HEADER
+        );
+        match super::parse(&input) {
+            Err(Error::IntegerOverflow {
+                literal,
+                line,
+                column,
+            }) => {
+                assert_eq!(literal, "9223372036854775808");
+                assert_eq!((line, column), (8, 11));
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // Overflowing instance name is not wrapped
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n#18446744073709551616 = A(1);\nENDSEC;\nEND-ISO-10303-21;\n",
+            HEADER
+        );
+        match super::parse(&input) {
+            Err(Error::IntegerOverflow { literal, line, .. }) => {
+                assert_eq!(literal, "#18446744073709551616");
+                assert_eq!(line, 8);
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // Minimum value of `i64` is still accepted
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n#1 = A(-9223372036854775808);\nENDSEC;\nEND-ISO-10303-21;\n",
+            HEADER
+        );
+        assert!(super::parse(&input).is_ok());
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n#1 = A(1.0);\n  /* stray\n#2 = A(2.0);\nENDSEC;\nEND-ISO-10303-21;\n",
+            HEADER
+        );
+        assert!(matches!(
+            super::parse(&input),
+            Err(Error::UnterminatedComment { line: 9, column: 3 })
+        ));
+
+        // at the end of file
+        let input = format!("ISO-10303-21;{}DATA;\n/*", HEADER);
+        assert!(matches!(
+            super::parse(&input),
+            Err(Error::UnterminatedComment { line: 8, column: 1 })
+        ));
+
+        // Comments do not nest
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n/* /* nested */ */\nENDSEC;\nEND-ISO-10303-21;\n",
+            HEADER
+        );
+        assert!(matches!(
+            super::parse(&input),
+            Err(Error::TokenizeFailed(_))
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn header_only() {
+        use std::io::Read;
+
+        // DATA section is not read, i.e. the failing reader is never reached
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "must not be read",
+                ))
+            }
+        }
+        let input = format!("ISO-10303-21;{}DATA;\n#1 = A(1.0);\n", HEADER);
+        let header = super::parse_header_only(input.as_bytes().chain(Failing)).unwrap();
+        assert_eq!(header.file_schema.schema, vec!["TEST".to_string()]);
+
+        // `ENDSEC;` in a string does not close HEADER section
+        let input = format!(
+            "ISO-10303-21;{}DATA;\n",
+            HEADER.replace("FILE_NAME(''", "FILE_NAME('ENDSEC;\n'")
+        );
+        let header = super::parse_header_only(input.as_bytes()).unwrap();
+        assert_eq!(header.file_name.name, "ENDSEC;\n");
+
+        let input = "ISO-10303-21;\nHEADER;\n  FILE_DESCRIPTION((''), '2;1');\n";
+        assert!(matches!(
+            super::parse_header_only(input.as_bytes()),
+            Err(Error::UnexpectedEof)
+        ));
+
+        let input = format!("ISO-10303-21;{}", HEADER.replace("'2;1')", "'2;1'"));
+        assert!(matches!(
+            super::parse_header_only(input.as_bytes()),
+            Err(Error::TokenizeFailed(_))
+        ));
     }
 }
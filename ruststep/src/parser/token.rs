@@ -2,12 +2,14 @@
 
 use crate::{
     ast::*,
-    parser::{basic::*, combinator::*},
+    parser::{basic::*, combinator::*, ParseOptions},
+    prelude::*,
 };
+use alloc::borrow::Cow;
 use nom::{
     branch::alt,
-    character::complete::{char, digit0, digit1, multispace0, none_of, satisfy},
-    combinator::opt,
+    character::complete::{char, digit0, digit1, multispace0, none_of, one_of, satisfy},
+    combinator::{opt, recognize, verify},
     multi::{many0, many1},
     sequence::tuple,
     Parser,
@@ -19,58 +21,181 @@ pub fn sign(input: &str) -> ParseResult<char> {
 }
 
 /// integer = \[ [sign] \] [digit] { [digit] } .
+///
+/// An integer not fitting into `i64` is a failure with the context [INTEGER_OVERFLOW].
 pub fn integer(input: &str) -> ParseResult<i64> {
-    tuple((opt(sign), multispace0, digit1))
-        .map(|(sign, _space, numbers)| {
-            let num: i64 = numbers.parse().expect("Failed to parse into integer");
-            match sign {
-                Some('-') => -num,
-                _ => num,
-            }
-        })
-        .parse(input)
+    let (residual, (sign, _space, numbers)) =
+        tuple((opt(sign), multispace0, digit1)).parse(input)?;
+    // Parse as `i128` to accept `i64::MIN`, whose absolute value exceeds `i64::MAX`
+    let num = numbers
+        .parse::<i128>()
+        .ok()
+        .map(|num| if sign == Some('-') { -num } else { num })
+        .and_then(|num| i64::try_from(num).ok())
+        .ok_or_else(|| integer_overflow(input))?;
+    Ok((residual, num))
 }
 
 /// `E` \[ [sign] \] [digit] { [digit] } .
-fn exponent(input: &str) -> ParseResult<i64> {
-    tuple((char('E'), multispace0, opt(sign), multispace0, digit1))
-        .map(|(_e, _sp1, sign, _sp2, digit)| {
-            let num: i64 = digit.parse().expect("Failed to parse integer in exponent");
-            match sign {
-                Some('-') => -num,
-                _ => num,
-            }
-        })
-        .parse(input)
+///
+/// Lower case `e` is also accepted if `strict` is false since it appears in files written by some vendors.
+fn exponent<'a>(strict: bool) -> impl ExchangeParser<'a, i64> {
+    move |input| {
+        let e = if strict { "E" } else { "Ee" };
+        let (residual, (_e, _sp1, sign, _sp2, digit)) =
+            tuple((one_of(e), multispace0, opt(sign), multispace0, digit1)).parse(input)?;
+        let num: i64 = digit.parse().map_err(|_| integer_overflow(input))?;
+        match sign {
+            Some('-') => Ok((residual, -num)),
+            _ => Ok((residual, num)),
+        }
+    }
 }
 
 /// real = \[ [sign] \] [digit] { [digit] } `.` { [digit] } \[ `E` \[ [sign] \] [digit] { [digit] } \] .
+///
+/// The sign is kept for zero, i.e. `-0.0` is parsed into negative zero.
 pub fn real(input: &str) -> ParseResult<f64> {
-    tuple((
-        opt(sign),
-        multispace0,
-        digit1,
-        char('.'),
-        digit0,
-        opt(exponent),
-    ))
-    .map(|(sign, _space, integral, _point, fractional, exp)| {
-        let num: f64 = format!("{}.{}e{}", integral, fractional, exp.unwrap_or(0))
-            .parse()
-            .expect("Failed to parse Float");
-        match sign {
-            Some('-') => -num,
-            _ => num,
-        }
-    })
-    .parse(input)
+    real_with(ParseOptions::default()).parse(input)
+}
+
+/// Same as [real], but if [ParseOptions::strict_reals] is false,
+/// a real without integral part like `.5` is accepted,
+/// and the exponent may be written by lower case `e`.
+pub fn real_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, f64> {
+    move |input| {
+        let strict = options.strict_reals;
+        let integral = tuple((digit1, char('.'), digit0))
+            .map(|(integral, _point, fractional)| (integral, fractional));
+        let fractional_only = verify(tuple((char('.'), digit1)), move |_| !strict)
+            .map(|(_point, fractional)| ("0", fractional));
+        let mantissa = alt((integral, fractional_only));
+        tuple((opt(sign), multispace0, mantissa, opt(exponent(strict))))
+            .map(|(sign, _space, (integral, fractional), exp)| {
+                let num: f64 = format!("{}.{}e{}", integral, fractional, exp.unwrap_or(0))
+                    .parse()
+                    .expect("Failed to parse Float");
+                match sign {
+                    Some('-') => -num,
+                    _ => num,
+                }
+            })
+            .parse(input)
+    }
 }
 
 /// string = `'` { [special] | [digit] | [space] | [lower] | [upper] | high_codepoint | [apostrophe] [apostrophe] | [reverse_solidus] [reverse_solidus] | control_directive } `'` .
+///
+/// Apostrophe in string is escaped by doubling, i.e. `'It''s'` is parsed into `It's`.
+/// Other control directives are kept as written, see [string_with] to decode them.
 pub fn string(input: &str) -> ParseResult<String> {
-    tuple((char('\''), many0(none_of("'")), char('\'')))
-        .map(|(_start, s, _end)| s.iter().collect())
-        .parse(input)
+    string_with(ParseOptions::default()).parse(input)
+}
+
+/// Same as [string], but control directives are decoded if [ParseOptions::decode_string_escapes] is set:
+///
+/// - `\\` into `\`
+/// - `\S\c` into the character of `c` shifted by 0x80 in ISO 8859
+/// - `\X\hh` into U+00hh
+/// - `\X2\hhhh...\X0\` and `\X4\hhhhhhhh...\X0\` into UCS-2 and UCS-4 characters
+/// - `\P?\` is dropped since only ISO 8859-1 is supported
+///
+/// Invalid directives are rejected.
+pub fn string_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, String> {
+    move |input: &'a str| {
+        let (input, s) = string_cow_with(options).parse(input)?;
+        Ok((input, s.into_owned()))
+    }
+}
+
+/// Same as [string_with], but borrows the input without allocation
+/// if the string contains nothing to be unescaped, i.e. doubled apostrophes `''`,
+/// and control directives when [ParseOptions::decode_string_escapes] is set.
+///
+/// ```
+/// use nom::{Finish, Parser};
+/// use ruststep::parser::{token::string_cow_with, ParseOptions};
+/// use std::borrow::Cow;
+///
+/// let (_, s) = string_cow_with(ParseOptions::default()).parse("'vim'").finish().unwrap();
+/// assert!(matches!(s, Cow::Borrowed("vim")));
+///
+/// let (_, s) = string_cow_with(ParseOptions::default()).parse("'It''s'").finish().unwrap();
+/// assert!(matches!(s, Cow::Owned(s) if s == "It's"));
+/// ```
+pub fn string_cow_with<'a>(options: ParseOptions) -> impl ExchangeParser<'a, Cow<'a, str>> {
+    move |input: &'a str| {
+        let apostrophe = tuple((char('\''), char('\''))).map(|_| '\'');
+        let (input, (_start, s, _end)) = tuple((
+            char('\''),
+            recognize(many0(alt((none_of("'"), apostrophe)))),
+            char('\''),
+        ))
+        .parse(input)?;
+        let s = if s.contains("''") {
+            Cow::Owned(s.replace("''", "'"))
+        } else {
+            Cow::Borrowed(s)
+        };
+        if !options.decode_string_escapes || !s.contains('\\') {
+            return Ok((input, s));
+        }
+        match decode_control_directives(&s) {
+            Some(decoded) => Ok((input, Cow::Owned(decoded))),
+            None => Err(nom::Err::Failure(nom::error::VerboseError {
+                errors: vec![(
+                    input,
+                    nom::error::VerboseErrorKind::Context("invalid-control-directive"),
+                )],
+            })),
+        }
+    }
+}
+
+fn decode_control_directives(s: &str) -> Option<String> {
+    let hex = |h: &str| u32::from_str_radix(h, 16).ok().and_then(char::from_u32);
+    let mut decoded = String::new();
+    let mut rest = s;
+    while let Some(pos) = rest.find('\\') {
+        decoded.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if let Some(r) = rest.strip_prefix("\\\\") {
+            decoded.push('\\');
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("\\S\\") {
+            let c = r.chars().next()?;
+            decoded.push(char::from_u32(c as u32 + 0x80)?);
+            rest = &r[c.len_utf8()..];
+        } else if let Some(r) = rest.strip_prefix("\\X\\") {
+            decoded.push(hex(r.get(..2)?)?);
+            rest = &r[2..];
+        } else if let Some(r) = rest.strip_prefix("\\X2\\") {
+            let (body, r) = r.split_once("\\X0\\")?;
+            if body.len() % 4 != 0 {
+                return None;
+            }
+            for i in (0..body.len()).step_by(4) {
+                decoded.push(hex(body.get(i..i + 4)?)?);
+            }
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("\\X4\\") {
+            let (body, r) = r.split_once("\\X0\\")?;
+            if body.len() % 8 != 0 {
+                return None;
+            }
+            for i in (0..body.len()).step_by(8) {
+                decoded.push(hex(body.get(i..i + 8)?)?);
+            }
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix("\\P") {
+            let r = r.get(1..)?.strip_prefix('\\')?;
+            rest = r;
+        } else {
+            return None;
+        }
+    }
+    decoded.push_str(rest);
+    Some(decoded)
 }
 
 /// resource = `<` UNIVERSAL_RESOURCE_IDENTIFIER `>` .
@@ -89,12 +214,18 @@ pub fn enumeration(input: &str) -> ParseResult<String> {
         .parse(input)
 }
 
-// Root error for u64 overflow
-//
-// FIXME Though it works, should we use `VerboseErrorKind::Context` for this usage?
-fn u64_overflow(input: &str) -> nom::Err<nom::error::VerboseError<&str>> {
+/// Context of the failure for an integer or an instance name too large to be represented,
+/// which is reported as [crate::error::Error::IntegerOverflow]
+pub const INTEGER_OVERFLOW: &str = "integer-overflow";
+
+// Root error for the overflowing integer at the beginning of `input`,
+// which is not backtracked to avoid reading it as other tokens.
+fn integer_overflow(input: &str) -> nom::Err<nom::error::VerboseError<&str>> {
     nom::Err::Failure(nom::error::VerboseError {
-        errors: vec![(input, nom::error::VerboseErrorKind::Context("u64-overflow"))],
+        errors: vec![(
+            input,
+            nom::error::VerboseErrorKind::Context(INTEGER_OVERFLOW),
+        )],
     })
 }
 
@@ -111,13 +242,13 @@ fn u64_overflow(input: &str) -> nom::Err<nom::error::VerboseError<&str>> {
 /// - FIXME: If the input cannot be represented by `u64`, i.e. larger than [std::u64::MAX]
 ///
 pub fn entity_instance_name(input: &str) -> ParseResult<u64> {
-    let (input, name) = tuple((char('#'), digit1))
+    let (residual, name) = tuple((char('#'), digit1))
         .map(|(_sharp, name): (_, &str)| name.parse())
         .parse(input)?;
     if let Ok(name) = name {
-        Ok((input, name))
+        Ok((residual, name))
     } else {
-        Err(u64_overflow(input))
+        Err(integer_overflow(input))
     }
 }
 
@@ -130,13 +261,13 @@ pub fn entity_instance_name(input: &str) -> ParseResult<u64> {
 /// - FIXME: If the input cannot be represented by `u64`, i.e. larger than [std::u64::MAX]
 ///
 pub fn value_instance_name(input: &str) -> ParseResult<u64> {
-    let (input, name) = tuple((char('@'), digit1))
+    let (residual, name) = tuple((char('@'), digit1))
         .map(|(_sharp, name): (_, &str)| name.parse())
         .parse(input)?;
     if let Ok(name) = name {
-        Ok((input, name))
+        Ok((residual, name))
     } else {
-        Err(u64_overflow(input))
+        Err(integer_overflow(input))
     }
 }
 
@@ -199,9 +330,12 @@ pub fn standard_keyword(input: &str) -> ParseResult<String> {
 }
 
 /// user_defined_keyword = `!` [upper] { [upper] | [digit] } .
+///
+/// The leading `!` is kept in the result to distinguish it from [standard_keyword],
+/// e.g. `!MY_ENTITY` is parsed into `"!MY_ENTITY"`.
 pub fn user_defined_keyword(input: &str) -> ParseResult<String> {
     tuple((char('!'), standard_keyword))
-        .map(|(_e, name)| name)
+        .map(|(e, name)| format!("{}{}", e, name))
         .parse(input)
 }
 
@@ -225,7 +359,17 @@ pub fn signature_content(input: &str) -> ParseResult<String> {
 
 #[cfg(test)]
 mod tests {
-    use nom::Finish;
+    use crate::parser::ParseOptions;
+    use alloc::borrow::Cow;
+    use nom::{error::VerboseErrorKind, Finish, Parser};
+
+    fn lenient() -> ParseOptions {
+        ParseOptions {
+            strict_reals: false,
+            decode_string_escapes: true,
+            ..Default::default()
+        }
+    }
 
     #[test]
     fn real() {
@@ -252,11 +396,177 @@ mod tests {
         assert!(super::real("123").finish().is_err());
     }
 
+    #[test]
+    fn integer_overflow() {
+        let (_, n) = super::integer("9223372036854775807").finish().unwrap();
+        assert_eq!(n, i64::MAX);
+        let (_, n) = super::integer("-9223372036854775808").finish().unwrap();
+        assert_eq!(n, i64::MIN);
+
+        for input in [
+            "9223372036854775808",
+            "-9223372036854775809",
+            "100000000000000000000000000000000000000000",
+        ] {
+            let err = super::integer(input).finish().unwrap_err();
+            assert_eq!(
+                err.errors,
+                vec![(input, VerboseErrorKind::Context(super::INTEGER_OVERFLOW))],
+            );
+        }
+
+        // Exponent is also an integer
+        assert!(super::real("1.0E9223372036854775808").finish().is_err());
+    }
+
+    #[test]
+    fn signed() {
+        for (input, expected) in [("+1", 1), ("-1", -1), ("1", 1), ("-0", 0)] {
+            let (res, n) = super::integer(input).finish().unwrap();
+            assert_eq!(res, "", "{}", input);
+            assert_eq!(n, expected, "{}", input);
+        }
+
+        for (input, expected) in [
+            ("+1.0", 1.0),
+            ("-1.0", -1.0),
+            ("+1.5", 1.5),
+            ("-1.0E-3", -1.0e-3),
+            ("+1.0E+3", 1.0e3),
+        ] {
+            let (res, x) = super::real(input).finish().unwrap();
+            assert_eq!(res, "", "{}", input);
+            assert_eq!(x, expected, "{}", input);
+        }
+
+        // Negative zero keeps its sign bit
+        let (_, x) = super::real("-0.0").finish().unwrap();
+        assert!(x == 0.0 && x.is_sign_negative());
+        let (_, x) = super::real("+0.0").finish().unwrap();
+        assert!(x == 0.0 && x.is_sign_positive());
+
+        // Sign is unary, and cannot be repeated
+        assert!(super::integer("+-1").finish().is_err());
+        assert!(super::real("--1.0").finish().is_err());
+    }
+
+    #[test]
+    fn real_forms() {
+        for (input, expected) in [
+            // trailing dot
+            ("1.", 1.0),
+            ("-1.", -1.0),
+            ("1.E3", 1e3),
+            // leading dot
+            (".5", 0.5),
+            ("-.5", -0.5),
+            (".5E-2", 0.5e-2),
+            // signed and unsigned exponent
+            ("1.0E+10", 1e10),
+            ("1.0E10", 1e10),
+            ("1.0E-3", 1e-3),
+            // lower case exponent
+            ("1.0e-3", 1e-3),
+            ("2.e+2", 2e2),
+        ] {
+            let (res, s) = super::real_with(lenient()).parse(input).finish().unwrap();
+            assert_eq!(res, "", "{}", input);
+            assert_eq!(s, expected, "{}", input);
+        }
+
+        assert!(super::real_with(lenient()).parse(".").finish().is_err());
+        assert!(super::real_with(lenient()).parse("E3").finish().is_err());
+
+        // rejected by default
+        assert!(super::real(".5").finish().is_err());
+        assert!(super::real("-.5").finish().is_err());
+        let (res, _) = super::real("1.0e-3").finish().unwrap();
+        assert_eq!(res, "e-3");
+    }
+
+    #[test]
+    fn keyword() {
+        let (res, s) = super::keyword("CARTESIAN_POINT").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(s, "CARTESIAN_POINT");
+
+        let (res, s) = super::keyword("!MY_POINT2").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(s, "!MY_POINT2");
+
+        assert!(super::keyword("!").finish().is_err());
+    }
+
     #[test]
     fn string() {
         let (res, s) = super::string("'vim'").finish().unwrap();
         assert_eq!(res, "");
         assert_eq!(s, "vim");
+
+        let (res, s) = super::string("'It''s'").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(s, "It's");
+
+        let (res, s) = super::string("''''").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(s, "'");
+    }
+
+    #[test]
+    fn string_escapes() {
+        // kept as written by default
+        let (res, s) = super::string(r"'\X2\03B1\X0\'").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(s, r"\X2\03B1\X0\");
+
+        for (input, expected) in [
+            (r"'a\\b'", r"a\b"),
+            (r"'\S\D'", "Ä"),
+            (r"'\X\E9t\X\E9'", "été"),
+            (r"'\X2\03B103B2\X0\'", "αβ"),
+            (r"'\X4\0001F980\X0\'", "🦀"),
+            (r"'\PA\abc'", "abc"),
+            (r"'It''s'", "It's"),
+        ] {
+            let (res, s) = super::string_with(lenient()).parse(input).finish().unwrap();
+            assert_eq!(res, "", "{}", input);
+            assert_eq!(s, expected, "{}", input);
+        }
+
+        assert!(super::string_with(lenient())
+            .parse(r"'\X2\03B\X0\'")
+            .finish()
+            .is_err());
+        assert!(super::string_with(lenient())
+            .parse(r"'\Q\'")
+            .finish()
+            .is_err());
+    }
+
+    #[test]
+    fn string_cow() {
+        for (input, expected, borrowed) in [
+            ("'vim'", "vim", true),
+            ("''", "", true),
+            (r"'a\\b'", r"a\b", false),
+            (r"'\X2\03B1\X0\'", "α", false),
+            ("'It''s'", "It's", false),
+        ] {
+            let (res, s) = super::string_cow_with(lenient())
+                .parse(input)
+                .finish()
+                .unwrap();
+            assert_eq!(res, "", "{}", input);
+            assert_eq!(s, expected, "{}", input);
+            assert_eq!(matches!(s, Cow::Borrowed(_)), borrowed, "{}", input);
+        }
+
+        // control directives are kept as written, and borrowed by default
+        let (_, s) = super::string_cow_with(ParseOptions::default())
+            .parse(r"'\X2\03B1\X0\'")
+            .finish()
+            .unwrap();
+        assert!(matches!(s, Cow::Borrowed(r"\X2\03B1\X0\")));
     }
 
     #[test]
@@ -0,0 +1,116 @@
+use super::Logical;
+use crate::{
+    ast::{Name, Parameter},
+    prelude::*,
+};
+use alloc::rc::Rc;
+
+/// Compare values with tolerance for `REAL` values
+///
+/// Two values are approximately equal if they have the same structure,
+/// and each pair of corresponding real numbers differs at most `tol`.
+/// Other primitive values are compared exactly by [PartialEq].
+/// Since owned entities keep the referenced entities by value or by `Rc`,
+/// references are compared structurally.
+///
+/// This trait is implemented for entity structs, defined types, and select types
+/// generated by espr via `#[derive(ApproxEq)]`.
+///
+/// ```
+/// use ruststep::primitive::ApproxEq;
+///
+/// assert!(1.0.approx_eq(&1.05, 0.1));
+/// assert!(!1.0.approx_eq(&1.2, 0.1));
+/// assert!(vec![1.0, 2.0].approx_eq(&vec![1.01, 1.99], 0.1));
+/// // Different length
+/// assert!(!vec![1.0, 2.0].approx_eq(&vec![1.0], 0.1));
+/// // Different variant
+/// assert!(!Some(1.0).approx_eq(&None, 0.1));
+/// ```
+pub trait ApproxEq {
+    fn approx_eq(&self, other: &Self, tol: f64) -> bool;
+}
+
+impl ApproxEq for f64 {
+    fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+        // `f64::abs` is not available in `core`
+        let diff = if self > other {
+            self - other
+        } else {
+            other - self
+        };
+        self == other || diff <= tol
+    }
+}
+
+macro_rules! impl_exact {
+    ($($ty:ty),*) => {
+        $(
+        impl ApproxEq for $ty {
+            fn approx_eq(&self, other: &Self, _tol: f64) -> bool {
+                self == other
+            }
+        }
+        )*
+    };
+}
+
+impl_exact!(i64, bool, String, Logical, Name);
+
+impl<T: ApproxEq + ?Sized> ApproxEq for Box<T> {
+    fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+        (**self).approx_eq(&**other, tol)
+    }
+}
+
+impl<T: ApproxEq + ?Sized> ApproxEq for Rc<T> {
+    fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+        (**self).approx_eq(&**other, tol)
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for Option<T> {
+    fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.approx_eq(b, tol),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for [T] {
+    fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.approx_eq(b, tol))
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for Vec<T> {
+    fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+        self.as_slice().approx_eq(other.as_slice(), tol)
+    }
+}
+
+impl<T: ApproxEq, const N: usize> ApproxEq for [T; N] {
+    fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+        self[..].approx_eq(&other[..], tol)
+    }
+}
+
+/// References are compared by their names, i.e. entity ids
+impl ApproxEq for Parameter {
+    fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+        match (self, other) {
+            (
+                Parameter::Typed { keyword, parameter },
+                Parameter::Typed {
+                    keyword: other_keyword,
+                    parameter: other_parameter,
+                },
+            ) => keyword == other_keyword && parameter.approx_eq(other_parameter, tol),
+            (Parameter::Real(a), Parameter::Real(b)) => a.approx_eq(b, tol),
+            (Parameter::List(a), Parameter::List(b)) => a.approx_eq(b, tol),
+            _ => self == other,
+        }
+    }
+}
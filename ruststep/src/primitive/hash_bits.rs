@@ -0,0 +1,160 @@
+use super::Logical;
+use crate::prelude::*;
+use alloc::rc::Rc;
+use core::hash::{Hash, Hasher};
+
+/// Hash and compare values with `REAL` values by their bit patterns
+///
+/// Since `f64` implements neither [Hash] nor [Eq],
+/// entity structs containing real-valued attributes cannot be used as keys of hash maps.
+/// This trait hashes and compares real numbers by [f64::to_bits] instead,
+/// i.e. `0.0` and `-0.0` are different, and `NaN`s with the same bit pattern are equal.
+/// Other primitive values are hashed by [Hash] and compared by [PartialEq].
+///
+/// This trait is implemented for entity structs, defined types, and select types
+/// generated by espr via `#[derive(HashBits)]`.
+/// Wrap values by [BitHashed] to use them as keys of hash maps.
+///
+/// ```
+/// use ruststep::primitive::HashBits;
+///
+/// assert!(1.0.eq_bits(&1.0));
+/// assert!(!0.0.eq_bits(&-0.0));
+/// assert!(f64::NAN.eq_bits(&f64::NAN));
+/// assert!(vec![1.0, 2.0].eq_bits(&vec![1.0, 2.0]));
+/// ```
+pub trait HashBits {
+    fn hash_bits<H: Hasher>(&self, state: &mut H);
+    fn eq_bits(&self, other: &Self) -> bool;
+}
+
+impl HashBits for f64 {
+    fn hash_bits<H: Hasher>(&self, state: &mut H) {
+        self.to_bits().hash(state)
+    }
+
+    fn eq_bits(&self, other: &Self) -> bool {
+        self.to_bits() == other.to_bits()
+    }
+}
+
+macro_rules! impl_exact {
+    ($($ty:ty),*) => {
+        $(
+        impl HashBits for $ty {
+            fn hash_bits<H: Hasher>(&self, state: &mut H) {
+                self.hash(state)
+            }
+
+            fn eq_bits(&self, other: &Self) -> bool {
+                self == other
+            }
+        }
+        )*
+    };
+}
+
+impl_exact!(i64, bool, String, Logical);
+
+impl<T: HashBits + ?Sized> HashBits for Box<T> {
+    fn hash_bits<H: Hasher>(&self, state: &mut H) {
+        (**self).hash_bits(state)
+    }
+
+    fn eq_bits(&self, other: &Self) -> bool {
+        (**self).eq_bits(&**other)
+    }
+}
+
+impl<T: HashBits + ?Sized> HashBits for Rc<T> {
+    fn hash_bits<H: Hasher>(&self, state: &mut H) {
+        (**self).hash_bits(state)
+    }
+
+    fn eq_bits(&self, other: &Self) -> bool {
+        (**self).eq_bits(&**other)
+    }
+}
+
+impl<T: HashBits> HashBits for Option<T> {
+    fn hash_bits<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Some(value) => {
+                state.write_u8(1);
+                value.hash_bits(state)
+            }
+            None => state.write_u8(0),
+        }
+    }
+
+    fn eq_bits(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.eq_bits(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: HashBits> HashBits for [T] {
+    fn hash_bits<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for value in self {
+            value.hash_bits(state)
+        }
+    }
+
+    fn eq_bits(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.eq_bits(b))
+    }
+}
+
+impl<T: HashBits> HashBits for Vec<T> {
+    fn hash_bits<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash_bits(state)
+    }
+
+    fn eq_bits(&self, other: &Self) -> bool {
+        self.as_slice().eq_bits(other.as_slice())
+    }
+}
+
+impl<T: HashBits, const N: usize> HashBits for [T; N] {
+    fn hash_bits<H: Hasher>(&self, state: &mut H) {
+        self[..].hash_bits(state)
+    }
+
+    fn eq_bits(&self, other: &Self) -> bool {
+        self[..].eq_bits(&other[..])
+    }
+}
+
+/// Wrapper implementing [Hash] and [Eq] by [HashBits]
+///
+/// This enables to deduplicate entities containing real-valued attributes, e.g. identical points:
+///
+/// ```
+/// use ruststep::primitive::BitHashed;
+/// use std::collections::HashSet;
+///
+/// let points = vec![[0.0, 1.0], [2.0, 3.0], [0.0, 1.0]];
+/// let unique: HashSet<_> = points.into_iter().map(BitHashed).collect();
+/// assert_eq!(unique.len(), 2);
+/// assert!(unique.contains(&BitHashed([2.0, 3.0])));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitHashed<T>(pub T);
+
+impl<T: HashBits> Hash for BitHashed<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_bits(state)
+    }
+}
+
+impl<T: HashBits> PartialEq for BitHashed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_bits(&other.0)
+    }
+}
+
+impl<T: HashBits> Eq for BitHashed<T> {}
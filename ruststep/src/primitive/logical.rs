@@ -1,5 +1,6 @@
+use crate::prelude::*;
+use core::{fmt, ops::*};
 use serde::{Deserialize, Serialize};
-use std::ops::*;
 
 /// `LOGICAL` type
 ///
@@ -69,8 +70,8 @@ impl Default for Logical {
     }
 }
 
-impl std::fmt::Display for Logical {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+impl fmt::Display for Logical {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
             Logical::True => f.pad("true"),
             Logical::Unknown => f.pad("unknown"),
@@ -169,7 +170,7 @@ const VARIANTS: &[&str] = &["T", "True", "F", "False", "U", "Unknown"];
 
 impl<'de> serde::de::Visitor<'de> for SubVisitor {
     type Value = SubLogical;
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(formatter, "Logical")
     }
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -229,7 +230,7 @@ struct Visitor;
 
 impl<'de> serde::de::Visitor<'de> for Visitor {
     type Value = Logical;
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(formatter, "enum Logical")
     }
     fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
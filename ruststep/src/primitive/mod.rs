@@ -1,4 +1,9 @@
 //! Primitive types appears in STEP and not defined in Rust
 
+mod approx_eq;
+mod hash_bits;
 mod logical;
+
+pub use approx_eq::*;
+pub use hash_bits::*;
 pub use logical::*;
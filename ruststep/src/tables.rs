@@ -100,100 +100,1479 @@
 //! `AHolder` will also be introduced to keep consistency.
 //! These are automated by [ruststep_derive::Holder] proc-macro.
 //!
+//! Sharing referenced entities
+//! ----------------------------
+//! Since `B` keeps `A` by value, `#1` is copied into both `#3` and `#4`
+//! when they are converted into owned values.
+//! espr generates `w: Rc<A>` instead with `--shared` option of `esprc`
+//! (or `shared` option of `inline_express!`),
+//! and then `BHolder` keeps `w` as a [SharedPlaceHolder]:
+//!
+//! ```
+//! # use ruststep::tables::SharedPlaceHolder;
+//! # use std::rc::Rc;
+//! # struct A {}
+//! # struct AHolder {}
+//! struct B {
+//!   z: i64,
+//!   w: Rc<A>,
+//! }
+//! struct BHolder {
+//!   z: i64,
+//!   w: SharedPlaceHolder<AHolder>,
+//! }
+//! ```
+//!
+//! [IntoOwned::into_owned] resolves each referenced entity once, and shares it within the owned value.
+//! Use [SharedTable] to share them also among the owned values resolved through it.
+//!
+
+use crate::{
+    ast::{
+        ser::{to_record, to_subsuper_record},
+        *,
+    },
+    error::*,
+    header::{Header, HeaderInfo},
+    primitive::{ApproxEq, BitHashed, HashBits},
+};
+use itertools::Itertools;
+use serde::{
+    de::{self, IntoDeserializer, VariantAccess},
+    ser, Deserialize,
+};
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    cell::RefCell,
+    collections::{hash_map, BTreeMap, HashMap, HashSet},
+    fmt,
+    marker::PhantomData,
+    rc::Rc,
+    str::FromStr,
+};
+
+/// Trait for resolving a reference through entity id
+pub trait IntoOwned: Clone + 'static {
+    type Owned;
+    type Table;
+
+    /// Resolve references by looking up `table`, see [Resolver::resolve]
+    fn into_owned(self, table: &Self::Table) -> Result<Self::Owned> {
+        Resolver::new(table).resolve(self)
+    }
+
+    /// Resolve references through `resolver`
+    ///
+    /// This is implemented for all holders by `#[derive(Holder)]` passing `resolver` to their fields.
+    /// It fails with [Error::Deferred] if a referenced entity has to be resolved beforehand,
+    /// and [Resolver::resolve] retries it after resolving the entity.
+    fn into_owned_with(self, resolver: &mut Resolver<'_, Self::Table>) -> Result<Self::Owned>;
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+    type Table = T::Table;
+    fn into_owned_with(self, resolver: &mut Resolver<'_, Self::Table>) -> Result<Self::Owned> {
+        // Continue after a deferred element to defer all elements at once
+        let mut owned = Vec::with_capacity(self.len());
+        let mut deferred = None;
+        for value in self {
+            match value.into_owned_with(resolver) {
+                Ok(value) => owned.push(value),
+                Err(e @ Error::Deferred { .. }) => deferred = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+        match deferred {
+            Some(e) => Err(e),
+            None => Ok(owned),
+        }
+    }
+}
+
+impl<T: IntoOwned, const N: usize> IntoOwned for [T; N] {
+    type Owned = [T::Owned; N];
+    type Table = T::Table;
+    fn into_owned_with(self, resolver: &mut Resolver<'_, Self::Table>) -> Result<Self::Owned> {
+        let owned = Vec::from(self).into_owned_with(resolver)?;
+        match owned.try_into() {
+            Ok(owned) => Ok(owned),
+            Err(_) => unreachable!("Length of array is kept"),
+        }
+    }
+}
+
+/// Trait for visiting entity references in a holder before resolving them
+///
+/// This is implemented for all holders by `#[derive(Holder)]`,
+/// and can be used to walk the exchange structure graph without [IntoOwned::into_owned],
+/// e.g. to collect entities reachable from a root entity.
+pub trait VisitRefs {
+    /// Call `visitor` with entity id for each reference, e.g. `1` for `#1`
+    fn visit_refs(&self, visitor: &mut dyn FnMut(u64));
+
+    /// Replace each reference by the entity id returned by `map`, e.g. `#1` into `#2` if `map(1) == 2`
+    fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64);
+}
+
+impl<T: VisitRefs> VisitRefs for Vec<T> {
+    fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+        for value in self {
+            value.visit_refs(visitor);
+        }
+    }
+
+    fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+        for value in self {
+            value.remap_refs(map);
+        }
+    }
+}
+
+impl<T: VisitRefs, const N: usize> VisitRefs for [T; N] {
+    fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+        for value in self {
+            value.visit_refs(visitor);
+        }
+    }
+
+    fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+        for value in self {
+            value.remap_refs(map);
+        }
+    }
+}
+
+impl<T: VisitRefs> VisitRefs for Option<T> {
+    fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+        if let Some(value) = self {
+            value.visit_refs(visitor);
+        }
+    }
+
+    fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+        if let Some(value) = self {
+            value.remap_refs(map);
+        }
+    }
+}
+
+impl<T: VisitRefs> VisitRefs for Box<T> {
+    fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+        self.as_ref().visit_refs(visitor);
+    }
+
+    fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+        self.as_mut().remap_refs(map);
+    }
+}
+
+/// Trait for a field of tables
+pub trait Holder: IntoOwned + VisitRefs {
+    fn name() -> &'static str;
+    fn attr_len() -> usize;
+
+    /// Names of attributes in the order of parameters,
+    /// or empty if the attributes are not named, e.g. for tuple structs
+    fn attr_names() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Whether a typed parameter `KEYWORD(...)` can be deserialized into this holder
+    ///
+    /// SELECT holders accept the keywords of their members recursively.
+    fn accepts_keyword(keyword: &str) -> bool {
+        keyword == Self::name()
+    }
+
+    /// Check constraints which deserialization does not, e.g. bounds of `LIST [1:?] OF ...`
+    ///
+    /// Implementations generated by `#[derive(Holder)]` check the number of elements
+    /// of attributes with `#[holder(bound(lower, upper))]`, and return [Error::AggregateBounds].
+    /// Attributes inherited from supertypes are not checked.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Deserialize a record into this holder, the inverse of [to_record]
+    ///
+    /// Parameters are read into the fields in order, and references like `#12` are kept as they are.
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::RecordNameMismatch] if the record is not accepted by [Holder::accepts_keyword]
+    /// - [Error::ArityMismatch] if the number of parameters does not match [Holder::attr_len]
+    ///
+    fn from_record(record: &Record) -> Result<Self>
+    where
+        Self: de::DeserializeOwned,
+    {
+        if !Self::accepts_keyword(&record.name) {
+            return Err(Error::RecordNameMismatch {
+                expected: Self::name().to_string(),
+                found: record.name.clone(),
+            });
+        }
+        // Records of subtypes are checked by their own holders
+        if record.name == Self::name() {
+            check_arity::<Self>(record)?;
+        }
+        de::Deserialize::deserialize(record)
+    }
+}
+
+pub trait WithVisitor {
+    type Visitor: for<'de> de::Visitor<'de, Value = Self>;
+    fn visitor_new() -> Self::Visitor;
+}
+
+/// Container of holders keyed by entity id, used as a field of tables
+///
+/// [HashMap] is used in tables generated by espr.
+/// Its entries are iterated in ascending id order by sorting ids
+/// so that the output, e.g. an exported exchange structure, is reproducible.
+/// Hand-written tables may use [BTreeMap] to iterate entities in ascending id order without sorting,
+/// or `indexmap::IndexMap` with `indexmap` feature to keep the order in the exchange structure.
+///
+/// ```
+/// use ruststep::tables::IdMap;
+/// use std::collections::BTreeMap;
+///
+/// let mut map = BTreeMap::new();
+/// assert!(IdMap::insert(&mut map, 2, "b").is_none());
+/// assert!(IdMap::insert(&mut map, 1, "a").is_none());
+/// assert_eq!(IdMap::get(&map, 1), Some(&"a"));
+/// assert_eq!(IdMap::iter(&map).collect::<Vec<_>>(), vec![(1, &"a"), (2, &"b")]);
+/// ```
+pub trait IdMap<T> {
+    fn get(&self, id: u64) -> Option<&T>;
+
+    /// Insert a holder, and returns the previous one for the same id if exists
+    fn insert(&mut self, id: u64, value: T) -> Option<T>;
+
+    /// Iterate over ids and holders in the order defined by the container
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (u64, &'a T)> + 'a>;
+
+    /// Iterate over mutable holders in the order defined by the container
+    fn values_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item = &'a mut T> + 'a>;
+
+    /// Retain holders whose id satisfies `f`
+    fn retain_ids(&mut self, f: impl FnMut(u64) -> bool);
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+macro_rules! impl_id_map {
+    ($map:ident, |$this:ident| $iter:expr) => {
+        impl<T> IdMap<T> for $map<u64, T> {
+            fn get(&self, id: u64) -> Option<&T> {
+                $map::get(self, &id)
+            }
+            fn insert(&mut self, id: u64, value: T) -> Option<T> {
+                $map::insert(self, id, value)
+            }
+            fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (u64, &'a T)> + 'a> {
+                let $this = self;
+                Box::new($iter)
+            }
+            fn values_mut<'a>(&'a mut self) -> Box<dyn Iterator<Item = &'a mut T> + 'a> {
+                Box::new($map::values_mut(self))
+            }
+            fn retain_ids(&mut self, mut f: impl FnMut(u64) -> bool) {
+                $map::retain(self, |id, _| f(*id))
+            }
+            fn len(&self) -> usize {
+                $map::len(self)
+            }
+        }
+    };
+}
+
+impl_id_map!(HashMap, |map| {
+    let mut entries: Vec<_> = HashMap::iter(map).map(|(id, value)| (*id, value)).collect();
+    entries.sort_unstable_by_key(|(id, _value)| *id);
+    entries.into_iter()
+});
+impl_id_map!(BTreeMap, |map| BTreeMap::iter(map)
+    .map(|(id, value)| (*id, value)));
+
+#[cfg(feature = "indexmap")]
+use indexmap_crate::IndexMap;
+#[cfg(feature = "indexmap")]
+impl_id_map!(IndexMap, |map| IndexMap::iter(map)
+    .map(|(id, value)| (*id, value)));
+
+/// Trait for tables which pulls an entity (`T`) from an entity id (`u64`)
+pub trait EntityTable<T: Holder<Table = Self>> {
+    /// Get owned entity from table
+    fn get_owned(&self, entity_id: u64) -> Result<T::Owned>;
+
+    /// Check if the table has an entity of the id without resolving it
+    ///
+    /// Default implementation resolves the entity by [EntityTable::get_owned],
+    /// and implementations generated by `#[derive(Holder)]` only look up the table.
+    fn contains(&self, entity_id: u64) -> bool {
+        self.get_owned(entity_id).is_ok()
+    }
+
+    /// Get the holder of the entity without resolving its references
+    ///
+    /// Implementations generated for entities and defined types borrow the holder in the table.
+    /// Since holders of SELECT types are not stored in tables by themselves,
+    /// the implementations for them wrap a clone of the holder of the member containing the entity.
+    ///
+    /// Default implementation returns `None`.
+    /// Then [IntoOwned::into_owned] resolves the references of the entity recursively.
+    fn get_holder(&self, _entity_id: u64) -> Option<Cow<'_, T>> {
+        None
+    }
+
+    /// Get owned entities as an iterator
+    ///
+    /// Entities are yielded in the order of [IdMap::iter],
+    /// i.e. in ascending id order for tables generated by espr.
+    fn owned_iter<'table>(&'table self) -> Box<dyn Iterator<Item = Result<T::Owned>> + 'table>;
+}
+
+/// Record which is not stored into tables since its entity name is not a member of the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownRecord {
+    /// Entity instance id, e.g. `1` for `#1 = FOO(...);`
+    pub id: u64,
+    /// Dropped records. Complex entity instance consists of several records.
+    pub records: Vec<Record>,
+}
+
+impl UnknownRecord {
+    /// Clone the records of the entity instance
+    pub fn new(entity: &EntityInstance) -> Self {
+        UnknownRecord {
+            id: entity.id(),
+            records: entity.records().to_vec(),
+        }
+    }
+}
+
+/// Create Table from [DataSection]
+pub trait TableInit: Default {
+    /// Name of the EXPRESS schema, specified by `#[table_init(schema = "...")]`
+    fn schema_name() -> Option<&'static str> {
+        None
+    }
+
+    /// Records in the data section whose entity names are not a member of the schema,
+    /// found without appending any records into tables
+    ///
+    /// Default implementation returns no records.
+    fn unknown_records(&self, _section: &DataSection) -> Result<Vec<UnknownRecord>> {
+        Ok(Vec::new())
+    }
+
+    /// Append records in the data section into tables,
+    /// and returns records whose entity name is not a member of the schema.
+    ///
+    /// Default implementation finds the records by [TableInit::unknown_records],
+    /// and appends the data section by [TableInit::append_data_section].
+    /// Implementations must override this or [TableInit::append_data_section].
+    fn append_data_section_lossy(&mut self, section: &DataSection) -> Result<Vec<UnknownRecord>> {
+        let unknowns = self.unknown_records(section)?;
+        self.append_data_section(section)?;
+        Ok(unknowns)
+    }
+
+    /// Append records in the data section into tables
+    ///
+    /// Tables are not modified if the data section contains a record not in the schema.
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::UnknownEntityName] if the data section contains a record not in the schema
+    ///
+    fn append_data_section(&mut self, section: &DataSection) -> Result<()> {
+        let unknowns = self.unknown_records(section)?;
+        if let Some(unknown) = unknowns.into_iter().next() {
+            return Err(Error::UnknownEntityName {
+                entity_name: unknown.records.iter().map(|record| &record.name).join(" "),
+                schema: Self::schema_name().unwrap_or_default().to_string(),
+            });
+        }
+        self.append_data_section_lossy(section)?;
+        Ok(())
+    }
+
+    fn from_data_section(section: &DataSection) -> Result<Self> {
+        let mut table = Self::default();
+        table.append_data_section(section)?;
+        Ok(table)
+    }
+
+    /// Create tables from the data section, and returns records dropped
+    /// since their entity names are not a member of the schema.
+    fn from_data_section_lossy(section: &DataSection) -> Result<(Self, Vec<UnknownRecord>)> {
+        let mut table = Self::default();
+        let unknowns = table.append_data_section_lossy(section)?;
+        Ok((table, unknowns))
+    }
+
+    /// Create tables from records with their entity ids, e.g. a part of [DataSection::records],
+    /// and returns records dropped since their entity names are not a member of the schema.
+    ///
+    /// Each record is stored as a simple entity instance.
+    /// `Tables::try_from_records` generated by espr uses this function,
+    /// see also [TableInit::from_records_skipping].
+    fn from_records_lossy(
+        records: impl IntoIterator<Item = (u64, Record)>,
+    ) -> Result<(Self, Vec<UnknownRecord>)> {
+        let section = DataSection {
+            meta: Vec::new(),
+            entities: records
+                .into_iter()
+                .map(|(id, record)| EntityInstance::Simple { id, record })
+                .collect(),
+        };
+        Self::from_data_section_lossy(&section)
+    }
+
+    /// Create tables from records with their entity ids, where records which cannot be stored are skipped:
+    ///
+    /// - records whose entity names are not a member of the schema
+    /// - records which cannot be deserialized into the holder of their entity names
+    /// - records whose entity ids are already stored, i.e. the first one is kept
+    ///
+    /// `FromIterator<(u64, Record)>` generated for tables by espr uses this function.
+    fn from_records_skipping(records: impl IntoIterator<Item = (u64, Record)>) -> Self {
+        let section = DataSection {
+            meta: Vec::new(),
+            entities: records
+                .into_iter()
+                .map(|(id, record)| EntityInstance::Simple { id, record })
+                .collect(),
+        };
+        if let Ok((table, _unknowns)) = Self::from_data_section_lossy(&section) {
+            return table;
+        }
+        // Append records one by one to skip only the records which fail
+        let mut table = Self::default();
+        for entity in section.entities {
+            let _ = table.append_data_section_lossy(&DataSection {
+                meta: Vec::new(),
+                entities: vec![entity],
+            });
+        }
+        table
+    }
+
+    fn from_data_sections(sections: &[DataSection]) -> Result<Self> {
+        let mut table = Self::default();
+        for section in sections {
+            table.append_data_section(section)?;
+        }
+        Ok(table)
+    }
+
+    /// Create tables from a whole exchange structure
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::MissingHeaderEntity] if the header section lacks a mandatory entity
+    /// - [Error::SchemaMismatch] if `FILE_SCHEMA` does not list [TableInit::schema_name]
+    /// - Errors of [TableInit::append_data_section] for each data section
+    ///
+    fn from_exchange(exchange: &Exchange) -> Result<Self> {
+        let header = Header::from_records(&exchange.header)?;
+        if let Some(schema) = Self::schema_name() {
+            let file_schema = &header.file_schema;
+            if !file_schema.contains(schema) {
+                return Err(Error::SchemaMismatch {
+                    expected: schema.to_string(),
+                    found: file_schema.schema.clone(),
+                });
+            }
+        }
+        Self::from_data_sections(&exchange.data)
+    }
+
+    /// Read tables from a file, which may be compressed by gzip, e.g. `*.stp.gz` or `*.stpZ`
+    ///
+    /// The file is decompressed by [crate::parser::decompress] if it starts with the magic bytes of gzip,
+    /// and then read as a whole exchange structure, or as a data section, like [tables_from_str].
+    ///
+    /// ```
+    /// use ruststep::{error::Result, tables::TableInit};
+    ///
+    /// // Same for both plain and compressed files
+    /// fn load<T: TableInit>() -> Result<(T, T)> {
+    ///     Ok((T::load_file("assembly.stp")?, T::load_file("assembly.stpZ")?))
+    /// }
+    /// ```
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::Io] if the file cannot be read or decompressed
+    /// - [Error::InvalidUtf8] if the content is not a valid UTF-8 sequence
+    /// - Errors of [tables_from_str]
+    ///
+    #[cfg(feature = "flate2")]
+    fn load_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let bytes = crate::parser::decompress(&bytes)?;
+        let input = std::str::from_utf8(&bytes).map_err(|e| Error::InvalidUtf8 {
+            valid_up_to: e.valid_up_to(),
+        })?;
+        tables_from_str(input)
+    }
+
+    /// Split into tasks inserting simple records into each table
+    ///
+    /// Records taken by the tasks are removed from `records`, which are grouped by their entity names.
+    /// This is implemented by `#[derive(TableInit)]` for `from_data_section_parallel` with `rayon` feature.
+    #[doc(hidden)]
+    fn table_tasks<'t, 'r: 't>(
+        &'t mut self,
+        _records: &mut HashMap<&'r str, Vec<(u64, &'r Record)>>,
+    ) -> Vec<TableTask<'t>> {
+        Vec::new()
+    }
+
+    /// Same as [TableInit::from_data_section], but records are deserialized into each table in parallel
+    ///
+    /// Simple records are grouped by their entity names,
+    /// and then each group is deserialized into its table concurrently.
+    /// Complex entity instances are inserted after them sequentially.
+    /// The resulting tables are same as [TableInit::from_data_section],
+    /// and the error of the first table in the struct is returned if several tables fail.
+    ///
+    /// Since holders are sent to other threads,
+    /// a large schema may require to increase `#![recursion_limit]` to check holders are `Send`.
+    #[cfg(feature = "rayon")]
+    fn from_data_section_parallel(section: &DataSection) -> Result<Self> {
+        use rayon_crate::prelude::*;
+
+        check_duplicated_ids(section, |_| false)?;
+        let mut records: HashMap<&str, Vec<(u64, &Record)>> = HashMap::new();
+        let mut rest = Vec::new();
+        for entity in &section.entities {
+            match entity {
+                EntityInstance::Simple { id, record } => records
+                    .entry(record.name.as_str())
+                    .or_default()
+                    .push((*id, record)),
+                EntityInstance::Complex { .. } => rest.push(entity.clone()),
+            }
+        }
+
+        let mut table = Self::default();
+        let results: Vec<Result<()>> = table
+            .table_tasks(&mut records)
+            .into_par_iter()
+            .map(|task| task())
+            .collect();
+        results.into_iter().collect::<Result<()>>()?;
+
+        // Simple records not taken by tasks, i.e. not in the schema,
+        // are appended with complex entity instances in the order of entity id.
+        rest.extend(
+            records
+                .into_values()
+                .flatten()
+                .map(|(id, record)| EntityInstance::Simple {
+                    id,
+                    record: record.clone(),
+                }),
+        );
+        rest.sort_by_key(EntityInstance::id);
+        table.append_data_section(&DataSection {
+            meta: section.meta.clone(),
+            entities: rest,
+        })?;
+        Ok(table)
+    }
+}
+
+/// Task inserting records into a table, see [TableInit::table_tasks]
+pub type TableTask<'t> = Box<dyn FnOnce() -> Result<()> + Send + 't>;
+
+/// Helper function to implement [TableInit::table_tasks]
+pub fn insert_records<'de, T: Holder + de::Deserialize<'de>>(
+    table: &mut impl IdMap<T>,
+    records: Vec<(u64, &Record)>,
+) -> Result<()> {
+    for (id, record) in records {
+        insert_record(table, id, record)?;
+    }
+    Ok(())
+}
+
+/// Exchange structure graph consists of entities in tables
+///
+/// This is implemented by `#[derive(TableInit)]` using [VisitRefs].
+pub trait EntityGraph {
+    /// References from each entity, e.g. `3 => [1, 2]` for `#3 = B(#1, #2);`
+    fn references(&self) -> HashMap<u64, Vec<u64>>;
+
+    /// Retain entities whose id is in `ids`, and returns the number of removed entities
+    fn retain_ids(&mut self, ids: &HashSet<u64>) -> usize;
+
+    /// Replace every reference in entities by [VisitRefs::remap_refs]
+    fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64);
+
+    /// Name of the record stored for the entity `id`, e.g. `"A"` for `#1 = A(...);`,
+    /// or `None` if no table contains it
+    fn type_of(&self, id: u64) -> Option<&'static str>;
+
+    /// Entity ids transitively referenced from `roots`, including `roots` themselves
+    fn reachable_from(&self, roots: &[u64]) -> HashSet<u64> {
+        let references = self.references();
+        let mut reachable: HashSet<u64> = roots.iter().cloned().collect();
+        let mut stack = roots.to_vec();
+        while let Some(id) = stack.pop() {
+            for next in references.get(&id).into_iter().flatten() {
+                if reachable.insert(*next) {
+                    stack.push(*next);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Drop entities not reachable from `roots`, and returns the number of removed entities
+    fn retain_reachable_from(&mut self, roots: &[u64]) -> usize {
+        let reachable = self.reachable_from(roots);
+        self.retain_ids(&reachable)
+    }
+
+    /// Find all dangling references, i.e. references to entity ids which do not exist in tables
+    ///
+    /// Each dangling reference is reported once as a pair of ids `(from, to)`
+    /// in ascending order, e.g. `(2, 5)` for `#2 = A(#5);` without `#5`.
+    /// [IntoOwned::into_owned] fails at the first dangling reference it meets,
+    /// and this is useful to report all of them up front.
+    fn check_references(&self) -> Vec<(u64, u64)> {
+        let references = self.references();
+        let mut dangling: Vec<(u64, u64)> = references
+            .iter()
+            .flat_map(|(from, to)| to.iter().map(move |to| (*from, *to)))
+            .filter(|(_from, to)| !references.contains_key(to))
+            .collect();
+        dangling.sort_unstable();
+        dangling.dedup();
+        dangling
+    }
+
+    /// Count entities and references between them, e.g. to see the shape of a large model quickly
+    fn stats(&self) -> TablesStats {
+        let references = self.references();
+        let mut stats = TablesStats {
+            entities: references.len(),
+            ..Default::default()
+        };
+        let mut referenced: HashSet<u64> = HashSet::new();
+        for (id, to) in &references {
+            if let Some(name) = self.type_of(*id) {
+                *stats.counts.entry(name).or_default() += 1;
+            }
+            stats.references += to.len();
+            stats.max_fan_out = stats.max_fan_out.max(to.len());
+            referenced.extend(to.iter().filter(|to| *to != id).cloned());
+        }
+        stats.orphans = references
+            .keys()
+            .filter(|id| !referenced.contains(*id))
+            .count();
+        stats
+    }
+
+    /// Check every entity by [Holder::validate], and returns errors with entity ids in ascending order
+    ///
+    /// Real files sometimes violate constraints, e.g. an empty list for `LIST [1:?] OF ...`,
+    /// which deserialization accepts.
+    fn validate(&self) -> Vec<(u64, Error)>;
+
+    /// Merge entities of the holder `T` with the same value, and returns the number of removed entities
+    ///
+    /// Entities are compared by [HashBits] of their owned values,
+    /// i.e. real numbers are compared exactly by their bit patterns.
+    /// The entity of the smallest id survives in each group of the same value,
+    /// and references to the others are rewritten to it by [EntityGraph::remap_refs].
+    fn dedup_by_type<T>(&mut self) -> Result<usize>
+    where
+        Self: Sized + EntityTable<T>,
+        T: Holder<Table = Self>,
+        T::Owned: HashBits,
+    {
+        let references = self.references();
+        let mut ids: Vec<u64> = references
+            .keys()
+            .filter(|id| EntityTable::<T>::contains(self, **id))
+            .cloned()
+            .collect();
+        ids.sort_unstable();
+
+        let mut survivors = HashMap::new();
+        let mut merged = HashMap::new();
+        for id in ids {
+            let owned = EntityTable::<T>::get_owned(self, id)?;
+            match survivors.entry(BitHashed(owned)) {
+                hash_map::Entry::Occupied(survivor) => {
+                    merged.insert(id, *survivor.get());
+                }
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(id);
+                }
+            }
+        }
+        if merged.is_empty() {
+            return Ok(0);
+        }
+
+        self.remap_refs(&mut |id| merged.get(&id).cloned().unwrap_or(id));
+        let retained = references
+            .keys()
+            .filter(|id| !merged.contains_key(id))
+            .cloned()
+            .collect();
+        Ok(self.retain_ids(&retained))
+    }
+}
+
+/// Summary of entities in tables, see [EntityGraph::stats]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TablesStats {
+    /// Number of entities for each record name, e.g. `"CARTESIAN_POINT" => 2`
+    pub counts: HashMap<&'static str, usize>,
+    /// Number of entities in all tables
+    pub entities: usize,
+    /// Number of references, where each occurrence is counted, e.g. 2 for `#3 = B(#1, #1);`
+    pub references: usize,
+    /// Maximum number of references from a single entity
+    pub max_fan_out: usize,
+    /// Number of entities not referenced by any other entity
+    ///
+    /// This includes root entities of the model, e.g. `PRODUCT` in AP203,
+    /// as well as unused ones.
+    pub orphans: usize,
+}
+
+/// Helper function to implement [std::str::FromStr] for tables
+///
+/// The input is parsed as a whole exchange structure by [TableInit::from_exchange]
+/// if it starts with `ISO-10303-21;`, or as a data section otherwise.
+pub fn tables_from_str<T: TableInit>(input: &str) -> Result<T> {
+    if input.trim_start().starts_with("ISO-10303-21") {
+        T::from_exchange(&Exchange::from_str(input)?)
+    } else {
+        T::from_data_section(&DataSection::from_str(input)?)
+    }
+}
+
+/// Helper function to write a table into [DataSection]
+///
+/// Each holder is serialized by [to_record],
+/// and thus references in it are kept as references.
+pub fn entity_instances<T: ser::Serialize>(table: &impl IdMap<T>) -> Result<Vec<EntityInstance>> {
+    table
+        .iter()
+        .map(|(id, holder)| {
+            Ok(EntityInstance::Simple {
+                id,
+                record: to_record(holder)?,
+            })
+        })
+        .collect()
+}
+
+/// Helper function to write a table of complex entities into [DataSection]
+///
+/// Each holder is serialized by [to_subsuper_record] with `names` of components and their supertypes.
+pub fn complex_entity_instances<T: ser::Serialize>(
+    table: &impl IdMap<T>,
+    names: &[&str],
+) -> Result<Vec<EntityInstance>> {
+    table
+        .iter()
+        .map(|(id, holder)| {
+            Ok(EntityInstance::Complex {
+                id,
+                subsuper: to_subsuper_record(holder, names)?,
+            })
+        })
+        .collect()
+}
+
+/// Helper function to iterate over records of a table,
+/// used by `Tables::all_records` of espr-generated code
+///
+/// Each holder is serialized by [to_record] as [entity_instances] does.
+pub fn records<'a, T: ser::Serialize + 'a>(
+    table: &'a impl IdMap<T>,
+) -> Box<dyn Iterator<Item = Result<(u64, Record)>> + 'a> {
+    Box::new(
+        table
+            .iter()
+            .map(|(id, holder)| Ok((id, to_record(holder)?))),
+    )
+}
+
+/// Difference between two tables, returned by `Tables::diff` of espr-generated code
+///
+/// Entities are matched by their ids, and each list is sorted by ids.
+/// An entity whose type is changed is reported as removed and added.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TablesDiff {
+    /// Entities only in the other table
+    pub added: Vec<EntitySummary>,
+    /// Entities only in this table
+    pub removed: Vec<EntitySummary>,
+    /// Entities of the same type but with different parameters
+    pub changed: Vec<EntityChange>,
+}
+
+impl TablesDiff {
+    /// Whether two tables have the same entities
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Id and type of an entity in [TablesDiff]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntitySummary {
+    pub id: u64,
+    /// Record name of the entity, e.g. `"CARTESIAN_POINT"`
+    pub entity: String,
+}
+
+/// Entity changed between two tables, in [TablesDiff::changed]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityChange {
+    pub id: u64,
+    /// Record name of the entity, e.g. `"CARTESIAN_POINT"`
+    pub entity: String,
+    /// Changed attributes in the order of parameters
+    pub attributes: Vec<AttributeChange>,
+}
+
+/// Attribute changed between two tables, in [EntityChange::attributes]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeChange {
+    /// Position of the attribute in the parameters, starting from 0
+    pub index: usize,
+    pub before: Parameter,
+    pub after: Parameter,
+}
+
+/// Helper function to implement `Tables::diff` of espr-generated code
+///
+/// Records are compared parameter by parameter using [ApproxEq] with tolerance `tol`,
+/// i.e. references are compared by entity ids, and inline entities structurally.
+pub fn diff_records(
+    before: impl Iterator<Item = Result<(u64, Record)>>,
+    after: impl Iterator<Item = Result<(u64, Record)>>,
+    tol: f64,
+) -> Result<TablesDiff> {
+    let before: BTreeMap<u64, Record> = before.collect::<Result<_>>()?;
+    let mut after: BTreeMap<u64, Record> = after.collect::<Result<_>>()?;
+    let mut diff = TablesDiff::default();
+    for (id, record) in before {
+        match after.remove(&id) {
+            Some(other) if other.name == record.name => {
+                let attributes = changed_attributes(&record, &other, tol);
+                if !attributes.is_empty() {
+                    diff.changed.push(EntityChange {
+                        id,
+                        entity: record.name,
+                        attributes,
+                    });
+                }
+            }
+            other => {
+                if let Some(other) = other {
+                    after.insert(id, other);
+                }
+                diff.removed.push(EntitySummary {
+                    id,
+                    entity: record.name,
+                });
+            }
+        }
+    }
+    diff.added
+        .extend(after.into_iter().map(|(id, record)| EntitySummary {
+            id,
+            entity: record.name,
+        }));
+    Ok(diff)
+}
+
+fn changed_attributes(before: &Record, after: &Record, tol: f64) -> Vec<AttributeChange> {
+    let (before, after) = (before.parameters(), after.parameters());
+    (0..before.len().max(after.len()))
+        .filter_map(|index| {
+            let before = before.get(index).cloned().unwrap_or(Parameter::NotProvided);
+            let after = after.get(index).cloned().unwrap_or(Parameter::NotProvided);
+            if before.approx_eq(&after, tol) {
+                None
+            } else {
+                Some(AttributeChange {
+                    index,
+                    before,
+                    after,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Helper function to write tables into a whole exchange structure
+///
+/// Entity instances are sorted by their ids for deterministic output,
+/// and `FILE_SCHEMA` lists [TableInit::schema_name] unless [HeaderInfo::schema] is specified.
+/// [HeaderInfo::raw] is written as it is if specified.
+/// This is used by `Tables::to_step_string` of espr-generated code.
+///
+/// Returns [Error::NonFiniteReal] if an entity has NaN or infinity as a `REAL` value.
+pub fn write_exchange<T: TableInit>(
+    header: &HeaderInfo,
+    mut entities: Vec<EntityInstance>,
+) -> Result<String> {
+    entities.sort_by_key(EntityInstance::id);
+    let header = match &header.raw {
+        Some(raw) => raw.trim().to_string(),
+        None => to_step_string(&HeaderSection(&header.to_records(T::schema_name())?))?,
+    };
+    let data = to_step_string(&DataSection {
+        meta: Vec::new(),
+        entities,
+    })?;
+    Ok(format!(
+        "ISO-10303-21;\n{}\n{}\nEND-ISO-10303-21;",
+        header, data
+    ))
+}
+
+/// Helper function to implement [EntityGraph::references]
+pub fn collect_references<T: VisitRefs>(
+    table: &impl IdMap<T>,
+    references: &mut HashMap<u64, Vec<u64>>,
+) {
+    for (id, holder) in table.iter() {
+        let refs = references.entry(id).or_default();
+        holder.visit_refs(&mut |next| refs.push(next));
+    }
+}
+
+/// Helper function to implement [EntityGraph::remap_refs]
+pub fn remap_refs<T: VisitRefs>(table: &mut impl IdMap<T>, map: &mut dyn FnMut(u64) -> u64) {
+    for holder in table.values_mut() {
+        holder.remap_refs(map);
+    }
+}
+
+/// Helper function to implement [EntityGraph::type_of]
+pub fn type_of<T: Holder>(table: &impl IdMap<T>, id: u64) -> Option<&'static str> {
+    table.get(id).map(|_| T::name())
+}
+
+/// Helper function to implement [EntityGraph::validate]
+pub fn validate<T: Holder>(table: &impl IdMap<T>, errors: &mut Vec<(u64, Error)>) {
+    for (id, holder) in table.iter() {
+        if let Err(err) = holder.validate() {
+            errors.push((id, err));
+        }
+    }
+}
+
+/// Helper function to implement [Holder::validate] for an attribute with bound
+///
+/// `found` is the number of elements, or `None` if the optional attribute is not provided.
+pub fn check_bound(attr: &str, found: Option<usize>, min: usize, max: Option<usize>) -> Result<()> {
+    match found {
+        Some(found) if found < min || max.map_or(false, |max| found > max) => {
+            Err(Error::AggregateBounds {
+                attr: attr.to_string(),
+                min,
+                max,
+                found,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Entity instances violating a `UNIQUE` rule, i.e. sharing the same values of its attributes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniqueViolation {
+    /// Record name of the entity, e.g. `"PRODUCT"`
+    pub entity: &'static str,
+    /// Label of the rule, e.g. `"ur1"` for `UNIQUE ur1: id;`
+    pub rule: Option<&'static str>,
+    /// Ids of entities sharing the same values in ascending order
+    pub ids: Vec<u64>,
+}
+
+/// Helper function to implement `Tables::check_unique` of espr-generated code
+///
+/// `key` returns the values of attributes in the rule serialized into [Parameter]s,
+/// and they are compared in exchange structure notation,
+/// i.e. references are compared by entity ids.
+/// Violations are sorted by their smallest ids.
+pub fn check_unique<T: Holder>(
+    table: &impl IdMap<T>,
+    rule: Option<&'static str>,
+    key: impl Fn(&T) -> Result<Vec<Parameter>>,
+) -> Result<Vec<UniqueViolation>> {
+    let mut groups: HashMap<String, Vec<u64>> = HashMap::new();
+    for (id, holder) in table.iter() {
+        let key = to_step_string(&Parameter::List(key(holder)?))?;
+        groups.entry(key).or_default().push(id);
+    }
+    let mut violations: Vec<UniqueViolation> = groups
+        .into_values()
+        .filter(|ids| ids.len() > 1)
+        .map(|mut ids| {
+            ids.sort_unstable();
+            UniqueViolation {
+                entity: T::name(),
+                rule,
+                ids,
+            }
+        })
+        .collect();
+    violations.sort_by_key(|violation| violation.ids[0]);
+    Ok(violations)
+}
+
+/// Helper function to implement [EntityGraph::retain_ids]
+pub fn retain_ids<T>(table: &mut impl IdMap<T>, ids: &HashSet<u64>) -> usize {
+    let len = table.len();
+    table.retain_ids(|id| ids.contains(&id));
+    len - table.len()
+}
+
+/// Count records for each entity name in [DataSection] without schema
+///
+/// Each component of complex entity instances is counted separately.
+///
+/// ```
+/// use ruststep::{ast::DataSection, tables::count_records};
+/// use std::str::FromStr;
+///
+/// let input = r#"
+/// DATA;
+///   #1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+///   #2 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+///   #3 = (A(1.0) B(#1));
+///   #4 = A(2.0);
+/// ENDSEC;
+/// "#;
+/// let data_section = DataSection::from_str(input).unwrap();
+/// let counts = count_records(&data_section);
+/// assert_eq!(counts["CARTESIAN_POINT"], 2);
+/// assert_eq!(counts["A"], 2);
+/// assert_eq!(counts["B"], 1);
+/// ```
+pub fn count_records(data_section: &DataSection) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for (_id, record) in data_section.records() {
+        *counts.entry(record.name.clone()).or_default() += 1;
+    }
+    counts
+}
+
+pub fn get_owned<T, Table>(table: &Table, map: &impl IdMap<T>, entity_id: u64) -> Result<T::Owned>
+where
+    T: Holder<Table = Table>,
+    Table: EntityTable<T>,
+{
+    match map.get(entity_id) {
+        Some(holder) => holder.clone().into_owned(table),
+        None => Err(Error::UnknownEntity(entity_id)),
+    }
+}
+
+pub fn owned_iter<'table, T, Table>(
+    table: &'table Table,
+    map: &'table impl IdMap<T>,
+) -> Box<dyn Iterator<Item = Result<T::Owned>> + 'table>
+where
+    T: Holder<Table = Table>,
+    Table: EntityTable<T>,
+{
+    Box::new(
+        map.iter()
+            .map(move |(_id, value)| value.clone().into_owned(table)),
+    )
+}
+
+/// Resolver of references keeping the state of resolution explicitly
+///
+/// [IntoOwned::into_owned] creates a resolver for each call,
+/// and holders pass it to their fields by [IntoOwned::into_owned_with]:
+///
+/// - A reference to an entity being resolved is reported as [Error::CyclicReference],
+///   since owned value cannot represent it.
+/// - Entities referenced through `Rc<T>` attributes, i.e. [SharedPlaceHolder],
+///   are resolved in post-order with an explicit stack, and shared among their referrers.
+///   Thus deep reference chains through such attributes do not overflow the stack.
+/// - Other references are resolved recursively into values for each reference.
+///
+/// Shared entities are kept until the resolver is dropped,
+/// and they are dropped after their referrers so that a deep chain is not dropped recursively.
+pub struct Resolver<'table, Table> {
+    table: &'table Table,
+    /// Entities being resolved, i.e. the path from the resolving value to the current reference
+    resolving: HashSet<(TypeId, u64)>,
+    /// Entities deferred by [IntoOwned::into_owned_with] to be resolved beforehand
+    deferred: Vec<Task<Table>>,
+    /// Position of shared entities in `shared`
+    index: HashMap<(TypeId, u64), usize>,
+    /// `Rc<T::Owned>` of shared entities in post-order, i.e. referenced entities come first
+    shared: Vec<Rc<dyn Any>>,
+}
+
+/// Entity to be resolved into a shared value, see [Resolver::resolve_deferred]
+struct Task<Table> {
+    key: (TypeId, u64),
+    name: &'static str,
+    resolve: ResolveFn<Table>,
+}
+
+/// Resolve entity of the entity id into `Rc<T::Owned>` for a holder type `T`
+type ResolveFn<Table> = fn(&mut Resolver<'_, Table>, u64) -> Result<Rc<dyn Any>>;
+
+impl<Table> Task<Table> {
+    fn new<T>(entity_id: u64) -> Self
+    where
+        T: Holder<Table = Table>,
+        Table: EntityTable<T>,
+        T::Owned: 'static,
+    {
+        Task {
+            key: (TypeId::of::<T>(), entity_id),
+            name: T::name(),
+            resolve: resolve_shared::<T>,
+        }
+    }
+}
+
+fn resolve_shared<T>(resolver: &mut Resolver<'_, T::Table>, entity_id: u64) -> Result<Rc<dyn Any>>
+where
+    T: Holder,
+    T::Table: EntityTable<T>,
+    T::Owned: 'static,
+{
+    Ok(Rc::new(resolver.resolve_holder::<T>(entity_id)?))
+}
+
+impl<'table, Table> Resolver<'table, Table> {
+    pub fn new(table: &'table Table) -> Self {
+        Resolver {
+            table,
+            resolving: HashSet::new(),
+            deferred: Vec::new(),
+            index: HashMap::new(),
+            shared: Vec::new(),
+        }
+    }
+
+    pub fn table(&self) -> &'table Table {
+        self.table
+    }
+
+    /// Resolve `value` into owned value
+    ///
+    /// [IntoOwned::into_owned_with] is retried after resolving the entities it deferred.
+    pub fn resolve<V>(&mut self, value: V) -> Result<V::Owned>
+    where
+        V: IntoOwned<Table = Table>,
+    {
+        loop {
+            match value.clone().into_owned_with(self) {
+                Err(Error::Deferred { .. }) if !self.deferred.is_empty() => {
+                    self.resolve_deferred()?
+                }
+                result => {
+                    self.deferred.clear();
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Get shared entity, resolved only at the first call for each entity id
+    pub fn get_shared<T>(&mut self, entity_id: u64) -> Result<Rc<T::Owned>>
+    where
+        T: Holder<Table = Table>,
+        Table: EntityTable<T>,
+        T::Owned: 'static,
+    {
+        if let Some(owned) = self.lookup::<T>(entity_id) {
+            return Ok(owned);
+        }
+        self.deferred.push(Task::new::<T>(entity_id));
+        self.resolve_deferred()?;
+        Ok(self
+            .lookup::<T>(entity_id)
+            .expect("Resolved by resolve_deferred"))
+    }
+
+    fn lookup<T>(&self, entity_id: u64) -> Option<Rc<T::Owned>>
+    where
+        T: Holder,
+        T::Owned: 'static,
+    {
+        let position = self.index.get(&(TypeId::of::<T>(), entity_id))?;
+        Some(
+            self.shared[*position]
+                .clone()
+                .downcast()
+                .expect("Shared entity is stored with its holder type"),
+        )
+    }
 
-use crate::{ast::*, error::*};
-use serde::{
-    de::{self, IntoDeserializer, VariantAccess},
-    Deserialize,
-};
-use std::{collections::HashMap, fmt, marker::PhantomData};
+    /// Get shared entity, or defer it with [Error::Deferred] if not resolved yet
+    fn shared<T>(&mut self, entity_id: u64) -> Result<Rc<T::Owned>>
+    where
+        T: Holder<Table = Table>,
+        Table: EntityTable<T>,
+        T::Owned: 'static,
+    {
+        if let Some(owned) = self.lookup::<T>(entity_id) {
+            return Ok(owned);
+        }
+        self.deferred.push(Task::new::<T>(entity_id));
+        Err(Error::Deferred {
+            entity_id,
+            entity_name: T::name().to_string(),
+        })
+    }
 
-/// Trait for resolving a reference through entity id
-pub trait IntoOwned: Clone + 'static {
-    type Owned;
-    type Table;
-    fn into_owned(self, table: &Self::Table) -> Result<Self::Owned>;
-}
+    /// Resolve entity into its value, where the reference must not go back to the entities being resolved
+    fn owned<T>(&mut self, entity_id: u64) -> Result<T::Owned>
+    where
+        T: Holder<Table = Table>,
+        Table: EntityTable<T>,
+    {
+        let key = (TypeId::of::<T>(), entity_id);
+        if !self.resolving.insert(key) {
+            return Err(Error::CyclicReference {
+                entity_id,
+                entity_name: T::name().to_string(),
+            });
+        }
+        let owned = self.resolve_holder::<T>(entity_id);
+        self.resolving.remove(&key);
+        owned
+    }
 
-impl<T: IntoOwned> IntoOwned for Vec<T> {
-    type Owned = Vec<T::Owned>;
-    type Table = T::Table;
-    fn into_owned(self, table: &Self::Table) -> Result<Self::Owned> {
-        self.into_iter().map(|x| x.into_owned(table)).collect()
+    fn resolve_holder<T>(&mut self, entity_id: u64) -> Result<T::Owned>
+    where
+        T: Holder<Table = Table>,
+        Table: EntityTable<T>,
+    {
+        let table = self.table;
+        match table.get_holder(entity_id) {
+            Some(holder) => holder.into_owned().into_owned_with(self),
+            // Table without `get_holder` resolves the entity by itself
+            None => table.get_owned(entity_id),
+        }
+    }
+
+    /// Resolve deferred entities and the entities they defer in post-order with an explicit stack
+    fn resolve_deferred(&mut self) -> Result<()> {
+        // Tasks with whether it has been tried, i.e. it is in `resolving` waiting for the tasks above it
+        let mut stack: Vec<(Task<Table>, bool)> =
+            self.deferred.drain(..).map(|task| (task, false)).collect();
+        let result = self.run(&mut stack);
+        // Clear the state for the next call after an error
+        for (task, tried) in stack {
+            if tried {
+                self.resolving.remove(&task.key);
+            }
+        }
+        self.deferred.clear();
+        result
+    }
+
+    fn run(&mut self, stack: &mut Vec<(Task<Table>, bool)>) -> Result<()> {
+        while let Some((task, tried)) = stack.last_mut() {
+            let (key, resolve) = (task.key, task.resolve);
+            // Deferred several times before resolved
+            if self.index.contains_key(&key) {
+                stack.pop();
+                continue;
+            }
+            *tried = true;
+            self.resolving.insert(key);
+            match resolve(self, key.1) {
+                Ok(owned) => {
+                    self.resolving.remove(&key);
+                    stack.pop();
+                    self.index.insert(key, self.shared.len());
+                    self.shared.push(owned);
+                }
+                Err(Error::Deferred { .. }) if !self.deferred.is_empty() => {
+                    for task in self.deferred.drain(..) {
+                        if self.resolving.contains(&task.key) {
+                            return Err(Error::CyclicReference {
+                                entity_id: task.key.1,
+                                entity_name: task.name.to_string(),
+                            });
+                        }
+                        stack.push((task, false));
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
     }
 }
 
-/// Trait for a field of tables
-pub trait Holder: IntoOwned {
-    fn name() -> &'static str;
-    fn attr_len() -> usize;
+impl<Table> Drop for Resolver<'_, Table> {
+    fn drop(&mut self) {
+        // Referrers come after referenced entities, and are dropped first
+        while let Some(owned) = self.shared.pop() {
+            drop(owned);
+        }
+    }
 }
 
-pub trait WithVisitor {
-    type Visitor: for<'de> de::Visitor<'de, Value = Self>;
-    fn visitor_new() -> Self::Visitor;
+/// Resolve entities into [Rc] shared for the same entity id
+///
+/// [EntityTable::get_owned] creates a new owned value for each call,
+/// and thus an entity looked up several times, e.g. a point shared by many edges,
+/// is materialized every time. This wrapper memoizes resolved entities by [Resolver]
+/// so that they are resolved only once and shared in memory.
+///
+/// Entities referenced through `Rc<T>` attributes, see [SharedPlaceHolder],
+/// are shared with the entities returned by this table.
+/// Other referenced entities are still embedded by value.
+pub struct SharedTable<'table, Table> {
+    resolver: RefCell<Resolver<'table, Table>>,
 }
 
-/// Trait for tables which pulls an entity (`T`) from an entity id (`u64`)
-pub trait EntityTable<T: Holder<Table = Self>> {
-    /// Get owned entity from table
-    fn get_owned(&self, entity_id: u64) -> Result<T::Owned>;
+impl<'table, Table> SharedTable<'table, Table> {
+    pub fn new(table: &'table Table) -> Self {
+        SharedTable {
+            resolver: RefCell::new(Resolver::new(table)),
+        }
+    }
 
-    /// Get owned entities as an iterator
-    fn owned_iter<'table>(&'table self) -> Box<dyn Iterator<Item = Result<T::Owned>> + 'table>;
+    /// Get shared entity, resolved only at the first call for each entity id
+    pub fn get_shared<T>(&self, entity_id: u64) -> Result<Rc<T::Owned>>
+    where
+        T: Holder<Table = Table>,
+        Table: EntityTable<T>,
+        T::Owned: 'static,
+    {
+        self.resolver.borrow_mut().get_shared::<T>(entity_id)
+    }
 }
 
-/// Create Table from [DataSection]
-pub trait TableInit: Default {
-    fn append_data_section(&mut self, section: &DataSection) -> Result<()>;
+/// Resolve references lazily, only when they are followed
+///
+/// [IntoOwned::into_owned] and [SharedTable] materialize the whole graph reachable from an entity,
+/// which is wasteful when only a few entities are inspected in a large model.
+/// This wrapper returns holders keeping their references by [LazyTable::get],
+/// and each reference is resolved by [LazyTable::resolve] at its first access.
+/// Resolved entities are cached in [Resolver], and shared as [SharedTable] does.
+pub struct LazyTable<'table, Table> {
+    resolver: RefCell<Resolver<'table, Table>>,
+}
 
-    fn from_data_section(section: &DataSection) -> Result<Self> {
-        let mut table = Self::default();
-        table.append_data_section(section)?;
-        Ok(table)
+impl<'table, Table> LazyTable<'table, Table> {
+    pub fn new(table: &'table Table) -> Self {
+        LazyTable {
+            resolver: RefCell::new(Resolver::new(table)),
+        }
     }
 
-    fn from_data_sections(sections: &[DataSection]) -> Result<Self> {
-        let mut table = Self::default();
-        for section in sections {
-            table.append_data_section(section)?;
+    /// Get the holder of entity keeping its references
+    ///
+    /// Returns [Error::UnknownEntity] if the table does not have the entity,
+    /// or does not provide holders by [EntityTable::get_holder].
+    pub fn get<T>(&self, entity_id: u64) -> Result<Cow<'table, T>>
+    where
+        T: Holder<Table = Table>,
+        Table: EntityTable<T>,
+    {
+        let table: &'table Table = self.resolver.borrow().table();
+        table
+            .get_holder(entity_id)
+            .ok_or(Error::UnknownEntity(entity_id))
+    }
+
+    /// Follow a reference of a holder, e.g. `edge.first` of `EdgeHolder`,
+    /// and resolve it into the owned value only at the first access for each entity id
+    ///
+    /// Inline entities are not cached since they have no entity id.
+    /// Returns [Error::UnsupportedReference] for references other than entity instance names,
+    /// e.g. `@1` for a value instance.
+    pub fn resolve<T>(&self, place_holder: &PlaceHolder<T>) -> Result<Rc<T::Owned>>
+    where
+        T: Holder<Table = Table>,
+        Table: EntityTable<T>,
+        T::Owned: 'static,
+    {
+        let mut resolver = self.resolver.borrow_mut();
+        match place_holder {
+            PlaceHolder::Ref(Name::Entity(id)) => resolver.get_shared::<T>(*id),
+            PlaceHolder::Ref(name) => Err(Error::UnsupportedReference(name.clone())),
+            PlaceHolder::Owned(holder) => Ok(Rc::new(resolver.resolve(holder.clone())?)),
         }
-        Ok(table)
     }
-}
 
-pub fn get_owned<T, Table>(table: &Table, map: &HashMap<u64, T>, entity_id: u64) -> Result<T::Owned>
-where
-    T: Holder<Table = Table>,
-    Table: EntityTable<T>,
-{
-    match map.get(&entity_id) {
-        Some(holder) => holder.clone().into_owned(table),
-        None => Err(Error::UnknownEntity(entity_id)),
+    /// Number of entities resolved so far
+    pub fn len(&self) -> usize {
+        self.resolver.borrow().index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 }
 
-pub fn owned_iter<'table, T, Table>(
-    table: &'table Table,
-    map: &'table HashMap<u64, T>,
-) -> Box<dyn Iterator<Item = Result<T::Owned>> + 'table>
-where
-    T: Holder<Table = Table>,
-    Table: EntityTable<T>,
-{
-    Box::new(
-        map.values()
-            .cloned()
-            .map(move |value| value.into_owned(table)),
-    )
+/// Record name stored in the table, i.e. [Holder::name] of its holders
+///
+/// This is used by `#[derive(TableInit)]` to dispatch records into tables,
+/// so that a name overridden by `#[holder(rename = "...")]` is respected.
+pub fn holder_name<T: Holder>(_table: &impl IdMap<T>) -> &'static str {
+    T::name()
+}
+
+/// Check that entity ids in a data section are unique,
+/// and not stored yet where `stored(id)` returns true
+///
+/// This is used by `#[derive(TableInit)]` before dispatching records,
+/// since instances of the same id may be stored into different tables
+/// or not stored at all as [UnknownRecord].
+pub fn check_duplicated_ids(section: &DataSection, stored: impl Fn(u64) -> bool) -> Result<()> {
+    let mut ids = HashSet::with_capacity(section.entities.len());
+    for entity in &section.entities {
+        let id = entity.id();
+        if !ids.insert(id) || stored(id) {
+            return Err(Error::DuplicatedEntity(id));
+        }
+    }
+    Ok(())
 }
 
 /// Helper function to implement TableInit trait
-pub fn insert_record<'de, T: de::Deserialize<'de>>(
-    table: &mut HashMap<u64, T>,
+///
+/// Returns [Error::ArityMismatch] without deserializing
+/// if the number of parameters differs from [Holder::attr_len].
+pub fn insert_record<'de, T: Holder + de::Deserialize<'de>>(
+    table: &mut impl IdMap<T>,
     id: u64,
     record: &Record,
 ) -> crate::error::Result<()> {
+    check_arity::<T>(record)?;
     if table
         .insert(id, de::Deserialize::deserialize(record)?)
         .is_some()
@@ -204,6 +1583,201 @@ pub fn insert_record<'de, T: de::Deserialize<'de>>(
     }
 }
 
+fn check_arity<T: Holder>(record: &Record) -> Result<()> {
+    let found = record.parameters().len();
+    if found != T::attr_len() {
+        return Err(Error::ArityMismatch {
+            type_name: T::name().to_string(),
+            expected: T::attr_len(),
+            found,
+            missing: T::attr_names().get(found).map(|name| name.to_string()),
+        });
+    }
+    Ok(())
+}
+
+/// Helper function to implement `TryFrom<&Record>` for owned types of espr-generated code
+///
+/// The record is deserialized into the holder `T` and converted into its owned type
+/// with an empty table, i.e. inline typed parameters are resolved,
+/// but references to other entities like `#12` result in [Error::UnknownEntity].
+///
+/// Returns [Error::RecordNameMismatch] if the record is not named [Holder::name].
+pub fn owned_from_record<T>(record: &Record) -> Result<T::Owned>
+where
+    T: Holder + de::DeserializeOwned,
+    T::Table: Default,
+{
+    if record.name != T::name() {
+        return Err(Error::RecordNameMismatch {
+            expected: T::name().to_string(),
+            found: record.name.clone(),
+        });
+    }
+    let holder = T::from_record(record)?;
+    holder.validate()?;
+    holder.into_owned(&T::Table::default())
+}
+
+/// Helper function to implement TableInit trait for complex entity instance
+///
+/// Returns `false` without inserting if the component `name` is not the most derived one,
+/// i.e. there are other components which are not its supertypes.
+pub fn insert_subsuper_record<'de, T: de::Deserialize<'de>>(
+    table: &mut impl IdMap<T>,
+    id: u64,
+    record: &SubSuperRecord,
+    name: &str,
+) -> crate::error::Result<bool> {
+    match record.deserialize_component(name)? {
+        Some(value) => {
+            if table.insert(id, value).is_some() {
+                Err(Error::DuplicatedEntity(id))
+            } else {
+                Ok(true)
+            }
+        }
+        None => Ok(false),
+    }
+}
+
+/// Helper function to implement [TableInit::unknown_records]
+///
+/// Returns whether [insert_subsuper_record] inserts the record into `table`.
+pub fn matches_subsuper_record<'de, T: de::Deserialize<'de>>(
+    _table: &impl IdMap<T>,
+    record: &SubSuperRecord,
+    name: &str,
+) -> crate::error::Result<bool> {
+    Ok(record.deserialize_component::<T>(name)?.is_some())
+}
+
+/// Helper function to implement [TableInit::unknown_records]
+///
+/// Returns whether [insert_combined_record] inserts the record into `table`.
+pub fn matches_combined_record<'de, T: de::Deserialize<'de>>(
+    _table: &impl IdMap<T>,
+    record: &SubSuperRecord,
+) -> crate::error::Result<bool> {
+    Ok(record.deserialize_combined::<T>()?.is_some())
+}
+
+/// Helper function to implement TableInit trait for complex entity instance
+/// corresponding to a combined holder
+///
+/// Returns `false` without inserting if the components do not match to the holder.
+pub fn insert_combined_record<'de, T: de::Deserialize<'de>>(
+    table: &mut impl IdMap<T>,
+    id: u64,
+    record: &SubSuperRecord,
+) -> crate::error::Result<bool> {
+    match record.deserialize_combined()? {
+        Some(value) => {
+            if table.insert(id, value).is_some() {
+                Err(Error::DuplicatedEntity(id))
+            } else {
+                Ok(true)
+            }
+        }
+        None => Ok(false),
+    }
+}
+
+/// Holder of an attribute derived in subtypes, e.g. `w` of `point` for the following schema:
+///
+/// ```text
+/// ENTITY point;
+///   x: REAL;
+///   w: REAL;
+/// END_ENTITY;
+///
+/// ENTITY unit_point SUBTYPE OF (point);
+/// DERIVE
+///   SELF\point.w : REAL := 1.0;
+/// END_ENTITY;
+/// ```
+///
+/// The value is omitted as `*` in the instances of the subtype, e.g. `UNIT_POINT(POINT((3.0, *)))`,
+/// while `$` is not accepted unless the attribute is `OPTIONAL`.
+/// `#[holder(derived_in_subtype)]` wraps the holder of such attribute in this type,
+/// and the subtype fills the value by `#[holder(derive(w = 1.0))]` on its supertype field
+/// before resolving it into the owned value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Derived<T> {
+    /// `*`, to be derived by the subtype
+    Omitted,
+    Value(T),
+}
+
+impl<T: VisitRefs> VisitRefs for Derived<T> {
+    fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+        if let Derived::Value(value) = self {
+            value.visit_refs(visitor);
+        }
+    }
+
+    fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+        if let Derived::Value(value) = self {
+            value.remap_refs(map);
+        }
+    }
+}
+
+impl<T: ser::Serialize> ser::Serialize for Derived<T> {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            Derived::Omitted => serializer.serialize_newtype_struct(DERIVED_TOKEN, &()),
+            Derived::Value(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Derived<T> {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(
+            DERIVED_TOKEN,
+            DerivedVisitor::<T> {
+                phantom: PhantomData,
+            },
+        )
+    }
+}
+
+struct DerivedVisitor<T> {
+    phantom: PhantomData<T>,
+}
+
+impl<'de, T: Deserialize<'de>> de::Visitor<'de> for DerivedVisitor<T> {
+    type Value = Derived<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "value or `*` for the attribute derived in subtypes"
+        )
+    }
+
+    fn visit_unit<E: de::Error>(self) -> ::std::result::Result<Self::Value, E> {
+        Ok(Derived::Omitted)
+    }
+
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> ::std::result::Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Ok(Derived::Value(T::deserialize(deserializer)?))
+    }
+}
+
 /// Owned value or reference through entity/value id
 #[derive(Debug, Clone, PartialEq)]
 pub enum PlaceHolder<T> {
@@ -211,25 +1785,57 @@ pub enum PlaceHolder<T> {
     Owned(T),
 }
 
-impl<T: Holder> IntoOwned for PlaceHolder<T>
+impl<T> PlaceHolder<T> {
+    /// Entity id of the reference, e.g. `Some(1)` for `#1`,
+    /// or `None` for a value reference or an inline value
+    pub fn entity_id(&self) -> Option<u64> {
+        match self {
+            PlaceHolder::Ref(Name::Entity(id)) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+impl<T> IntoOwned for PlaceHolder<T>
 where
+    T: Holder,
     T::Table: EntityTable<T>,
 {
     type Owned = T::Owned;
     type Table = T::Table;
-    /// Get owned value, or look up entity table and clone it for a reference.
+    /// Get owned value, or look up entity table and resolve it for a reference.
     ///
     /// Errors
     /// -------
     /// - if table lookup failed, i.e. unknown entity id not registered in the table
     ///
-    fn into_owned(self, table: &Self::Table) -> Result<T::Owned> {
+    /// - if the reference is cyclic, e.g. `#1 = A(#2); #2 = B(#1);`,
+    ///   since owned value cannot represent it
+    ///
+    /// - [Error::UnsupportedReference] for references other than entity instance names, e.g. `@1`
+    fn into_owned_with(self, resolver: &mut Resolver<'_, Self::Table>) -> Result<T::Owned> {
         match self {
-            PlaceHolder::Ref(id) => match id {
-                Name::Entity(id) => table.get_owned(id),
-                _ => unimplemented!("ENTITY is only supported now"),
-            },
-            PlaceHolder::Owned(a) => a.into_owned(table),
+            PlaceHolder::Ref(Name::Entity(id)) => resolver.owned::<T>(id),
+            PlaceHolder::Ref(name) => Err(Error::UnsupportedReference(name)),
+            PlaceHolder::Owned(a) => a.into_owned_with(resolver),
+        }
+    }
+}
+
+impl<T: VisitRefs> VisitRefs for PlaceHolder<T> {
+    fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+        match self {
+            PlaceHolder::Ref(Name::Entity(id)) => visitor(*id),
+            PlaceHolder::Ref(_) => {}
+            PlaceHolder::Owned(holder) => holder.visit_refs(visitor),
+        }
+    }
+
+    fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+        match self {
+            PlaceHolder::Ref(Name::Entity(id)) => *id = map(*id),
+            PlaceHolder::Ref(_) => {}
+            PlaceHolder::Owned(holder) => holder.remap_refs(map),
         }
     }
 }
@@ -246,6 +1852,18 @@ impl<T> From<Name> for PlaceHolder<T> {
     }
 }
 
+impl<T: ser::Serialize> ser::Serialize for PlaceHolder<T> {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            PlaceHolder::Ref(name) => name.serialize(serializer),
+            PlaceHolder::Owned(holder) => holder.serialize(serializer),
+        }
+    }
+}
+
 impl<'de, T: Holder + WithVisitor + Deserialize<'de>> Deserialize<'de> for PlaceHolder<T> {
     fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
     where
@@ -343,3 +1961,149 @@ impl<'de, T: Deserialize<'de> + Holder + WithVisitor> de::Visitor<'de> for Place
         Ok(PlaceHolder::Owned(visitor.visit_map(map)?))
     }
 }
+
+/// [PlaceHolder] resolved into [Rc] of the owned value, used for `Rc<T>` attributes
+///
+/// An entity referenced from several entities is resolved once by [Resolver] and shared among them,
+/// i.e. within an [IntoOwned::into_owned] call, or among the entities resolved through [SharedTable].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedPlaceHolder<T>(pub PlaceHolder<T>);
+
+impl<T> IntoOwned for SharedPlaceHolder<T>
+where
+    T: Holder,
+    T::Table: EntityTable<T>,
+    T::Owned: 'static,
+{
+    type Owned = Rc<T::Owned>;
+    type Table = T::Table;
+    fn into_owned_with(self, resolver: &mut Resolver<'_, Self::Table>) -> Result<Self::Owned> {
+        match self.0 {
+            PlaceHolder::Ref(Name::Entity(id)) => resolver.shared::<T>(id),
+            place_holder => Ok(Rc::new(place_holder.into_owned_with(resolver)?)),
+        }
+    }
+}
+
+impl<T: VisitRefs> VisitRefs for SharedPlaceHolder<T> {
+    fn visit_refs(&self, visitor: &mut dyn FnMut(u64)) {
+        self.0.visit_refs(visitor)
+    }
+
+    fn remap_refs(&mut self, map: &mut dyn FnMut(u64) -> u64) {
+        self.0.remap_refs(map)
+    }
+}
+
+impl<T: Holder> From<T> for SharedPlaceHolder<T> {
+    fn from(owned: T) -> Self {
+        SharedPlaceHolder(PlaceHolder::Owned(owned))
+    }
+}
+
+impl<T> From<Name> for SharedPlaceHolder<T> {
+    fn from(rvalue: Name) -> Self {
+        SharedPlaceHolder(PlaceHolder::Ref(rvalue))
+    }
+}
+
+impl<T: ser::Serialize> ser::Serialize for SharedPlaceHolder<T> {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Holder + WithVisitor + Deserialize<'de>> Deserialize<'de> for SharedPlaceHolder<T> {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        PlaceHolder::deserialize(deserializer).map(SharedPlaceHolder)
+    }
+}
+
+/// [de::DeserializeSeed] re-attaching the keyword of a typed parameter `KEYWORD(...)`
+///
+/// The visitor of a SELECT holder has already consumed the keyword
+/// when it finds that the keyword belongs to a nested SELECT member.
+/// This seed hands the keyword and the remaining value to the member holder
+/// as if it were the original typed parameter.
+#[doc(hidden)]
+pub struct KeywordSeed<T> {
+    keyword: String,
+    phantom: PhantomData<T>,
+}
+
+impl<T> KeywordSeed<T> {
+    pub fn new(keyword: String) -> Self {
+        KeywordSeed {
+            keyword,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> de::DeserializeSeed<'de> for KeywordSeed<T> {
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> ::std::result::Result<T, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        T::deserialize(KeywordDeserializer {
+            keyword: Some(self.keyword),
+            value: Some(deserializer),
+        })
+    }
+}
+
+/// Single-key map `{ keyword: value }` whose value is yet another deserializer
+struct KeywordDeserializer<D> {
+    keyword: Option<String>,
+    value: Option<D>,
+}
+
+impl<'de, D: de::Deserializer<'de>> de::Deserializer<'de> for KeywordDeserializer<D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> ::std::result::Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_map(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        struct tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de, D: de::Deserializer<'de>> de::MapAccess<'de> for KeywordDeserializer<D> {
+    type Error = D::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> ::std::result::Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.keyword.take() {
+            Some(keyword) => seed.deserialize(keyword.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> ::std::result::Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed is called before next_key_seed");
+        seed.deserialize(value)
+    }
+}
@@ -0,0 +1,374 @@
+//! Reader and writer of ISO-10303-28 XML in the late binding form
+//!
+//! [from_str] reads entity instances written as XML elements into [DataSection]s,
+//! which is the same AST as the Part 21 parser produces,
+//! and thus tables are built through [TableInit::from_data_sections](crate::tables::TableInit::from_data_sections):
+//!
+//! ```
+//! use ruststep::{ast::*, xml};
+//!
+//! let sections = xml::from_str(r#"
+//! <iso_10303_28 version="2.0">
+//!   <uos id="uos_1">
+//!     <Point id="i1">
+//!       <x>1.0</x>
+//!       <y><real>2</real></y>
+//!     </Point>
+//!     <Segment id="i2">
+//!       <name><string>s</string></name>
+//!       <start><Point ref="i1"/></start>
+//!       <stop nil="true"/>
+//!     </Segment>
+//!   </uos>
+//! </iso_10303_28>
+//! "#).unwrap();
+//!
+//! assert_eq!(sections.len(), 1);
+//! assert_eq!(
+//!     sections[0].entities[1],
+//!     EntityInstance::Simple {
+//!         id: 2,
+//!         record: Record {
+//!             name: "SEGMENT".to_string(),
+//!             parameter: vec![
+//!                 Parameter::String("s".to_string()),
+//!                 Parameter::Ref(Name::Entity(1)),
+//!                 Parameter::NotProvided,
+//!             ].into(),
+//!         },
+//!     }
+//! );
+//! ```
+//!
+//! Mapping
+//! --------
+//! Namespace prefixes of elements and attributes are ignored.
+//!
+//! - Each `uos` element becomes a [DataSection].
+//!   If there is no `uos` element, the root element is read as a single section.
+//! - Each child element of a section is an entity instance.
+//!   Its name is the entity name, e.g. `Point` for `POINT`,
+//!   and its `id` attribute, e.g. `i1` or `1`, becomes the entity instance id.
+//! - Each child element of an entity instance is an attribute in the declared order.
+//!   Names of attribute elements are not checked.
+//!
+//! Values of attributes and members of aggregates are read as follows:
+//!
+//! | XML                                                   | [Parameter]                    |
+//! |:------------------------------------------------------|:-------------------------------|
+//! | `ref="i1"` attribute                                  | `#1`                           |
+//! | `nil="true"` attribute                                | `$`                            |
+//! | `derived="true"` attribute                            | `*`                            |
+//! | `cType` attribute, or `list`, `set`, `bag`, `array`   | list of child elements         |
+//! | `<integer>1</integer>`                                | `1`                            |
+//! | `<real>1</real>`, `<double>1</double>`                | `1.0`                          |
+//! | `<string>s</string>`                                  | `'s'`                          |
+//! | `<boolean>`, `<logical>` with `true`/`false`/`unknown`| `.T.`, `.F.`, `.U.`            |
+//! | `<enumeration>red</enumeration>`                      | `.RED.`                        |
+//! | other element, e.g. `<Length_measure>`                | typed parameter `LENGTH_MEASURE(..)` |
+//! | text without wrapper                                  | integer, real, or string       |
+//!
+//! Suffix `-wrapper` of value elements, e.g. `double-wrapper`, is ignored.
+//! Text without wrapper element is read as integer or real if possible,
+//! and use wrapper elements to read e.g. `'10'` as a string.
+//!
+//! Writing
+//! --------
+//! [to_string] writes records, e.g. `Tables::all_records` of espr-generated code,
+//! into a single `uos` element in the subset which [from_str] reads back:
+//!
+//! ```
+//! use ruststep::{ast::*, xml};
+//! use std::str::FromStr;
+//!
+//! let records = vec![
+//!     Ok((1, Record::from_str("POINT(1.0, 2.0)").unwrap())),
+//!     Ok((2, Record::from_str("SEGMENT('s', #1, $)").unwrap())),
+//! ];
+//! let xml = xml::to_string(records).unwrap();
+//! assert!(xml.contains(r#"<entity ref="i1"/>"#));
+//!
+//! let sections = xml::from_str(&xml).unwrap();
+//! assert_eq!(sections[0].entities.len(), 2);
+//! assert_eq!(sections[0].entities[1].records()[0].to_string(), "SEGMENT('s', #1, $)");
+//! ```
+//!
+//! Element names are written in lower case, and attribute elements are named `attribute`
+//! since records do not know the names of attributes.
+//! Every value is written with its wrapper element, e.g. `<real>1.0</real>`,
+//! and enumerations including `.T.` and `.F.` are written as `<enumeration>`.
+//!
+//! Limitations
+//! ------------
+//! - Complex entity instances are not supported.
+//! - Attributes of supertypes are not nested,
+//!   i.e. instances of subtypes cannot be read yet.
+//! - Header information, e.g. `express_schema_name` of `uos`, is not checked nor written.
+//! - References other than entity instance names, e.g. `@1` or `#CONST`,
+//!   cannot be written.
+//!
+
+use crate::{ast::*, error::*};
+use quick_xml::{
+    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+    Writer,
+};
+use roxmltree::{Document, Node};
+
+/// Read data sections from ISO-10303-28 XML
+///
+/// See the module document for supported elements.
+pub fn from_str(input: &str) -> Result<Vec<DataSection>> {
+    let document = Document::parse(input).map_err(|e| Error::InvalidXml {
+        line: e.pos().row,
+        message: e.to_string(),
+    })?;
+    let root = document.root_element();
+    let mut uos: Vec<Node> = root
+        .descendants()
+        .filter(|node| node.is_element() && node.tag_name().name() == "uos")
+        .collect();
+    if uos.is_empty() {
+        uos.push(root);
+    }
+    uos.into_iter().map(data_section).collect()
+}
+
+fn data_section(uos: Node) -> Result<DataSection> {
+    let entities = elements(uos)
+        .map(|node| {
+            let id = attribute(node, "id")
+                .ok_or_else(|| invalid(node, "entity instance lacks `id` attribute"))?;
+            Ok(EntityInstance::Simple {
+                id: entity_id(node, id)?,
+                record: Record {
+                    name: keyword(node),
+                    parameter: Parameter::List(elements(node).map(content).collect::<Result<_>>()?),
+                },
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(DataSection {
+        meta: Vec::new(),
+        entities,
+    })
+}
+
+/// Read a value element, e.g. a member of an aggregate
+fn value(node: Node) -> Result<Parameter> {
+    if attribute(node, "ref").is_some()
+        || attribute(node, "nil").is_some()
+        || attribute(node, "derived").is_some()
+        || attribute(node, "cType").is_some()
+    {
+        return content(node);
+    }
+    let name = node.tag_name().name();
+    let text = || node.text().unwrap_or("").trim();
+    Ok(
+        match name
+            .strip_suffix("-wrapper")
+            .unwrap_or(name)
+            .to_lowercase()
+            .as_str()
+        {
+            "integer" => Parameter::Integer(
+                text()
+                    .parse()
+                    .map_err(|_| invalid(node, "invalid integer"))?,
+            ),
+            "real" | "double" => {
+                Parameter::Real(text().parse().map_err(|_| invalid(node, "invalid real"))?)
+            }
+            "string" => Parameter::String(node.text().unwrap_or("").to_string()),
+            "boolean" | "logical" => Parameter::Enumeration(
+                match text().to_lowercase().as_str() {
+                    "true" => "T",
+                    "false" => "F",
+                    "unknown" => "U",
+                    _ => return Err(invalid(node, "invalid boolean or logical")),
+                }
+                .to_string(),
+            ),
+            "enumeration" => Parameter::Enumeration(text().to_uppercase()),
+            "list" | "set" | "bag" | "array" => aggregate(node)?,
+            _ => Parameter::Typed {
+                keyword: keyword(node),
+                parameter: Box::new(content(node)?),
+            },
+        },
+    )
+}
+
+/// Read the content of an attribute element, or of a value element regardless of its name
+fn content(node: Node) -> Result<Parameter> {
+    if let Some(id) = attribute(node, "ref") {
+        return Ok(Parameter::Ref(Name::Entity(entity_id(node, id)?)));
+    }
+    if attribute(node, "nil") == Some("true") {
+        return Ok(Parameter::NotProvided);
+    }
+    if attribute(node, "derived") == Some("true") {
+        return Ok(Parameter::Omitted);
+    }
+    if attribute(node, "cType").is_some() {
+        return aggregate(node);
+    }
+    let mut children = elements(node);
+    match (children.next(), children.next()) {
+        (None, _) => {
+            let text = node.text().unwrap_or("").trim();
+            Ok(if let Ok(i) = text.parse() {
+                Parameter::Integer(i)
+            } else if let Ok(x) = text.parse() {
+                Parameter::Real(x)
+            } else {
+                Parameter::String(text.to_string())
+            })
+        }
+        (Some(child), None) => value(child),
+        _ => aggregate(node),
+    }
+}
+
+fn aggregate(node: Node) -> Result<Parameter> {
+    Ok(Parameter::List(
+        elements(node).map(value).collect::<Result<_>>()?,
+    ))
+}
+
+fn elements<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(Node::is_element)
+}
+
+/// Get an attribute by its local name, ignoring namespace
+fn attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|attr| attr.name() == name)
+        .map(|attr| attr.value())
+}
+
+/// Entity instance id from `id` or `ref` attribute, e.g. `1` for `i1`
+fn entity_id(node: Node, id: &str) -> Result<u64> {
+    id.trim_start_matches(|c: char| !c.is_ascii_digit())
+        .parse()
+        .map_err(|_| invalid(node, &format!("invalid entity instance id '{}'", id)))
+}
+
+fn keyword(node: Node) -> String {
+    node.tag_name().name().to_uppercase()
+}
+
+fn invalid(node: Node, message: &str) -> Error {
+    Error::InvalidXml {
+        line: node.document().text_pos_at(node.range().start).row,
+        message: message.to_string(),
+    }
+}
+
+/// Write records into ISO-10303-28 XML
+///
+/// See the module document for the written subset.
+pub fn to_string(records: impl IntoIterator<Item = Result<(u64, Record)>>) -> Result<String> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    let mut write = |event: Event| {
+        writer
+            .write_event(event)
+            .expect("Writing into Vec<u8> never fails")
+    };
+    write(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)));
+    write(Event::Start(
+        BytesStart::new("iso_10303_28").with_attributes([("version", "2.0")]),
+    ));
+    write(Event::Start(
+        BytesStart::new("uos").with_attributes([("id", "uos_1")]),
+    ));
+    for record in records {
+        let (id, record) = record?;
+        let id = format!("i{}", id);
+        let name = record.name.to_lowercase();
+        let entity = BytesStart::new(name.as_str()).with_attributes([("id", id.as_str())]);
+        let mut events = Vec::new();
+        match &record.parameter {
+            Parameter::List(attributes) => {
+                for attribute in attributes {
+                    write_content(&mut events, "attribute", attribute)?;
+                }
+            }
+            parameter => write_content(&mut events, "attribute", parameter)?,
+        }
+        if events.is_empty() {
+            write(Event::Empty(entity));
+        } else {
+            write(Event::Start(entity));
+            events.into_iter().for_each(&mut write);
+            write(Event::End(BytesEnd::new(name.as_str())));
+        }
+    }
+    write(Event::End(BytesEnd::new("uos")));
+    write(Event::End(BytesEnd::new("iso_10303_28")));
+    Ok(String::from_utf8(writer.into_inner()).expect("Written XML is valid UTF-8"))
+}
+
+/// Write an element whose content is read by [content]
+fn write_content(
+    events: &mut Vec<Event<'static>>,
+    name: &str,
+    parameter: &Parameter,
+) -> Result<()> {
+    let start = BytesStart::new(name.to_string());
+    match parameter {
+        Parameter::NotProvided => {
+            events.push(Event::Empty(start.with_attributes([("nil", "true")])))
+        }
+        Parameter::Omitted => {
+            events.push(Event::Empty(start.with_attributes([("derived", "true")])))
+        }
+        _ => {
+            events.push(Event::Start(start));
+            write_value(events, parameter)?;
+            events.push(Event::End(BytesEnd::new(name.to_string())));
+        }
+    }
+    Ok(())
+}
+
+/// Write an element read by [value]
+fn write_value(events: &mut Vec<Event<'static>>, parameter: &Parameter) -> Result<()> {
+    let mut text = |name: &'static str, text: String| {
+        if text.is_empty() {
+            events.push(Event::Empty(BytesStart::new(name)));
+        } else {
+            events.push(Event::Start(BytesStart::new(name)));
+            events.push(Event::Text(BytesText::new(&text).into_owned()));
+            events.push(Event::End(BytesEnd::new(name)));
+        }
+    };
+    match parameter {
+        Parameter::Typed { keyword, parameter } => {
+            write_content(events, &keyword.to_lowercase(), parameter)?
+        }
+        Parameter::Integer(i) => text("integer", i.to_string()),
+        Parameter::Real(x) => text("real", format!("{:?}", x)),
+        Parameter::String(s) => text("string", s.clone()),
+        Parameter::Enumeration(e) => text("enumeration", e.clone()),
+        Parameter::List(list) => {
+            if list.is_empty() {
+                events.push(Event::Empty(BytesStart::new("list")));
+            } else {
+                events.push(Event::Start(BytesStart::new("list")));
+                for parameter in list {
+                    write_value(events, parameter)?;
+                }
+                events.push(Event::End(BytesEnd::new("list")));
+            }
+        }
+        Parameter::Ref(Name::Entity(id)) => events.push(Event::Empty(
+            BytesStart::new("entity").with_attributes([("ref", format!("i{}", id).as_str())]),
+        )),
+        Parameter::Ref(name) => return Err(Error::UnsupportedXml(format!("Reference {}", name))),
+        Parameter::NotProvided => write_content(events, "nil", parameter)?,
+        Parameter::Omitted => write_content(events, "derived", parameter)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use ruststep::{ast::*, tables::*};
+use serde::Deserialize;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY curve ABSTRACT SUPERTYPE OF (ONEOF (line, circle));
+        label: STRING;
+      END_ENTITY;
+
+      ENTITY line SUBTYPE OF (curve);
+        dir: REAL;
+      END_ENTITY;
+
+      ENTITY circle SUBTYPE OF (curve);
+        radius: REAL;
+      END_ENTITY;
+
+      ENTITY path;
+        edges: LIST [1:?] OF curve;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = LINE(CURVE(('l')), 1.0);
+  #2 = CIRCLE(CURVE(('c')), 2.0);
+  #3 = PATH((#1, #2, #1));
+ENDSEC;
+"#;
+
+fn line(label: &str, dir: f64) -> CurveAny {
+    CurveAny::Line(Box::new(Line {
+        curve: Curve {
+            label: label.to_string(),
+        },
+        dir,
+    }))
+}
+
+fn circle(label: &str, radius: f64) -> CurveAny {
+    CurveAny::Circle(Box::new(Circle {
+        curve: Curve {
+            label: label.to_string(),
+        },
+        radius,
+    }))
+}
+
+/// Abstract supertype itself is not a variant
+fn is_line(curve: &CurveAny) -> bool {
+    match curve {
+        CurveAny::Line(_) => true,
+        CurveAny::Circle(_) => false,
+    }
+}
+
+#[test]
+fn resolve_subtypes() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let path = EntityTable::<PathHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(
+        path.edges,
+        vec![line("l", 1.0), circle("c", 2.0), line("l", 1.0)]
+    );
+    assert_eq!(
+        path.edges.iter().map(is_line).collect::<Vec<_>>(),
+        vec![true, false, true]
+    );
+
+    let curves: Vec<CurveAny> = EntityTable::<CurveAnyHolder>::owned_iter(&table)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(curves, vec![line("l", 1.0), circle("c", 2.0)]);
+}
+
+#[test]
+fn deserialize_abstract() {
+    let record = Record::from_str("CIRCLE(CURVE(('c')), 2.0)").unwrap();
+    assert!(CurveAnyHolder::deserialize(&record).is_ok());
+    // Abstract supertype cannot be instantiated by itself
+    let record = Record::from_str("CURVE(('c'))").unwrap();
+    assert!(CurveAnyHolder::deserialize(&record).is_err());
+}
+
+#[test]
+fn reference_not_subtype() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = PATH((#2));
+          #2 = PATH((#3));
+          #3 = LINE(#99, 1.0);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    // #2 is PATH, not a subtype of CURVE
+    match EntityTable::<PathHolder>::get_owned(&table, 1) {
+        Err(ruststep::error::Error::UnexpectedEntityType {
+            entity_id,
+            expected,
+        }) => {
+            assert_eq!(entity_id, 2);
+            assert_eq!(expected, vec!["LINE", "CIRCLE"]);
+        }
+        result => panic!("Unexpected result: {:?}", result),
+    }
+    // Error while resolving the subtype is reported as it is
+    assert!(matches!(
+        EntityTable::<PathHolder>::get_owned(&table, 2),
+        Err(ruststep::error::Error::UnknownEntity(99))
+    ));
+}
@@ -0,0 +1,99 @@
+use ruststep::{
+    error::Error,
+    tables::{EntityTable, Holder},
+};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY direction;
+        ratios: LIST [3:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY placement;
+        axis: direction;
+        ref_direction: OPTIONAL direction;
+        others: LIST [0:?] OF direction;
+      END_ENTITY;
+
+      ENTITY named;
+        name: direction;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn resolve_on_demand() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = DIRECTION((0.0, 0.0, 1.0));
+          #2 = DIRECTION((1.0, 0.0, 0.0));
+          #3 = PLACEMENT(#1, $, (#2, DIRECTION(((0.0, 1.0, 0.0)))));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let holder = EntityTable::<PlacementHolder>::get_holder(&table, 3).unwrap();
+
+    assert_eq!(
+        holder.axis(&table).unwrap(),
+        Direction {
+            ratios: vec![0.0, 0.0, 1.0]
+        }
+    );
+    assert_eq!(holder.ref_direction(&table).unwrap(), None);
+    assert_eq!(
+        holder.others(&table).unwrap(),
+        vec![
+            Direction {
+                ratios: vec![1.0, 0.0, 0.0]
+            },
+            Direction {
+                ratios: vec![0.0, 1.0, 0.0]
+            },
+        ]
+    );
+}
+
+#[test]
+fn dangling_reference() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #3 = PLACEMENT(#1, $, ());
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let holder = EntityTable::<PlacementHolder>::get_holder(&table, 3).unwrap();
+    assert!(matches!(holder.axis(&table), Err(Error::UnknownEntity(1))));
+    assert_eq!(holder.others(&table).unwrap(), vec![]);
+}
+
+#[test]
+fn collision_with_holder_methods() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = DIRECTION((0.0, 0.0, 1.0));
+          #2 = NAMED(#1);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let holder = EntityTable::<NamedHolder>::get_holder(&table, 2).unwrap();
+
+    // `Holder::name` is not shadowed by the accessor of `name` attribute
+    assert_eq!(NamedHolder::name(), "NAMED");
+    assert_eq!(
+        holder.resolve_name(&table).unwrap(),
+        Direction {
+            ratios: vec![0.0, 0.0, 1.0]
+        }
+    );
+}
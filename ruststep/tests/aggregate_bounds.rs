@@ -0,0 +1,54 @@
+use ruststep::{error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY polyline;
+        points: LIST [2:?] OF REAL;
+        tags: OPTIONAL SET [1:3] OF STRING;
+        weights: LIST OF REAL;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = POLYLINE((0.0, 1.0), ('a'), ());
+  #2 = POLYLINE((0.0), $, ());
+  #3 = POLYLINE((0.0, 1.0, 2.0), ('a', 'b', 'c', 'd'), ());
+  #4 = POLYLINE((0.0, 1.0), (), ());
+ENDSEC;
+"#;
+
+#[test]
+fn holder_validate() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    assert!(table.polyline_holders()[&1].validate().is_ok());
+
+    let err = table.polyline_holders()[&2].validate().unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::AggregateBounds { attr, min: 2, max: None, found: 1 } if attr == "points"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Attribute 'points' has 1 elements, but its bound is [2:?]"
+    );
+
+    let err = table.polyline_holders()[&3].validate().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Attribute 'tags' has 4 elements, but its bound is [1:3]"
+    );
+}
+
+#[test]
+fn tables_validate() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let ids: Vec<u64> = table.validate().into_iter().map(|(id, _err)| id).collect();
+    assert_eq!(ids, vec![2, 3, 4]);
+}
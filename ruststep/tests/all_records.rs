@@ -0,0 +1,61 @@
+use ruststep::ast::Record;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY person SUPERTYPE OF (employee ANDOR student);
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY employee SUBTYPE OF (person);
+        pay: INTEGER;
+      END_ENTITY;
+
+      ENTITY student SUBTYPE OF (person);
+        school_name: STRING;
+      END_ENTITY;
+
+      ENTITY club;
+        leader: person;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = PERSON('Hitori Goto');
+  #2 = STUDENT(PERSON(('Ikuno Kita')), 'Shuka');
+  #3 = (STUDENT('Simokitazawa') PERSON('Nizika Iziti') EMPLOYEE(15));
+  #4 = CLUB(#1);
+ENDSEC;
+"#;
+
+#[test]
+fn all_ids() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let mut ids: Vec<u64> = table.all_ids().collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn all_records() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let mut records: Vec<(u64, Record)> = table.all_records().collect::<Result<_, _>>().unwrap();
+    records.sort_by_key(|(id, _)| *id);
+    assert_eq!(
+        records,
+        vec![
+            (1, Record::from_str("PERSON('Hitori Goto')").unwrap()),
+            (
+                2,
+                Record::from_str("STUDENT(PERSON(('Ikuno Kita')), 'Shuka')").unwrap()
+            ),
+            (4, Record::from_str("CLUB(#1)").unwrap()),
+        ]
+    );
+}
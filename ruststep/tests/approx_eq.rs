@@ -0,0 +1,82 @@
+use ruststep::{primitive::ApproxEq, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      TYPE distance = REAL;
+      END_TYPE;
+
+      TYPE side = ENUMERATION OF (left, right);
+      END_TYPE;
+
+      ENTITY point;
+        coords: ARRAY [1:2] OF distance;
+      END_ENTITY;
+
+      ENTITY edge;
+        name: STRING;
+        first: point;
+        second: point;
+        weight: OPTIONAL REAL;
+        at: side;
+      END_ENTITY;
+
+      TYPE curve_or_point = SELECT (edge, point);
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn edge(input: &str) -> Edge {
+    let table = Tables::from_str(input).unwrap();
+    EntityTable::<EdgeHolder>::get_owned(&table, 3).unwrap()
+}
+
+const ORIGINAL: &str = r#"
+DATA;
+  #1 = POINT(((0.0), (0.0)));
+  #2 = POINT(((1.0), (0.5)));
+  #3 = EDGE('e', #1, #2, 1.0, .LEFT.);
+ENDSEC;
+"#;
+
+#[test]
+fn rounding() {
+    let rounded = edge(
+        r#"
+        DATA;
+          #1 = POINT(((1.0E-9), (0.0)));
+          #2 = POINT(((0.9999999999), (0.5000000001)));
+          #3 = EDGE('e', #1, #2, 1.0000000001, .LEFT.);
+        ENDSEC;
+        "#,
+    );
+    let original = edge(ORIGINAL);
+    assert_ne!(original, rounded);
+    assert!(original.approx_eq(&rounded, 1e-6));
+    assert!(!original.approx_eq(&rounded, 1e-12));
+}
+
+#[test]
+fn structure() {
+    let original = edge(ORIGINAL);
+    // String, enumeration, and OPTIONAL are compared exactly
+    let renamed = edge(&ORIGINAL.replace("'e'", "'f'"));
+    assert!(!original.approx_eq(&renamed, 1.0));
+    let right = edge(&ORIGINAL.replace(".LEFT.", ".RIGHT."));
+    assert!(!original.approx_eq(&right, 1.0));
+    let no_weight = edge(&ORIGINAL.replace("1.0, .LEFT.", "$, .LEFT."));
+    assert!(!original.approx_eq(&no_weight, 1.0));
+}
+
+#[test]
+fn select() {
+    let original = edge(ORIGINAL);
+    let a = CurveOrPoint::Edge(Box::new(original.clone()));
+    let b = CurveOrPoint::Point(Box::new(original.first.clone()));
+    assert!(a.approx_eq(&a.clone(), 0.0));
+    assert!(!a.approx_eq(&b, 1.0));
+}
@@ -0,0 +1,78 @@
+use ruststep::error::Error;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+        z: REAL;
+      END_ENTITY;
+
+      TYPE label = STRING;
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn table(data: &str) -> ruststep::error::Result<Tables> {
+    Tables::from_str(&format!("DATA;\n{}\nENDSEC;", data))
+}
+
+#[test]
+fn matched_arity() {
+    let table = table("#1 = POINT(0.0, 1.0, 2.0);\n#2 = LABEL('a');").unwrap();
+    assert_eq!(table.point_holders().len(), 1);
+    assert_eq!(table.label_holders().len(), 1);
+}
+
+#[test]
+fn too_many_parameters() {
+    let err = table("#1 = POINT(0.0, 1.0, 2.0, 3.0);").unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::ArityMismatch { type_name, expected: 3, found: 4, missing: None } if type_name == "POINT"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Record 'POINT' expects 3 parameters, but 4 parameters found"
+    );
+}
+
+#[test]
+fn too_few_parameters() {
+    let err = table("#1 = POINT(0.0, 1.0);").unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::ArityMismatch {
+            expected: 3,
+            found: 2,
+            missing: Some(name),
+            ..
+        } if name == "z"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Record 'POINT' expects 3 parameters, but 2 parameters found, lacking 'z'"
+    );
+
+    let err = table("#1 = LABEL('a', 'b');").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ArityMismatch {
+            expected: 1,
+            found: 2,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn attr_names() {
+    use ruststep::tables::Holder;
+    assert_eq!(PointHolder::attr_names(), &["x", "y", "z"]);
+    assert!(LabelHolder::attr_names().is_empty());
+}
@@ -0,0 +1,126 @@
+use ruststep::{ast::*, tables::*};
+use serde::Deserialize;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        coords: ARRAY [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY matrix;
+        rows: ARRAY [1:2] OF ARRAY [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY triangle;
+        vertices: ARRAY [1:3] OF point;
+      END_ENTITY;
+
+      ENTITY sparse;
+        values: ARRAY [0:1] OF OPTIONAL REAL;
+      END_ENTITY;
+
+      ENTITY polyline;
+        points: ARRAY [1:?] OF point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn holder<T: for<'de> Deserialize<'de>>(input: &str) -> ruststep::error::Result<T> {
+    T::deserialize(&Record::from_str(input).unwrap())
+}
+
+#[test]
+fn deserialize_array() {
+    let p: PointHolder = holder("POINT((1.0, 2.0, 3.0))").unwrap();
+    assert_eq!(
+        p,
+        PointHolder {
+            coords: [1.0, 2.0, 3.0]
+        }
+    );
+
+    let m: MatrixHolder = holder("MATRIX(((1.0, 0.0, 0.0), (0.0, 1.0, 0.0)))").unwrap();
+    assert_eq!(
+        m,
+        MatrixHolder {
+            rows: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        }
+    );
+
+    let s: SparseHolder = holder("SPARSE((1.0, $))").unwrap();
+    assert_eq!(
+        s,
+        SparseHolder {
+            values: [Some(1.0), None]
+        }
+    );
+}
+
+#[test]
+fn deserialize_array_length_mismatch() {
+    assert!(holder::<PointHolder>("POINT((1.0, 2.0))").is_err());
+    assert!(holder::<PointHolder>("POINT((1.0, 2.0, 3.0, 4.0))").is_err());
+    assert!(holder::<MatrixHolder>("MATRIX(((1.0, 0.0, 0.0), (0.0, 1.0)))").is_err());
+}
+
+#[test]
+fn indeterminate_bound_array() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT((0.0, 0.0, 0.0));
+          #2 = POINT((1.0, 0.0, 0.0));
+          #3 = POLYLINE((#1, #2, #1));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let polyline = EntityTable::<PolylineHolder>::get_owned(&table, 3).unwrap();
+    let points: &Vec<Point> = &polyline.points;
+    assert_eq!(points.len(), 3);
+}
+
+#[test]
+fn resolve_array_of_references() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT((0.0, 0.0, 0.0));
+          #2 = POINT((1.0, 0.0, 0.0));
+          #3 = POINT((0.0, 1.0, 0.0));
+          #4 = TRIANGLE((#1, #2, #3));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let triangle = EntityTable::<TriangleHolder>::get_owned(&table, 4).unwrap();
+    assert_eq!(
+        triangle.vertices,
+        [
+            Point::new([0.0, 0.0, 0.0]),
+            Point::new([1.0, 0.0, 0.0]),
+            Point::new([0.0, 1.0, 0.0]),
+        ]
+    );
+
+    let mut ids = Vec::new();
+    table.triangle_holders()[&4].visit_refs(&mut |id| ids.push(id));
+    assert_eq!(ids, vec![1, 2, 3]);
+}
+
+#[test]
+fn array_length_mismatch_in_data_section() {
+    assert!(Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT((0.0, 0.0));
+        ENDSEC;
+        "#,
+    )
+    .is_err());
+}
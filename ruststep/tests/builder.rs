@@ -0,0 +1,52 @@
+use ruststep::ast::ser::to_record;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+        z: REAL;
+      END_ENTITY;
+
+      ENTITY labeled_point;
+        location: point;
+        name: OPTIONAL STRING;
+        weights: OPTIONAL LIST [1:?] OF REAL;
+      END_ENTITY;
+    END_SCHEMA;
+    "#,
+    builder
+);
+
+use test_schema::*;
+
+#[test]
+fn required_only() {
+    let point = PointBuilder::new(1.0, 2.0, 3.0).build();
+    assert_eq!(point, Point::new(1.0, 2.0, 3.0));
+}
+
+#[test]
+fn optional_default() {
+    let location = PointBuilder::new(1.0, 2.0, 3.0).build();
+    let p = LabeledPointBuilder::new(location.clone()).build();
+    assert_eq!(p, LabeledPoint::new(location, None, None));
+    assert_eq!(
+        to_record(&p).unwrap().to_string(),
+        "LABELED_POINT(POINT((1., 2., 3.)), $, $)"
+    );
+}
+
+#[test]
+fn optional_setter() {
+    let location = PointBuilder::new(1.0, 2.0, 3.0).build();
+    let p = LabeledPointBuilder::new(location.clone())
+        .name("origin".to_string())
+        .weights(vec![0.5, 1.0])
+        .build();
+    assert_eq!(
+        p,
+        LabeledPoint::new(location, Some("origin".to_string()), Some(vec![0.5, 1.0]))
+    );
+}
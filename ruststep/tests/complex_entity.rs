@@ -0,0 +1,166 @@
+use ruststep::{ast::*, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY person SUPERTYPE OF (employee ANDOR student);
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY employee SUBTYPE OF (person);
+        pay: INTEGER;
+      END_ENTITY;
+
+      ENTITY student SUBTYPE OF (person);
+        school_name: STRING;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn deserialize_combined() {
+    let answer = EmployeeStudentHolder {
+        employee: EmployeeHolder {
+            person: PersonHolder {
+                name: "Nizika Iziti".to_string(),
+            }
+            .into(),
+            pay: 15,
+        }
+        .into(),
+        student: StudentHolder {
+            person: PersonHolder {
+                name: "Nizika Iziti".to_string(),
+            }
+            .into(),
+            school_name: "Simokitazawa".to_string(),
+        }
+        .into(),
+    };
+
+    // Components can be in any order
+    for input in [
+        "(PERSON('Nizika Iziti') EMPLOYEE(15) STUDENT('Simokitazawa'))",
+        "(STUDENT('Simokitazawa') EMPLOYEE(15) PERSON('Nizika Iziti'))",
+        "(EMPLOYEE(15) STUDENT('Simokitazawa') PERSON('Nizika Iziti'))",
+    ] {
+        let p = SubSuperRecord::from_str(input).unwrap();
+        let combined: Option<EmployeeStudentHolder> = p.deserialize_combined().unwrap();
+        assert_eq!(combined.as_ref(), Some(&answer));
+    }
+
+    // Lacks STUDENT component
+    let p = SubSuperRecord::from_str("(PERSON('Hitori Goto') EMPLOYEE(10))").unwrap();
+    let combined: Option<EmployeeStudentHolder> = p.deserialize_combined().unwrap();
+    assert_eq!(combined, None);
+}
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = (PERSON('Hitori Goto') EMPLOYEE(10));
+  #2 = (PERSON('Ikuno Kita') STUDENT('Shuka'));
+  #3 = (STUDENT('Simokitazawa') PERSON('Nizika Iziti') EMPLOYEE(15));
+ENDSEC;
+"#;
+
+#[test]
+fn get_owned_complex() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+
+    let employee = EntityTable::<EmployeeHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(
+        employee,
+        Employee {
+            person: Person {
+                name: "Hitori Goto".to_string()
+            },
+            pay: 10
+        }
+    );
+
+    let student = EntityTable::<StudentHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(
+        student,
+        Student {
+            person: Person {
+                name: "Ikuno Kita".to_string()
+            },
+            school_name: "Shuka".to_string()
+        }
+    );
+
+    let combined = EntityTable::<EmployeeStudentHolder>::get_owned(&table, 3).unwrap();
+    let person = Person {
+        name: "Nizika Iziti".to_string(),
+    };
+    assert_eq!(
+        combined,
+        EmployeeStudent {
+            employee: Employee {
+                person: person.clone(),
+                pay: 15
+            },
+            student: Student {
+                person,
+                school_name: "Simokitazawa".to_string()
+            }
+        }
+    );
+}
+
+#[test]
+fn serialize_combined() {
+    use ruststep::{ast::ser::to_record, express_builtins::TypeOf};
+
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let combined = EntityTable::<EmployeeStudentHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(
+        combined.r#typeof(),
+        &[
+            "TEST_SCHEMA.EMPLOYEE",
+            "TEST_SCHEMA.PERSON",
+            "TEST_SCHEMA.STUDENT"
+        ]
+    );
+    // Components are serialized as fields, including their supertypes
+    assert_eq!(
+        to_record(&combined).unwrap().to_string(),
+        "EMPLOYEE_STUDENT(EMPLOYEE((PERSON(('Nizika Iziti')), 15)), STUDENT((PERSON(('Nizika Iziti')), 'Simokitazawa')))"
+    );
+}
+
+#[test]
+fn write_complex() {
+    use ruststep::{ast::ser::to_subsuper_record, error::Error, header::HeaderInfo};
+
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let output = table.to_step_string(HeaderInfo::default()).unwrap();
+    // Components are sorted by name, and the shared supertype is written once
+    assert!(output.contains("#3 = (EMPLOYEE(15) PERSON('Nizika Iziti') STUDENT('Simokitazawa'));"));
+    assert_eq!(Tables::from_str(&output).unwrap(), table);
+
+    let names = ["EMPLOYEE", "PERSON", "STUDENT"];
+    let holder = &table.employee_student_holders()[&3];
+    assert_eq!(
+        to_subsuper_record(holder, &names).unwrap(),
+        SubSuperRecord::from_str("(EMPLOYEE(15) PERSON('Nizika Iziti') STUDENT('Simokitazawa'))")
+            .unwrap()
+    );
+
+    // Supertype shared by components must be the same
+    let mut holder = holder.clone();
+    if let PlaceHolder::Owned(student) = &mut holder.student {
+        student.person = PersonHolder {
+            name: "Hitori Goto".to_string(),
+        }
+        .into();
+    }
+    assert!(matches!(
+        to_subsuper_record(&holder, &names),
+        Err(Error::InvalidComplexEntity(_))
+    ));
+}
@@ -0,0 +1,31 @@
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      CONSTANT
+        pref_name_len : INTEGER := 70;
+        syn_name_len : INTEGER := pref_name_len;
+        offset : REAL := -0.5;
+        sep_id : STRING := '.';
+        state : LOGICAL := UNKNOWN;
+        -- Not evaluated at compile time, and thus not generated
+        origin : point := point(0.0);
+      END_CONSTANT;
+
+      ENTITY point;
+        x : REAL;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use ruststep::primitive::Logical;
+use test_schema::*;
+
+#[test]
+fn evaluated() {
+    assert_eq!(PREF_NAME_LEN, 70);
+    assert_eq!(SYN_NAME_LEN, PREF_NAME_LEN);
+    assert_eq!(OFFSET, -0.5);
+    assert_eq!(SEP_ID, ".");
+    assert_eq!(STATE, Logical::Unknown);
+}
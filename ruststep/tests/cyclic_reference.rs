@@ -0,0 +1,62 @@
+use ruststep::{error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY face;
+        bounds: LIST [0:?] OF edge;
+      END_ENTITY;
+
+      ENTITY edge;
+        parent: OPTIONAL face;
+      END_ENTITY;
+
+      ENTITY pair;
+        first: edge;
+        second: edge;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = FACE((#2));
+  #2 = EDGE(#1);
+  #3 = EDGE($);
+  #4 = PAIR(#3, #3);
+ENDSEC;
+"#;
+
+#[test]
+fn cyclic_reference() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let err = EntityTable::<FaceHolder>::get_owned(&table, 1).unwrap_err();
+    assert!(
+        matches!(err, Error::CyclicReference { entity_id: 2, ref entity_name } if entity_name == "EDGE"),
+        "{:?}",
+        err
+    );
+    let err = EntityTable::<EdgeHolder>::get_owned(&table, 2).unwrap_err();
+    assert!(matches!(err, Error::CyclicReference { entity_id: 1, .. }));
+}
+
+#[test]
+fn shared_reference_is_not_cyclic() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    // Detection state must be cleared after errors
+    assert!(EntityTable::<FaceHolder>::get_owned(&table, 1).is_err());
+
+    let edge = Edge { parent: None };
+    let pair = EntityTable::<PairHolder>::get_owned(&table, 4).unwrap();
+    assert_eq!(
+        pair,
+        Pair {
+            first: edge.clone(),
+            second: edge
+        }
+    );
+}
@@ -0,0 +1,60 @@
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        first: point;
+        second: point;
+      END_ENTITY;
+
+      ENTITY wire;
+        edges: LIST [1:?] OF edge;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn check_references() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(0.0);
+          #2 = EDGE(#1, #10);
+          #3 = EDGE(#11, #11);
+          #4 = WIRE((#2, #3, #12));
+          #5 = EDGE(#1, POINT((1.0)));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    assert_eq!(table.check_references(), vec![(2, 10), (3, 11), (4, 12)]);
+
+    // Dangling reference is detected only when resolving
+    assert!(EntityTable::<EdgeHolder>::get_owned(&table, 5).is_ok());
+    assert!(EntityTable::<EdgeHolder>::get_owned(&table, 2).is_err());
+}
+
+#[test]
+fn no_dangling_reference() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(0.0);
+          #2 = POINT(1.0);
+          #3 = EDGE(#1, #2);
+          #4 = WIRE((#3));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    assert!(table.check_references().is_empty());
+}
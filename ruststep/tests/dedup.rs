@@ -0,0 +1,79 @@
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY cartesian_point;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        first: cartesian_point;
+        second: cartesian_point;
+      END_ENTITY;
+
+      ENTITY wire;
+        edges: LIST [1:?] OF edge;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = CARTESIAN_POINT((0.0, 0.0));
+  #2 = CARTESIAN_POINT((1.0, 0.0));
+  #3 = CARTESIAN_POINT((0.0, 0.0));
+  #4 = CARTESIAN_POINT((1.0, 0.0));
+  #5 = CARTESIAN_POINT((0.0, 1.0));
+  #6 = EDGE(#1, #2);
+  #7 = EDGE(#3, #4);
+  #8 = EDGE(#4, #5);
+  #9 = WIRE((#6, #7, #8));
+ENDSEC;
+"#;
+
+#[test]
+fn dedup_points() {
+    let mut table = Tables::from_str(EXAMPLE).unwrap();
+    let removed = table.dedup_by_type::<CartesianPointHolder>().unwrap();
+    assert_eq!(removed, 2);
+
+    // The smallest id survives
+    assert!(EntityTable::<CartesianPointHolder>::contains(&table, 1));
+    assert!(EntityTable::<CartesianPointHolder>::contains(&table, 2));
+    assert!(!EntityTable::<CartesianPointHolder>::contains(&table, 3));
+    assert!(!EntityTable::<CartesianPointHolder>::contains(&table, 4));
+
+    // References are rewritten to the survivors
+    let references = table.references();
+    assert_eq!(references[&6], vec![1, 2]);
+    assert_eq!(references[&7], vec![1, 2]);
+    assert_eq!(references[&8], vec![2, 5]);
+    assert!(table.check_references().is_empty());
+
+    // Nothing to remove
+    assert_eq!(table.dedup_by_type::<CartesianPointHolder>().unwrap(), 0);
+}
+
+#[test]
+fn dedup_cascade() {
+    let mut table = Tables::from_str(EXAMPLE).unwrap();
+    // Edges #6 and #7 are equal by value even before points are merged
+    assert_eq!(table.dedup_by_type::<EdgeHolder>().unwrap(), 1);
+    assert_eq!(table.references()[&9], vec![6, 6, 8]);
+
+    assert_eq!(table.dedup_by_type::<CartesianPointHolder>().unwrap(), 2);
+    assert_eq!(table.references()[&8], vec![2, 5]);
+}
+
+#[test]
+fn remap_refs() {
+    let mut table = Tables::from_str(EXAMPLE).unwrap();
+    table.remap_refs(&mut |id| id * 10);
+    assert_eq!(table.references()[&6], vec![10, 20]);
+    assert_eq!(table.references()[&9], vec![60, 70, 80]);
+}
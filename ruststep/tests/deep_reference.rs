@@ -0,0 +1,107 @@
+use ruststep::{ast::Name, tables::*};
+use std::{rc::Rc, str::FromStr};
+
+// Entities referenced through `Rc<T>` attributes are resolved with an explicit stack
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY node;
+        next: LIST [0:1] OF node;
+      END_ENTITY;
+
+      ENTITY pair;
+        first: node;
+        second: node;
+      END_ENTITY;
+
+      ENTITY fork;
+        branches: LIST OF fork;
+      END_ENTITY;
+    END_SCHEMA;
+    "#,
+    shared
+);
+
+use test_schema::*;
+
+const DEPTH: u64 = 50_000;
+
+fn chain() -> String {
+    let mut data = "DATA;\n".to_string();
+    for id in 1..DEPTH {
+        data += &format!("#{} = NODE((#{}));\n", id, id + 1);
+    }
+    data += &format!("#{} = NODE(());\n", DEPTH);
+    data += &format!("#{} = PAIR(#1, #1);\n", DEPTH + 1);
+    data += "ENDSEC;\n";
+    data
+}
+
+fn depth(node: &Node) -> u64 {
+    let mut depth = 1;
+    let mut node = node;
+    while let Some(next) = node.next.first() {
+        depth += 1;
+        node = next;
+    }
+    depth
+}
+
+#[test]
+fn deep_reference() {
+    let table = Tables::from_str(&chain()).unwrap();
+    // Resolved entities are dropped with `shared` without recursion
+    let shared = SharedTable::new(&table);
+    let node = shared.get_shared::<NodeHolder>(1).unwrap();
+    assert_eq!(depth(&node), DEPTH);
+
+    let pair = shared.get_shared::<PairHolder>(DEPTH + 1).unwrap();
+    assert!(Rc::ptr_eq(&pair.first, &node));
+    assert!(Rc::ptr_eq(&pair.second, &node));
+}
+
+#[test]
+fn deep_place_holder() {
+    let table = Tables::from_str(&chain()).unwrap();
+    let mut resolver = Resolver::new(&table);
+    let node = resolver
+        .resolve(SharedPlaceHolder::<NodeHolder>(PlaceHolder::Ref(
+            Name::Entity(2),
+        )))
+        .unwrap();
+    assert_eq!(depth(&node), DEPTH - 1);
+}
+
+#[test]
+fn shared_branches() {
+    // #1 embeds 2^40 values of #41 through the branches if they are not shared
+    let mut data = "DATA;\n".to_string();
+    for id in 1..=40 {
+        data += &format!("#{} = FORK((#{}, #{}));\n", id, id + 1, id + 1);
+    }
+    data += "#41 = FORK(());\nENDSEC;\n";
+    let table = Tables::from_str(&data).unwrap();
+    let fork = EntityTable::<ForkHolder>::get_owned(&table, 1).unwrap();
+    assert!(Rc::ptr_eq(&fork.branches[0], &fork.branches[1]));
+}
+
+#[test]
+fn cyclic_shared_reference() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = NODE((#2));
+          #2 = NODE((#1));
+          #3 = NODE(());
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let shared = SharedTable::new(&table);
+    assert!(matches!(
+        shared.get_shared::<NodeHolder>(1),
+        Err(ruststep::error::Error::CyclicReference { entity_id: 1, .. })
+    ));
+    // Detection state must be cleared after errors
+    assert!(shared.get_shared::<NodeHolder>(3).is_ok());
+}
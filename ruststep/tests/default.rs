@@ -0,0 +1,50 @@
+use ruststep::ast::ser::to_record;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+        id: INTEGER;
+      END_ENTITY;
+
+      ENTITY annotation;
+        text: STRING;
+        visible: BOOLEAN;
+        tags: LIST [0:?] OF STRING;
+        ids: SET OF INTEGER;
+        anchor: OPTIONAL point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn zeros() {
+    assert_eq!(Point::default(), Point::new(0.0, 0.0, 0));
+    assert_eq!(
+        to_record(&Point::default()).unwrap().to_string(),
+        "POINT(0., 0., 0)"
+    );
+}
+
+#[test]
+fn empty_and_none() {
+    let annotation = Annotation::default();
+    assert_eq!(
+        annotation,
+        Annotation::new(String::new(), false, Vec::new(), Vec::new(), None)
+    );
+
+    // Start from default and set some attributes
+    let annotation = Annotation {
+        text: "origin".to_string(),
+        anchor: Some(Point::default()),
+        ..Default::default()
+    };
+    assert_eq!(annotation.text, "origin");
+    assert!(annotation.tags.is_empty());
+}
@@ -0,0 +1,110 @@
+use ruststep::{
+    ast::{Name, Parameter},
+    tables::{AttributeChange, EntityChange, EntitySummary, TablesDiff},
+};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY label;
+        name: STRING;
+        target: point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const BEFORE: &str = r#"
+DATA;
+  #1 = POINT(0.0, 0.0);
+  #2 = POINT(1.0, 2.0);
+  #3 = LABEL('a', #1);
+  #4 = LABEL('b', #2);
+  #5 = POINT(3.0, 3.0);
+ENDSEC;
+"#;
+
+#[test]
+fn same() {
+    let before = Tables::from_str(BEFORE).unwrap();
+    let diff = before.diff(&before, 0.0).unwrap();
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn diff() {
+    let before = Tables::from_str(BEFORE).unwrap();
+    let after = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(0.0, 0.001);
+          #2 = POINT(1.0, 2.5);
+          #3 = LABEL('a', #2);
+          #5 = LABEL('c', #1);
+          #6 = POINT(4.0, 4.0);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        before.diff(&after, 0.01).unwrap(),
+        TablesDiff {
+            added: vec![
+                // type is changed
+                EntitySummary {
+                    id: 5,
+                    entity: "LABEL".to_string(),
+                },
+                EntitySummary {
+                    id: 6,
+                    entity: "POINT".to_string(),
+                },
+            ],
+            removed: vec![
+                EntitySummary {
+                    id: 4,
+                    entity: "LABEL".to_string(),
+                },
+                EntitySummary {
+                    id: 5,
+                    entity: "POINT".to_string(),
+                },
+            ],
+            changed: vec![
+                // #1 is regarded as same within tolerance
+                EntityChange {
+                    id: 2,
+                    entity: "POINT".to_string(),
+                    attributes: vec![AttributeChange {
+                        index: 1,
+                        before: Parameter::Real(2.0),
+                        after: Parameter::Real(2.5),
+                    }],
+                },
+                EntityChange {
+                    id: 3,
+                    entity: "LABEL".to_string(),
+                    attributes: vec![AttributeChange {
+                        index: 1,
+                        before: Parameter::Ref(Name::Entity(1)),
+                        after: Parameter::Ref(Name::Entity(2)),
+                    }],
+                },
+            ],
+        }
+    );
+
+    // #1 is changed without tolerance
+    let diff = before.diff(&after, 0.0).unwrap();
+    assert_eq!(diff.changed.len(), 3);
+    assert_eq!(diff.changed[0].id, 1);
+}
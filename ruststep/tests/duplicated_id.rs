@@ -0,0 +1,63 @@
+use ruststep::{ast::DataSection, error::Error, tables::TableInit};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      TYPE label = STRING;
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn section(data: &str) -> DataSection {
+    DataSection::from_str(&format!("DATA;\n{}\nENDSEC;", data)).unwrap()
+}
+
+fn assert_duplicated(result: ruststep::error::Result<impl std::fmt::Debug>, id: u64) {
+    let err = result.unwrap_err();
+    assert!(matches!(err, Error::DuplicatedEntity(found) if found == id));
+    assert_eq!(err.to_string(), format!("Entity ID #{} is duplicated", id));
+}
+
+#[test]
+fn same_table() {
+    assert_duplicated(
+        Tables::from_data_section(&section("#1 = POINT(0.0, 1.0);\n#1 = POINT(2.0, 3.0);")),
+        1,
+    );
+}
+
+#[test]
+fn different_tables() {
+    assert_duplicated(
+        Tables::from_data_section(&section("#5 = POINT(0.0, 1.0);\n#5 = LABEL('a');")),
+        5,
+    );
+}
+
+#[test]
+fn unknown_records() {
+    // Duplicated ids are detected even in the lossy variant
+    assert_duplicated(
+        Tables::from_data_section_lossy(&section("#2 = CIRCLE(1.0);\n#2 = SQUARE(2.0);")),
+        2,
+    );
+}
+
+#[test]
+fn appended_section() {
+    let mut table = Tables::from_data_section(&section("#1 = POINT(0.0, 1.0);")).unwrap();
+    assert_duplicated(table.append_data_section(&section("#1 = LABEL('a');")), 1);
+    table
+        .append_data_section(&section("#2 = LABEL('a');"))
+        .unwrap();
+    assert_eq!(table.label_holders().len(), 1);
+}
@@ -156,3 +156,85 @@ fn get_owned_b3() {
         }
     );
 }
+
+#[test]
+fn from_data_section_lossy() {
+    let data_sec = DataSection::from_str(
+        r#"
+        DATA;
+          #1 = A(1.0, 2.0);
+          #2 = C(3.0);
+          #3 = B(4.0, #1);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+
+    // Strict version fails on unknown entity name
+    assert!(Tables::from_data_section(&data_sec).is_err());
+
+    let (table, unknowns) = Tables::from_data_section_lossy(&data_sec).unwrap();
+    assert_eq!(
+        unknowns,
+        vec![UnknownRecord {
+            id: 2,
+            records: vec![Record {
+                name: "C".to_string(),
+                parameter: vec![Parameter::real(3.0)].into(),
+            }]
+        }]
+    );
+    let b = EntityTable::<BHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(
+        b,
+        B {
+            z: 4.0,
+            a: A { x: 1.0, y: 2.0 }
+        }
+    );
+}
+
+#[test]
+fn append_data_section_unknown_keeps_tables() {
+    let mut table = Tables::from_str(EXAMPLE).unwrap();
+    let data_sec = DataSection::from_str(
+        r#"
+        DATA;
+          #4 = A(7.0, 8.0);
+          #5 = C(9.0);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    assert!(matches!(
+        table.append_data_section(&data_sec),
+        Err(ruststep::error::Error::UnknownEntityName { .. })
+    ));
+    assert!(!EntityTable::<AHolder>::contains(&table, 4));
+    assert_eq!(table, Tables::from_str(EXAMPLE).unwrap());
+}
+
+/// Hand-written tables implementing only `append_data_section`
+#[derive(Debug, Default)]
+struct ATables {
+    a: std::collections::HashMap<u64, AHolder>,
+}
+
+impl TableInit for ATables {
+    fn append_data_section(&mut self, section: &DataSection) -> ruststep::error::Result<()> {
+        for entity in &section.entities {
+            if let EntityInstance::Simple { id, record } = entity {
+                insert_record(&mut self.a, *id, record)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn hand_written_table_init() {
+    let data_sec = DataSection::from_str("DATA; #1 = A(1.0, 2.0); ENDSEC;").unwrap();
+    let (table, unknowns) = ATables::from_data_section_lossy(&data_sec).unwrap();
+    assert!(unknowns.is_empty());
+    assert_eq!(table.a[&1], AHolder { x: 1.0, y: 2.0 });
+}
@@ -0,0 +1,176 @@
+use ruststep::{error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn exchange(file_schema: &str) -> String {
+    format!(
+        r#"
+        ISO-10303-21;
+        HEADER;
+          FILE_DESCRIPTION((''), '2;1');
+          FILE_NAME('test.stp', '2021-01-01T00:00:00', (''), (''), '', '', '');
+          FILE_SCHEMA(({}));
+        ENDSEC;
+        DATA;
+          #1 = POINT(1.0, 2.0);
+          #2 = POINT(3.0, 4.0);
+        ENDSEC;
+        END-ISO-10303-21;
+        "#,
+        file_schema
+    )
+}
+
+#[test]
+fn schema_name() {
+    assert_eq!(Tables::schema_name(), Some("test_schema"));
+}
+
+#[test]
+fn from_exchange_str() {
+    let table = Tables::from_str(&exchange("'TEST_SCHEMA'")).unwrap();
+    let point = EntityTable::<PointHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(point, Point { x: 3.0, y: 4.0 });
+
+    // object identifier is ignored
+    let table = Tables::from_str(&exchange("'TEST_SCHEMA { 1 0 10303 999 1 }'")).unwrap();
+    assert_eq!(table.point_holders().len(), 2);
+}
+
+#[test]
+fn schema_mismatch() {
+    let err = Tables::from_str(&exchange("'CONFIG_CONTROL_DESIGN'")).unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::SchemaMismatch { expected, found }
+            if expected == "test_schema" && found == &["CONFIG_CONTROL_DESIGN"]
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Exchange structure is written in the schema CONFIG_CONTROL_DESIGN, not 'test_schema'"
+    );
+}
+
+#[test]
+fn parse_failure() {
+    let input = exchange("'TEST_SCHEMA'").replace("#2 = POINT(3.0, 4.0);", "#2 = POINT(3.0, 4.0)");
+    assert!(matches!(
+        Tables::from_str(&input),
+        Err(Error::TokenizeFailed(_))
+    ));
+}
+
+#[test]
+fn data_section_only() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0, 2.0);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    assert_eq!(table.point_holders().len(), 1);
+}
+
+mod write {
+    use ruststep::{header::*, tables::*};
+    use std::str::FromStr;
+
+    espr_derive::inline_express!(
+        r#"
+        SCHEMA write_schema;
+          TYPE label = STRING;
+          END_TYPE;
+
+          TYPE content = SELECT (label, point);
+          END_TYPE;
+
+          ENTITY point;
+            x: REAL;
+            y: REAL;
+          END_ENTITY;
+
+          ENTITY edge;
+            name: label;
+            first: point;
+            second: point;
+            content: content;
+            note: OPTIONAL STRING;
+          END_ENTITY;
+        END_SCHEMA;
+        "#
+    );
+
+    use write_schema::*;
+
+    const DATA: &str = r#"
+    DATA;
+      #1 = POINT(1.0, 2.0);
+      #2 = POINT(3.0, 4.0);
+      #3 = EDGE(LABEL(('e''1')), #1, #2, POINT((5.0, 6.0)), $);
+      #4 = EDGE(LABEL(('e2')), #2, POINT((7.0, 8.0)), LABEL(('c')), 'note');
+    ENDSEC;
+    "#;
+
+    #[test]
+    fn round_trip() {
+        let table = Tables::from_str(DATA).unwrap();
+        let output = table.to_step_string(HeaderInfo::default()).unwrap();
+        assert!(output.starts_with("ISO-10303-21;\nHEADER;\n"));
+        assert!(output.contains("FILE_SCHEMA(('WRITE_SCHEMA'));"));
+        assert!(output.ends_with("END-ISO-10303-21;"));
+        // References are kept, and entities are sorted by id
+        assert!(output.contains(
+            "#1 = POINT(1., 2.);\n#2 = POINT(3., 4.);\n#3 = EDGE(LABEL(('e''1')), #1, #2,"
+        ));
+        assert_eq!(Tables::from_str(&output).unwrap(), table);
+    }
+
+    #[test]
+    fn header() {
+        let table = Tables::from_str(DATA).unwrap();
+        let mut header = HeaderInfo::default();
+        header.file_name.name = "edge.stp".to_string();
+        header.schema = vec!["WRITE_SCHEMA { 1 0 10303 999 }".to_string()];
+        let output = table.to_step_string(header.clone()).unwrap();
+
+        let exchange = ruststep::ast::Exchange::from_str(&output).unwrap();
+        let parsed = Header::from_records(&exchange.header).unwrap();
+        assert_eq!(parsed.file_name, header.file_name);
+        assert_eq!(parsed.file_description, header.file_description);
+        assert_eq!(parsed.file_schema.schema, header.schema);
+        assert_eq!(Tables::from_exchange(&exchange).unwrap(), table);
+    }
+
+    #[test]
+    fn raw_header() {
+        let input = format!(
+            "ISO-10303-21;\nHEADER;\n/* keep me */\nFILE_DESCRIPTION(('original'), '2;1');\n  FILE_NAME('edge.stp', '2021-01-01T00:00:00', ('a'), ('b'), 'pre', 'sys', '');\nFILE_SCHEMA(('WRITE_SCHEMA'));\nENDSEC;\n{}\nEND-ISO-10303-21;\n",
+            DATA.trim()
+        );
+        let table = Tables::from_str(&input).unwrap();
+        let raw = ruststep::parser::header_span(&input).unwrap();
+
+        let header = HeaderInfo {
+            raw: Some(raw.to_string()),
+            ..Default::default()
+        };
+        let output = table.to_step_string(header).unwrap();
+        assert!(output.starts_with(&format!("ISO-10303-21;\n{}\nDATA;\n", raw)));
+        assert_eq!(ruststep::parser::header_span(&output).unwrap(), raw);
+        assert_eq!(Tables::from_str(&output).unwrap(), table);
+    }
+}
@@ -0,0 +1,56 @@
+use ruststep::express_builtins::{r#typeof, TypeOf};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY base;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY sub SUBTYPE OF (base);
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY subsub SUBTYPE OF (sub);
+        z: REAL;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn supertypes() {
+    let subsub = Subsub {
+        sub: Sub {
+            base: Base { x: 1.0 },
+            y: 2.0,
+        },
+        z: 3.0,
+    };
+    assert_eq!(
+        r#typeof(&subsub),
+        &["TEST_SCHEMA.SUBSUB", "TEST_SCHEMA.SUB", "TEST_SCHEMA.BASE"]
+    );
+    assert_eq!(
+        subsub.sub.r#typeof(),
+        &["TEST_SCHEMA.SUB", "TEST_SCHEMA.BASE"]
+    );
+    assert_eq!(subsub.sub.base.r#typeof(), &["TEST_SCHEMA.BASE"]);
+}
+
+#[test]
+fn any() {
+    // Names of the entity actually stored in `Any`
+    let any: BaseAny = Base { x: 1.0 }.into();
+    assert_eq!(r#typeof(&any), &["TEST_SCHEMA.BASE"]);
+
+    let any: BaseAny = Sub {
+        base: Base { x: 1.0 },
+        y: 2.0,
+    }
+    .into();
+    assert!(r#typeof(&any).contains(&"TEST_SCHEMA.SUB"));
+    assert!(r#typeof(&any).contains(&"TEST_SCHEMA.BASE"));
+}
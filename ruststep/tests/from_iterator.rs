@@ -0,0 +1,129 @@
+use ruststep::{ast::*, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY vertex;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        edge_start: vertex;
+        edge_end: vertex;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = VERTEX(0.0);
+  #2 = VERTEX(1.0);
+  #3 = EDGE(#1, #2);
+  #4 = FACE(#3);
+ENDSEC;
+"#;
+
+#[test]
+fn try_from_records() {
+    let section = DataSection::from_str(EXAMPLE).unwrap();
+    let table =
+        Tables::try_from_records(section.records().map(|(id, record)| (id, record.clone())))
+            .unwrap();
+    assert_eq!(table.vertex_holders().len(), 2);
+    assert_eq!(
+        EntityTable::<EdgeHolder>::get_owned(&table, 3).unwrap(),
+        Edge::new(Vertex::new(0.0), Vertex::new(1.0))
+    );
+}
+
+#[test]
+fn filter_by_name() {
+    let section = DataSection::from_str(EXAMPLE).unwrap();
+    let table = Tables::try_from_records(
+        section
+            .records()
+            .filter(|(_id, record)| record.name == "VERTEX")
+            .map(|(id, record)| (id, record.clone())),
+    )
+    .unwrap();
+    assert_eq!(table.vertex_holders().len(), 2);
+    assert!(table.edge_holders().is_empty());
+}
+
+#[test]
+fn unknown_records() {
+    let section = DataSection::from_str(EXAMPLE).unwrap();
+    let (table, unknowns) =
+        Tables::from_records_lossy(section.records().map(|(id, record)| (id, record.clone())))
+            .unwrap();
+    assert_eq!(table.edge_holders().len(), 1);
+    assert_eq!(unknowns.len(), 1);
+    assert_eq!(unknowns[0].id, 4);
+
+    // Errors are returned instead of panic
+    let records = vec![
+        (1, Record::from_str("VERTEX(0.0)").unwrap()),
+        (1, Record::from_str("VERTEX(1.0)").unwrap()),
+    ];
+    assert!(matches!(
+        Tables::from_records_lossy(records),
+        Err(ruststep::error::Error::DuplicatedEntity(1))
+    ));
+}
+
+#[test]
+fn try_from_records_error() {
+    let records = vec![
+        (1, Record::from_str("VERTEX(0.0)").unwrap()),
+        (1, Record::from_str("VERTEX(1.0)").unwrap()),
+    ];
+    assert!(matches!(
+        Tables::try_from_records(records),
+        Err(ruststep::error::Error::DuplicatedEntity(1))
+    ));
+
+    let records = vec![(1, Record::from_str("VERTEX(#2)").unwrap())];
+    assert!(Tables::try_from_records(records).is_err());
+}
+
+#[test]
+fn collect() {
+    let section = DataSection::from_str(EXAMPLE).unwrap();
+    let table: Tables = section
+        .records()
+        .map(|(id, record)| (id, record.clone()))
+        .collect();
+    assert_eq!(table.vertex_holders().len(), 2);
+    assert_eq!(
+        EntityTable::<EdgeHolder>::get_owned(&table, 3).unwrap(),
+        Edge::new(Vertex::new(0.0), Vertex::new(1.0))
+    );
+}
+
+#[test]
+fn collect_skips_invalid_records() {
+    let records = vec![
+        (1, Record::from_str("VERTEX(0.0)").unwrap()),
+        // Duplicated id
+        (1, Record::from_str("VERTEX(1.0)").unwrap()),
+        // Cannot be deserialized
+        (2, Record::from_str("VERTEX(#1)").unwrap()),
+        // Unknown entity name
+        (3, Record::from_str("FACE(#1)").unwrap()),
+        (4, Record::from_str("VERTEX(2.0)").unwrap()),
+    ];
+    let table: Tables = records.into_iter().collect();
+    assert_eq!(table.vertex_holders().len(), 2);
+    assert_eq!(
+        EntityTable::<VertexHolder>::get_owned(&table, 1).unwrap(),
+        Vertex::new(0.0)
+    );
+    assert_eq!(
+        EntityTable::<VertexHolder>::get_owned(&table, 4).unwrap(),
+        Vertex::new(2.0)
+    );
+}
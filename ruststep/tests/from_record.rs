@@ -0,0 +1,96 @@
+use ruststep::{
+    ast::{ser::to_record, Name, Record},
+    error::Error,
+    tables::{Holder, PlaceHolder},
+};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY segment;
+        head: point;
+        tail: point;
+      END_ENTITY;
+
+      ENTITY base SUPERTYPE OF (sub);
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY sub SUBTYPE OF (base);
+        y: REAL;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn from_record() {
+    let record = Record::from_str("POINT(1.0, 2.0)").unwrap();
+    let point = PointHolder::from_record(&record).unwrap();
+    assert_eq!(point, PointHolder { x: 1.0, y: 2.0 });
+}
+
+#[test]
+fn references_kept() {
+    let record = Record::from_str("SEGMENT(#1, POINT((1.0, 2.0)))").unwrap();
+    let segment = SegmentHolder::from_record(&record).unwrap();
+    assert_eq!(segment.head, PlaceHolder::Ref(Name::Entity(1)));
+    assert_eq!(
+        segment.tail,
+        PlaceHolder::Owned(PointHolder { x: 1.0, y: 2.0 })
+    );
+}
+
+#[test]
+fn inverse_of_to_record() {
+    let record = Record::from_str("SEGMENT(#1, #2)").unwrap();
+    let segment = SegmentHolder::from_record(&record).unwrap();
+    assert_eq!(to_record(&segment).unwrap(), record);
+}
+
+#[test]
+fn name_mismatch() {
+    let record = Record::from_str("SEGMENT(1.0, 2.0)").unwrap();
+    assert!(matches!(
+        PointHolder::from_record(&record),
+        Err(Error::RecordNameMismatch { expected, found }) if expected == "POINT" && found == "SEGMENT"
+    ));
+}
+
+#[test]
+fn arity_mismatch() {
+    let record = Record::from_str("POINT(1.0)").unwrap();
+    assert!(matches!(
+        PointHolder::from_record(&record),
+        Err(Error::ArityMismatch {
+            expected: 2,
+            found: 1,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn subtype_into_any() {
+    let record = Record::from_str("SUB(BASE((1.0)), 2.0)").unwrap();
+    let any = BaseAnyHolder::from_record(&record).unwrap();
+    assert!(matches!(any, BaseAnyHolder::Sub(_)));
+
+    let record = Record::from_str("BASE(1.0)").unwrap();
+    let any = BaseAnyHolder::from_record(&record).unwrap();
+    assert!(matches!(any, BaseAnyHolder::Base(_)));
+
+    let record = Record::from_str("POINT(1.0, 2.0)").unwrap();
+    assert!(matches!(
+        BaseAnyHolder::from_record(&record),
+        Err(Error::RecordNameMismatch { .. })
+    ));
+}
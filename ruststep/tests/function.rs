@@ -0,0 +1,58 @@
+use ruststep::primitive::Logical;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      CONSTANT
+        max_len : INTEGER := 3;
+      END_CONSTANT;
+
+      FUNCTION is_short(items : LIST OF REAL) : BOOLEAN;
+        RETURN (SIZEOF(items) <= max_len);
+      END_FUNCTION;
+
+      FUNCTION norm(x : REAL; y : REAL) : REAL;
+        RETURN ((x ** 2 + y ** 2) ** 0.5);
+      END_FUNCTION;
+
+      FUNCTION half(n : INTEGER) : INTEGER;
+        RETURN (-n DIV 2);
+      END_FUNCTION;
+
+      FUNCTION is_unit(x : REAL; y : REAL) : LOGICAL;
+        RETURN (ABS(norm(x, y) - 1.0) < 1.0e-6);
+      END_FUNCTION;
+
+      FUNCTION is_named(name : STRING) : BOOLEAN;
+        RETURN (EXISTS(name) AND NOT (name = ''));
+      END_FUNCTION;
+
+      FUNCTION index_count(items : LIST OF INTEGER) : INTEGER;
+        RETURN (HIINDEX(items) - LOINDEX(items) + 1);
+      END_FUNCTION;
+
+      -- Not translated since it has local variables, and thus not generated
+      FUNCTION last_index(items : LIST OF INTEGER) : INTEGER;
+        LOCAL
+          n : INTEGER := HIINDEX(items);
+        END_LOCAL;
+        RETURN (n);
+      END_FUNCTION;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn translated() {
+    assert!(is_short(&[1.0, 2.0, 3.0]));
+    assert!(!is_short(&[1.0, 2.0, 3.0, 4.0]));
+    assert_eq!(norm(3.0, 4.0), 5.0);
+    assert_eq!(half(-7), 3);
+    assert_eq!(is_unit(0.6, 0.8), Logical::True);
+    assert_eq!(is_unit(1.0, 1.0), Logical::False);
+    assert!(is_named("a"));
+    assert!(!is_named(""));
+    assert_eq!(index_count(&[4, 5, 6]), 3);
+}
@@ -0,0 +1,80 @@
+#[cfg(feature = "flate2")]
+mod gzip {
+    use flate2_crate::{write::GzEncoder, Compression};
+    use ruststep::{error::Error, parser::decompress, tables::*};
+    use std::{io::Write, path::PathBuf, str::FromStr};
+
+    espr_derive::inline_express!(
+        r#"
+        SCHEMA test_schema;
+          ENTITY point;
+            x: REAL;
+            y: REAL;
+          END_ENTITY;
+        END_SCHEMA;
+        "#
+    );
+
+    use test_schema::*;
+
+    const EXAMPLE: &str = r#"
+    DATA;
+      #1 = POINT(0.0, 1.0);
+      #2 = POINT(2.0, 3.0);
+    ENDSEC;
+    "#;
+
+    fn compress(input: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(input).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    // Unique path in the temporary directory for each test
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ruststep-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn decompress_gzip() {
+        let compressed = compress(EXAMPLE.as_bytes());
+        assert_eq!(
+            decompress(&compressed).unwrap().as_ref(),
+            EXAMPLE.as_bytes()
+        );
+
+        // Not compressed
+        assert_eq!(
+            decompress(EXAMPLE.as_bytes()).unwrap().as_ref(),
+            EXAMPLE.as_bytes()
+        );
+
+        // Broken after the magic bytes
+        assert!(matches!(decompress(&compressed[..12]), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn load_file() {
+        let plain = temp_path("plain.stp");
+        std::fs::write(&plain, EXAMPLE).unwrap();
+        let compressed = temp_path("compressed.stpZ");
+        std::fs::write(&compressed, compress(EXAMPLE.as_bytes())).unwrap();
+
+        let expected = Tables::from_str(EXAMPLE).unwrap();
+        let from_plain = Tables::load_file(&plain).unwrap();
+        let from_compressed = Tables::load_file(&compressed).unwrap();
+        std::fs::remove_file(plain).unwrap();
+        std::fs::remove_file(compressed).unwrap();
+
+        assert_eq!(from_plain, expected);
+        assert_eq!(from_compressed, expected);
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(matches!(
+            Tables::load_file(temp_path("missing.stp")),
+            Err(Error::Io(_))
+        ));
+    }
+}
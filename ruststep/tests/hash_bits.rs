@@ -0,0 +1,72 @@
+use ruststep::{
+    primitive::{BitHashed, HashBits},
+    tables::*,
+};
+use std::{collections::HashSet, str::FromStr};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      TYPE side = ENUMERATION OF (left, right);
+      END_TYPE;
+
+      ENTITY cartesian_point;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        first: cartesian_point;
+        second: cartesian_point;
+        at: side;
+      END_ENTITY;
+
+      TYPE curve_or_point = SELECT (edge, cartesian_point);
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const DATA: &str = r#"
+DATA;
+  #1 = CARTESIAN_POINT((0.0, 0.0, 0.0));
+  #2 = CARTESIAN_POINT((1.0, 0.5, 0.0));
+  #3 = CARTESIAN_POINT((0.0, 0.0, 0.0));
+  #4 = CARTESIAN_POINT((1.0, 0.5, 0.0));
+  #5 = CARTESIAN_POINT((-0.0, 0.0, 0.0));
+  #6 = EDGE(#1, #2, .LEFT.);
+  #7 = EDGE(#3, #4, .LEFT.);
+  #8 = EDGE(#3, #4, .RIGHT.);
+ENDSEC;
+"#;
+
+#[test]
+fn dedup_points() {
+    let table = Tables::from_str(DATA).unwrap();
+    let points: HashSet<_> = EntityTable::<CartesianPointHolder>::owned_iter(&table)
+        .map(|point| BitHashed(point.unwrap()))
+        .collect();
+    // `-0.0` differs from `0.0` by bit pattern
+    assert_eq!(points.len(), 3);
+    assert!(points.contains(&BitHashed(CartesianPoint::new(vec![1.0, 0.5, 0.0]))));
+}
+
+#[test]
+fn nested() {
+    let table = Tables::from_str(DATA).unwrap();
+    let edges: Vec<_> = (6..=8)
+        .map(|id| EntityTable::<EdgeHolder>::get_owned(&table, id).unwrap())
+        .collect();
+    assert!(edges[0].eq_bits(&edges[1]));
+    assert!(!edges[1].eq_bits(&edges[2]));
+
+    let selects: HashSet<_> = edges
+        .iter()
+        .map(|edge| BitHashed(CurveOrPoint::Edge(Box::new(edge.clone()))))
+        .chain([BitHashed(CurveOrPoint::CartesianPoint(Box::new(
+            edges[0].first.clone(),
+        )))])
+        .collect();
+    assert_eq!(selects.len(), 3);
+}
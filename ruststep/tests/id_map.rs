@@ -0,0 +1,126 @@
+// Test for hand-written tables using containers other than `HashMap`
+
+use ruststep::tables::*;
+use ruststep_derive::{as_holder, Holder, TableInit};
+use std::{collections::BTreeMap, str::FromStr};
+
+#[derive(TableInit, Default)]
+pub struct Table {
+    a: BTreeMap<u64, as_holder!(A)>,
+    b: BTreeMap<u64, as_holder!(B)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = a)]
+#[holder(generate_deserialize)]
+pub struct A {
+    pub x: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = b)]
+#[holder(generate_deserialize)]
+pub struct B {
+    #[holder(use_place_holder)]
+    pub a: A,
+}
+
+const EXAMPLE: &str = r#"
+DATA;
+  #5 = A(5);
+  #3 = A(3);
+  #4 = B(#3);
+  #1 = A(1);
+  #2 = B(A((2)));
+ENDSEC;
+"#;
+
+#[test]
+fn btree_map() {
+    let table = Table::from_str(EXAMPLE).unwrap();
+    let a: Vec<A> = EntityTable::<AHolder>::owned_iter(&table)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    // ascending id order
+    assert_eq!(a, vec![A { x: 1 }, A { x: 3 }, A { x: 5 }]);
+    let b = EntityTable::<BHolder>::get_owned(&table, 4).unwrap();
+    assert_eq!(b.a, A { x: 3 });
+    assert_eq!(table.references()[&4], vec![3]);
+}
+
+mod hash_map {
+    use ruststep::tables::*;
+    use ruststep_derive::{as_holder, Holder, TableInit};
+    use std::{collections::HashMap, str::FromStr};
+
+    #[derive(TableInit, Default)]
+    pub struct Table {
+        a: HashMap<u64, as_holder!(A)>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Holder)]
+    #[holder(table = Table)]
+    #[holder(field = a)]
+    #[holder(generate_deserialize)]
+    pub struct A {
+        pub x: i64,
+    }
+
+    #[test]
+    fn sorted() {
+        let records: String = (1..=100)
+            .rev()
+            .map(|id| format!("#{} = A({});\n", id, id))
+            .collect();
+        let table = Table::from_str(&format!("DATA;\n{}ENDSEC;\n", records)).unwrap();
+        let a: Vec<i64> = EntityTable::<AHolder>::owned_iter(&table)
+            .map(|a| a.unwrap().x)
+            .collect();
+        // ascending id order regardless of hash
+        assert_eq!(a, (1..=100).collect::<Vec<_>>());
+    }
+}
+
+#[cfg(feature = "indexmap")]
+mod index_map {
+    use super::EXAMPLE;
+    use indexmap_crate::IndexMap;
+    use ruststep::tables::*;
+    use ruststep_derive::{as_holder, Holder, TableInit};
+    use std::str::FromStr;
+
+    #[derive(TableInit, Default)]
+    pub struct Table {
+        a: IndexMap<u64, as_holder!(A)>,
+        b: IndexMap<u64, as_holder!(B)>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Holder)]
+    #[holder(table = Table)]
+    #[holder(field = a)]
+    #[holder(generate_deserialize)]
+    pub struct A {
+        pub x: i64,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Holder)]
+    #[holder(table = Table)]
+    #[holder(field = b)]
+    #[holder(generate_deserialize)]
+    pub struct B {
+        #[holder(use_place_holder)]
+        pub a: A,
+    }
+
+    #[test]
+    fn index_map() {
+        let table = Table::from_str(EXAMPLE).unwrap();
+        let a: Vec<i64> = EntityTable::<AHolder>::owned_iter(&table)
+            .map(|a| a.unwrap().x)
+            .collect();
+        // order in the exchange structure
+        assert_eq!(a, vec![5, 3, 1]);
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(feature = "json")]
+mod json {
+    use ruststep::{json::*, tables::*};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    espr_derive::inline_express!(
+        r#"
+        SCHEMA test_schema;
+          ENTITY point;
+            x: REAL;
+            y: REAL;
+          END_ENTITY;
+
+          ENTITY segment;
+            name: STRING;
+            start: point;
+            stop: OPTIONAL point;
+          END_ENTITY;
+        END_SCHEMA;
+        "#
+    );
+
+    use test_schema::*;
+
+    const EXAMPLE: &str = r#"
+    DATA;
+      #1 = POINT(0.0, 1.0);
+      #2 = POINT(2.0, 3.0);
+      #3 = SEGMENT('s', #1, $);
+    ENDSEC;
+    "#;
+
+    #[test]
+    fn holder() {
+        let table = Tables::from_str(EXAMPLE).unwrap();
+        let segment = &table.segment_holders()[&3];
+        assert_eq!(
+            to_json(segment).unwrap(),
+            json!({ "SEGMENT": ["s", { "$ref": 1 }, null] })
+        );
+    }
+
+    #[test]
+    fn owned() {
+        let table = Tables::from_str(EXAMPLE).unwrap();
+        let segment = table.segment_holders()[&3]
+            .clone()
+            .into_owned(&table)
+            .unwrap();
+        assert_eq!(
+            to_json(&segment).unwrap(),
+            json!({ "SEGMENT": ["s", { "POINT": [0.0, 1.0] }, null] })
+        );
+    }
+
+    #[test]
+    fn all_records() {
+        let table = Tables::from_str(EXAMPLE).unwrap();
+        assert_eq!(
+            records_to_json(table.all_records()).unwrap(),
+            json!({
+                "1": { "POINT": [0.0, 1.0] },
+                "2": { "POINT": [2.0, 3.0] },
+                "3": { "SEGMENT": ["s", { "$ref": 1 }, null] },
+            })
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use ruststep::{error::Error, tables::*};
+use std::{rc::Rc, str::FromStr};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        first: point;
+        second: point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = POINT(0.0, 0.0);
+  #2 = POINT(1.0, 0.0);
+  #3 = EDGE(#1, #2);
+  #4 = EDGE(#2, POINT((2.0, 0.0)));
+  #5 = EDGE(#1, #6);
+ENDSEC;
+"#;
+
+#[test]
+fn resolve_lazily() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let lazy = LazyTable::new(&table);
+
+    let edge = lazy.get::<EdgeHolder>(3).unwrap();
+    assert_eq!(edge.first, PlaceHolder::Ref(ruststep::ast::Name::Entity(1)));
+    // references are not resolved until followed
+    assert_eq!(lazy.len(), 0);
+
+    let p2 = lazy.resolve(&edge.second).unwrap();
+    assert_eq!(*p2, Point { x: 1.0, y: 0.0 });
+    assert_eq!(lazy.len(), 1);
+
+    // cached
+    let edge = lazy.get::<EdgeHolder>(4).unwrap();
+    assert!(Rc::ptr_eq(&lazy.resolve(&edge.first).unwrap(), &p2));
+    assert_eq!(
+        *lazy.resolve(&edge.second).unwrap(),
+        Point { x: 2.0, y: 0.0 }
+    );
+    // inline entity is not cached
+    assert_eq!(lazy.len(), 1);
+}
+
+#[test]
+fn resolve_entity_with_references() {
+    use ruststep::ast::Name;
+
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let lazy = LazyTable::new(&table);
+
+    // references of the followed entity are resolved together
+    let e3 = PlaceHolder::<EdgeHolder>::Ref(Name::Entity(3));
+    let edge = lazy.resolve(&e3).unwrap();
+    assert_eq!(
+        *edge,
+        EntityTable::<EdgeHolder>::get_owned(&table, 3).unwrap()
+    );
+    assert!(Rc::ptr_eq(&lazy.resolve(&e3).unwrap(), &edge));
+    assert_eq!(lazy.len(), 1);
+}
+
+#[test]
+fn dangling_reference() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let lazy = LazyTable::new(&table);
+
+    // dangling reference does not fail until followed
+    let edge = lazy.get::<EdgeHolder>(5).unwrap();
+    assert!(lazy.resolve(&edge.first).is_ok());
+    assert!(matches!(
+        lazy.resolve(&edge.second),
+        Err(Error::UnknownEntity(6))
+    ));
+    assert!(matches!(
+        lazy.get::<PointHolder>(3),
+        Err(Error::UnknownEntity(3))
+    ));
+}
+
+#[test]
+fn value_instance_reference() {
+    use ruststep::ast::Name;
+
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(0.0, 0.0);
+          #2 = EDGE(#1, @3);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let lazy = LazyTable::new(&table);
+    let edge = lazy.get::<EdgeHolder>(2).unwrap();
+    assert!(lazy.resolve(&edge.first).is_ok());
+    assert!(matches!(
+        lazy.resolve(&edge.second),
+        Err(Error::UnsupportedReference(Name::Value(3)))
+    ));
+
+    // Owned value cannot be resolved either
+    assert!(matches!(
+        EntityTable::<EdgeHolder>::get_owned(&table, 2),
+        Err(Error::UnsupportedReference(Name::Value(3)))
+    ));
+}
@@ -0,0 +1,72 @@
+use ruststep::{ast::*, tables::*};
+use serde::Deserialize;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY vertex;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        edge_start: vertex;
+        edge_end: vertex;
+      END_ENTITY;
+
+      ENTITY path;
+        edge_list: LIST [1:?] OF edge;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = VERTEX(0.0);
+  #2 = VERTEX(1.0);
+  #3 = VERTEX(2.0);
+  #11 = EDGE(#1, #2);
+  #12 = EDGE(#2, #3);
+  #13 = EDGE(#3, #1);
+  #20 = PATH((#12, #13, #11));
+ENDSEC;
+"#;
+
+#[test]
+fn raw_ids() {
+    let list = Parameter::from_str("(#12, #13, #11)").unwrap();
+    let ids = Vec::<u64>::deserialize(&list).unwrap();
+    assert_eq!(ids, vec![12, 13, 11]);
+
+    // Non-reference parameters are not ids
+    let list = Parameter::from_str("(#12, 'edge')").unwrap();
+    assert!(Vec::<u64>::deserialize(&list).is_err());
+}
+
+#[test]
+fn holder_ids() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let path = &table.path_holders()[&20];
+    let ids: Vec<_> = path.edge_list.iter().map(|edge| edge.entity_id()).collect();
+    assert_eq!(ids, vec![Some(12), Some(13), Some(11)]);
+
+    // Inline value does not have an entity id
+    let path: PathHolder =
+        PathHolder::deserialize(&Record::from_str("PATH((#12, EDGE((#1, #2))))").unwrap()).unwrap();
+    let ids: Vec<_> = path.edge_list.iter().map(|edge| edge.entity_id()).collect();
+    assert_eq!(ids, vec![Some(12), None]);
+}
+
+#[test]
+fn owned_in_order() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let path = table.path_holders()[&20]
+        .clone()
+        .into_owned(&table)
+        .unwrap();
+    let starts: Vec<f64> = path.edge_list.iter().map(|e| e.edge_start.x).collect();
+    assert_eq!(starts, vec![1.0, 2.0, 0.0]);
+}
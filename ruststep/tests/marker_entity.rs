@@ -0,0 +1,50 @@
+//! Entities without explicit attributes, e.g. abstract markers only having inherited attributes
+
+use ruststep::{ast::ser::to_record, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY marker;
+      END_ENTITY;
+
+      ENTITY item;
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY geometric_item SUBTYPE OF (item);
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn attr_len() {
+    assert_eq!(MarkerHolder::attr_len(), 0);
+    assert_eq!(GeometricItemHolder::attr_len(), 1);
+}
+
+#[test]
+fn get_owned() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = MARKER();
+          #2 = GEOMETRIC_ITEM(ITEM(('origin')));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let marker = EntityTable::<MarkerHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(marker, Marker {});
+    let item = EntityTable::<GeometricItemHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(item.item.name, "origin");
+}
+
+#[test]
+fn serialize() {
+    assert_eq!(to_record(&Marker {}).unwrap().to_string(), "MARKER()");
+}
@@ -0,0 +1,124 @@
+use ruststep::{ast::*, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY named_item;
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY measured_item;
+        magnitude: REAL;
+        unit: STRING;
+      END_ENTITY;
+
+      ENTITY measured_named_item SUBTYPE OF (named_item, measured_item);
+        tolerance: REAL;
+      END_ENTITY;
+
+      ENTITY shape;
+        id: INTEGER;
+      END_ENTITY;
+
+      ENTITY colored_shape SUBTYPE OF (shape);
+        color: STRING;
+      END_ENTITY;
+
+      ENTITY textured_shape SUBTYPE OF (shape);
+        texture: STRING;
+      END_ENTITY;
+
+      ENTITY styled_shape SUBTYPE OF (colored_shape, textured_shape);
+        style: STRING;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn deserialize_two_supertypes() {
+    let answer = MeasuredNamedItemHolder {
+        named_item: NamedItemHolder {
+            name: "bolt".to_string(),
+        }
+        .into(),
+        measured_item: MeasuredItemHolder {
+            magnitude: 1.5,
+            unit: "mm".to_string(),
+        }
+        .into(),
+        tolerance: 0.1,
+    };
+
+    // Components can be in any order
+    for input in [
+        "(MEASURED_ITEM(1.5, 'mm') MEASURED_NAMED_ITEM(0.1) NAMED_ITEM('bolt'))",
+        "(MEASURED_NAMED_ITEM(0.1) NAMED_ITEM('bolt') MEASURED_ITEM(1.5, 'mm'))",
+    ] {
+        let p = SubSuperRecord::from_str(input).unwrap();
+        let value: Option<MeasuredNamedItemHolder> =
+            p.deserialize_component("MEASURED_NAMED_ITEM").unwrap();
+        assert_eq!(value.as_ref(), Some(&answer));
+    }
+
+    // Lacks MEASURED_ITEM component
+    let p = SubSuperRecord::from_str("(MEASURED_NAMED_ITEM(0.1) NAMED_ITEM('bolt'))").unwrap();
+    assert!(p
+        .deserialize_component::<MeasuredNamedItemHolder>("MEASURED_NAMED_ITEM")
+        .is_err());
+}
+
+#[test]
+fn get_owned_two_supertypes() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = (MEASURED_ITEM(1.5, 'mm') MEASURED_NAMED_ITEM(0.1) NAMED_ITEM('bolt'));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let item = EntityTable::<MeasuredNamedItemHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(
+        item,
+        MeasuredNamedItem {
+            named_item: NamedItem {
+                name: "bolt".to_string()
+            },
+            measured_item: MeasuredItem {
+                magnitude: 1.5,
+                unit: "mm".to_string()
+            },
+            tolerance: 0.1
+        }
+    );
+}
+
+#[test]
+fn deserialize_shared_supertype() {
+    let shape = || ShapeHolder { id: 3 }.into();
+    let answer = StyledShapeHolder {
+        colored_shape: ColoredShapeHolder {
+            shape: shape(),
+            color: "red".to_string(),
+        }
+        .into(),
+        textured_shape: TexturedShapeHolder {
+            shape: shape(),
+            texture: "wood".to_string(),
+        }
+        .into(),
+        style: "bold".to_string(),
+    };
+    for input in [
+        "(COLORED_SHAPE('red') SHAPE(3) STYLED_SHAPE('bold') TEXTURED_SHAPE('wood'))",
+        "(STYLED_SHAPE('bold') TEXTURED_SHAPE('wood') COLORED_SHAPE('red') SHAPE(3))",
+    ] {
+        let p = SubSuperRecord::from_str(input).unwrap();
+        let value: Option<StyledShapeHolder> = p.deserialize_component("STYLED_SHAPE").unwrap();
+        assert_eq!(value.as_ref(), Some(&answer));
+    }
+}
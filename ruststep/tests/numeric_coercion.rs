@@ -0,0 +1,49 @@
+use ruststep::ast::*;
+use serde::Deserialize;
+
+#[test]
+fn integer_into_real() {
+    let p = Parameter::Integer(2);
+    let x: f64 = Deserialize::deserialize(&p).unwrap();
+    assert_eq!(x, 2.0);
+
+    let x: f32 = Deserialize::deserialize(&p).unwrap();
+    assert_eq!(x, 2.0);
+
+    let x: Option<f64> = Deserialize::deserialize(&p).unwrap();
+    assert_eq!(x, Some(2.0));
+}
+
+#[test]
+fn integer_list_into_reals() {
+    let p: Parameter = vec![Parameter::Integer(1), Parameter::Real(2.5)].into();
+    let x: Vec<f64> = Deserialize::deserialize(&p).unwrap();
+    assert_eq!(x, vec![1.0, 2.5]);
+}
+
+#[test]
+fn whole_real_into_integer() {
+    let p = Parameter::Real(2.0);
+    let x: i64 = Deserialize::deserialize(&p).unwrap();
+    assert_eq!(x, 2);
+
+    let p = Parameter::Real(-3.0);
+    let x: i32 = Deserialize::deserialize(&p).unwrap();
+    assert_eq!(x, -3);
+}
+
+#[test]
+fn fractional_real_into_integer() {
+    let p = Parameter::Real(2.5);
+    let x: Result<i64, _> = Deserialize::deserialize(&p);
+    assert!(x.is_err());
+
+    let p = Parameter::Real(f64::MAX);
+    let x: Result<i64, _> = Deserialize::deserialize(&p);
+    assert!(x.is_err());
+
+    // Whole number but out of range
+    let p = Parameter::Real(-1.0);
+    let x: Result<u32, _> = Deserialize::deserialize(&p);
+    assert!(x.is_err());
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "rayon")]
+
+use ruststep::{ast::DataSection, error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY base;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY sub SUBTYPE OF (base);
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY line;
+        start: point;
+        end_: point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn data_section(n: u64) -> DataSection {
+    let mut data = String::from("DATA;\n");
+    for i in 0..n {
+        let id = 3 * i + 1;
+        data.push_str(&format!("#{} = POINT({}., 0.);\n", id, i));
+        data.push_str(&format!("#{} = POINT({}., 1.);\n", id + 1, i));
+        data.push_str(&format!("#{} = LINE(#{}, #{});\n", id + 2, id, id + 1));
+    }
+    data.push_str(&format!("#{} = BASE(1.);\n", 3 * n + 1));
+    data.push_str(&format!("#{} = (BASE(1.) SUB(2.));\n", 3 * n + 2));
+    data.push_str("ENDSEC;");
+    DataSection::from_str(&data).unwrap()
+}
+
+#[test]
+fn same_as_sequential() {
+    let section = data_section(1000);
+    let sequential = Tables::from_data_section(&section).unwrap();
+    let parallel = Tables::from_data_section_parallel(&section).unwrap();
+    assert_eq!(parallel, sequential);
+    assert_eq!(parallel.point_holders().len(), 2000);
+    assert_eq!(parallel.line_holders().len(), 1000);
+    assert_eq!(parallel.base_holders().len(), 1);
+    assert_eq!(parallel.sub_holders().len(), 1);
+}
+
+#[test]
+fn unknown_entity() {
+    let section =
+        DataSection::from_str("DATA; #1 = POINT(0., 0.); #2 = UNKNOWN(1.); ENDSEC;").unwrap();
+    assert!(matches!(
+        Tables::from_data_section_parallel(&section),
+        Err(Error::UnknownEntityName { entity_name, .. }) if entity_name == "UNKNOWN"
+    ));
+}
+
+#[test]
+fn duplicated_entity() {
+    let section =
+        DataSection::from_str("DATA; #1 = POINT(0., 0.); #1 = POINT(1., 0.); ENDSEC;").unwrap();
+    assert!(matches!(
+        Tables::from_data_section_parallel(&section),
+        Err(Error::DuplicatedEntity(1))
+    ));
+}
@@ -0,0 +1,99 @@
+use nom::{Finish, Parser};
+use ruststep::{
+    ast::*,
+    parser::{exchange::data_section_with, parse_with_options, ParseOptions},
+    tables::*,
+};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY polyline;
+        points: LIST [2:?] OF point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const BROKEN: &str = r#"
+DATA ;
+  #1 = POINT(1.0, 2.0, );
+  #2 = POINT( 3.0 ,
+              4.0 ) ;
+  #3 = POLYLINE((#1, #2, ));
+ENDSEC ;
+"#;
+
+fn lenient() -> ParseOptions {
+    ParseOptions {
+        tolerate_trailing_commas: true,
+        tolerate_spaced_keywords: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn strict_by_default() {
+    assert!(DataSection::from_str(BROKEN).is_err());
+}
+
+#[test]
+fn trailing_comma() {
+    let (res, data) = data_section_with(lenient())
+        .parse(BROKEN.trim())
+        .finish()
+        .unwrap();
+    assert_eq!(res, "");
+    assert_eq!(data.entities.len(), 3);
+    let table = Tables::from_data_section(&data).unwrap();
+    let polyline = EntityTable::<PolylineHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(
+        polyline.points,
+        vec![Point::new(1.0, 2.0), Point::new(3.0, 4.0)]
+    );
+}
+
+#[test]
+fn empty_list_is_not_a_trailing_comma() {
+    assert!(data_section_with(lenient())
+        .parse("DATA; #1 = POLYLINE((,)); ENDSEC;")
+        .finish()
+        .is_err());
+}
+
+const UNKNOWN_SECTION: &str = r#"
+ISO-10303-21;
+HEADER;
+  FILE_DESCRIPTION((''), '2;1');
+  FILE_NAME('', '', (''), (''), '', '', '');
+  FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+VENDOR_SECTION;
+  whatever the exporter wants to write
+ENDSEC;
+DATA;
+  #1 = POINT(1.0, 2.0);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+#[test]
+fn unknown_section() {
+    assert!(parse_with_options(UNKNOWN_SECTION.trim(), &ParseOptions::default()).is_err());
+
+    let options = ParseOptions {
+        tolerate_unknown_sections: true,
+        ..Default::default()
+    };
+    let ex = parse_with_options(UNKNOWN_SECTION.trim(), &options).unwrap();
+    assert_eq!(ex.data.len(), 1);
+    assert_eq!(ex.data[0].entities.len(), 1);
+}
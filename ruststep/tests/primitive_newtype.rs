@@ -0,0 +1,104 @@
+// Test for deserializing defined types of simple types from bare parameters
+
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      TYPE length_measure = REAL;
+      END_TYPE;
+
+      TYPE count_measure = INTEGER;
+      END_TYPE;
+
+      TYPE text = STRING;
+      END_TYPE;
+
+      TYPE positive_length_measure = length_measure;
+      END_TYPE;
+
+      ENTITY measured;
+        name: text;
+        size: length_measure;
+        positive: positive_length_measure;
+        amount: count_measure;
+        sizes: LIST [1:?] OF length_measure;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn bare_parameters() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = MEASURED('x', 1.5, 2.0, 3, (1.0, 2.0));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let measured = EntityTable::<MeasuredHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(
+        measured,
+        Measured {
+            name: Text("x".to_string()),
+            size: LengthMeasure(1.5),
+            positive: PositiveLengthMeasure(LengthMeasure(2.0)),
+            amount: CountMeasure(3),
+            sizes: vec![LengthMeasure(1.0), LengthMeasure(2.0)],
+        }
+    );
+}
+
+#[test]
+fn integer_as_real() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = MEASURED('x', 1, 2, 3, (1, 2.0));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let measured = EntityTable::<MeasuredHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(measured.size, LengthMeasure(1.0));
+    assert_eq!(measured.sizes[0], LengthMeasure(1.0));
+}
+
+#[test]
+fn typed_and_positional_parameters() {
+    // Typed parameters and one-element lists are still accepted
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = MEASURED(TEXT('x'), LENGTH_MEASURE(1.5), (2.0), 3, ((1.0)));
+          #2 = MEASURED('y', #3, 2.0, 3, (1.0));
+          #3 = LENGTH_MEASURE(4.0);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let measured = EntityTable::<MeasuredHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(measured.name, Text("x".to_string()));
+    assert_eq!(measured.size, LengthMeasure(1.5));
+    assert_eq!(measured.sizes, vec![LengthMeasure(1.0)]);
+
+    let measured = EntityTable::<MeasuredHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(measured.size, LengthMeasure(4.0));
+}
+
+#[test]
+fn mismatched_type() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = MEASURED('x', 1.5, 2.0, 3.5, (1.0));
+        ENDSEC;
+        "#,
+    );
+    assert!(table.is_err());
+}
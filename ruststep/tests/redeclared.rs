@@ -0,0 +1,109 @@
+//! Attributes redeclared by `SELF\supertype.attr` are stored in the inherited fields
+
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY base;
+        x: NUMBER;
+        y: OPTIONAL REAL;
+      END_ENTITY;
+
+      ENTITY sub SUBTYPE OF (base);
+        SELF\base.x RENAMED rx: REAL;
+        z: REAL;
+      DERIVE
+        SELF\base.y : REAL := 2.0 * z;
+      END_ENTITY;
+
+      ENTITY point;
+        x: REAL;
+        w: REAL;
+      END_ENTITY;
+
+      ENTITY unit_point SUBTYPE OF (point);
+      DERIVE
+        SELF\point.w : REAL := 1.0;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn get_owned() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = SUB(BASE((1.0, *)), 3.0);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    // `y` omitted as `*` is taken from the redeclaration `2.0 * z`
+    let sub = EntityTable::<SubHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(
+        sub,
+        Sub {
+            base: Base {
+                x: 1.0,
+                y: Some(6.0)
+            },
+            z: 3.0,
+        }
+    );
+}
+
+#[test]
+fn derived_in_subtype() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0, 2.0);
+          #2 = UNIT_POINT(POINT((3.0, *)));
+          #3 = (POINT(4.0, *) UNIT_POINT());
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+
+    let point = EntityTable::<PointHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(point, Point { x: 1.0, w: 2.0 });
+    let unit = EntityTable::<UnitPointHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(unit.point, Point { x: 3.0, w: 1.0 });
+    let unit = EntityTable::<UnitPointHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(unit.point, Point { x: 4.0, w: 1.0 });
+}
+
+#[test]
+fn omitted_without_subtype() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0, *);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    // `*` is only allowed where a subtype derives the value
+    assert!(matches!(
+        EntityTable::<PointHolder>::get_owned(&table, 1),
+        Err(ruststep::error::Error::DerivedAttribute { .. })
+    ));
+}
+
+#[test]
+fn null_for_derived_attribute() {
+    // `w` is still mandatory for plain `POINT`
+    assert!(Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0, $);
+        ENDSEC;
+        "#,
+    )
+    .is_err());
+}
@@ -0,0 +1,83 @@
+// Test for `#[holder(rename = "...")]` for irregular record names
+
+use ruststep::{ast::*, tables::*};
+use ruststep_derive::{as_holder, Holder, Serialize, TableInit};
+use serde::Deserialize;
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(TableInit, Default)]
+pub struct Table {
+    curve2d: HashMap<u64, as_holder!(Curve2d)>,
+    b_spline: HashMap<u64, as_holder!(BSpline)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder, Serialize)]
+#[holder(table = Table)]
+#[holder(field = curve2d)]
+#[holder(generate_deserialize)]
+#[holder(rename = "CURVE_2D")]
+pub struct Curve2d {
+    pub degree: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = b_spline)]
+#[holder(generate_deserialize)]
+pub struct BSpline {
+    #[holder(use_place_holder)]
+    pub curve: Curve2d,
+}
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = CURVE_2D(3);
+  #2 = B_SPLINE(#1);
+  #3 = B_SPLINE(CURVE_2D((2)));
+ENDSEC;
+"#;
+
+#[test]
+fn holder_name() {
+    assert_eq!(<Curve2dHolder as Holder>::name(), "CURVE_2D");
+    assert_eq!(<BSplineHolder as Holder>::name(), "B_SPLINE");
+}
+
+#[test]
+fn deserialize_renamed() {
+    let record = Record::from_str("CURVE_2D(3)").unwrap();
+    let holder = Curve2dHolder::deserialize(&record).unwrap();
+    assert_eq!(holder, Curve2dHolder { degree: 3 });
+
+    // Name from struct identifier is not accepted
+    let record = Record::from_str("CURVE2D(3)").unwrap();
+    assert!(Curve2dHolder::deserialize(&record).is_err());
+}
+
+#[test]
+fn table_renamed() {
+    let table = Table::from_str(EXAMPLE).unwrap();
+    let curve = EntityTable::<Curve2dHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(curve, Curve2d { degree: 3 });
+    let b = EntityTable::<BSplineHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(b.curve, curve);
+    let b = EntityTable::<BSplineHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(b.curve, Curve2d { degree: 2 });
+}
+
+#[test]
+fn table_dispatch_by_holder_name() {
+    // Tables store records by the renamed name, not by the field name
+    let data =
+        DataSection::from_str("DATA;\n#1 = CURVE_2D(3);\n#2 = CURVE2D(3);\nENDSEC;\n").unwrap();
+    let (table, unknowns) = Table::from_data_section_lossy(&data).unwrap();
+    assert!(EntityTable::<Curve2dHolder>::get_owned(&table, 1).is_ok());
+    assert_eq!(unknowns.len(), 1);
+    assert_eq!(unknowns[0].id, 2);
+}
+
+#[test]
+fn serialize_renamed() {
+    let record = ruststep::ast::ser::to_record(&Curve2d { degree: 3 }).unwrap();
+    assert_eq!(record.to_string(), "CURVE_2D(3)");
+}
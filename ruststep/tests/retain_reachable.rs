@@ -0,0 +1,71 @@
+use ruststep::tables::*;
+use std::{collections::HashSet, str::FromStr};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        first: point;
+        second: point;
+      END_ENTITY;
+
+      ENTITY wire;
+        edges: LIST [1:?] OF edge;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = POINT(0.0);
+  #2 = POINT(1.0);
+  #3 = POINT(2.0);
+  #4 = EDGE(#1, #2);
+  #5 = EDGE(#2, POINT((3.0)));
+  #6 = WIRE((#4, #5));
+  #7 = EDGE(#3, #3);
+  #8 = POINT(4.0);
+ENDSEC;
+"#;
+
+#[test]
+fn references() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let references = table.references();
+    assert_eq!(references[&4], vec![1, 2]);
+    assert_eq!(references[&5], vec![2]);
+    assert_eq!(references[&6], vec![4, 5]);
+    assert!(references[&1].is_empty());
+}
+
+#[test]
+fn reachable_from() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let reachable = table.reachable_from(&[6]);
+    assert_eq!(reachable, HashSet::from([1, 2, 4, 5, 6]));
+    let reachable = table.reachable_from(&[7, 8]);
+    assert_eq!(reachable, HashSet::from([3, 7, 8]));
+}
+
+#[test]
+fn retain_reachable_from() {
+    let mut table = Tables::from_str(EXAMPLE).unwrap();
+    let removed = table.retain_reachable_from(&[6]);
+    assert_eq!(removed, 3);
+
+    let wire = EntityTable::<WireHolder>::get_owned(&table, 6).unwrap();
+    assert_eq!(wire.edges.len(), 2);
+    assert!(EntityTable::<EdgeHolder>::get_owned(&table, 7).is_err());
+    assert!(EntityTable::<PointHolder>::get_owned(&table, 3).is_err());
+    assert!(EntityTable::<PointHolder>::get_owned(&table, 8).is_err());
+
+    // Nothing to remove
+    assert_eq!(table.retain_reachable_from(&[6]), 0);
+}
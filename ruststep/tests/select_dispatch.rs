@@ -0,0 +1,102 @@
+//! SELECT fields accept both a reference to a member entity
+//! and a typed parameter of a member defined type, even through a nested SELECT.
+
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      TYPE length_measure = REAL;
+      END_TYPE;
+
+      TYPE area_measure = REAL;
+      END_TYPE;
+
+      ENTITY measure_with_unit;
+        unit_name: STRING;
+      END_ENTITY;
+
+      TYPE measure_select = SELECT (length_measure, area_measure);
+      END_TYPE;
+
+      TYPE value_select = SELECT (measure_select, measure_with_unit);
+      END_TYPE;
+
+      ENTITY holder;
+        item: value_select;
+      END_ENTITY;
+
+      ENTITY measure_holder;
+        item: measure_select;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = MEASURE_WITH_UNIT('mm');
+  #2 = HOLDER(#1);
+  #3 = HOLDER(LENGTH_MEASURE(1.5));
+  #4 = HOLDER(AREA_MEASURE(2.5));
+  #5 = MEASURE_HOLDER(LENGTH_MEASURE(3.5));
+ENDSEC;
+"#;
+
+#[test]
+fn reference() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let holder = EntityTable::<HolderHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(
+        holder.item,
+        ValueSelect::MeasureWithUnit(Box::new(MeasureWithUnit {
+            unit_name: "mm".into()
+        }))
+    );
+}
+
+#[test]
+fn typed() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let holder = EntityTable::<MeasureHolderHolder>::get_owned(&table, 5).unwrap();
+    assert_eq!(
+        holder.item,
+        MeasureSelect::LengthMeasure(Box::new(LengthMeasure(3.5)))
+    );
+}
+
+#[test]
+fn typed_nested() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let holder = EntityTable::<HolderHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(
+        holder.item,
+        ValueSelect::MeasureSelect(Box::new(MeasureSelect::LengthMeasure(Box::new(
+            LengthMeasure(1.5)
+        ))))
+    );
+    let holder = EntityTable::<HolderHolder>::get_owned(&table, 4).unwrap();
+    assert_eq!(
+        holder.item,
+        ValueSelect::MeasureSelect(Box::new(MeasureSelect::AreaMeasure(Box::new(AreaMeasure(
+            2.5
+        )))))
+    );
+}
+
+#[test]
+fn accepts_keyword() {
+    assert!(ValueSelectHolder::accepts_keyword("LENGTH_MEASURE"));
+    assert!(ValueSelectHolder::accepts_keyword("MEASURE_WITH_UNIT"));
+    assert!(!ValueSelectHolder::accepts_keyword("VALUE_SELECT"));
+    assert!(!MeasureSelectHolder::accepts_keyword("MEASURE_WITH_UNIT"));
+}
+
+#[test]
+fn unknown_keyword() {
+    let err = Tables::from_str("DATA;\n#1 = HOLDER(VOLUME_MEASURE(1.0));\nENDSEC;\n");
+    assert!(err.is_err());
+}
@@ -0,0 +1,82 @@
+//! Generated types deriving `serde::Serialize` and `serde::Deserialize`
+
+#[cfg(feature = "json")]
+mod serde_derive {
+    use ruststep::{ast::ser::to_record, tables::*};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    espr_derive::inline_express!(
+        r#"
+        SCHEMA test_schema;
+          TYPE label = STRING;
+          END_TYPE;
+
+          TYPE shape = SELECT (circle, square);
+          END_TYPE;
+
+          ENTITY base SUPERTYPE OF (ONEOF (circle, square));
+            name: label;
+          END_ENTITY;
+
+          ENTITY circle SUBTYPE OF (base);
+            radius: REAL;
+          END_ENTITY;
+
+          ENTITY square SUBTYPE OF (base);
+            side: REAL;
+          END_ENTITY;
+
+          ENTITY drawing;
+            shapes: LIST [1:?] OF shape;
+            background: OPTIONAL base;
+          END_ENTITY;
+        END_SCHEMA;
+        "#,
+        serde
+    );
+
+    use test_schema::*;
+
+    const EXAMPLE: &str = r#"
+    DATA;
+      #1 = CIRCLE(BASE(('c')), 1.0);
+      #2 = SQUARE(BASE(('s')), 2.0);
+      #3 = DRAWING((#1, #2), $);
+    ENDSEC;
+    "#;
+
+    fn drawing() -> Drawing {
+        let table = Tables::from_str(EXAMPLE).unwrap();
+        table.drawing_holders()[&3]
+            .clone()
+            .into_owned(&table)
+            .unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let drawing = drawing();
+        let value = serde_json::to_value(&drawing).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "shapes": [
+                    { "Circle": { "base": { "name": "c" }, "radius": 1.0 } },
+                    { "Square": { "base": { "name": "s" }, "side": 2.0 } },
+                ],
+                "background": null,
+            })
+        );
+        let back: Drawing = serde_json::from_value(value).unwrap();
+        assert_eq!(back, drawing);
+    }
+
+    #[test]
+    fn holder_into_record() {
+        // Holders are still serialized into exchange structure
+        let table = Tables::from_str(EXAMPLE).unwrap();
+        let record = to_record(&table.drawing_holders()[&3]).unwrap();
+        assert_eq!(record.name, "DRAWING");
+    }
+}
@@ -0,0 +1,104 @@
+use ruststep::{ast::ser::*, tables::IntoOwned};
+use serde::Deserialize;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      TYPE label = STRING;
+      END_TYPE;
+
+      TYPE color = ENUMERATION OF (red, dark_green);
+      END_TYPE;
+
+      TYPE content = SELECT (label, point);
+      END_TYPE;
+
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY shape;
+        name: label;
+        color: color;
+        points: LIST [1:?] OF point;
+        content: content;
+        note: OPTIONAL STRING;
+      END_ENTITY;
+
+      ENTITY base SUPERTYPE OF (sub);
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY sub SUBTYPE OF (base);
+        y: INTEGER;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn shape() -> Shape {
+    Shape::new(
+        Label("a".to_string()),
+        Color::DarkGreen,
+        vec![Point::new(1.0, 2.0), Point::new(3.0, 4.0)],
+        Content::Label(Box::new(Label("b".to_string()))),
+        None,
+    )
+}
+
+#[test]
+fn serialize_entity() {
+    let record = to_record(&Point::new(1.0, 2.0)).unwrap();
+    assert_eq!(record.to_string(), "POINT(1., 2.)");
+
+    let record = to_record(&shape()).unwrap();
+    assert_eq!(
+        record.to_string(),
+        "SHAPE(LABEL(('a')), .DARK_GREEN., (POINT((1., 2.)), POINT((3., 4.))), LABEL(('b')), $)"
+    );
+}
+
+#[test]
+fn serialize_select() {
+    let content = Content::Point(Box::new(Point::new(1.0, 2.0)));
+    assert_eq!(to_record(&content).unwrap().to_string(), "POINT(1., 2.)");
+    assert_eq!(
+        to_parameter(&content).unwrap().to_string(),
+        "POINT((1., 2.))"
+    );
+
+    let content = Content::Label(Box::new(Label("b".to_string())));
+    assert_eq!(to_record(&content).unwrap().to_string(), "LABEL('b')");
+    assert_eq!(to_parameter(&content).unwrap().to_string(), "LABEL(('b'))");
+}
+
+#[test]
+fn serialize_subtype() {
+    let sub = Sub::new(Base::new(1.0), 2);
+    assert_eq!(to_record(&sub).unwrap().to_string(), "SUB(BASE((1.)), 2)");
+
+    let any: BaseAny = sub.into();
+    assert_eq!(to_record(&any).unwrap().to_string(), "SUB(BASE((1.)), 2)");
+}
+
+#[test]
+fn round_trip() {
+    let table = Tables::default();
+
+    let shape = shape();
+    let record = to_record(&shape).unwrap();
+    let holder: ShapeHolder = Deserialize::deserialize(&record).unwrap();
+    assert_eq!(holder.into_owned(&table).unwrap(), shape);
+
+    let parameter = to_parameter(&shape).unwrap();
+    let holder: ShapeHolder = Deserialize::deserialize(&parameter).unwrap();
+    assert_eq!(holder.into_owned(&table).unwrap(), shape);
+
+    let sub = Sub::new(Base::new(1.0), 2);
+    let record = to_record(&sub).unwrap();
+    let holder: SubHolder = Deserialize::deserialize(&record).unwrap();
+    assert_eq!(holder.into_owned(&table).unwrap(), sub);
+}
@@ -0,0 +1,164 @@
+use ruststep::{ast::ser::to_record, error::Error, tables::*};
+use std::{rc::Rc, str::FromStr};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        first: point;
+        second: point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = POINT(0.0, 0.0);
+  #2 = POINT(1.0, 0.0);
+  #3 = EDGE(#1, #2);
+  #4 = EDGE(#2, #1);
+ENDSEC;
+"#;
+
+#[test]
+fn shared_entity() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let shared = SharedTable::new(&table);
+
+    let p1 = shared.get_shared::<PointHolder>(1).unwrap();
+    let p2 = shared.get_shared::<PointHolder>(1).unwrap();
+    assert!(Rc::ptr_eq(&p1, &p2));
+    assert_eq!(*p1, Point { x: 0.0, y: 0.0 });
+
+    let e3 = shared.get_shared::<EdgeHolder>(3).unwrap();
+    let e4 = shared.get_shared::<EdgeHolder>(4).unwrap();
+    assert!(!Rc::ptr_eq(&e3, &e4));
+    assert_eq!(
+        *e3,
+        EntityTable::<EdgeHolder>::get_owned(&table, 3).unwrap()
+    );
+    assert_eq!(e3.first, *p1);
+}
+
+#[test]
+fn shared_unknown_entity() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let shared = SharedTable::new(&table);
+    assert!(matches!(
+        shared.get_shared::<PointHolder>(5),
+        Err(Error::UnknownEntity(5))
+    ));
+}
+
+// Attributes referencing entities are generated as `Rc<T>` with `shared` option
+espr_derive::inline_express!(
+    r#"
+    SCHEMA shared_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        start: point;
+        finish: point;
+      END_ENTITY;
+
+      ENTITY polyline;
+        points: LIST [2:?] OF point;
+        label: OPTIONAL point;
+      END_ENTITY;
+
+      ENTITY curve
+        SUPERTYPE OF (ONEOF(line));
+        origin: point;
+      END_ENTITY;
+
+      ENTITY line
+        SUBTYPE OF (curve);
+        direction: point;
+      END_ENTITY;
+
+      ENTITY trimmed;
+        basis: curve;
+        bounds: LIST [2:2] OF point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#,
+    shared
+);
+
+const SHARED_EXAMPLE: &str = r#"
+DATA;
+  #1 = POINT(0.0, 0.0);
+  #2 = POINT(1.0, 0.0);
+  #3 = EDGE(#1, #2);
+  #4 = EDGE(#2, #2);
+  #5 = POLYLINE((#1, #2, #1), $);
+  #6 = LINE(CURVE((#1)), #2);
+  #7 = TRIMMED(#6, (#1, #2));
+ENDSEC;
+"#;
+
+#[test]
+fn shared_within_entity() {
+    use shared_schema::*;
+
+    let table = shared_schema::Tables::from_str(SHARED_EXAMPLE).unwrap();
+    let edge = EntityTable::<EdgeHolder>::get_owned(&table, 4).unwrap();
+    assert!(Rc::ptr_eq(&edge.start, &edge.finish));
+    assert_eq!(*edge.start, Point::new(1.0, 0.0));
+
+    let polyline = EntityTable::<PolylineHolder>::get_owned(&table, 5).unwrap();
+    assert!(Rc::ptr_eq(&polyline.points[0], &polyline.points[2]));
+    assert!(!Rc::ptr_eq(&polyline.points[0], &polyline.points[1]));
+    assert_eq!(polyline.label, None);
+
+    // Not shared among the values resolved separately without SharedTable
+    let e3 = EntityTable::<EdgeHolder>::get_owned(&table, 3).unwrap();
+    assert!(!Rc::ptr_eq(&e3.finish, &edge.start));
+    assert_eq!(e3.finish, edge.start);
+}
+
+#[test]
+fn shared_among_entities() {
+    use shared_schema::*;
+
+    let table = shared_schema::Tables::from_str(SHARED_EXAMPLE).unwrap();
+    let shared = SharedTable::new(&table);
+    let edge = shared.get_shared::<EdgeHolder>(3).unwrap();
+    let polyline = shared.get_shared::<PolylineHolder>(5).unwrap();
+    assert!(Rc::ptr_eq(&edge.start, &polyline.points[0]));
+    assert!(Rc::ptr_eq(&edge.finish, &polyline.points[1]));
+
+    // Subtypes referenced as the supertype are shared as `XxxAny`
+    let trimmed = shared.get_shared::<TrimmedHolder>(7).unwrap();
+    match trimmed.basis.as_ref() {
+        CurveAny::Line(line) => {
+            assert!(Rc::ptr_eq(&line.curve.origin, &edge.start));
+            assert!(Rc::ptr_eq(&line.direction, &edge.finish));
+        }
+        _ => panic!("Unexpected variant"),
+    }
+    assert!(Rc::ptr_eq(&trimmed.bounds[1], &edge.finish));
+}
+
+#[test]
+fn serialize_shared() {
+    use shared_schema::*;
+
+    let table = shared_schema::Tables::from_str(SHARED_EXAMPLE).unwrap();
+    let edge = EntityTable::<EdgeHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(
+        to_record(&edge).unwrap().to_string(),
+        "EDGE(POINT((0., 0.)), POINT((1., 0.)))"
+    );
+}
@@ -0,0 +1,56 @@
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY edge;
+        first: point;
+        second: point;
+      END_ENTITY;
+
+      ENTITY wire;
+        edges: LIST [1:?] OF edge;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn stats() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(0.0);
+          #2 = POINT(1.0);
+          #3 = POINT(2.0);
+          #4 = EDGE(#1, #2);
+          #5 = EDGE(#2, #2);
+          #6 = WIRE((#4, #5, #4));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let stats = table.stats();
+    assert_eq!(stats.entities, 6);
+    assert_eq!(stats.counts["POINT"], 3);
+    assert_eq!(stats.counts["EDGE"], 2);
+    assert_eq!(stats.counts["WIRE"], 1);
+    // Each occurrence is counted
+    assert_eq!(stats.references, 7);
+    assert_eq!(stats.max_fan_out, 3);
+    // `#6` is a root, and `#3` is unused
+    assert_eq!(stats.orphans, 2);
+}
+
+#[test]
+fn empty() {
+    let table = Tables::from_str("DATA;\nENDSEC;\n").unwrap();
+    assert_eq!(table.stats(), TablesStats::default());
+}
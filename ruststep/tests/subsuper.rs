@@ -168,3 +168,104 @@ fn get_owned_any() {
         }))
     );
 }
+
+#[test]
+fn deserialize_complex() {
+    let p = SubSuperRecord::from_str("(BASE(1.0) SUB(2.0))").unwrap();
+    let sub: Option<SubHolder> = p.deserialize_component("SUB").unwrap();
+    assert_eq!(
+        sub,
+        Some(SubHolder {
+            base: BaseHolder { x: 1.0 }.into(),
+            y: 2.0,
+        })
+    );
+    // `SUB` component is not consumed by `BASE`
+    let base: Option<BaseHolder> = p.deserialize_component("BASE").unwrap();
+    assert_eq!(base, None);
+
+    // Order of components does not matter
+    let p = SubSuperRecord::from_str("(SUBSUB(3.0) BASE(1.0) SUB(2.0))").unwrap();
+    let subsub: Option<SubsubHolder> = p.deserialize_component("SUBSUB").unwrap();
+    assert_eq!(
+        subsub,
+        Some(SubsubHolder {
+            sub: SubHolder {
+                base: BaseHolder { x: 1.0 }.into(),
+                y: 2.0,
+            }
+            .into(),
+            z: 3.0,
+        })
+    );
+
+    // Too many parameters
+    let p = SubSuperRecord::from_str("(BASE(1.0) SUB(2.0, 3.0))").unwrap();
+    assert!(p.deserialize_component::<SubHolder>("SUB").is_err());
+}
+
+const COMPLEX_EXAMPLE: &str = r#"
+DATA;
+  #1 = (BASE(1.0) SUB(2.0));
+  #2 = (BASE(1.0) SUB(2.0) SUBSUB(3.0));
+  #3 = (BASE(1.0) UNKNOWN(2.0));
+ENDSEC;
+"#;
+
+#[test]
+fn get_owned_complex() {
+    let data_sec = DataSection::from_str(COMPLEX_EXAMPLE).unwrap();
+    let (table, unknowns) = Tables::from_data_section_lossy(&data_sec).unwrap();
+    assert_eq!(unknowns.len(), 1);
+    assert_eq!(unknowns[0].id, 3);
+
+    let owned = EntityTable::<SubHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(
+        owned,
+        Sub {
+            base: Base { x: 1.0 },
+            y: 2.0
+        }
+    );
+    let owned = EntityTable::<SubsubHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(
+        owned,
+        Subsub {
+            sub: Sub {
+                base: Base { x: 1.0 },
+                y: 2.0,
+            },
+            z: 3.0,
+        }
+    );
+}
+
+#[test]
+fn project_into_supertype() {
+    let subsub = Subsub {
+        sub: Sub {
+            base: Base { x: 1.0 },
+            y: 2.0,
+        },
+        z: 3.0,
+    };
+    let sub = Sub::from(&subsub);
+    assert_eq!(sub, subsub.sub);
+    let base: Base = Sub::from(subsub).into();
+    assert_eq!(base, Base { x: 1.0 });
+}
+
+#[test]
+fn downcast_from_any() {
+    let sub = Sub {
+        base: Base { x: 1.0 },
+        y: 2.0,
+    };
+    let any: BaseAny = sub.clone().into();
+    let sub_any = SubAny::try_from(any).unwrap();
+    assert_eq!(Sub::try_from(sub_any).unwrap(), sub);
+
+    let any: BaseAny = Base { x: 1.0 }.into();
+    let any = SubAny::try_from(any).unwrap_err();
+    assert_eq!(Base::try_from(any).unwrap(), Base { x: 1.0 });
+}
@@ -0,0 +1,74 @@
+use ruststep::{ast::Record, error::Error};
+use std::{convert::TryFrom, str::FromStr};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY segment;
+        head: point;
+        tail: point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn try_from_record() {
+    let record = Record::from_str("POINT(1.0, 2.0)").unwrap();
+    assert_eq!(Point::try_from(&record).unwrap(), Point { x: 1.0, y: 2.0 });
+}
+
+#[test]
+fn inline_reference() {
+    let record = Record::from_str("SEGMENT(POINT((0.0, 0.0)), POINT((1.0, 2.0)))").unwrap();
+    assert_eq!(
+        Segment::try_from(&record).unwrap(),
+        Segment {
+            head: Point { x: 0.0, y: 0.0 },
+            tail: Point { x: 1.0, y: 2.0 },
+        }
+    );
+}
+
+#[test]
+fn entity_reference() {
+    let record = Record::from_str("SEGMENT(#1, #2)").unwrap();
+    assert!(matches!(
+        Segment::try_from(&record),
+        Err(Error::UnknownEntity(1))
+    ));
+}
+
+#[test]
+fn name_mismatch() {
+    let record = Record::from_str("SEGMENT(1.0, 2.0)").unwrap();
+    let err = Point::try_from(&record).unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::RecordNameMismatch { expected, found } if expected == "POINT" && found == "SEGMENT"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Record 'SEGMENT' cannot be read as 'POINT'"
+    );
+}
+
+#[test]
+fn arity_mismatch() {
+    let record = Record::from_str("POINT(1.0)").unwrap();
+    assert!(matches!(
+        Point::try_from(&record),
+        Err(Error::ArityMismatch {
+            expected: 2,
+            found: 1,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,68 @@
+// Test for `#[derive(Holder)]` on tuple structs
+
+use ruststep::{ast::*, tables::*};
+use ruststep_derive::{as_holder, Holder, Serialize, TableInit};
+use serde::Deserialize;
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(TableInit, Default)]
+pub struct Table {
+    point: HashMap<u64, as_holder!(Point)>,
+    segment: HashMap<u64, as_holder!(Segment)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder, Serialize)]
+#[holder(table = Table)]
+#[holder(field = point)]
+#[holder(generate_deserialize)]
+pub struct Point(pub f64, pub f64);
+
+#[derive(Debug, Clone, PartialEq, Holder, Serialize)]
+#[holder(table = Table)]
+#[holder(field = segment)]
+#[holder(generate_deserialize)]
+pub struct Segment(
+    pub String,
+    #[holder(use_place_holder)] pub Point,
+    #[holder(use_place_holder)] pub Point,
+);
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = POINT(0.0, 1.0);
+  #2 = SEGMENT('s', #1, POINT((2.0, 3.0)));
+ENDSEC;
+"#;
+
+#[test]
+fn deserialize_positional() {
+    let record = Record::from_str("POINT(0.0, 1.0)").unwrap();
+    let holder = PointHolder::deserialize(&record).unwrap();
+    assert_eq!(holder, PointHolder(0.0, 1.0));
+
+    // Arity mismatch
+    let record = Record::from_str("POINT(0.0)").unwrap();
+    assert!(PointHolder::deserialize(&record).is_err());
+}
+
+#[test]
+fn into_owned() {
+    let table = Table::from_str(EXAMPLE).unwrap();
+    let segment = EntityTable::<SegmentHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(
+        segment,
+        Segment("s".to_string(), Point(0.0, 1.0), Point(2.0, 3.0))
+    );
+    assert_eq!(table.references()[&2], vec![1]);
+}
+
+#[test]
+fn serialize_positional() {
+    let table = Table::from_str(EXAMPLE).unwrap();
+    let segment = EntityTable::<SegmentHolder>::get_owned(&table, 2).unwrap();
+    let record = ruststep::ast::ser::to_record(&segment).unwrap();
+    assert_eq!(
+        record,
+        Record::from_str("SEGMENT('s', POINT((0.0, 1.0)), POINT((2.0, 3.0)))").unwrap()
+    );
+}
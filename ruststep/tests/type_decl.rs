@@ -66,3 +66,32 @@ fn get_owned() {
         }
     );
 }
+
+#[test]
+fn unknown_enum_variant() {
+    let err = Tables::from_str(
+        r#"
+        DATA;
+          #1 = A('KORE');
+          #5 = E(#1, .KORE., C(#1), D(.DORE.));
+        ENDSEC;
+        "#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Enumeration value '.KORE.' is not a variant of 'B', expected one of Are, Sore, Dore"
+    );
+    match err {
+        ruststep::error::Error::UnknownEnumVariant {
+            type_name,
+            value,
+            expected,
+        } => {
+            assert_eq!(type_name, "B");
+            assert_eq!(value, "KORE");
+            assert_eq!(expected, &["Are", "Sore", "Dore"]);
+        }
+        _ => panic!("Unexpected error: {:?}", err),
+    }
+}
@@ -0,0 +1,42 @@
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY person SUPERTYPE OF (employee ANDOR student);
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY employee SUBTYPE OF (person);
+        pay: INTEGER;
+      END_ENTITY;
+
+      ENTITY student SUBTYPE OF (person);
+        school_name: STRING;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = PERSON('Hitori Goto');
+  #2 = (PERSON('Ikuno Kita') STUDENT('Shuka'));
+  #3 = (STUDENT('Simokitazawa') PERSON('Nizika Iziti') EMPLOYEE(15));
+ENDSEC;
+"#;
+
+#[test]
+fn type_of() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    assert_eq!(table.type_of(1), Some("PERSON"));
+    assert_eq!(table.type_of(2), Some("STUDENT"));
+    assert_eq!(
+        table.type_of(3),
+        Some(<EmployeeStudentHolder as Holder>::name())
+    );
+    assert_eq!(table.type_of(4), None);
+}
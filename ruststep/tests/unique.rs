@@ -0,0 +1,74 @@
+use ruststep::tables::UniqueViolation;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY organization;
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY product;
+        id: STRING;
+        revision: INTEGER;
+        owner: organization;
+        note: OPTIONAL STRING;
+      UNIQUE
+        ur1: id, revision;
+        owner, note;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn no_violation() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = ORGANIZATION('a');
+          #2 = PRODUCT('p', 1, #1, $);
+          #3 = PRODUCT('p', 2, #1, 'n');
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    assert_eq!(table.check_unique().unwrap(), vec![]);
+}
+
+#[test]
+fn violations() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = ORGANIZATION('a');
+          #2 = ORGANIZATION('a');
+          #3 = PRODUCT('p', 1, #1, $);
+          #4 = PRODUCT('q', 1, #2, $);
+          #5 = PRODUCT('p', 1, #1, 'n');
+          #6 = PRODUCT('r', 1, #2, $);
+          #7 = PRODUCT('p', 1, #2, 'm');
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        table.check_unique().unwrap(),
+        vec![
+            // same `id` and `revision`
+            UniqueViolation {
+                entity: "PRODUCT",
+                rule: Some("ur1"),
+                ids: vec![3, 5, 7],
+            },
+            // same `owner` and `note`, where references are compared by ids
+            UniqueViolation {
+                entity: "PRODUCT",
+                rule: None,
+                ids: vec![4, 6],
+            },
+        ]
+    );
+}
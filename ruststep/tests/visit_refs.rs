@@ -0,0 +1,67 @@
+use ruststep::{ast::*, tables::*};
+use serde::Deserialize;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY a;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY b;
+        a: a;
+        others: LIST [0:?] OF a;
+        opt: OPTIONAL a;
+      END_ENTITY;
+
+      TYPE c = b;
+      END_TYPE;
+
+      TYPE ab = SELECT (a, b);
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn refs<T: VisitRefs>(holder: &T) -> Vec<u64> {
+    let mut ids = Vec::new();
+    holder.visit_refs(&mut |id| ids.push(id));
+    ids
+}
+
+fn holder<T: for<'de> Deserialize<'de>>(input: &str) -> T {
+    T::deserialize(&Record::from_str(input).unwrap()).unwrap()
+}
+
+#[test]
+fn visit_entity() {
+    let a: AHolder = holder("A(1.0)");
+    assert!(refs(&a).is_empty());
+
+    let b: BHolder = holder("B(#1, (#2, A((3.0)), #4), #5)");
+    assert_eq!(refs(&b), vec![1, 2, 4, 5]);
+
+    let b: BHolder = holder("B(A((1.0)), (), $)");
+    assert!(refs(&b).is_empty());
+}
+
+#[test]
+fn visit_type_decl() {
+    let c: CHolder = holder("C(B((#1, (#2), $)))");
+    assert_eq!(refs(&c), vec![1, 2]);
+}
+
+#[test]
+fn visit_select() {
+    let s: AbHolder = holder("B(#1, (), #3)");
+    assert_eq!(refs(&s), vec![1, 3]);
+}
+
+#[test]
+fn visit_place_holder() {
+    let p: PlaceHolder<BHolder> = PlaceHolder::Ref(Name::Entity(7));
+    assert_eq!(refs(&p), vec![7]);
+}
@@ -0,0 +1,120 @@
+#[cfg(feature = "xml")]
+mod xml {
+    use ruststep::{error::Error, tables::*, xml};
+    use std::str::FromStr;
+
+    espr_derive::inline_express!(
+        r#"
+        SCHEMA test_schema;
+          TYPE color = ENUMERATION OF (red, green);
+          END_TYPE;
+
+          TYPE tag = STRING;
+          END_TYPE;
+
+          ENTITY point;
+            x: REAL;
+            y: REAL;
+          END_ENTITY;
+
+          ENTITY polyline;
+            name: tag;
+            points: LIST [2:?] OF point;
+            paint: OPTIONAL color;
+          END_ENTITY;
+        END_SCHEMA;
+        "#
+    );
+
+    use test_schema::*;
+
+    const EXAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+    <ex:iso_10303_28 xmlns:ex="urn:iso10303-28:ex" version="2.0">
+      <ex:uos id="uos_1" express_schema_name="test_schema">
+        <Point id="i1"><x>0</x><y><ex:double-wrapper>1.5</ex:double-wrapper></y></Point>
+        <Point id="i2"><x>2.0</x><y>3.0</y></Point>
+        <Polyline id="i3">
+          <name><Tag>line</Tag></name>
+          <points ex:cType="list">
+            <Point ref="i1"/>
+            <Point ref="i2"/>
+          </points>
+          <paint><enumeration>red</enumeration></paint>
+        </Polyline>
+        <Polyline id="i4">
+          <name><Tag><string>10</string></Tag></name>
+          <points ex:cType="list"><Point ref="i2"/><Point ref="i1"/></points>
+          <paint ex:nil="true"/>
+        </Polyline>
+      </ex:uos>
+    </ex:iso_10303_28>
+    "#;
+
+    #[test]
+    fn read_tables() {
+        let sections = xml::from_str(EXAMPLE).unwrap();
+        let table = Tables::from_data_sections(&sections).unwrap();
+
+        let line = table.polyline_holders()[&3]
+            .clone()
+            .into_owned(&table)
+            .unwrap();
+        assert_eq!(line.name, Tag("line".to_string()));
+        assert_eq!(
+            line.points,
+            vec![Point { x: 0.0, y: 1.5 }, Point { x: 2.0, y: 3.0 }]
+        );
+        assert_eq!(line.paint, Some(Color::Red));
+
+        let line = table.polyline_holders()[&4]
+            .clone()
+            .into_owned(&table)
+            .unwrap();
+        assert_eq!(line.name, Tag("10".to_string()));
+        assert_eq!(line.paint, None);
+    }
+
+    #[test]
+    fn missing_id() {
+        let err = xml::from_str("<uos>\n<Point><x>1.0</x><y>1.0</y></Point>\n</uos>").unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidXml { line: 2, .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let err = xml::from_str("<uos>\n<Point id=\"i1\">\n</uos>").unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidXml { line: 3, .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let sections = xml::from_str(EXAMPLE).unwrap();
+        let table = Tables::from_data_sections(&sections).unwrap();
+
+        let written = xml::to_string(table.all_records()).unwrap();
+        let read = Tables::from_data_sections(&xml::from_str(&written).unwrap()).unwrap();
+        assert_eq!(read.point_holders(), table.point_holders());
+        assert_eq!(read.polyline_holders(), table.polyline_holders());
+        assert_eq!(
+            xml::to_string(read.all_records()).unwrap(),
+            written,
+            "{}",
+            written
+        );
+    }
+
+    #[test]
+    fn unsupported_reference() {
+        let record = ruststep::ast::Record::from_str("POINT(@1, 2.0)").unwrap();
+        let err = xml::to_string(vec![Ok((1, record))]).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedXml(_)), "{:?}", err);
+    }
+}